@@ -0,0 +1,38 @@
+//! Decoding time of a tile, through [`decode_image`] and through the `image` crate directly.
+//!
+//! `cargo bench -p api --features fast-jpeg` compares `zune-jpeg` to the `image` crate, without the
+//! feature both take the same path.
+use api::decode_image;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use image::{DynamicImage, ImageOutputFormat, RgbImage};
+
+/// A 500x281 tile, the default width and aspect ratio, encoded as per `format`. A gradient so it
+/// doesn't compress down to nothing.
+fn tile(format: ImageOutputFormat) -> Vec<u8> {
+    let img = RgbImage::from_fn(500, 281, |x, y| {
+        image::Rgb([x as u8, y as u8, (x ^ y) as u8])
+    });
+    let mut buf = Vec::new();
+    DynamicImage::ImageRgb8(img)
+        .write_to(&mut buf, format)
+        .unwrap();
+    buf
+}
+
+fn decode(c: &mut Criterion) {
+    let jpeg = tile(ImageOutputFormat::Jpeg(90));
+    let png = tile(ImageOutputFormat::Png);
+
+    let mut group = c.benchmark_group("decode");
+    group.bench_function("jpeg", |b| {
+        b.iter(|| decode_image(black_box(&jpeg)).unwrap())
+    });
+    group.bench_function("jpeg/image", |b| {
+        b.iter(|| image::load_from_memory(black_box(&jpeg)).unwrap())
+    });
+    group.bench_function("png", |b| b.iter(|| decode_image(black_box(&png)).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
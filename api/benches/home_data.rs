@@ -0,0 +1,75 @@
+//! Time spent going through the home data for the tiles of a row, looked up every frame the row is
+//! drawn: picking the artwork of an item and building its url, the picking alone, and the whole of
+//! [`SetData::get_home_tile_image`] with the artwork cached.
+//!
+//! The home data is read from the `tests/fixtures`, nothing goes to the network.
+use api::{Api, AspectRatioPolicy, SetData, DEFAULT_TILE_ASPECT_RATIO};
+use bytes::Bytes;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::fs;
+use std::path::PathBuf;
+
+fn fixtures() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+/// The home data of the fixtures, with the artwork of every tile of the first set in the cache.
+fn api() -> Api {
+    let mut api = Api::new();
+    api.load_from_file(fixtures().join("home.json")).unwrap();
+    let tile = Bytes::from(fs::read(fixtures().join("tile.png")).unwrap());
+    let set_data = api.get_set(0).unwrap();
+    for item_num in 0..set_data.get_item_count() {
+        let url = set_data.get_home_tile_url(item_num).unwrap();
+        api.image_cache().insert(&url, tile.clone());
+    }
+    api
+}
+
+/// Goes through every item of `set_data` with `lookup`.
+fn each_item<T>(set_data: &SetData, lookup: impl Fn(usize) -> T) {
+    for item_num in 0..set_data.get_item_count() {
+        black_box(lookup(black_box(item_num)));
+    }
+}
+
+fn tiles(c: &mut Criterion) {
+    let api = api();
+    let set_data = api.get_set(0).unwrap();
+
+    let mut group = c.benchmark_group("tiles");
+    group.bench_function("get_home_tile", |b| {
+        b.iter(|| each_item(&set_data, |item_num| set_data.get_home_tile(item_num)))
+    });
+    group.bench_function("get_home_tile_image", |b| {
+        b.iter(|| {
+            each_item(&set_data, |item_num| {
+                set_data.get_home_tile_image(item_num).unwrap()
+            })
+        })
+    });
+    group.finish();
+}
+
+fn aspect_ratios(c: &mut Criterion) {
+    // The keys of an item with every variant the backend sends.
+    let ratios = [0.71, 0.75, 1.0, 1.33, 1.78, 2.0, 3.91];
+
+    let mut group = c.benchmark_group("aspect_ratio");
+    for (name, policy) in [
+        ("widest", AspectRatioPolicy::Widest),
+        (
+            "closest",
+            AspectRatioPolicy::Closest(DEFAULT_TILE_ASPECT_RATIO),
+        ),
+        ("exact", AspectRatioPolicy::Exact(DEFAULT_TILE_ASPECT_RATIO)),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| black_box(policy).pick(black_box(&ratios).iter().copied()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, tiles, aspect_ratios);
+criterion_main!(benches);
@@ -0,0 +1,14 @@
+//! Feeds arbitrary json to the home data loader, as the home.json and as every `SetRef` set it
+//! points to, then goes through whatever got loaded, see [`api_fuzz::walk`].
+//!
+//! The home.json and `sets` of `tests/fixtures` make a good starting corpus.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(payload) = serde_json::from_slice(data) {
+        if let Some(api) = api_fuzz::load(payload) {
+            api_fuzz::walk(&api);
+        }
+    }
+});
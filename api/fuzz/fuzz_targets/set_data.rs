@@ -0,0 +1,21 @@
+//! Feeds arbitrary json as the `set` of the only container of the home data, so the fuzzing goes
+//! into the shape of the sets and their items instead of the envelope around them. See
+//! [`api_fuzz::walk`] for what is checked.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use serde_json::{json, Value};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(set) = serde_json::from_slice::<Value>(data) {
+        let payload = json!({
+            "data": {
+                "StandardCollection": {
+                    "containers": [{ "set": set, "style": "standard" }],
+                },
+            },
+        });
+        if let Some(api) = api_fuzz::load(payload) {
+            api_fuzz::walk(&api);
+        }
+    }
+});
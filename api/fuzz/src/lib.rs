@@ -0,0 +1,87 @@
+//! What the fuzz targets share: a [`Transport`] serving the fuzzed json in place of the backend, and
+//! a walk through every accessor of the [`SetData`] loaded from it.
+//!
+//! Whatever the json, loading it either fails with an [`ApiError`] or gives home data whose
+//! accessors don't panic, and tell the items past the end of a set apart from the others.
+use api::{Api, ApiError, ContentRating, RetryPolicy, SetData, Transport};
+use bytes::Bytes;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Serves the same json for the home.json and every `SetRef` set. There is no artwork.
+pub struct Payload(pub Value);
+
+impl Transport for Payload {
+    fn get_json(&self, _url: &str, _timeout: Duration) -> Result<Value, ApiError> {
+        Ok(self.0.clone())
+    }
+
+    fn get_bytes(&self, _url: &str, _timeout: Duration) -> Result<Bytes, ApiError> {
+        Err(ApiError::HttpStatus { code: 404 })
+    }
+}
+
+/// The home data loaded from `payload`, None if it isn't home data.
+pub fn load(payload: Value) -> Option<Api> {
+    let mut api = Api::new()
+        .with_base_url("http://fuzz.invalid")
+        .with_retry_policy(RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        })
+        .with_transport(Payload(payload));
+    api.load_home_data().ok()?;
+    Some(api)
+}
+
+/// Goes through every set of the home data, as is and with a rating ceiling, then through what
+/// looks at all of them at once.
+pub fn walk(api: &Api) {
+    let num_of_sets = api.get_num_of_sets().unwrap_or(0);
+    for set_idx in 0..num_of_sets {
+        let set = api.get_set(set_idx).expect("set below get_num_of_sets");
+        walk_set(&set);
+        if let Some(content_id) = set.get_content_id(0) {
+            assert!(api.find_item(content_id).is_some());
+        }
+        walk_set(&set.with_rating_ceiling(ContentRating::Pg));
+    }
+    assert!(api.get_set(num_of_sets).is_none());
+
+    api.anomalies();
+    api.search("a");
+}
+
+/// Every accessor of `set`, for each of its items and the one past the end.
+fn walk_set(set: &SetData) {
+    set.get_title();
+    set.get_title_source();
+    set.get_style();
+    set.get_content_class();
+
+    let item_count = set.get_item_count();
+    for item_num in 0..item_count {
+        assert!(set.get_item(item_num).is_some());
+        walk_item(set, item_num);
+    }
+    assert!(set.get_item(item_count).is_none());
+    assert!(set.get_home_tile(item_count).is_err());
+    assert!(set.get_tile_aspect_ratios(item_count).is_empty());
+    walk_item(set, item_count);
+}
+
+fn walk_item(set: &SetData, item_num: usize) {
+    set.get_content_id(item_num);
+    set.get_item_title(item_num);
+    set.get_item_content_type(item_num);
+    set.get_item_release_year(item_num);
+    set.get_item_rating(item_num);
+    set.get_item_content_rating(item_num);
+    set.get_item_metadata(item_num);
+    set.get_item_info(item_num);
+    set.get_tile_aspect_ratios(item_num);
+    let _ = set.get_home_tile(item_num);
+    let _ = set.get_hero_image_url(item_num);
+    let _ = set.get_item_video_art(item_num);
+    let _ = set.get_title_treatment(item_num);
+}
@@ -0,0 +1,113 @@
+//! Non-blocking counterpart of the [`Api`], enabled by the `async` feature.
+use crate::model::{HomeResponse, RefSetResponse, Set};
+use crate::{first_set, ref_ids, retry, Api, ApiError};
+use futures::future;
+use image::DynamicImage;
+use log::info;
+use serde::de::DeserializeOwned;
+use std::future::Future;
+
+/// Wraps an [`Api`] and fetches through futures instead of blocking calls.
+///
+/// Every request goes through the same `reqwest::Client` so connections are reused. The futures
+/// need to be polled from a tokio runtime. Once the home data is loaded the [`Api`] is available
+/// through [`AsyncApi::api`] for everything that doesn't hit the network, like [`Api::get_set`].
+pub struct AsyncApi {
+    client: reqwest::Client,
+    api: Api,
+}
+
+impl Default for AsyncApi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AsyncApi {
+    /// New up an empty [`AsyncApi`]. To populate call [`AsyncApi::load_home_data`].
+    pub fn new() -> Self {
+        Self::with_api(Api::new())
+    }
+
+    /// Wraps `api`, e.g. one built with a custom [`ImageCache`](crate::ImageCache) and
+    /// [`ImageOptions`](crate::ImageOptions).
+    pub fn with_api(api: Api) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api,
+        }
+    }
+
+    /// The wrapped [`Api`].
+    pub fn api(&self) -> &Api {
+        &self.api
+    }
+
+    /// Unwraps the [`Api`], e.g. once the home data is loaded.
+    pub fn into_api(self) -> Api {
+        self.api
+    }
+
+    /// Fetch the home data. Same as [`Api::load_home_data`] except that the `SetRef` containers are
+    /// resolved concurrently.
+    pub async fn load_home_data(&mut self) -> Result<(), ApiError> {
+        let resp = self
+            .get_json::<HomeResponse>(&self.api.home_fetcher().home_url())
+            .await?;
+
+        let resolved =
+            future::join_all(ref_ids(&resp).map(|ref_id| self.resolve_ref_set(ref_id))).await;
+        for (ref_id, res) in ref_ids(&resp).zip(resolved) {
+            match res {
+                Ok(set) => {
+                    self.api.ref_sets.insert(ref_id.clone(), set);
+                }
+                Err(e) => info!("Could not resolve ref set '{}': {}", ref_id, e),
+            }
+        }
+        self.api.home_data.replace(resp);
+
+        Ok(())
+    }
+
+    /// Fetch the set referenced by a `SetRef` container, see [`Api::resolve_ref_set`].
+    pub async fn resolve_ref_set(&self, ref_id: &str) -> Result<Set, ApiError> {
+        let resp = self
+            .get_json::<RefSetResponse>(&self.api.home_fetcher().ref_set_url(ref_id))
+            .await?;
+        first_set(ref_id, resp)
+    }
+
+    /// Fetch and parse the json found at `url`, as per the [`RetryPolicy`](crate::RetryPolicy) of the [`Api`].
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, ApiError> {
+        let policy = self.api.retry_policy();
+        policy
+            .run_async(url, || async move {
+                Ok(retry::get_async(&self.client, url, policy.timeout)
+                    .await?
+                    .json::<T>()
+                    .await?)
+            })
+            .await
+    }
+
+    /// Fetch the tile image of the item `item_num` of the set `set_idx`, going through the
+    /// [`ImageCache`](crate::ImageCache) like [`SetData::get_home_tile_image`](crate::SetData::get_home_tile_image).
+    ///
+    /// The url is resolved right away so the returned future doesn't borrow the [`AsyncApi`] and can
+    /// be spawned, several of them can run at once.
+    pub fn get_home_tile_image(
+        &self,
+        set_idx: usize,
+        item_num: usize,
+    ) -> impl Future<Output = Result<DynamicImage, ApiError>> + Send + 'static {
+        let url = self
+            .api
+            .get_set(set_idx)
+            .ok_or_else(|| ApiError::missing(format!("containers[{}]", set_idx)))
+            .and_then(|set_data| set_data.get_home_tile_url(item_num));
+        let image_cache = self.api.image_cache();
+        let client = self.client.clone();
+        async move { image_cache.fetch_image_async(client, url?).await }
+    }
+}
@@ -0,0 +1,178 @@
+//! Size of the tile artwork picked after the download throughput.
+use crate::ImageOptions;
+use log::info;
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Number of downloads the throughput is measured over.
+const WINDOW: usize = 16;
+/// Downloads smaller than this, in bytes, are left out. They take about the latency of the
+/// connection whatever its bandwidth, e.g. the tile previews, and would drag the throughput down.
+const MIN_SAMPLE_BYTES: usize = 8 * 1024;
+/// Downloads needed before leaving the [`ImageTier::Medium`] tier the monitor starts at.
+const MIN_SAMPLES: usize = 4;
+/// Throughput, in bytes per second, above which [`ImageTier::Medium`] is picked over [`ImageTier::Low`].
+const MEDIUM_THROUGHPUT: f64 = 256.0 * 1024.0;
+/// Throughput, in bytes per second, above which [`ImageTier::High`] is picked over [`ImageTier::Medium`].
+const HIGH_THROUGHPUT: f64 = 2.0 * 1024.0 * 1024.0;
+/// How far past a threshold the throughput has to go before the tier changes, as a fraction of the
+/// threshold. Keeps a throughput hovering around a threshold from flapping between two tiers.
+const HYSTERESIS: f64 = 0.25;
+/// Jpeg quality the [`ImageTier::Low`] tier is capped at.
+const LOW_TIER_QUALITY: u8 = 60;
+
+/// Size and quality of the tile artwork, relative to the [`ImageOptions`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ImageTier {
+    /// Half the width, with a jpeg quality of at most 60.
+    Low,
+    /// The [`ImageOptions`] as they are.
+    Medium,
+    /// One and a half times the width.
+    High,
+}
+
+impl ImageTier {
+    /// The `options` with the width and quality of this tier.
+    pub fn apply(self, options: &ImageOptions) -> ImageOptions {
+        let (width, quality) = match self {
+            ImageTier::Low => (options.width / 2, options.quality.min(LOW_TIER_QUALITY)),
+            ImageTier::Medium => (options.width, options.quality),
+            ImageTier::High => (options.width * 3 / 2, options.quality),
+        };
+        ImageOptions {
+            width,
+            quality,
+            ..options.clone()
+        }
+    }
+
+    /// The tier for a `throughput` in bytes per second, coming from the `current` one.
+    fn for_throughput(throughput: f64, current: ImageTier) -> ImageTier {
+        // The bar is lowered to stay at a tier and raised to get to it.
+        let reaches = |threshold: f64, tier: ImageTier| {
+            if current >= tier {
+                throughput >= threshold * (1.0 - HYSTERESIS)
+            } else {
+                throughput > threshold * (1.0 + HYSTERESIS)
+            }
+        };
+        if reaches(HIGH_THROUGHPUT, ImageTier::High) {
+            ImageTier::High
+        } else if reaches(MEDIUM_THROUGHPUT, ImageTier::Medium) {
+            ImageTier::Medium
+        } else {
+            ImageTier::Low
+        }
+    }
+}
+
+impl FromStr for ImageTier {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(ImageTier::Low),
+            "medium" => Ok(ImageTier::Medium),
+            "high" => Ok(ImageTier::High),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for ImageTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ImageTier::Low => "low",
+            ImageTier::Medium => "medium",
+            ImageTier::High => "high",
+        })
+    }
+}
+
+/// How the [`ImageTier`] of the tile artwork is picked, see [`ImageOptions::tier`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TierPolicy {
+    /// After the throughput measured by the [`BandwidthMonitor`] of the [`ImageCache`](crate::ImageCache).
+    #[default]
+    Adaptive,
+    /// Always the given tier.
+    Fixed(ImageTier),
+}
+
+/// Measures the throughput of the last downloads and picks the [`ImageTier`] after it.
+///
+/// Each download is measured on its own, from the request to the last byte, retries included. With
+/// several downloads at once that's less than the total bandwidth, but it's what tells how long a
+/// tile takes to show up.
+#[derive(Debug)]
+pub struct BandwidthMonitor {
+    state: Mutex<MonitorState>,
+}
+
+#[derive(Debug)]
+struct MonitorState {
+    /// Size and duration of the last [`WINDOW`] downloads, oldest first.
+    samples: VecDeque<(usize, Duration)>,
+    tier: ImageTier,
+}
+
+impl Default for BandwidthMonitor {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(MonitorState {
+                samples: VecDeque::with_capacity(WINDOW),
+                tier: ImageTier::Medium,
+            }),
+        }
+    }
+}
+
+impl BandwidthMonitor {
+    /// Remembers a download of `bytes` that took `elapsed`, which may change the tier. Downloads
+    /// under [`MIN_SAMPLE_BYTES`] are ignored.
+    pub fn record(&self, bytes: usize, elapsed: Duration) {
+        if bytes < MIN_SAMPLE_BYTES {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.samples.len() == WINDOW {
+            state.samples.pop_front();
+        }
+        state.samples.push_back((bytes, elapsed));
+        if let Some(throughput) = throughput(&state.samples) {
+            let tier = ImageTier::for_throughput(throughput, state.tier);
+            if tier != state.tier {
+                info!(
+                    "Artwork tier {} -> {} at {:.0} KB/s",
+                    state.tier,
+                    tier,
+                    throughput / 1024.0
+                );
+                state.tier = tier;
+            }
+        }
+    }
+
+    /// Throughput of the last downloads, in bytes per second. None until there are enough of them.
+    pub fn throughput(&self) -> Option<f64> {
+        throughput(&self.state.lock().unwrap().samples)
+    }
+
+    /// The tier picked after the [`throughput`](BandwidthMonitor::throughput).
+    pub fn tier(&self) -> ImageTier {
+        self.state.lock().unwrap().tier
+    }
+}
+
+fn throughput(samples: &VecDeque<(usize, Duration)>) -> Option<f64> {
+    if samples.len() < MIN_SAMPLES {
+        return None;
+    }
+    let bytes: usize = samples.iter().map(|(bytes, _)| bytes).sum();
+    let elapsed: Duration = samples.iter().map(|(_, elapsed)| *elapsed).sum();
+    Some(bytes as f64 / elapsed.as_secs_f64().max(1e-6))
+}
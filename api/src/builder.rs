@@ -0,0 +1,64 @@
+//! Step by step construction of an [`Api`] whose client needs setting up.
+use crate::{
+    Api, ApiError, ClientOptions, ImageCache, Proxy, ReqwestTransport, TlsBackend,
+    DEFAULT_IMAGE_CACHE_BYTES,
+};
+use std::path::PathBuf;
+
+/// Builds an [`Api`] going through a [`ReqwestTransport`] set up as per its [`ClientOptions`],
+/// e.g. behind a proxy or trusting another certificate authority. See [`Api::builder`].
+///
+/// The rest of the setup is done on the [`Api`] it builds, e.g. [`Api::with_base_url`].
+#[derive(Debug, Default)]
+pub struct ApiBuilder {
+    image_cache: Option<ImageCache>,
+    client_options: ClientOptions,
+}
+
+impl ApiBuilder {
+    /// Same as [`Api::builder`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses the given `image_cache`, see [`Api::with_image_cache`]. A cache of
+    /// [`DEFAULT_IMAGE_CACHE_BYTES`] otherwise.
+    pub fn image_cache(mut self, image_cache: ImageCache) -> Self {
+        self.image_cache = Some(image_cache);
+        self
+    }
+
+    /// Replaces all the [`ClientOptions`], the default ones otherwise.
+    pub fn client_options(mut self, client_options: ClientOptions) -> Self {
+        self.client_options = client_options;
+        self
+    }
+
+    /// Sets the [`Proxy`] of the requests, [`Proxy::System`] otherwise.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.client_options.proxy = proxy;
+        self
+    }
+
+    /// Trusts the certificate in the PEM file at `path` on top of the others. Can be called
+    /// several times.
+    pub fn root_certificate(mut self, path: impl Into<PathBuf>) -> Self {
+        self.client_options.root_certificates.push(path.into());
+        self
+    }
+
+    /// Sets the [`TlsBackend`], [`TlsBackend::Native`] otherwise.
+    pub fn tls_backend(mut self, tls_backend: TlsBackend) -> Self {
+        self.client_options.tls_backend = tls_backend;
+        self
+    }
+
+    /// Makes the client and the [`Api`] going through it. Fails as per [`ReqwestTransport::new`].
+    pub fn build(self) -> Result<Api, ApiError> {
+        let client = ReqwestTransport::new(&self.client_options)?;
+        let image_cache = self
+            .image_cache
+            .unwrap_or_else(|| ImageCache::new(DEFAULT_IMAGE_CACHE_BYTES));
+        Ok(Api::with_image_cache(image_cache).with_client(client))
+    }
+}
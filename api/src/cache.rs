@@ -0,0 +1,544 @@
+//! In-memory cache for the fetched artwork.
+use crate::disk_cache::fnv1a;
+use crate::folder::file_path;
+#[cfg(feature = "async")]
+use crate::retry;
+use crate::retry::RetryPolicy;
+use crate::{
+    ApiError, ArtworkFormat, BandwidthMonitor, Conditional, DiskCache, ReqwestTransport, Transport,
+    Validators,
+};
+use bytes::Bytes;
+use image::io::Reader as ImageReader;
+use image::DynamicImage;
+use log::{debug, info};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Instant;
+use std::{fs, io};
+
+/// Default byte budget of the [`ImageCache`].
+pub const DEFAULT_IMAGE_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+struct CacheEntry {
+    bytes: Bytes,
+    /// Value of [`CacheState::tick`] the last time this entry was used.
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    used_bytes: usize,
+    tick: u64,
+}
+
+/// A fetch by [`ImageCache::fetch_bytes`] or [`ImageCache::fetch_image`], which the other callers
+/// asking for the same url wait on.
+struct Download<T> {
+    state: Mutex<DownloadState<T>>,
+    done: Condvar,
+}
+
+struct DownloadState<T> {
+    /// Number of callers waiting on the download besides the one doing it.
+    waiters: usize,
+    /// Set once the download is over, only if someone waits on it.
+    result: Option<Result<T, Arc<ApiError>>>,
+}
+
+/// The downloads under way, keyed by url.
+type InFlight<T> = Mutex<HashMap<String, Arc<Download<T>>>>;
+
+impl<T: Clone> Download<T> {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(DownloadState {
+                waiters: 0,
+                result: None,
+            }),
+            done: Condvar::new(),
+        }
+    }
+
+    /// Blocks until the caller doing the download is done, then returns a copy of its result.
+    fn wait(&self) -> Result<T, ApiError> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            match &state.result {
+                Some(Ok(value)) => return Ok(value.clone()),
+                Some(Err(e)) => return Err(ApiError::Shared(Arc::clone(e))),
+                None => state = self.done.wait(state).unwrap(),
+            }
+        }
+    }
+
+    /// Hands the `result` of the download to the callers waiting on it, if any. Returns what the
+    /// caller that did the download gets: the `result` itself when nobody waits, a copy otherwise.
+    fn finish(&self, result: Result<T, ApiError>) -> Result<T, ApiError> {
+        let mut state = self.state.lock().unwrap();
+        if state.waiters == 0 {
+            return result;
+        }
+        let (shared, own) = match result {
+            Ok(value) => (Ok(value.clone()), Ok(value)),
+            Err(e) => {
+                let e = Arc::new(e);
+                (Err(Arc::clone(&e)), Err(ApiError::Shared(e)))
+            }
+        };
+        state.result = Some(shared);
+        self.done.notify_all();
+        own
+    }
+}
+
+/// Runs `fetch` for `url`, unless a call for the same url is already under way in `in_flight`, in
+/// which case its result is waited for instead.
+fn coalesce<T: Clone>(
+    in_flight: &InFlight<T>,
+    url: &str,
+    fetch: impl FnOnce() -> Result<T, ApiError>,
+) -> Result<T, ApiError> {
+    let download = {
+        let mut in_flight = in_flight.lock().unwrap();
+        match in_flight.get(url) {
+            Some(download) => {
+                download.state.lock().unwrap().waiters += 1;
+                Some(Arc::clone(download))
+            }
+            None => {
+                in_flight.insert(url.to_string(), Arc::new(Download::new()));
+                None
+            }
+        }
+    };
+    if let Some(download) = download {
+        debug!("Waiting on the download of {} in flight", url);
+        return download.wait();
+    }
+
+    let result = fetch();
+    // Nobody can start waiting on it once it's out of the map.
+    let download = in_flight.lock().unwrap().remove(url);
+    match download {
+        Some(download) => download.finish(result),
+        None => result,
+    }
+}
+
+/// Least recently used cache of the encoded image bytes keyed by url.
+///
+/// The encoded (jpeg) bytes are kept instead of the decoded image since they are roughly an order
+/// of magnitude smaller. Once `max_bytes` is exceeded the least recently used entries are evicted.
+///
+/// Optionally backed by a [`DiskCache`] which is consulted on a memory miss before going to the network.
+/// The stale images found there are revalidated with a conditional request, only downloaded again if
+/// they changed.
+///
+/// Network calls go through its [`Transport`] and follow its [`RetryPolicy`]. Once [`offline`](ImageCache::offline) the network is
+/// never hit.
+///
+/// It's safe to share between threads, the lock is never held while doing network calls. Threads
+/// fetching the same url at the same time share a single download, see [`ImageCache::fetch_bytes`]
+/// and [`ImageCache::fetch_image`].
+pub struct ImageCache {
+    max_bytes: usize,
+    state: Mutex<CacheState>,
+    /// The [`ImageCache::fetch_bytes`] calls under way.
+    bytes_in_flight: InFlight<Bytes>,
+    /// The [`ImageCache::fetch_image`] calls under way.
+    images_in_flight: InFlight<DynamicImage>,
+    disk: Option<DiskCache>,
+    /// Behind a lock so the [`Api`](crate::Api) can change it once the cache is shared.
+    retry_policy: Mutex<RetryPolicy>,
+    /// Behind a lock for the same reason as the `retry_policy`.
+    transport: Mutex<Arc<dyn Transport>>,
+    /// Sample images served in place of the network while offline, None when online.
+    offline_samples: Option<Vec<PathBuf>>,
+    /// Measures the downloads from the network.
+    bandwidth: BandwidthMonitor,
+}
+
+impl ImageCache {
+    /// New up an empty cache that holds at most `max_bytes`.
+    pub fn new(max_bytes: usize) -> Self {
+        Self::with_disk_cache(max_bytes, None)
+    }
+
+    /// Same as [`ImageCache::new`] but backed by the given `disk` cache.
+    pub fn with_disk_cache(max_bytes: usize, disk: Option<DiskCache>) -> Self {
+        Self {
+            max_bytes,
+            state: Mutex::new(CacheState::default()),
+            bytes_in_flight: Mutex::new(HashMap::new()),
+            images_in_flight: Mutex::new(HashMap::new()),
+            disk,
+            retry_policy: Mutex::new(RetryPolicy::default()),
+            transport: Mutex::new(Arc::new(ReqwestTransport::default())),
+            offline_samples: None,
+            bandwidth: BandwidthMonitor::default(),
+        }
+    }
+
+    /// Sets the [`Transport`] used when fetching from the network.
+    pub fn with_transport(self, transport: Arc<dyn Transport>) -> Self {
+        self.set_transport(transport);
+        self
+    }
+
+    /// Same as [`ImageCache::with_transport`] on a shared cache, affects the fetches started afterwards.
+    pub fn set_transport(&self, transport: Arc<dyn Transport>) {
+        *self.transport.lock().unwrap() = transport;
+    }
+
+    /// Never hits the network. Images missing from memory and from the [`DiskCache`] are replaced by
+    /// one of the png/jpeg files found in `samples_dir`, picked after the url so a given tile always
+    /// gets the same one. Without samples they fail with [`ApiError::Offline`].
+    pub fn offline(mut self, samples_dir: Option<&Path>) -> Self {
+        let samples = match samples_dir.map(list_samples) {
+            Some(Ok(samples)) => samples,
+            Some(Err(e)) => {
+                info!("Could not list the sample images: {}", e);
+                Vec::new()
+            }
+            None => Vec::new(),
+        };
+        debug!("Offline with {} sample images", samples.len());
+        self.offline_samples = Some(samples);
+        self
+    }
+
+    /// Returns true if the network is never hit, see [`ImageCache::offline`].
+    pub fn is_offline(&self) -> bool {
+        self.offline_samples.is_some()
+    }
+
+    /// Throughput of the recent downloads from the network, see [`TierPolicy`](crate::TierPolicy).
+    pub fn bandwidth(&self) -> &BandwidthMonitor {
+        &self.bandwidth
+    }
+
+    /// Sets the [`RetryPolicy`] used when fetching from the network.
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        self.set_retry_policy(retry_policy);
+        self
+    }
+
+    /// Same as [`ImageCache::with_retry_policy`] on a shared cache, affects the fetches started afterwards.
+    pub fn set_retry_policy(&self, retry_policy: RetryPolicy) {
+        *self.retry_policy.lock().unwrap() = retry_policy;
+    }
+
+    /// The [`RetryPolicy`] used when fetching from the network.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        *self.retry_policy.lock().unwrap()
+    }
+
+    /// Returns the cached bytes for `url` and marks them as recently used.
+    pub fn get(&self, url: &str) -> Option<Bytes> {
+        let mut state = self.state.lock().unwrap();
+        state.tick += 1;
+        let tick = state.tick;
+        state.entries.get_mut(url).map(|entry| {
+            entry.last_used = tick;
+            entry.bytes.clone()
+        })
+    }
+
+    /// Stores `bytes` for `url`, evicting the least recently used entries if needed.
+    /// Entries bigger than the whole budget are not stored.
+    pub fn insert(&self, url: &str, bytes: Bytes) {
+        if bytes.len() > self.max_bytes {
+            info!(
+                "Not caching {}, {} bytes exceeds the budget",
+                url,
+                bytes.len()
+            );
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.tick += 1;
+        let entry = CacheEntry {
+            last_used: state.tick,
+            bytes,
+        };
+        state.used_bytes += entry.bytes.len();
+        if let Some(prev) = state.entries.insert(url.to_string(), entry) {
+            state.used_bytes -= prev.bytes.len();
+        }
+
+        while state.used_bytes > self.max_bytes {
+            let lru_key = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            match lru_key.and_then(|key| state.entries.remove(&key).map(|e| (key, e))) {
+                Some((key, evicted)) => {
+                    debug!("Evicted {} from the image cache", key);
+                    state.used_bytes -= evicted.bytes.len();
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Number of bytes currently stored.
+    pub fn used_bytes(&self) -> usize {
+        self.state.lock().unwrap().used_bytes
+    }
+
+    /// Number of images currently stored.
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    /// Returns true if no image is stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Fetch and decode the image found at `url`.
+    ///
+    /// Looks in memory first, then in the [`DiskCache`] (if any) and only then goes to the network.
+    ///
+    /// While an image is being fetched, e.g. an item showing up in two sets, the other calls for the
+    /// same url wait for it instead of downloading and decoding it again, and get a copy of the
+    /// decoded image. They fail with [`ApiError::Shared`] if it fails.
+    pub fn fetch_image(&self, url: &str) -> Result<DynamicImage, ApiError> {
+        coalesce(&self.images_in_flight, url, || {
+            self.fetch_bytes(url).and_then(|buf| decode_image(&buf))
+        })
+    }
+
+    /// Same as [`ImageCache::fetch_image`] without the decoding, which is left to the caller, e.g.
+    /// to do it on another thread with [`decode_image`]. The concurrent calls share the download
+    /// the same way, with the ones of [`ImageCache::fetch_image`] too.
+    pub fn fetch_bytes(&self, url: &str) -> Result<Bytes, ApiError> {
+        coalesce(&self.bytes_in_flight, url, || self.cached_bytes(url))
+    }
+
+    /// Makes sure the bytes of the image at `url` are in memory so a later [`ImageCache::fetch_image`]
+    /// only has to decode them. Same lookup order as [`ImageCache::fetch_image`].
+    pub fn prefetch(&self, url: &str) -> Result<(), ApiError> {
+        self.fetch_bytes(url).map(|_| ())
+    }
+
+    /// Returns the encoded bytes of the image at `url`, from memory, the [`DiskCache`] or the network.
+    fn cached_bytes(&self, url: &str) -> Result<Bytes, ApiError> {
+        if let Some(buf) = self.get(url) {
+            return Ok(buf);
+        }
+        // Images on disk are read as is, offline or not, and not copied to the `DiskCache`.
+        if let Some(path) = file_path(url) {
+            let buf = Bytes::from(fs::read(path)?);
+            self.insert(url, buf.clone());
+            return Ok(buf);
+        }
+
+        let disk_hit = self.disk.as_ref().and_then(|disk| disk.lookup(url));
+        let buf = match disk_hit {
+            Some(entry) if entry.fresh => Bytes::from(entry.bytes),
+            // Stale is better than nothing without network.
+            Some(entry) if self.is_offline() => Bytes::from(entry.bytes),
+            None if self.is_offline() => self.sample_bytes(url)?,
+            stale => {
+                let validators = stale
+                    .as_ref()
+                    .map_or_else(Validators::default, |entry| entry.validators.clone());
+                let transport = Arc::clone(&self.transport.lock().unwrap());
+                let started = Instant::now();
+                match fetch_bytes_if_changed(&*transport, url, &validators, &self.retry_policy())? {
+                    Conditional::NotModified => {
+                        debug!("{} not modified", url);
+                        let entry = stale.ok_or_else(|| ApiError::HttpStatus { code: 304 })?;
+                        if let Some(disk) = self.disk.as_ref() {
+                            disk.revalidate(url, &validators);
+                        }
+                        Bytes::from(entry.bytes)
+                    }
+                    Conditional::Modified(buf, validators) => {
+                        self.bandwidth.record(buf.len(), started.elapsed());
+                        if let Some(disk) = self.disk.as_ref() {
+                            disk.insert_validated(url, &buf, &validators);
+                        }
+                        buf
+                    }
+                }
+            }
+        };
+        self.insert(url, buf.clone());
+        Ok(buf)
+    }
+
+    /// The sample image standing for `url` while offline.
+    fn sample_bytes(&self, url: &str) -> Result<Bytes, ApiError> {
+        let samples = self.offline_samples.as_deref().unwrap_or_default();
+        if samples.is_empty() {
+            return Err(ApiError::Offline {
+                url: url.to_string(),
+            });
+        }
+        let path = &samples[(fnv1a(url.as_bytes()) % samples.len() as u64) as usize];
+        Ok(Bytes::from(fs::read(path)?))
+    }
+}
+
+#[cfg(feature = "async")]
+impl ImageCache {
+    /// Same as [`ImageCache::fetch_image`] but the network is hit through `client`, and the
+    /// [`DiskCache`] and the decoding run on tokio's blocking thread pool. The downloads are not
+    /// shared with the other calls, and the stale images of the [`DiskCache`] are downloaded again
+    /// instead of being revalidated.
+    ///
+    /// Takes an `Arc` so the future is `'static` and can be spawned.
+    pub async fn fetch_image_async(
+        self: Arc<Self>,
+        client: reqwest::Client,
+        url: String,
+    ) -> Result<DynamicImage, ApiError> {
+        if let Some(buf) = self.get(&url) {
+            return blocking(move || decode_image(&buf)).await;
+        }
+
+        let disk_hit = {
+            let cache = Arc::clone(&self);
+            let url = url.clone();
+            blocking(move || cache.disk.as_ref().and_then(|disk| disk.get(&url))).await
+        };
+        let buf = match disk_hit {
+            Some(buf) => Bytes::from(buf),
+            None if self.is_offline() => {
+                let cache = Arc::clone(&self);
+                let url = url.clone();
+                blocking(move || cache.sample_bytes(&url)).await?
+            }
+            None => {
+                let started = Instant::now();
+                let buf = fetch_bytes_async(&client, &url, &self.retry_policy()).await?;
+                self.bandwidth.record(buf.len(), started.elapsed());
+                if self.disk.is_some() {
+                    let cache = Arc::clone(&self);
+                    let (url, buf) = (url.clone(), buf.clone());
+                    blocking(move || {
+                        if let Some(disk) = cache.disk.as_ref() {
+                            disk.insert(&url, &buf);
+                        }
+                    })
+                    .await;
+                }
+                buf
+            }
+        };
+        self.insert(&url, buf.clone());
+        blocking(move || decode_image(&buf)).await
+    }
+}
+
+impl std::fmt::Debug for ImageCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ImageCache")
+            .field("max_bytes", &self.max_bytes)
+            .field("used_bytes", &self.used_bytes())
+            .field("in_flight", &self.bytes_in_flight.lock().unwrap().len())
+            .field("disk", &self.disk)
+            .field("retry_policy", &self.retry_policy())
+            .field("offline_samples", &self.offline_samples)
+            .field("bandwidth", &self.bandwidth)
+            .finish()
+    }
+}
+
+/// Fetch the raw bytes found at `url` through the `transport`, retrying as per the `retry_policy`.
+pub(crate) fn fetch_bytes(
+    transport: &dyn Transport,
+    url: &str,
+    retry_policy: &RetryPolicy,
+) -> Result<Bytes, ApiError> {
+    retry_policy.run(url, || transport.get_bytes(url, retry_policy.timeout))
+}
+
+/// Same as [`fetch_bytes`] with a conditional request, see [`Transport::get_bytes_if_changed`].
+fn fetch_bytes_if_changed(
+    transport: &dyn Transport,
+    url: &str,
+    validators: &Validators,
+    retry_policy: &RetryPolicy,
+) -> Result<Conditional<Bytes>, ApiError> {
+    retry_policy.run(url, || {
+        transport.get_bytes_if_changed(url, retry_policy.timeout, validators)
+    })
+}
+
+/// Same as [`fetch_bytes`] through the async `client`.
+#[cfg(feature = "async")]
+pub(crate) async fn fetch_bytes_async(
+    client: &reqwest::Client,
+    url: &str,
+    retry_policy: &RetryPolicy,
+) -> Result<Bytes, ApiError> {
+    retry_policy
+        .run_async(url, || async move {
+            Ok(retry::get_async(client, url, retry_policy.timeout)
+                .await?
+                .bytes()
+                .await?)
+        })
+        .await
+}
+
+/// Runs `f` on tokio's blocking thread pool, for file system access and decoding.
+#[cfg(feature = "async")]
+async fn blocking<T, F>(f: F) -> T
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .expect("blocking task panicked")
+}
+
+/// The png and jpeg files directly under `dir`, sorted so the pick doesn't depend on the file system.
+fn list_samples(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut samples = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ["png", "jpg", "jpeg"].contains(&ext.to_lowercase().as_str()))
+        })
+        .collect::<Vec<_>>();
+    samples.sort();
+    Ok(samples)
+}
+
+/// Decode the image `buf`, the format is told from its content (see [`ArtworkFormat::sniff`]),
+/// not from the url it came from. The other formats, e.g. the images of a [`FolderProvider`](crate::FolderProvider),
+/// are guessed by the `image` crate.
+///
+/// With the `fast-jpeg` feature enabled the jpeg images are decoded by `zune-jpeg`, the `image`
+/// crate only decoding the ones it can't.
+pub fn decode_image(buf: &[u8]) -> Result<DynamicImage, ApiError> {
+    let format = ArtworkFormat::sniff(buf);
+    #[cfg(feature = "fast-jpeg")]
+    if format == Some(ArtworkFormat::Jpeg) {
+        match crate::fast_jpeg::decode(buf) {
+            Ok(img) => return Ok(img),
+            Err(e) => debug!("Decoding with the image crate instead of zune-jpeg: {}", e),
+        }
+    }
+    let mut reader = ImageReader::new(Cursor::new(buf));
+    match format {
+        Some(format) => reader.set_format(format.image_format()),
+        None => {
+            reader = reader
+                .with_guessed_format()
+                .expect("reading from memory can't fail")
+        }
+    }
+    Ok(reader.decode()?)
+}
@@ -0,0 +1,205 @@
+//! Fault injection, with the `chaos` feature enabled.
+//!
+//! [`ChaosTransport`] wraps another [`Transport`] and makes some of its requests slow or fail like
+//! a flaky network would, as often as the [`FaultRates`] say. That way the placeholders and retries
+//! of the UI can be checked, and that nothing panics, without a bad connection at hand.
+use crate::{ApiError, Conditional, Transport, Validators};
+use bytes::Bytes;
+use log::debug;
+use serde_json::Value;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How often each fault is injected, from 0 (never) to 1 (every request). A request gets one fault
+/// at most, so the rates add up to 1 at most.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FaultRates {
+    /// Extra latency of every request, drawn between 0 and this. Past the timeout of the request
+    /// it times out.
+    pub max_latency: Duration,
+    /// Requests hanging until their timeout, which then fail with [`ApiError::Timeout`].
+    pub timeout: f64,
+    /// Requests answered with a `500 Internal Server Error`.
+    pub server_error: f64,
+    /// Responses cut short, they then fail to parse or decode.
+    pub truncated: f64,
+    /// Seed of the draws, the same seed injects the same faults in the same order given the same
+    /// requests. Drawn from the clock if None.
+    pub seed: Option<u64>,
+}
+
+impl FromStr for FaultRates {
+    type Err = ();
+
+    /// Parses comma separated `name=value` pairs, e.g. `latency=800,timeout=0.05,error=0.1`. The
+    /// names are `latency` (in milliseconds), `timeout`, `error`, `truncate` and `seed`, the ones
+    /// left out are 0.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rates = FaultRates::default();
+        for pair in s.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let (name, value) = pair.split_once('=').ok_or(())?;
+            let rate = || match value.trim().parse::<f64>() {
+                Ok(rate) if (0.0..=1.0).contains(&rate) => Ok(rate),
+                _ => Err(()),
+            };
+            match name.trim() {
+                "latency" => {
+                    rates.max_latency = Duration::from_millis(value.trim().parse().map_err(|_| ())?)
+                }
+                "timeout" => rates.timeout = rate()?,
+                "error" => rates.server_error = rate()?,
+                "truncate" => rates.truncated = rate()?,
+                "seed" => rates.seed = Some(value.trim().parse().map_err(|_| ())?),
+                _ => return Err(()),
+            }
+        }
+        if rates.timeout + rates.server_error + rates.truncated > 1.0 {
+            return Err(());
+        }
+        Ok(rates)
+    }
+}
+
+/// What happens to a request.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Fault {
+    Timeout,
+    ServerError,
+    Truncated,
+    None,
+}
+
+/// [`Transport`] injecting faults into the requests of the `inner` one, see the [module docs](self).
+#[derive(Debug)]
+pub struct ChaosTransport<T> {
+    inner: T,
+    rates: FaultRates,
+    /// State of the xorshift generator the faults are drawn from.
+    rng: Mutex<u64>,
+}
+
+impl<T: Transport> ChaosTransport<T> {
+    /// Injects faults into the requests of `inner` as per the `rates`.
+    pub fn new(inner: T, rates: FaultRates) -> Self {
+        let seed = rates.seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+        });
+        Self {
+            inner,
+            rates,
+            // Xorshift gets stuck on 0.
+            rng: Mutex::new(seed.max(1)),
+        }
+    }
+
+    fn next(&self) -> u64 {
+        let mut state = self.rng.lock().unwrap();
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    /// A number from 0 to 1 excluded.
+    fn unit(&self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Waits for the latency drawn for the request of `url` and picks its [`Fault`]. Fails if the
+    /// request is to time out or to get a `500`, after the wait it would take.
+    fn inject(&self, url: &str, timeout: Duration) -> Result<Fault, ApiError> {
+        let latency = self.rates.max_latency.mul_f64(self.unit());
+        let draw = self.unit();
+        let fault = if draw < self.rates.timeout || latency >= timeout {
+            Fault::Timeout
+        } else if draw < self.rates.timeout + self.rates.server_error {
+            Fault::ServerError
+        } else if draw < self.rates.timeout + self.rates.server_error + self.rates.truncated {
+            Fault::Truncated
+        } else {
+            Fault::None
+        };
+        if fault != Fault::None {
+            debug!("injecting {:?} into {}", fault, url);
+        }
+        match fault {
+            Fault::Timeout => {
+                thread::sleep(timeout);
+                Err(ApiError::Timeout {
+                    url: url.to_string(),
+                })
+            }
+            Fault::ServerError => {
+                thread::sleep(latency);
+                Err(ApiError::HttpStatus { code: 500 })
+            }
+            _ => {
+                thread::sleep(latency);
+                Ok(fault)
+            }
+        }
+    }
+
+    /// The first bytes of `body`, fewer than there are.
+    fn truncate(&self, body: &[u8]) -> Bytes {
+        let len = (body.len() as f64 * self.unit()) as usize;
+        Bytes::copy_from_slice(&body[..len.min(body.len().saturating_sub(1))])
+    }
+}
+
+impl<T: Transport> Transport for ChaosTransport<T> {
+    fn get_json(&self, url: &str, timeout: Duration) -> Result<Value, ApiError> {
+        let fault = self.inject(url, timeout)?;
+        let json = self.inner.get_json(url, timeout)?;
+        if fault != Fault::Truncated {
+            return Ok(json);
+        }
+        let body = serde_json::to_vec(&json)?;
+        Ok(serde_json::from_slice(&self.truncate(&body))?)
+    }
+
+    fn get_json_if_changed(
+        &self,
+        url: &str,
+        timeout: Duration,
+        validators: &Validators,
+    ) -> Result<Conditional<Value>, ApiError> {
+        let fault = self.inject(url, timeout)?;
+        match self.inner.get_json_if_changed(url, timeout, validators)? {
+            Conditional::Modified(json, validators) if fault == Fault::Truncated => {
+                let body = serde_json::to_vec(&json)?;
+                let json = serde_json::from_slice(&self.truncate(&body))?;
+                Ok(Conditional::Modified(json, validators))
+            }
+            fetched => Ok(fetched),
+        }
+    }
+
+    fn get_bytes_if_changed(
+        &self,
+        url: &str,
+        timeout: Duration,
+        validators: &Validators,
+    ) -> Result<Conditional<Bytes>, ApiError> {
+        let fault = self.inject(url, timeout)?;
+        match self.inner.get_bytes_if_changed(url, timeout, validators)? {
+            Conditional::Modified(bytes, validators) if fault == Fault::Truncated => {
+                Ok(Conditional::Modified(self.truncate(&bytes), validators))
+            }
+            fetched => Ok(fetched),
+        }
+    }
+
+    fn get_bytes(&self, url: &str, timeout: Duration) -> Result<Bytes, ApiError> {
+        let fault = self.inject(url, timeout)?;
+        let bytes = self.inner.get_bytes(url, timeout)?;
+        if fault != Fault::Truncated {
+            return Ok(bytes);
+        }
+        Ok(self.truncate(&bytes))
+    }
+}
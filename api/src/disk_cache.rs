@@ -0,0 +1,175 @@
+//! On-disk cache for the fetched artwork.
+use crate::Validators;
+use log::{debug, info};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Default time to live of the entries in the [`DiskCache`].
+pub const DEFAULT_DISK_CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Stores the encoded image bytes under a directory, one file per url.
+///
+/// Files are named after a hash of the url and are considered stale once their modification time
+/// is older than the configured `ttl`, or the `max-age` the image came with (see
+/// [`Validators::max_age`]).
+///
+/// The [`Validators`] of the images are kept next to them, so a stale image can be revalidated
+/// with a conditional request instead of being downloaded again, see [`DiskCache::lookup`].
+#[derive(Debug)]
+pub struct DiskCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl DiskCache {
+    /// New up a cache rooted at `dir`, creating the directory if needed.
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, ttl })
+    }
+
+    /// Cache rooted at `$XDG_CACHE_HOME/helloplus/images` (or `~/.cache/helloplus/images`).
+    /// Returns None if neither location can be resolved or created.
+    pub fn in_default_dir(ttl: Duration) -> Option<Self> {
+        match Self::new(cache_dir()?.join("images"), ttl) {
+            Ok(cache) => Some(cache),
+            Err(e) => {
+                info!("Could not create the disk cache: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Returns the bytes stored for `url` if present and not stale.
+    /// Stale entries that can't be revalidated are removed.
+    pub fn get(&self, url: &str) -> Option<Vec<u8>> {
+        self.lookup(url)
+            .filter(|entry| entry.fresh)
+            .map(|entry| entry.bytes)
+    }
+
+    /// Returns what is stored for `url`, stale or not. Stale entries without [`Validators`] are
+    /// removed, nothing tells whether they changed.
+    pub fn lookup(&self, url: &str) -> Option<DiskEntry> {
+        let path = self.path_for(url);
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        let validators = fs::read(path.with_extension("meta"))
+            .ok()
+            .and_then(|meta| serde_json::from_slice::<Validators>(&meta).ok())
+            .unwrap_or_default();
+        let fresh = age < validators.max_age.unwrap_or(self.ttl);
+        if !fresh && validators.is_empty() {
+            debug!("Disk cache entry for {} is stale", url);
+            self.remove(&path);
+            return None;
+        }
+        Some(DiskEntry {
+            bytes: fs::read(&path).ok()?,
+            validators,
+            fresh,
+        })
+    }
+
+    /// Stores `bytes` for `url`. Errors are logged and otherwise ignored since the cache is best effort.
+    pub fn insert(&self, url: &str, bytes: &[u8]) {
+        self.insert_validated(url, bytes, &Validators::default())
+    }
+
+    /// Same as [`DiskCache::insert`], keeping the `validators` the bytes came with.
+    pub fn insert_validated(&self, url: &str, bytes: &[u8], validators: &Validators) {
+        let path = self.path_for(url);
+        // Written first, the image is what tells whether there is an entry.
+        self.write_meta(url, &path, validators);
+        // Write to a temporary file first so a concurrent reader never sees a partial image.
+        let tmp = path.with_extension(format!("tmp{:?}", std::thread::current().id()));
+        let res = fs::write(&tmp, bytes).and_then(|_| fs::rename(&tmp, &path));
+        if let Err(e) = res {
+            info!("Could not write {} to the disk cache: {}", url, e);
+            let _ = fs::remove_file(&tmp);
+        }
+    }
+
+    /// Marks the entry of `url` as fresh again, the server said it didn't change. The `validators`
+    /// replace the stored ones if it sent any.
+    pub fn revalidate(&self, url: &str, validators: &Validators) {
+        let path = self.path_for(url);
+        if *validators != Validators::default() {
+            self.write_meta(url, &path, validators);
+        }
+        let res = File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        if let Err(e) = res {
+            info!("Could not revalidate {} in the disk cache: {}", url, e);
+        }
+    }
+
+    /// Writes the `validators` of the image at `path` next to it, none removes them.
+    fn write_meta(&self, url: &str, path: &Path, validators: &Validators) {
+        let meta = path.with_extension("meta");
+        if *validators == Validators::default() {
+            let _ = fs::remove_file(&meta);
+            return;
+        }
+        let tmp = path.with_extension(format!("metatmp{:?}", std::thread::current().id()));
+        let res = serde_json::to_vec(validators)
+            .map_err(io::Error::from)
+            .and_then(|contents| fs::write(&tmp, contents))
+            .and_then(|_| fs::rename(&tmp, &meta));
+        if let Err(e) = res {
+            info!(
+                "Could not write the validators of {} to the disk cache: {}",
+                url, e
+            );
+            let _ = fs::remove_file(&tmp);
+        }
+    }
+
+    /// Removes the image at `path` along with its validators.
+    fn remove(&self, path: &Path) {
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_file(path.with_extension("meta"));
+    }
+
+    fn path_for(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.img", fnv1a(url.as_bytes())))
+    }
+}
+
+/// What the [`DiskCache`] has for a url, see [`DiskCache::lookup`].
+#[derive(Debug)]
+pub struct DiskEntry {
+    /// The encoded image.
+    pub bytes: Vec<u8>,
+    /// What the image came with, to revalidate it.
+    pub validators: Validators,
+    /// False once older than the ttl or the `max-age` of the image, it's then to be revalidated
+    /// before being used.
+    pub fresh: bool,
+}
+
+/// `$XDG_CACHE_HOME/helloplus` (or `~/.cache/helloplus`), None if neither can be resolved.
+pub(crate) fn cache_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+    Some(base.join("helloplus"))
+}
+
+/// 64 bit FNV-1a. Used instead of the std hasher since file names need to be stable across runs
+/// and compiler versions.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
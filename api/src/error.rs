@@ -0,0 +1,98 @@
+//! Error type returned by the [`Api`](crate::Api) crate.
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Everything that can go wrong while fetching or parsing the backend data.
+#[derive(Debug, Error)]
+pub enum ApiError {
+    /// The request could not be completed or the body could not be read/parsed.
+    #[error("network error: {0}")]
+    Network(#[from] reqwest::Error),
+    /// The image bytes could not be decoded.
+    #[error("decode error: {0}")]
+    Decode(#[from] image::ImageError),
+    /// An expected attribute is not present in the json data.
+    #[error("missing field: '{path}'")]
+    MissingField {
+        /// Attribute path of the missing field, e.g. `items[3].image.tile`.
+        path: String,
+    },
+    /// The item has artwork, but none of it can be used: its aspect ratios aren't numbers, none
+    /// suits the [`AspectRatioPolicy`](crate::AspectRatioPolicy) or they hold no tile type.
+    #[error("no usable artwork at '{path}', found: [{}]", .available.join(", "))]
+    NoUsableArtwork {
+        /// Attribute path of the artwork, e.g. `items[3].image.tile`.
+        path: String,
+        /// What was found instead, as `<aspect ratio>/<tile type>`, or the aspect ratio alone when
+        /// it holds no tile type.
+        available: Vec<String>,
+    },
+    /// The backend answered with something other than `200 OK`.
+    #[error("unexpected http status: {code}")]
+    HttpStatus {
+        /// The status code received.
+        code: u16,
+    },
+    /// A local file could not be read, see [`Api::load_from_file`](crate::Api::load_from_file).
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    /// A local json file could not be parsed.
+    #[error("parse error: {0}")]
+    Parse(#[from] serde_json::Error),
+    /// The image is neither cached nor replaceable by a sample while offline, see
+    /// [`ImageCache::offline`](crate::ImageCache::offline).
+    #[error("not available offline: {url}")]
+    Offline {
+        /// Url of the image.
+        url: String,
+    },
+    /// The request got no answer before its timeout. Only injected by the
+    /// [`ChaosTransport`](crate::ChaosTransport), the `reqwest` timeouts are [`ApiError::Network`] errors.
+    #[error("timed out: {url}")]
+    Timeout {
+        /// Url of the request.
+        url: String,
+    },
+    /// The error of a download shared with other callers, see [`ImageCache::fetch_image`](crate::ImageCache::fetch_image).
+    #[error("{0}")]
+    Shared(Arc<ApiError>),
+}
+
+/// A rating value that isn't one of the [`ContentRating`](crate::ContentRating)s.
+#[derive(Clone, Debug, Error, PartialEq)]
+#[error("unknown content rating '{0}'")]
+pub struct UnknownRating(pub String);
+
+impl ApiError {
+    pub(crate) fn missing(path: impl Into<String>) -> Self {
+        ApiError::MissingField { path: path.into() }
+    }
+
+    /// Returns true if the same request may succeed later: timeouts, connection failures and the
+    /// `408`, `429` and `5xx` statuses. See [`RetryPolicy`](crate::RetryPolicy).
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ApiError::Network(e) => {
+                e.is_timeout() || e.is_connect() || e.is_request() || e.is_body()
+            }
+            ApiError::HttpStatus { code } => *code == 408 || *code == 429 || *code >= 500,
+            ApiError::Timeout { .. } => true,
+            ApiError::Shared(e) => e.is_retryable(),
+            ApiError::Decode(_)
+            | ApiError::MissingField { .. }
+            | ApiError::NoUsableArtwork { .. }
+            | ApiError::Io(_)
+            | ApiError::Parse(_)
+            | ApiError::Offline { .. } => false,
+        }
+    }
+
+    /// Returns true if the backend says the resource doesn't exist (`404` or `410`).
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            ApiError::HttpStatus { code } => *code == 404 || *code == 410,
+            ApiError::Shared(e) => e.is_not_found(),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+//! Jpeg decoding through `zune-jpeg`, with the `fast-jpeg` feature enabled.
+//!
+//! It decodes the tiles about twice as fast as the `image` crate, which matters on the low-end
+//! devices where decoding is most of the time it takes a tile to show up. [`decode_image`](crate::decode_image)
+//! goes through it for the jpeg artwork, and falls back to the `image` crate for the few files it
+//! can't decode, e.g. CMYK ones. `cargo bench -p api --features fast-jpeg` compares both.
+use image::{DynamicImage, RgbImage};
+use zune_core::colorspace::ColorSpace;
+use zune_core::options::DecoderOptions;
+use zune_jpeg::JpegDecoder;
+
+/// Decodes the jpeg `buf` to RGB. The error is the reason it couldn't, to be logged.
+pub(crate) fn decode(buf: &[u8]) -> Result<DynamicImage, String> {
+    let options = DecoderOptions::default().jpeg_set_out_colorspace(ColorSpace::RGB);
+    let mut decoder = JpegDecoder::new_with_options(buf, options);
+    let pixels = decoder.decode().map_err(|e| format!("{:?}", e))?;
+    let (width, height) = decoder
+        .dimensions()
+        .ok_or_else(|| String::from("no dimensions"))?;
+    RgbImage::from_raw(width as u32, height as u32, pixels)
+        .map(DynamicImage::ImageRgb8)
+        .ok_or_else(|| String::from("fewer pixels than the dimensions"))
+}
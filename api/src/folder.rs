@@ -0,0 +1,209 @@
+//! Rows read from a folder of images instead of the backend, see [`FolderProvider`].
+use crate::{ApiError, ContentProvider, ItemMetadata, TileArt};
+use log::{debug, info};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Prefix of the urls of the images read from disk. The [`ImageCache`](crate::ImageCache) reads
+/// them straight from their file, whatever query parameters were added to them.
+pub const FILE_SCHEME: &str = "file://";
+/// Extensions of the files picked up as tiles, compared ignoring case.
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "bmp", "webp"];
+
+/// Returns true if `url` points to a file on disk, see [`FILE_SCHEME`].
+pub fn is_file_url(url: &str) -> bool {
+    url.starts_with(FILE_SCHEME)
+}
+
+/// The file the `url` of an image read from disk points to, None for any other url.
+pub(crate) fn file_path(url: &str) -> Option<&Path> {
+    let path = url.strip_prefix(FILE_SCHEME)?;
+    Some(Path::new(path.split('?').next().unwrap_or(path)))
+}
+
+/// An image file of a [`FolderSet`].
+#[derive(Debug)]
+struct FolderItem {
+    /// Path of the file relative to the root folder, unique across the sets.
+    content_id: String,
+    url: String,
+    title: String,
+    /// Width over height of the image.
+    aspect_ratio: f32,
+    /// Year the picture was taken.
+    release_year: Option<u32>,
+}
+
+/// A subfolder of the root folder.
+#[derive(Debug)]
+struct FolderSet {
+    title: String,
+    items: Vec<FolderItem>,
+}
+
+/// [`ContentProvider`] browsing a folder of images: each subfolder is a set and each image file
+/// in it an item, both sorted by name. Handy to demo the UI without network access, or to try it
+/// with catalogs much larger than the home page.
+///
+/// The items are titled after the `ImageDescription` found in the EXIF data of the file, else after
+/// the file name. The `DateTimeOriginal` gives their release year. Hidden folders and files are
+/// skipped, and so are the files that can't be read as images.
+///
+/// Load it into an [`Api`](crate::Api) with [`Api::load_from_provider`](crate::Api::load_from_provider).
+#[derive(Debug)]
+pub struct FolderProvider {
+    sets: Vec<FolderSet>,
+}
+
+impl FolderProvider {
+    /// Reads the subfolders of `root` and the image files directly under them. Only the image
+    /// headers are read, the images themselves are read when their tile is shown.
+    pub fn scan(root: impl AsRef<Path>) -> Result<Self, ApiError> {
+        let root = root.as_ref().canonicalize()?;
+        let mut sets = Vec::new();
+        for dir in sorted_entries(&root)?.into_iter().filter(|p| p.is_dir()) {
+            let items = sorted_entries(&dir)?
+                .into_iter()
+                .filter(|path| path.is_file() && is_image(path))
+                .filter_map(|path| FolderItem::read(&root, &path))
+                .collect::<Vec<_>>();
+            debug!("{} images in {}", items.len(), dir.display());
+            sets.push(FolderSet {
+                title: file_name(&dir),
+                items,
+            });
+        }
+        info!("{} sets found in {}", sets.len(), root.display());
+        Ok(Self { sets })
+    }
+
+    fn item(&self, set_idx: usize, item_idx: usize) -> Option<&FolderItem> {
+        self.sets.get(set_idx)?.items.get(item_idx)
+    }
+}
+
+impl FolderItem {
+    /// Reads the header and the EXIF data of the image at `path`, None if it isn't an image.
+    fn read(root: &Path, path: &Path) -> Option<Self> {
+        let (width, height) = match image::image_dimensions(path) {
+            Ok((width, height)) if width > 0 && height > 0 => (width, height),
+            Ok(_) => return None,
+            Err(e) => {
+                info!("Skipping {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        let exif = File::open(path).ok().and_then(|file| {
+            exif::Reader::new()
+                .read_from_container(&mut BufReader::new(file))
+                .ok()
+        });
+        let title = exif
+            .as_ref()
+            .and_then(|exif| exif_text(exif, exif::Tag::ImageDescription))
+            .unwrap_or_else(|| title_from_file_name(path));
+        let release_year = exif.as_ref().and_then(|exif| {
+            let field = exif.get_field(exif::Tag::DateTimeOriginal, exif::In::PRIMARY)?;
+            match &field.value {
+                exif::Value::Ascii(values) => {
+                    let date = exif::DateTime::from_ascii(values.first()?).ok()?;
+                    Some(date.year as u32)
+                }
+                _ => None,
+            }
+        });
+        Some(Self {
+            content_id: path
+                .strip_prefix(root)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .into_owned(),
+            url: format!("{}{}", FILE_SCHEME, path.display()),
+            title,
+            aspect_ratio: width as f32 / height as f32,
+            release_year,
+        })
+    }
+}
+
+impl ContentProvider for FolderProvider {
+    fn list_sets(&self) -> Range<usize> {
+        0..self.sets.len()
+    }
+
+    fn set_title(&self, set_idx: usize) -> Option<&str> {
+        self.sets.get(set_idx).map(|set| set.title.as_str())
+    }
+
+    fn item_count(&self, set_idx: usize) -> usize {
+        self.sets.get(set_idx).map_or(0, |set| set.items.len())
+    }
+
+    fn item_image_url(&self, set_idx: usize, item_idx: usize) -> Result<TileArt, ApiError> {
+        let item = self
+            .item(set_idx, item_idx)
+            .ok_or_else(|| ApiError::missing(format!("sets[{}].items[{}]", set_idx, item_idx)))?;
+        Ok(TileArt {
+            url: item.url.clone(),
+            aspect_ratio: item.aspect_ratio,
+        })
+    }
+
+    fn item_metadata(&self, set_idx: usize, item_idx: usize) -> Option<ItemMetadata> {
+        let item = self.item(set_idx, item_idx)?;
+        Some(ItemMetadata {
+            title: Some(item.title.clone()),
+            content_id: Some(item.content_id.clone()),
+            release_year: item.release_year,
+            ..ItemMetadata::default()
+        })
+    }
+}
+
+/// The entries of `dir` that aren't hidden, sorted so the order doesn't depend on the file system.
+fn sorted_entries(dir: &Path) -> Result<Vec<PathBuf>, ApiError> {
+    let mut entries = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| !file_name(path).starts_with('.'))
+        .collect::<Vec<_>>();
+    entries.sort();
+    Ok(entries)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// The file name of `path` without its extension, the `_` and `-` turned into spaces.
+fn title_from_file_name(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+    stem.replace(['_', '-'], " ").trim().to_string()
+}
+
+/// The text of the `tag` of the main image, None if it's missing or blank.
+fn exif_text(exif: &exif::Exif, tag: exif::Tag) -> Option<String> {
+    let field = exif.get_field(tag, exif::In::PRIMARY)?;
+    match &field.value {
+        exif::Value::Ascii(values) => {
+            let text = String::from_utf8_lossy(values.first()?);
+            let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+            if text.is_empty() {
+                None
+            } else {
+                Some(text.to_string())
+            }
+        }
+        _ => None,
+    }
+}
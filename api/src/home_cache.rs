@@ -0,0 +1,104 @@
+//! On-disk copy of the home data, shown at startup while it's fetched again.
+use crate::disk_cache::{cache_dir, fnv1a};
+use crate::model::{HomeResponse, RefSetResponse};
+use crate::{first_set, ApiError, HomeSnapshot, Validators};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Keeps the last home data fetched, along with the `SetRef` sets it points to and the
+/// [`Validators`] of the home.json, one file per home.json url.
+///
+/// [`Api::load_cached_home_data`](crate::Api::load_cached_home_data) loads it without going to the
+/// network. Fetching the home data again is then a conditional request, a `304 Not Modified` answer
+/// means the cached copy is still good. The `SetRef` sets aren't fetched again in that case.
+#[derive(Debug)]
+pub struct HomeCache {
+    dir: PathBuf,
+}
+
+/// What is saved in the files of the [`HomeCache`], the json as it came.
+#[derive(Deserialize, Serialize)]
+pub(crate) struct CachedHome {
+    pub(crate) validators: Validators,
+    pub(crate) home: Value,
+    /// The `SetRef` sets that could be fetched, keyed by their `refId`.
+    pub(crate) ref_sets: HashMap<String, Value>,
+}
+
+impl CachedHome {
+    /// Parses the saved json. The `SetRef` sets that don't parse are left out.
+    pub(crate) fn snapshot(&self) -> Result<HomeSnapshot, ApiError> {
+        let home_data = serde_json::from_value::<HomeResponse>(self.home.clone())?;
+        let ref_sets = self
+            .ref_sets
+            .iter()
+            .filter_map(|(ref_id, value)| {
+                let resp = serde_json::from_value::<RefSetResponse>(value.clone()).ok()?;
+                Some((ref_id.clone(), first_set(ref_id, resp).ok()?))
+            })
+            .collect();
+        Ok(HomeSnapshot {
+            home_data,
+            ref_sets,
+        })
+    }
+}
+
+impl HomeCache {
+    /// New up a cache rooted at `dir`, creating the directory if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Cache rooted at `$XDG_CACHE_HOME/helloplus/home` (or `~/.cache/helloplus/home`).
+    /// Returns None if neither location can be resolved or created.
+    pub fn in_default_dir() -> Option<Self> {
+        match Self::new(cache_dir()?.join("home")) {
+            Ok(cache) => Some(cache),
+            Err(e) => {
+                info!("Could not create the home data cache: {}", e);
+                None
+            }
+        }
+    }
+
+    /// The home data saved for the home.json at `url`, if any.
+    pub(crate) fn get(&self, url: &str) -> Option<CachedHome> {
+        let contents = fs::read(self.path_for(url)).ok()?;
+        match serde_json::from_slice(&contents) {
+            Ok(cached) => Some(cached),
+            Err(e) => {
+                info!("Ignoring the cached home data of {}: {}", url, e);
+                None
+            }
+        }
+    }
+
+    /// Saves the home data fetched from `url`. Errors are logged and otherwise ignored since the
+    /// cache is best effort.
+    pub(crate) fn insert(&self, url: &str, cached: &CachedHome) {
+        let path = self.path_for(url);
+        // Write to a temporary file first so a concurrent reader never sees a partial file.
+        let tmp = path.with_extension("tmp");
+        let res = serde_json::to_vec(cached)
+            .map_err(io::Error::from)
+            .and_then(|contents| fs::write(&tmp, contents))
+            .and_then(|_| fs::rename(&tmp, &path));
+        if let Err(e) = res {
+            info!("Could not save the home data of {}: {}", url, e);
+            let _ = fs::remove_file(&tmp);
+        }
+    }
+
+    fn path_for(&self, url: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.json", fnv1a(url.as_bytes())))
+    }
+}
@@ -3,25 +3,375 @@
 //! The main responsibility of the [`Api`] crate is to abstract away the interactions with the backend.
 //! It's in charge of fetching and parsing the json data.
 //!
+//! Fetched artwork is kept in an in-memory [`ImageCache`] shared by every [`SetData`], optionally
+//! backed by a [`DiskCache`] so previously seen tiles survive restarts. [`Api::prefetch_images`] fills
+//! it in the background with the tiles likely to be shown next. The tile artwork gets larger or
+//! smaller following the download throughput measured by the cache, see [`TierPolicy`].
+//!
+//! [`Api::load_from_file`] and [`ImageCache::offline`] make it work without network access, from a
+//! saved home.json and whatever artwork is on disk.
+//!
+//! [`Api::refresh_home_data`] reloads the home data while it's being shown and tells how the sets
+//! changed, see [`HomeDataDiff`]. [`HomeFetcher`] does the fetching part from another thread.
+//! With a [`HomeCache`], [`Api::load_cached_home_data`] shows the home data of the last run right
+//! away, fetching it again is then a conditional request answered with a `304` if it didn't change.
+//! [`Api::anomalies`] lists what looks wrong in the loaded home data, see [`Anomaly`].
+//!
+//! Every request goes through a [`Transport`], [`ReqwestTransport`] unless another one is given with
+//! [`Api::with_transport`]. It gives up after a timeout and the ones failing for transient reasons
+//! (timeouts, `5xx`...) are attempted again as per the [`RetryPolicy`], see [`ApiError::is_retryable`]. The
+//! [`ReqwestTransport`] keeps its connections open for the next requests, see [`ClientOptions`].
+//! [`Api::builder`] sets up its proxy, the certificates it trusts and its TLS implementation, see
+//! [`ApiBuilder`].
+//!
+//! The UI only needs the titles, artwork and metadata of the rows, which any catalog can give, see
+//! [`ContentProvider`]. The [`Api`] is the one for the home.json schema, [`FolderProvider`] browses
+//! a folder of images and [`TmdbProvider`] the lists of The Movie Database. [`Api::load_from_provider`] loads any of them in place of the home data.
+//!
+//! ### Media servers
+//! With the `media-server` feature enabled, [`MediaServerProvider`] shows the libraries of a
+//! Jellyfin or Plex server, authenticated with an access token sent by a [`TokenTransport`].
+//!
+//! ### Fault injection
+//! With the `chaos` feature enabled, [`ChaosTransport`] wraps a [`Transport`] and injects latency,
+//! timeouts, `500`s and truncated bodies into its requests at the [`FaultRates`] given.
+//!
+//! ### Async
+//! Every call above blocks on the network. With the `async` feature enabled, [`AsyncApi`] offers
+//! the same home data loading and tile fetching as futures sharing a single `reqwest::Client`, so
+//! several downloads can be in flight at once. It needs to be polled from a tokio runtime and
+//! doesn't go through the [`Transport`].
+//!
 //! ### Improvements
 //! - It could shed unused fields to lower the memory footprint.
-//! - It could provide some caching.
-use image::io::Reader as ImageReader;
-use image::{DynamicImage, ImageFormat};
-use log::info;
-use reqwest;
-use reqwest::StatusCode;
-use serde_json::Value;
-use std::io::Cursor;
+#[cfg(feature = "async")]
+pub use async_api::AsyncApi;
+pub use bandwidth::{BandwidthMonitor, ImageTier, TierPolicy};
+pub use builder::ApiBuilder;
+pub use cache::{decode_image, ImageCache, DEFAULT_IMAGE_CACHE_BYTES};
+#[cfg(feature = "chaos")]
+pub use chaos::{ChaosTransport, FaultRates};
+pub use disk_cache::{DiskCache, DiskEntry, DEFAULT_DISK_CACHE_TTL};
+pub use error::{ApiError, UnknownRating};
+pub use folder::{is_file_url, FolderProvider, FILE_SCHEME};
+pub use home_cache::HomeCache;
+use image::DynamicImage;
+use log::{debug, info};
+#[cfg(feature = "media-server")]
+pub use media_server::{MediaServer, MediaServerProvider, MAX_LIBRARY_ITEMS};
+use model::{Container, HomeResponse, ImageSource, Item, RefSetResponse, Set};
+pub use prefetch::{PrefetchHandle, PREFETCH_PARALLELISM};
+pub use provider::{ContentProvider, ItemMetadata};
+use refresh::SetKey;
+pub use refresh::{HomeDataDiff, HomeFetcher, HomeSnapshot};
+pub use retry::RetryPolicy;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::{fmt, fs};
+pub use tmdb::{Tmdb, TmdbApiKey, TmdbProvider, TMDB_API_URL, TMDB_IMAGE_URL};
+pub use transport::{
+    ClientOptions, Conditional, Proxy, ReqwestTransport, TlsBackend, TokenTransport, Transport,
+    Validators,
+};
+pub use url::{ArtworkFormat, UrlBuilder};
+pub use validate::{Anomaly, AnomalyKind};
+
+#[cfg(feature = "async")]
+mod async_api;
+mod bandwidth;
+mod builder;
+mod cache;
+#[cfg(feature = "chaos")]
+mod chaos;
+mod disk_cache;
+mod error;
+#[cfg(feature = "fast-jpeg")]
+mod fast_jpeg;
+mod folder;
+mod home_cache;
+#[cfg(feature = "media-server")]
+mod media_server;
+pub mod model;
+mod prefetch;
+mod provider;
+mod refresh;
+mod retry;
+mod tmdb;
+mod transport;
+mod url;
+mod validate;
+
+/// Size, quality and format requested for the artwork.
+///
+/// The CDN serves the artwork through a scaling endpoint taking these as query parameters,
+/// see [`ImageOptions::apply`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageOptions {
+    /// Width of the tile artwork, ideally the width the tiles are drawn at. The large artwork keeps
+    /// the width picked by the backend.
+    pub width: u32,
+    /// Quality of the jpeg, webp and avif artwork, from 1 to 100.
+    pub quality: u8,
+    /// Encoding of the artwork. Only the [decodable](ArtworkFormat::is_decodable) ones can be shown.
+    pub format: ArtworkFormat,
+    /// Which of the aspect ratios available for the tile artwork is picked.
+    pub tile_aspect_ratio: AspectRatioPolicy,
+    /// How the `width` and `quality` are scaled to the bandwidth, see [`ImageTier::apply`].
+    pub tier: TierPolicy,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        Self {
+            width: 500,
+            quality: 90,
+            format: ArtworkFormat::Jpeg,
+            tile_aspect_ratio: AspectRatioPolicy::default(),
+            tier: TierPolicy::default(),
+        }
+    }
+}
+
+/// Aspect ratio the tile artwork is usually shown at.
+pub const DEFAULT_TILE_ASPECT_RATIO: f32 = 1.78;
+/// Aspect ratio of the poster (vertical) artwork.
+pub const POSTER_ASPECT_RATIO: f32 = 0.71;
+/// Tile types the artwork is picked from, in order of preference. Any other type is used only when
+/// none of these is there, see [`pick_tile_type`].
+pub const TILE_TYPES: [&str; 3] = ["series", "program", "default"];
+
+/// The artwork of `by_type` to use and its tile type: the first of the [`TILE_TYPES`] found,
+/// otherwise the first other type in the order of their names. None if `by_type` is empty.
+pub fn pick_tile_type(by_type: &BTreeMap<String, ImageSource>) -> Option<(&str, &ImageSource)> {
+    TILE_TYPES
+        .iter()
+        .find_map(|tile_type| by_type.get_key_value(*tile_type))
+        .or_else(|| by_type.iter().next())
+        .map(|(tile_type, source)| (tile_type.as_str(), source))
+}
+
+/// How to pick among the aspect ratios an item has artwork for.
+///
+/// The artwork is keyed by its aspect ratio, e.g. `"1.78"`. Keys that are not numbers are ignored.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AspectRatioPolicy {
+    /// The widest one. Some items only have ultra-wide art at that end.
+    Widest,
+    /// The one closest to the given ratio.
+    Closest(f32),
+    /// Exactly the given ratio (give or take the rounding of the keys), missing otherwise.
+    Exact(f32),
+}
+
+impl Default for AspectRatioPolicy {
+    fn default() -> Self {
+        AspectRatioPolicy::Closest(DEFAULT_TILE_ASPECT_RATIO)
+    }
+}
+
+impl AspectRatioPolicy {
+    /// Picks among `ratios` as per this policy.
+    pub fn pick(self, mut ratios: impl Iterator<Item = f32>) -> Option<f32> {
+        let cmp = |a: &f32, b: &f32| a.partial_cmp(b).unwrap_or(Ordering::Equal);
+        match self {
+            AspectRatioPolicy::Widest => ratios.max_by(cmp),
+            AspectRatioPolicy::Closest(target) => {
+                ratios.min_by(|a, b| cmp(&(a - target).abs(), &(b - target).abs()))
+            }
+            // The keys are rounded to two decimals, e.g. 16/9 shows up as "1.78".
+            AspectRatioPolicy::Exact(target) => ratios.find(|ar| (ar - target).abs() < 0.01),
+        }
+    }
+}
+
+/// The tile artwork picked for an item, see [`SetData::get_home_tile`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileArt {
+    /// Url of the artwork, with the [`ImageOptions`] applied.
+    pub url: String,
+    /// Aspect ratio (width over height) of the artwork, as advertised by the backend. Lets the
+    /// caller size the tile before the image is even fetched.
+    pub aspect_ratio: f32,
+}
+
+/// The background video loop picked for an item, see [`SetData::get_item_video_art`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct VideoLoop {
+    /// Url of the video, as is: unlike the artwork, no [`ImageOptions`] apply.
+    pub url: String,
+    /// Other urls the same video is served at, in the order the backend lists them. Worth a try if
+    /// `url` can't be played.
+    pub fallback_urls: Vec<String>,
+    /// What the backend says the video is for, if anything.
+    pub purpose: Option<String>,
+}
+
+impl ImageOptions {
+    /// Returns `url` with its `width` (only if `with_width`), `quality` and `format` query parameters
+    /// replaced, or appended if missing. Other parameters are left untouched, see [`UrlBuilder`].
+    pub fn apply(&self, url: &str, with_width: bool) -> String {
+        let mut builder = UrlBuilder::new(url);
+        if with_width {
+            builder = builder.width(self.width);
+        }
+        builder.quality(self.quality).format(self.format).build()
+    }
+
+    /// The tier the artwork is requested at, following the [`BandwidthMonitor`] of the
+    /// `image_cache` unless it's [`TierPolicy::Fixed`].
+    pub fn current_tier(&self, image_cache: &ImageCache) -> ImageTier {
+        match self.tier {
+            TierPolicy::Adaptive => image_cache.bandwidth().tier(),
+            TierPolicy::Fixed(tier) => tier,
+        }
+    }
+}
+
+/// What an item is, from its `type`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContentType<'a> {
+    /// A show made of episodes, `DmcSeries`.
+    Series,
+    /// A movie or a single video, `DmcVideo`.
+    Program,
+    /// A group of titles, `StandardCollection`.
+    Collection,
+    /// Any other `type`, as found in the json.
+    Other(&'a str),
+}
+
+impl<'a> ContentType<'a> {
+    /// Maps the `type` of an item.
+    pub fn from_type(item_type: &'a str) -> Self {
+        match item_type {
+            "DmcSeries" => ContentType::Series,
+            "DmcVideo" => ContentType::Program,
+            "StandardCollection" => ContentType::Collection,
+            other => ContentType::Other(other),
+        }
+    }
+}
+
+impl fmt::Display for ContentType<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContentType::Series => f.write_str("Series"),
+            ContentType::Program => f.write_str("Program"),
+            ContentType::Collection => f.write_str("Collection"),
+            ContentType::Other(other) => f.write_str(other),
+        }
+    }
+}
+
+/// Content rating of an item, the movie (MPAA) and TV (TV Parental Guidelines) ratings mapped to a
+/// single scale so they can be compared, e.g. `TV-14` and `PG-13` are both [`ContentRating::Pg13`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ContentRating {
+    /// `G`, `TV-Y`, `TV-Y7` and `TV-G`.
+    G,
+    /// `PG` and `TV-PG`.
+    Pg,
+    /// `PG-13` and `TV-14`.
+    Pg13,
+    /// `R` and `TV-MA`.
+    R,
+    /// `NC-17`.
+    Nc17,
+}
+
+impl FromStr for ContentRating {
+    type Err = UnknownRating;
+
+    /// Parses a rating value as found in the json, ignoring case.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let rating = match value.trim().to_uppercase().as_str() {
+            "G" | "TV-Y" | "TV-Y7" | "TV-Y7-FV" | "TV-G" => ContentRating::G,
+            "PG" | "TV-PG" => ContentRating::Pg,
+            "PG-13" | "TV-14" => ContentRating::Pg13,
+            "R" | "TV-MA" => ContentRating::R,
+            "NC-17" => ContentRating::Nc17,
+            _ => return Err(UnknownRating(value.to_string())),
+        };
+        Ok(rating)
+    }
+}
+
+impl TryFrom<String> for ContentRating {
+    type Error = UnknownRating;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ContentRating> for String {
+    fn from(rating: ContentRating) -> Self {
+        rating.to_string()
+    }
+}
+
+impl fmt::Display for ContentRating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ContentRating::G => "G",
+            ContentRating::Pg => "PG",
+            ContentRating::Pg13 => "PG-13",
+            ContentRating::R => "R",
+            ContentRating::Nc17 => "NC-17",
+        })
+    }
+}
+
+/// An item matching a [`Api::search`] query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchResult {
+    /// Index of the set the item was found in. Use with [`Api::get_set`].
+    pub true_set_idx: usize,
+    /// Index of the item within the set.
+    pub true_item_idx: usize,
+    /// Full title of the item.
+    pub title: String,
+}
 
 /// Struct used to interact with the backend.
 pub struct Api {
-    json_data: Option<Value>,
-    //Consider a cached layer to avoid fetching resources already here.
+    home_data: Option<HomeResponse>,
+    /// Sets referenced by `SetRef` containers, keyed by their `refId`.
+    ref_sets: HashMap<String, Set>,
+    /// Cache of the fetched artwork. Shared with whoever fetches images outside of the [`Api`].
+    image_cache: Arc<ImageCache>,
+    image_options: ImageOptions,
+    /// Applied to the json requests, the [`ImageCache`] holds the one for the artwork.
+    retry_policy: RetryPolicy,
+    /// Used for the json requests, the [`ImageCache`] holds the one for the artwork.
+    transport: Arc<dyn Transport>,
+    /// See [`Api::client`].
+    client: ReqwestTransport,
+    /// Prefix of the json urls, without the trailing slash.
+    base_url: String,
+    /// Items rated above it are left out of the sets, see [`Api::with_rating_ceiling`].
+    rating_ceiling: Option<ContentRating>,
+    /// Language the texts are requested in, see [`Api::with_language`].
+    language: Option<String>,
+    /// Last home data fetched, see [`Api::with_home_cache`].
+    home_cache: Option<Arc<HomeCache>>,
 }
 
 const TITLE_NOT_FOUND: &str = "Title not found";
-const TILE_TYPE_DEFAULT: &str = "program";
+const SET_TYPE_REF: &str = "SetRef";
+/// Aspect ratio used for the large artwork (detail page, hero banner).
+const HERO_ASPECT_RATIO: &str = "1.78";
+/// Where the home.json and the `SetRef` sets are fetched from, see [`Api::with_base_url`].
+pub const DEFAULT_BASE_URL: &str = "https://cd-static.bamgrid.com/dp-117731241344";
 
 /// Struct that encapsulates a given set's data.
 ///
@@ -29,133 +379,984 @@ const TILE_TYPE_DEFAULT: &str = "program";
 /// - getting the title
 /// - getting how many items are in this set
 /// - getting the image to display for a given item on this set
+///
+/// For `SetRef` containers the items live in a separately fetched set (see [`Api::resolve_ref_set`]),
+/// in that case the items come from the resolved set while [`SetData::set`] still points to the container's set.
+/// Sets not backed by a container, like the ones built with [`Api::custom_set`], have no [`Set`].
 #[derive(Debug)]
 pub struct SetData<'a> {
-    entry: &'a Value,
+    source: SetSource<'a>,
+    image_cache: &'a Arc<ImageCache>,
+    image_options: &'a ImageOptions,
+    /// Defaults to the [`ImageOptions::tile_aspect_ratio`], see [`SetData::with_tile_aspect_ratio`].
+    tile_aspect_ratio: AspectRatioPolicy,
+    /// Indices, in the `source`, of the items left by [`SetData::with_rating_ceiling`]. None if
+    /// every item is shown.
+    shown: Option<Vec<usize>>,
+}
+
+/// Where the title and items of a [`SetData`] come from.
+#[derive(Debug)]
+enum SetSource<'a> {
+    /// A home page container, `items_set` is either `set` itself or the resolved `SetRef`.
+    Home {
+        set: &'a Set,
+        items_set: &'a Set,
+        /// `style` of the container.
+        style: Option<&'a str>,
+    },
+    /// Items picked from any of the home page sets.
+    Custom {
+        title: &'a str,
+        items: Vec<&'a Item>,
+    },
 }
 
 impl<'a> SetData<'a> {
-    fn new(entry: &'a Value) -> Self {
-        Self { entry }
+    fn new(
+        container: &'a Container,
+        image_cache: &'a Arc<ImageCache>,
+        image_options: &'a ImageOptions,
+    ) -> Self {
+        Self::with_ref(container, &container.set, image_cache, image_options)
+    }
+
+    fn with_ref(
+        container: &'a Container,
+        resolved: &'a Set,
+        image_cache: &'a Arc<ImageCache>,
+        image_options: &'a ImageOptions,
+    ) -> Self {
+        Self {
+            source: SetSource::Home {
+                set: &container.set,
+                items_set: resolved,
+                style: container.style.as_deref(),
+            },
+            image_cache,
+            image_options,
+            tile_aspect_ratio: image_options.tile_aspect_ratio,
+            shown: None,
+        }
+    }
+
+    /// Overrides the policy used to pick the tile artwork of this set, e.g. to show posters.
+    pub fn with_tile_aspect_ratio(mut self, policy: AspectRatioPolicy) -> Self {
+        self.tile_aspect_ratio = policy;
+        self
+    }
+
+    /// Leaves out the items rated above `rating_ceiling`, the items are indexed among the ones left.
+    /// Items without a known rating are kept, see [`SetData::get_item_content_rating`].
+    pub fn with_rating_ceiling(mut self, rating_ceiling: ContentRating) -> Self {
+        self.shown = None;
+        let shown = (0..self.get_item_count())
+            .filter(|item_num| {
+                self.get_item_content_rating(*item_num)
+                    .is_none_or(|rating| rating <= rating_ceiling)
+            })
+            .collect();
+        self.shown = Some(shown);
+        self
+    }
+
+    /// The typed [`Set`] backing this data as found in the home page container.
+    /// None for sets built with [`Api::custom_set`].
+    pub fn set(&self) -> Option<&'a Set> {
+        match &self.source {
+            SetSource::Home { set, .. } => Some(*set),
+            SetSource::Custom { .. } => None,
+        }
+    }
+
+    /// The items of this set. For `SetRef` containers these come from the resolved set.
+    pub fn items(&self) -> impl Iterator<Item = &'a Item> + '_ {
+        (0..self.get_item_count()).filter_map(move |item_num| self.get_item(item_num))
+    }
+
+    /// Get the typed [`Item`] at `item_num` if present.
+    pub fn get_item(&self, item_num: usize) -> Option<&'a Item> {
+        let item_num = match &self.shown {
+            Some(shown) => *shown.get(item_num)?,
+            None => item_num,
+        };
+        match &self.source {
+            SetSource::Home { items_set, .. } => items_set.items.get(item_num),
+            SetSource::Custom { items, .. } => items.get(item_num).copied(),
+        }
+    }
+
+    /// Get the `contentId` of the item at `item_num`, which identifies it across sets.
+    pub fn get_content_id(&self, item_num: usize) -> Option<&'a str> {
+        self.get_item(item_num)?.content_id.as_deref()
+    }
+
+    /// Get the full title of the item at `item_num`, None if the item or its title is missing.
+    pub fn get_item_title(&self, item_num: usize) -> Option<&'a str> {
+        self.get_item(item_num)?.text.any_title("full")
+    }
+
+    /// Get what the item at `item_num` is, from its `type`. None if the item or its type is missing.
+    pub fn get_item_content_type(&self, item_num: usize) -> Option<ContentType<'a>> {
+        self.get_item(item_num)?
+            .item_type
+            .as_deref()
+            .map(ContentType::from_type)
+    }
+
+    /// Get the year the item at `item_num` came out, from the first of its `releases` that has one.
+    pub fn get_item_release_year(&self, item_num: usize) -> Option<u32> {
+        self.get_item(item_num)?
+            .releases
+            .iter()
+            .find_map(|r| r.release_year)
+    }
+
+    /// Get the content rating of the item at `item_num` (e.g. `TV-14`), from the first of its
+    /// `ratings` that has a value.
+    pub fn get_item_rating(&self, item_num: usize) -> Option<&'a str> {
+        self.get_item(item_num)?
+            .ratings
+            .iter()
+            .find_map(|r| r.value.as_deref())
+    }
+
+    /// Get the rating of the item at `item_num` on the [`ContentRating`] scale, from the first of its
+    /// `ratings` that is a known rating.
+    pub fn get_item_content_rating(&self, item_num: usize) -> Option<ContentRating> {
+        self.get_item(item_num)?
+            .ratings
+            .iter()
+            .find_map(|r| r.value.as_deref()?.parse().ok())
+    }
+
+    /// Get the content type, release year and rating of the item at `item_num` joined in a single
+    /// line, e.g. `Series  |  2019  |  TV-14`. The ones missing are left out, empty if none is known.
+    pub fn get_item_metadata(&self, item_num: usize) -> String {
+        self.get_item_info(item_num)
+            .map_or_else(String::new, |info| info.summary())
+    }
+
+    /// Get everything the [`ItemMetadata`] covers about the item at `item_num`, None if the item
+    /// is missing.
+    pub fn get_item_info(&self, item_num: usize) -> Option<ItemMetadata> {
+        self.get_item(item_num)?;
+        Some(ItemMetadata {
+            title: self.get_item_title(item_num).map(String::from),
+            content_id: self.get_content_id(item_num).map(String::from),
+            content_type: self
+                .get_item_content_type(item_num)
+                .map(|content_type| content_type.to_string()),
+            release_year: self.get_item_release_year(item_num),
+            rating: self.get_item_rating(item_num).map(String::from),
+        })
+    }
+
+    /// The `contentClass` of the set, e.g. `editorial`. None for sets built with [`Api::custom_set`].
+    pub fn get_content_class(&self) -> Option<&'a str> {
+        match &self.source {
+            SetSource::Home { set, items_set, .. } => set
+                .content_class
+                .as_deref()
+                .or(items_set.content_class.as_deref()),
+            SetSource::Custom { .. } => None,
+        }
+    }
+
+    /// The `style` of the home page container, e.g. `standard` or `brand`. None for sets built with
+    /// [`Api::custom_set`].
+    pub fn get_style(&self) -> Option<&'a str> {
+        match &self.source {
+            SetSource::Home { style, .. } => *style,
+            SetSource::Custom { .. } => None,
+        }
     }
 
     pub fn get_title(&self) -> &'a str {
-        if let Value::String(ref s) =
-            self.entry["text"]["title"]["full"]["set"]["default"]["content"]
-        {
-            s
-        } else {
-            TITLE_NOT_FOUND
+        self.find_title().unwrap_or(TITLE_NOT_FOUND)
+    }
+
+    /// Get the title of the set, None if the json has none. Lets the caller pick its own
+    /// placeholder, e.g. a translated one, where [`SetData::get_title`] falls back to an English one.
+    /// See [`SetData::get_title_source`] for the attribute paths used.
+    pub fn find_title(&self) -> Option<&'a str> {
+        match &self.source {
+            SetSource::Home { .. } => self.home_title().map(|(title, _)| title),
+            SetSource::Custom { title, .. } => Some(*title),
+        }
+    }
+
+    /// Where the title of the set was read from. Looks for it in the following attribute paths, in
+    /// order, in the home page container and then in the `SetRef` set it points to, if any:
+    ///
+    /// > `.text.title.full.<set|collection|series|program>.default.content`
+    ///
+    /// > `.text.title.full.<any other source>.default.content`
+    ///
+    /// None if the json has no title, or for sets built with [`Api::custom_set`].
+    pub fn get_title_source(&self) -> Option<model::TitleSource<'a>> {
+        self.home_title().map(|(_, source)| source)
+    }
+
+    fn home_title(&self) -> Option<(&'a str, model::TitleSource<'a>)> {
+        match &self.source {
+            SetSource::Home { set, items_set, .. } => {
+                set.text.set_title().or_else(|| items_set.text.set_title())
+            }
+            SetSource::Custom { .. } => None,
         }
     }
 
     pub fn get_item_count(&self) -> usize {
-        if let Value::Array(ref vec) = self.entry["items"] {
-            vec.len()
-        } else {
-            0
+        if let Some(shown) = &self.shown {
+            return shown.len();
+        }
+        match &self.source {
+            SetSource::Home { items_set, .. } => items_set.items.len(),
+            SetSource::Custom { items, .. } => items.len(),
         }
     }
 
-    /// This method parses the set and fetches the url to be used for the tile.
+    /// This method parses the set and fetches the image to be used for the tile.
+    /// The network is only hit if the image is not in the [`ImageCache`].
+    /// See [`SetData::get_home_tile_url`] for the attribute path used.
+    pub fn get_home_tile_image(&self, item_num: usize) -> Result<DynamicImage, ApiError> {
+        let url = self.get_home_tile_url(item_num)?;
+        self.image_cache.fetch_image(&url)
+    }
+
+    /// Fetches the tile images of the `items`, e.g. a range of them, into the [`ImageCache`] in the
+    /// background. Items past the end of the set or without a tile are skipped.
+    pub fn prefetch_images(&self, items: impl IntoIterator<Item = usize>) -> PrefetchHandle {
+        let urls = items
+            .into_iter()
+            .filter_map(|item_num| self.get_home_tile_url(item_num).ok())
+            .collect();
+        PrefetchHandle::spawn(Arc::clone(self.image_cache), urls)
+    }
+
+    /// The [`ImageOptions`] at their [`current_tier`](ImageOptions::current_tier).
+    fn tiered_options(&self) -> ImageOptions {
+        self.image_options
+            .current_tier(self.image_cache)
+            .apply(self.image_options)
+    }
+
+    /// This method parses the set and returns the url to be used for the tile, with the [`ImageOptions`] applied.
+    /// See [`SetData::get_home_tile`] for the attribute path used.
+    pub fn get_home_tile_url(&self, item_num: usize) -> Result<String, ApiError> {
+        self.get_home_tile(item_num).map(|tile| tile.url)
+    }
+
+    /// Same as [`SetData::get_home_tile_with`] using the policy of this set, the
+    /// [`ImageOptions::tile_aspect_ratio`] unless overridden with [`SetData::with_tile_aspect_ratio`].
+    pub fn get_home_tile(&self, item_num: usize) -> Result<TileArt, ApiError> {
+        self.get_home_tile_with(item_num, self.tile_aspect_ratio)
+    }
+
+    /// Every aspect ratio the tile artwork of the item at `item_num` is available in, ascending.
+    /// Empty if the item doesn't exist or has no tile artwork.
+    pub fn get_tile_aspect_ratios(&self, item_num: usize) -> Vec<f32> {
+        let mut ratios = self.get_item(item_num).map_or_else(Vec::new, |item| {
+            item.image
+                .tile
+                .keys()
+                .filter_map(|key| key.parse::<f32>().ok())
+                .collect()
+        });
+        ratios.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        ratios
+    }
+
+    /// This method parses the set and returns the tile artwork whose aspect ratio is picked as per
+    /// `policy`, with the [`ImageOptions`] applied.
     /// Assumes the following attribute path:
     ///
-    /// > `.items[IDX].image.tile[AR].<series|program>.default.url`
+    /// > `.items[IDX].image.tile[AR].<series|program|default|...>.default.url`
     ///
     /// Where `IDX` is an index
     /// Where `AR` is the aspect ratio
     ///
-    pub fn get_home_tile_image(
+    /// Only the aspect ratios holding a tile type are picked from, and the tile type is picked as
+    /// per [`pick_tile_type`]. If there is artwork but none of it can be used, the error is an
+    /// [`ApiError::NoUsableArtwork`] naming what was found.
+    pub fn get_home_tile_with(
         &self,
         item_num: usize,
-    ) -> Result<DynamicImage, Box<dyn std::error::Error>> {
-        if let Value::Object(ref map) = self.entry["items"][item_num]["image"]["tile"] {
-            let (key, tile_data) = map
-                .iter()
-                .reduce(|cur, prev| {
-                    let cur_key = cur.0;
-                    let cur_key = cur_key.parse::<f32>().expect("float value");
-
-                    let prev_key = prev.0;
-                    let prev_key = prev_key.parse::<f32>().expect("float value");
-
-                    if cur_key > prev_key {
-                        cur
-                    } else {
-                        prev
-                    }
-                })
-                .expect("some tile data to be present"); //TODO improve this error handling
+        policy: AspectRatioPolicy,
+    ) -> Result<TileArt, ApiError> {
+        let item = self
+            .get_item(item_num)
+            .ok_or_else(|| ApiError::missing(format!("items[{}]", item_num)))?;
+        let path = format!("items[{}].image.tile", item_num);
+        if item.image.tile.is_empty() {
+            return Err(ApiError::missing(path));
+        }
 
-            let tile_type = if let Value::Object(ref map) = tile_data {
-                map.keys().into_iter().last().unwrap().as_str()
-            } else {
-                TILE_TYPE_DEFAULT
-            };
+        // Malformed aspect-ratio keys and the ones without a tile type are skipped instead of
+        // aborting the whole lookup.
+        let usable = || {
+            item.image.tile.iter().filter_map(|(key, by_type)| {
+                let (tile_type, source) = pick_tile_type(by_type)?;
+                Some((key.parse::<f32>().ok()?, key, tile_type, source))
+            })
+        };
+        let (aspect_ratio, key, tile_type, source) = policy
+            .pick(usable().map(|(ar, _, _, _)| ar))
+            .and_then(|picked| usable().find(|(ar, _, _, _)| *ar == picked))
+            .ok_or_else(|| ApiError::NoUsableArtwork {
+                available: available_tiles(&item.image.tile),
+                path,
+            })?;
+
+        let url = self.tiered_options().apply(&source.default.url, true);
+        debug!(
+            "Tile url for item {}, key {} and type {}: {}",
+            item_num, key, tile_type, url
+        );
+        Ok(TileArt { url, aspect_ratio })
+    }
 
-            if let Value::String(ref url) = tile_data[tile_type]["default"]["url"] {
-                let response = reqwest::blocking::get(url)?;
-                if response.status() != StatusCode::OK {
-                    info!("Status not good for item {} and key {}", item_num, key);
+    /// Returns the first background video loop of the item at `item_num` served at any url.
+    /// Assumes the following attribute path:
+    ///
+    /// > `.items[IDX].videoArt[N].mediaMetadata.urls[M].url`
+    ///
+    /// Where `IDX` is an index
+    /// Where `N` is the first entry with at least one url, `M` every url of that entry
+    ///
+    pub fn get_item_video_art(&self, item_num: usize) -> Result<VideoLoop, ApiError> {
+        let item = self
+            .get_item(item_num)
+            .ok_or_else(|| ApiError::missing(format!("items[{}]", item_num)))?;
+        item.video_art
+            .iter()
+            .find_map(|entry| {
+                let mut urls = entry.media_metadata.urls.iter().map(|u| u.url.clone());
+                Some(VideoLoop {
+                    url: urls.next()?,
+                    fallback_urls: urls.collect(),
+                    purpose: entry.purpose.clone(),
+                })
+            })
+            .ok_or_else(|| ApiError::missing(format!("items[{}].videoArt", item_num)))
+    }
+
+    /// Returns the url of the large artwork for the given item.
+    /// Looks for the [`HERO_ASPECT_RATIO`] variant in the following attribute paths, in order:
+    ///
+    /// > `.items[IDX].image.background[AR].<series|program|default|...>.default.url`
+    ///
+    /// > `.items[IDX].image.hero_tile[AR].<series|program|default|...>.default.url`
+    ///
+    /// The tile type is picked as per [`pick_tile_type`].
+    pub fn get_hero_image_url(&self, item_num: usize) -> Result<String, ApiError> {
+        if let Some(item) = self.get_item(item_num) {
+            for variant in [&item.image.background, &item.image.hero_tile].iter() {
+                if let Some((_, source)) = variant.get(HERO_ASPECT_RATIO).and_then(pick_tile_type) {
+                    return Ok(self.tiered_options().apply(&source.default.url, false));
                 }
-                let buf = response.bytes()?;
-                let img = ImageReader::with_format(Cursor::new(buf), ImageFormat::Jpeg).decode()?;
-                Ok(img)
-            } else {
-                let err_msg = format!("No url found for item num: '{}'", item_num);
-                Err(err_msg.into())
             }
-        } else {
-            let err_msg = format!("Did not find tile image for item num: '{}'", item_num);
-            Err(err_msg.into())
         }
+        Err(ApiError::missing(format!(
+            "items[{}].image.background[{}]",
+            item_num, HERO_ASPECT_RATIO
+        )))
+    }
+
+    /// Returns the logo of the title of the given item, meant to be drawn over the large artwork.
+    /// Assumes the following attribute path:
+    ///
+    /// > `.items[IDX].image.title_treatment[AR].<series|program|default|...>.default.url`
+    ///
+    /// Where `IDX` is an index
+    /// Where `AR` is the widest aspect ratio holding a tile type
+    ///
+    /// The tile type is picked as per [`pick_tile_type`]. The logo is transparent, so unless the
+    /// [`ImageOptions::format`] keeps the transparency it's asked for in png instead.
+    pub fn get_title_treatment(&self, item_num: usize) -> Result<TileArt, ApiError> {
+        let path = format!("items[{}].image.title_treatment", item_num);
+        let (aspect_ratio, source) = self
+            .get_item(item_num)
+            .and_then(|item| {
+                let usable = || {
+                    item.image
+                        .title_treatment
+                        .iter()
+                        .filter_map(|(key, by_type)| {
+                            Some((key.parse::<f32>().ok()?, pick_tile_type(by_type)?.1))
+                        })
+                };
+                let widest = AspectRatioPolicy::Widest.pick(usable().map(|(ar, _)| ar))?;
+                usable().find(|(ar, _)| *ar == widest)
+            })
+            .ok_or_else(|| ApiError::missing(path))?;
+
+        let mut options = self.tiered_options();
+        if !options.format.has_alpha() {
+            options.format = ArtworkFormat::Png;
+        }
+        let url = options.apply(&source.default.url, false);
+        debug!("Title treatment url for item {}: {}", item_num, url);
+        Ok(TileArt { url, aspect_ratio })
     }
 }
 
+/// Every aspect ratio and tile type of `tile`, as `<aspect ratio>/<tile type>`, or the aspect ratio
+/// alone when it holds no tile type.
+fn available_tiles(tile: &model::AspectRatioMap) -> Vec<String> {
+    tile.iter()
+        .flat_map(|(key, by_type)| {
+            if by_type.is_empty() {
+                vec![key.clone()]
+            } else {
+                by_type
+                    .keys()
+                    .map(|tile_type| format!("{}/{}", key, tile_type))
+                    .collect()
+            }
+        })
+        .collect()
+}
+
+/// Fetch and decode the image found at `url`, bypassing any cache. Uses the default [`RetryPolicy`]
+/// and [`ReqwestTransport`].
+///
+/// This is a free function so it can be called from threads that don't hold a reference to the [`Api`].
+/// Prefer [`ImageCache::fetch_image`] (see [`Api::image_cache`]) to avoid refetching the same image.
+pub fn fetch_image(url: &str) -> Result<DynamicImage, ApiError> {
+    let buf = cache::fetch_bytes(&ReqwestTransport::default(), url, &RetryPolicy::default())?;
+    cache::decode_image(&buf)
+}
+
 impl Api {
     /// New up an empty [`Api`]. To populate call load ['Api.load`]
     pub fn new() -> Self {
-        Self { json_data: None }
+        Self::with_image_cache_budget(DEFAULT_IMAGE_CACHE_BYTES)
+    }
+
+    /// Builds an [`Api`] whose client is set up differently, e.g. to go through a proxy. See
+    /// [`ApiBuilder`].
+    pub fn builder() -> ApiBuilder {
+        ApiBuilder::new()
+    }
+
+    /// Same as [`Api::new`] but the [`ImageCache`] will hold at most `max_bytes`.
+    pub fn with_image_cache_budget(max_bytes: usize) -> Self {
+        Self::with_image_cache(ImageCache::new(max_bytes))
     }
 
-    pub fn load_home_data(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let resp =
-            reqwest::blocking::get("https://cd-static.bamgrid.com/dp-117731241344/home.json")?
-                .json::<Value>()?;
-        self.json_data.replace(resp);
+    /// Same as [`Api::new`] but using the given `image_cache`, e.g. one backed by a [`DiskCache`].
+    pub fn with_image_cache(image_cache: ImageCache) -> Self {
+        let retry_policy = image_cache.retry_policy();
+        Self {
+            home_data: None,
+            ref_sets: HashMap::new(),
+            image_cache: Arc::new(image_cache),
+            image_options: ImageOptions::default(),
+            retry_policy,
+            transport: Arc::new(ReqwestTransport::default()),
+            client: ReqwestTransport::default(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            rating_ceiling: None,
+            language: None,
+            home_cache: None,
+        }
+    }
+
+    /// Fetches the json from `base_url` instead of the [`DEFAULT_BASE_URL`], e.g. a local server.
+    /// The artwork urls are absolute, they come from the json.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into();
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Where the json is fetched from.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Asks for the titles and descriptions in `language`, a language tag like `fr-FR`, through the
+    /// `lang` query parameter of the json urls. None leaves it to the backend, which serves them
+    /// in English. The artwork urls are left untouched.
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Saves the home data fetched in `home_cache`, see [`Api::load_cached_home_data`]. None keeps
+    /// it in memory only.
+    pub fn with_home_cache(mut self, home_cache: Option<HomeCache>) -> Self {
+        self.home_cache = home_cache.map(Arc::new);
+        self
+    }
+
+    /// Sets the [`Transport`] of every request, the artwork fetched through the [`ImageCache`] included.
+    pub fn with_transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.set_transport(transport);
+        self
+    }
+
+    /// Goes through `client`, the artwork included, see [`Api::client`].
+    pub(crate) fn with_client(mut self, client: ReqwestTransport) -> Self {
+        self.client = client.clone();
+        self.set_transport(client);
+        self
+    }
+
+    /// The client the [`Api`] was built with, see [`ApiBuilder`]. For the requests made outside of
+    /// it to go through the same proxy and trust the same certificates, e.g. with a
+    /// [`TokenTransport`]. It stays the same when another [`Transport`] is given.
+    pub fn client(&self) -> &ReqwestTransport {
+        &self.client
+    }
+
+    /// Same as [`Api::with_transport`] on an [`Api`] already built, e.g. before loading a
+    /// [`ContentProvider`] whose artwork needs an access token.
+    pub fn set_transport(&mut self, transport: impl Transport + 'static) {
+        let transport: Arc<dyn Transport> = Arc::new(transport);
+        self.image_cache.set_transport(Arc::clone(&transport));
+        self.transport = transport;
+    }
+
+    /// Sets the [`RetryPolicy`] of every request, the artwork fetched through the [`ImageCache`] included.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.image_cache.set_retry_policy(retry_policy);
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// The [`RetryPolicy`] of the requests.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    /// Sets the [`ImageOptions`] used when building the artwork urls.
+    pub fn with_image_options(mut self, image_options: ImageOptions) -> Self {
+        self.image_options = image_options;
+        self
+    }
+
+    /// Same as [`Api::with_image_options`] once the home data is loaded. The sets handed out from
+    /// then on build their artwork urls with the new options.
+    pub fn set_image_options(&mut self, image_options: ImageOptions) {
+        self.image_options = image_options;
+    }
+
+    /// The tier the tile artwork is currently requested at, see [`ImageOptions::current_tier`].
+    pub fn image_tier(&self) -> ImageTier {
+        self.image_options.current_tier(&self.image_cache)
+    }
+
+    /// Leaves the items rated above `rating_ceiling` out of every set, see [`SetData::with_rating_ceiling`].
+    /// The sets, their item indices and the search only see the items left. None keeps every item.
+    pub fn with_rating_ceiling(mut self, rating_ceiling: Option<ContentRating>) -> Self {
+        self.rating_ceiling = rating_ceiling;
+        self
+    }
+
+    /// The rating above which the items are left out, see [`Api::with_rating_ceiling`].
+    pub fn rating_ceiling(&self) -> Option<ContentRating> {
+        self.rating_ceiling
+    }
+
+    /// Same as [`Api::with_rating_ceiling`] once the home data is loaded. Returns how the sets
+    /// changed, the items left out coming back or the other way around.
+    pub fn set_rating_ceiling(&mut self, rating_ceiling: Option<ContentRating>) -> HomeDataDiff {
+        let diff = match &self.home_data {
+            Some(home_data) => {
+                let resolve = |ref_id: &str| self.ref_sets.get(ref_id);
+                HomeDataDiff::between(
+                    &self.set_keys(home_data, resolve, self.rating_ceiling),
+                    &self.set_keys(home_data, resolve, rating_ceiling),
+                )
+            }
+            None => HomeDataDiff::default(),
+        };
+        self.rating_ceiling = rating_ceiling;
+        diff
+    }
+
+    /// Handle to the [`ImageCache`] so images can be fetched from other threads.
+    pub fn image_cache(&self) -> Arc<ImageCache> {
+        Arc::clone(&self.image_cache)
+    }
+
+    /// Fetch the home data. Containers of type `SetRef` are resolved eagerly via [`Api::resolve_ref_set`].
+    pub fn load_home_data(&mut self) -> Result<(), ApiError> {
+        self.refresh_home_data().map(|_| ())
+    }
+
+    /// Loads the home data saved by the last fetch in the [`HomeCache`], without going to the
+    /// network. Returns false if there is none, e.g. on the first run or without a cache.
+    ///
+    /// It may be outdated: fetching it again with [`Api::refresh_home_data`], or a
+    /// [`HomeFetcher`], is cheap when it's not.
+    pub fn load_cached_home_data(&mut self) -> bool {
+        match self.home_fetcher().cached() {
+            Some(snapshot) => {
+                self.set_home_data(snapshot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Same as [`Api::load_home_data`], for home data already loaded. Returns how the sets changed
+    /// so whatever shows them can be updated, see [`HomeDataDiff`].
+    ///
+    /// The loaded home data is left as is if the home.json can't be fetched. The `SetRef` sets that
+    /// can't be fetched again keep their previous items.
+    pub fn refresh_home_data(&mut self) -> Result<HomeDataDiff, ApiError> {
+        let snapshot = self.home_fetcher().fetch()?;
+        Ok(self.set_home_data(snapshot))
+    }
+
+    /// Handle to fetch the home data from other threads, as [`Api::refresh_home_data`] would.
+    /// See [`Api::set_home_data`] to use what it fetched.
+    pub fn home_fetcher(&self) -> HomeFetcher {
+        HomeFetcher {
+            transport: Arc::clone(&self.transport),
+            retry_policy: self.retry_policy,
+            base_url: self.base_url.clone(),
+            language: self.language.clone(),
+            home_cache: self.home_cache.clone(),
+        }
+    }
+
+    /// How the sets would change if `snapshot` replaced the loaded home data.
+    pub fn diff_home_data(&self, snapshot: &HomeSnapshot) -> HomeDataDiff {
+        let previous = match &self.home_data {
+            Some(home_data) => self.set_keys(
+                home_data,
+                |ref_id| self.ref_sets.get(ref_id),
+                self.rating_ceiling,
+            ),
+            None => Vec::new(),
+        };
+        // The `SetRef` sets missing from the snapshot are kept, see `Api::set_home_data`.
+        let current = self.set_keys(
+            &snapshot.home_data,
+            |ref_id| {
+                snapshot
+                    .ref_sets
+                    .get(ref_id)
+                    .or_else(|| self.ref_sets.get(ref_id))
+            },
+            self.rating_ceiling,
+        );
+        HomeDataDiff::between(&previous, &current)
+    }
+
+    /// Replaces the loaded home data with the one fetched by a [`HomeFetcher`]. Returns how the sets
+    /// changed, see [`Api::diff_home_data`].
+    ///
+    /// The `SetRef` sets that couldn't be fetched keep their previous items, if any. The [`ImageCache`]
+    /// is kept as is.
+    pub fn set_home_data(&mut self, snapshot: HomeSnapshot) -> HomeDataDiff {
+        let diff = self.diff_home_data(&snapshot);
+        let HomeSnapshot {
+            home_data,
+            mut ref_sets,
+        } = snapshot;
+        for ref_id in ref_ids(&home_data) {
+            if !ref_sets.contains_key(ref_id) {
+                if let Some(previous) = self.ref_sets.remove(ref_id) {
+                    ref_sets.insert(ref_id.clone(), previous);
+                }
+            }
+        }
+        self.ref_sets = ref_sets;
+        self.home_data.replace(home_data);
+        diff
+    }
+
+    /// Same as [`Api::load_home_data`] from the home.json saved at `path`, for working offline.
+    ///
+    /// The `SetRef` containers are resolved from the `sets/<refId>.json` files next to it, the ones
+    /// missing are left unresolved.
+    pub fn load_from_file(&mut self, path: impl AsRef<Path>) -> Result<(), ApiError> {
+        let path = path.as_ref();
+        let home_data = read_json::<HomeResponse>(path)?;
+
+        let sets_dir = path.parent().unwrap_or_else(|| Path::new(".")).join("sets");
+        let mut ref_sets = HashMap::new();
+        for ref_id in ref_ids(&home_data) {
+            let ref_path = sets_dir.join(format!("{}.json", ref_id));
+            match read_json::<RefSetResponse>(&ref_path).and_then(|resp| first_set(ref_id, resp)) {
+                Ok(resolved) => {
+                    ref_sets.insert(ref_id.clone(), resolved);
+                }
+                Err(e) => info!(
+                    "Could not resolve ref set '{}' from {}: {}",
+                    ref_id,
+                    ref_path.display(),
+                    e
+                ),
+            }
+        }
+        self.set_home_data(HomeSnapshot {
+            home_data,
+            ref_sets,
+        });
 
         Ok(())
     }
 
+    /// Same as [`Api::load_home_data`] from whatever catalog the `provider` gives, e.g. a
+    /// [`FolderProvider`]. Each of its sets becomes a home page container, the items keeping their
+    /// tile artwork, title and metadata. Their artwork urls still get the [`ImageOptions`] applied.
+    pub fn load_from_provider(&mut self, provider: &dyn ContentProvider) -> Result<(), ApiError> {
+        let containers = provider
+            .list_sets()
+            .map(|set_idx| {
+                let items = (0..provider.item_count(set_idx))
+                    .map(|item_idx| provided_item(provider, set_idx, item_idx))
+                    .collect::<Vec<_>>();
+                json!({
+                    "set": {
+                        "type": "CuratedSet",
+                        "setId": format!("set-{}", set_idx),
+                        "items": items,
+                        "text": text_bundle("set", provider.set_title(set_idx)),
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        let home_data = serde_json::from_value(json!({
+            "data": { "StandardCollection": { "containers": containers } },
+        }))?;
+        self.set_home_data(HomeSnapshot {
+            home_data,
+            ref_sets: HashMap::new(),
+        });
+        Ok(())
+    }
+
+    /// Fetch the set referenced by a `SetRef` container.
+    ///
+    /// See [`RefSetResponse`] for the payload shape, the first set found under `data` is returned.
+    pub fn resolve_ref_set(&self, ref_id: &str) -> Result<Set, ApiError> {
+        self.home_fetcher().resolve_ref_set(ref_id)
+    }
+
     /// Attempt to get the [`SetData`] for the given `set_idx`
     pub fn get_set(&self, set_idx: usize) -> Option<SetData> {
-        if let Some(data) = self.json_data.as_ref() {
-            let ct = &data["data"]["StandardCollection"]["containers"];
+        let data = self.home_data.as_ref()?;
+        let container = data.data.standard_collection.containers.get(set_idx)?;
 
-            if let Value::Array(arr) = ct {
-                if set_idx >= arr.len() {
-                    return None;
-                }
+        let resolved = container
+            .set
+            .ref_id
+            .as_ref()
+            .and_then(|id| self.ref_sets.get(id));
+        Some(self.set_data(container, resolved, self.rating_ceiling))
+    }
+
+    /// The [`SetData`] of the `container`, its items coming from the `resolved` set if any.
+    fn set_data<'a>(
+        &'a self,
+        container: &'a Container,
+        resolved: Option<&'a Set>,
+        rating_ceiling: Option<ContentRating>,
+    ) -> SetData<'a> {
+        let set_data = match resolved {
+            Some(resolved) => {
+                SetData::with_ref(container, resolved, &self.image_cache, &self.image_options)
             }
+            None => SetData::new(container, &self.image_cache, &self.image_options),
+        };
+        match rating_ceiling {
+            Some(rating_ceiling) => set_data.with_rating_ceiling(rating_ceiling),
+            None => set_data,
+        }
+    }
 
-            let res = &ct[set_idx]["set"];
-            let set = SetData::new(res);
-            Some(set)
-        } else {
-            None
+    /// [`SetKey`]s of the containers of `home_data`, the `SetRef` ones resolved through `resolve`,
+    /// as they would be with the given `rating_ceiling`.
+    fn set_keys<'a>(
+        &'a self,
+        home_data: &'a HomeResponse,
+        resolve: impl Fn(&str) -> Option<&'a Set>,
+        rating_ceiling: Option<ContentRating>,
+    ) -> Vec<SetKey<'a>> {
+        home_data
+            .data
+            .standard_collection
+            .containers
+            .iter()
+            .map(|container| {
+                let resolved = container.set.ref_id.as_deref().and_then(&resolve);
+                SetKey::new(&self.set_data(container, resolved, rating_ceiling))
+            })
+            .collect()
+    }
+
+    /// Fetches the tile images of the items in `range` of the set `set_num` into the [`ImageCache`],
+    /// at most [`PREFETCH_PARALLELISM`] at a time. See [`SetData::prefetch_images`].
+    ///
+    /// The returned handle tells how far along the batch is, dropping it cancels what's left.
+    pub fn prefetch_images(&self, set_num: usize, range: Range<usize>) -> PrefetchHandle {
+        match self.get_set(set_num) {
+            Some(set_data) => set_data.prefetch_images(range),
+            None => PrefetchHandle::spawn(self.image_cache(), Vec::new()),
         }
     }
 
-    /// Returns the number of containers that were previously loaded.
-    /// Returns None if the api has not fetched any data.
-    pub fn get_num_of_sets(&self) -> Option<usize> {
-        if let Some(data) = self.json_data.as_ref() {
-            let ct = &data["data"]["StandardCollection"]["containers"];
+    /// Builds a set titled `title` out of the items with the given `content_ids`, in that order.
+    ///
+    /// Ids not found in the loaded home data are skipped. See [`Api::find_item`].
+    pub fn custom_set<'a, S: AsRef<str>>(
+        &'a self,
+        title: &'a str,
+        content_ids: &[S],
+    ) -> SetData<'a> {
+        let items = content_ids
+            .iter()
+            .filter_map(|content_id| {
+                let (true_set_idx, true_item_idx) = self.find_item(content_id.as_ref())?;
+                self.get_set(true_set_idx)?.get_item(true_item_idx)
+            })
+            .collect();
+        SetData {
+            source: SetSource::Custom { title, items },
+            image_cache: &self.image_cache,
+            image_options: &self.image_options,
+            tile_aspect_ratio: self.image_options.tile_aspect_ratio,
+            shown: None,
+        }
+    }
 
-            if let Value::Array(arr) = ct {
-                return Some(arr.len());
+    /// Returns the set and item indices of the first item with the given `content_id`.
+    pub fn find_item(&self, content_id: &str) -> Option<(usize, usize)> {
+        (0..self.get_num_of_sets()?).find_map(|true_set_idx| {
+            self.get_set(true_set_idx)?
+                .items()
+                .position(|item| item.content_id.as_deref() == Some(content_id))
+                .map(|true_item_idx| (true_set_idx, true_item_idx))
+        })
+    }
+
+    /// Returns the items whose full title contains `query`, ignoring case.
+    ///
+    /// As a first step this filters the already loaded home data instead of hitting a search endpoint,
+    /// so only items present in the home page can be found. Items showing up in several sets are
+    /// only returned once, with the indices of the first set they appear in.
+    pub fn search(&self, query: &str) -> Vec<SearchResult> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut seen = HashSet::new();
+        let mut results = Vec::new();
+        for true_set_idx in 0..self.get_num_of_sets().unwrap_or(0) {
+            let set_data = match self.get_set(true_set_idx) {
+                Some(set_data) => set_data,
+                None => continue,
+            };
+            for (true_item_idx, item) in set_data.items().enumerate() {
+                let title = match item.text.any_title("full") {
+                    Some(title) => title,
+                    None => continue,
+                };
+                if !title.to_lowercase().contains(&query) {
+                    continue;
+                }
+                if let Some(content_id) = item.content_id.as_ref() {
+                    if !seen.insert(content_id) {
+                        continue;
+                    }
+                }
+                results.push(SearchResult {
+                    true_set_idx,
+                    true_item_idx,
+                    title: title.to_string(),
+                });
             }
         }
-        None
+        debug!("search {:?}: {} results", query, results.len());
+        results
     }
+
+    /// Number of sets in the home data, [`get_set`](Api::get_set) returns one for every index below
+    /// it. The ref sets count whether they were resolved or not.
+    ///
+    /// Returns None until the home data is loaded, see [`load_home_data`](Api::load_home_data).
+    pub fn get_num_of_sets(&self) -> Option<usize> {
+        self.home_data
+            .as_ref()
+            .map(|data| data.data.standard_collection.containers.len())
+    }
+}
+
+/// The item `item_idx` of the set `set_idx` of the `provider`, shaped like the items of the home.json.
+fn provided_item(provider: &dyn ContentProvider, set_idx: usize, item_idx: usize) -> Value {
+    let metadata = provider
+        .item_metadata(set_idx, item_idx)
+        .unwrap_or_default();
+    let releases: Vec<Value> = metadata
+        .release_year
+        .map(|year| json!({ "releaseYear": year }))
+        .into_iter()
+        .collect();
+    let ratings: Vec<Value> = metadata
+        .rating
+        .as_ref()
+        .map(|rating| json!({ "value": rating }))
+        .into_iter()
+        .collect();
+    let mut item = json!({
+        "contentId": metadata.content_id,
+        "type": metadata.content_type,
+        "text": text_bundle("program", metadata.title.as_deref()),
+        "releases": releases,
+        "ratings": ratings,
+    });
+    match provider.item_image_url(set_idx, item_idx) {
+        Ok(tile) => {
+            // The artwork is keyed by its aspect ratio rounded to two decimals, e.g. "1.78".
+            let aspect_ratio = format!("{:.2}", tile.aspect_ratio);
+            item["image"] = json!({
+                "tile": { aspect_ratio: { "default": { "default": { "url": tile.url } } } },
+            })
+        }
+        Err(e) => debug!("No artwork for item {} of set {}: {}", item_idx, set_idx, e),
+    }
+    item
+}
+
+/// A `text` object holding the full `title`, as coming from the `source` entity.
+fn text_bundle(source: &str, title: Option<&str>) -> Value {
+    match title {
+        Some(title) => json!({
+            "title": { "full": { source: { "default": { "content": title } } } },
+        }),
+        None => json!({}),
+    }
+}
+
+/// Read and parse the json file at `path`.
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, ApiError> {
+    Ok(serde_json::from_slice(&fs::read(path)?)?)
+}
+
+/// The `refId`s of the `SetRef` containers found in the home data.
+fn ref_ids(resp: &HomeResponse) -> impl Iterator<Item = &String> {
+    resp.data
+        .standard_collection
+        .containers
+        .iter()
+        .map(|container| &container.set)
+        .filter(|set| set.set_type.as_deref() == Some(SET_TYPE_REF))
+        .filter_map(|set| set.ref_id.as_ref())
+}
+
+/// The first set found under `data` in the payload of the `SetRef` `ref_id`.
+fn first_set(ref_id: &str, resp: RefSetResponse) -> Result<Set, ApiError> {
+    resp.data
+        .into_values()
+        .next()
+        .ok_or_else(|| ApiError::missing(format!("sets/{}.json: data.<SetType>", ref_id)))
 }
@@ -0,0 +1,342 @@
+//! Rows read from a Jellyfin or Plex server, enabled by the `media-server` feature. See
+//! [`MediaServerProvider`].
+use crate::{
+    ApiError, ContentProvider, ItemMetadata, RetryPolicy, TileArt, TokenTransport, Transport,
+    POSTER_ASPECT_RATIO,
+};
+use log::{debug, info};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// Items fetched at most per library, the rest is left out.
+pub const MAX_LIBRARY_ITEMS: usize = 1000;
+
+/// The kinds of media servers, each with its own REST API.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MediaServer {
+    /// <https://jellyfin.org>, Emby speaks the same API.
+    Jellyfin,
+    /// <https://www.plex.tv>.
+    Plex,
+}
+
+impl MediaServer {
+    /// Header the access token goes in.
+    pub fn token_header(self) -> &'static str {
+        match self {
+            MediaServer::Jellyfin => "X-Emby-Token",
+            MediaServer::Plex => "X-Plex-Token",
+        }
+    }
+
+    /// The [`Transport`] sending the `token` to this kind of server.
+    pub fn transport(self, token: impl Into<String>) -> TokenTransport {
+        TokenTransport::new(self.token_header(), token)
+    }
+}
+
+impl FromStr for MediaServer {
+    type Err = String;
+
+    /// Parses `jellyfin` or `plex`, ignoring case.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "jellyfin" => Ok(MediaServer::Jellyfin),
+            "plex" => Ok(MediaServer::Plex),
+            _ => Err(value.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for MediaServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MediaServer::Jellyfin => "jellyfin",
+            MediaServer::Plex => "plex",
+        })
+    }
+}
+
+/// A movie, show or video of a [`Library`].
+#[derive(Debug)]
+struct LibraryItem {
+    metadata: ItemMetadata,
+    /// The poster, None if the item has none.
+    tile: Option<TileArt>,
+}
+
+/// A library of the server, e.g. "Movies".
+#[derive(Debug)]
+struct Library {
+    title: String,
+    items: Vec<LibraryItem>,
+}
+
+/// [`ContentProvider`] showing the libraries of a media server as sets, their movies and shows as
+/// items with their poster for a tile. Only the first [`MAX_LIBRARY_ITEMS`] of each library are
+/// fetched, sorted by name.
+///
+/// Every request sends the access token, see [`MediaServer::transport`]. The posters need it too:
+/// give the same [`Transport`] to the [`Api`](crate::Api) the provider is loaded into, with
+/// [`Api::set_transport`](crate::Api::set_transport).
+#[derive(Debug)]
+pub struct MediaServerProvider {
+    libraries: Vec<Library>,
+}
+
+impl MediaServerProvider {
+    /// Fetches the libraries of the `server` at `base_url` and their items, through the
+    /// `transport` as per the `retry_policy`.
+    pub fn connect(
+        server: MediaServer,
+        base_url: &str,
+        transport: &dyn Transport,
+        retry_policy: RetryPolicy,
+    ) -> Result<Self, ApiError> {
+        let client = Client {
+            base_url: base_url.trim_end_matches('/'),
+            transport,
+            retry_policy,
+        };
+        let libraries = match server {
+            MediaServer::Jellyfin => client.jellyfin_libraries()?,
+            MediaServer::Plex => client.plex_libraries()?,
+        };
+        info!(
+            "{} libraries found on the {} server {}",
+            libraries.len(),
+            server,
+            base_url
+        );
+        Ok(Self { libraries })
+    }
+
+    fn item(&self, set_idx: usize, item_idx: usize) -> Option<&LibraryItem> {
+        self.libraries.get(set_idx)?.items.get(item_idx)
+    }
+}
+
+impl ContentProvider for MediaServerProvider {
+    fn list_sets(&self) -> Range<usize> {
+        0..self.libraries.len()
+    }
+
+    fn set_title(&self, set_idx: usize) -> Option<&str> {
+        self.libraries
+            .get(set_idx)
+            .map(|library| library.title.as_str())
+    }
+
+    fn item_count(&self, set_idx: usize) -> usize {
+        self.libraries
+            .get(set_idx)
+            .map_or(0, |library| library.items.len())
+    }
+
+    fn item_image_url(&self, set_idx: usize, item_idx: usize) -> Result<TileArt, ApiError> {
+        self.item(set_idx, item_idx)
+            .and_then(|item| item.tile.clone())
+            .ok_or_else(|| {
+                ApiError::missing(format!("libraries[{}].items[{}].poster", set_idx, item_idx))
+            })
+    }
+
+    fn item_metadata(&self, set_idx: usize, item_idx: usize) -> Option<ItemMetadata> {
+        self.item(set_idx, item_idx)
+            .map(|item| item.metadata.clone())
+    }
+}
+
+/// The requests to a server.
+struct Client<'a> {
+    /// Without the trailing slash.
+    base_url: &'a str,
+    transport: &'a dyn Transport,
+    retry_policy: RetryPolicy,
+}
+
+impl Client<'_> {
+    /// Fetch the json found at `path` on the server, as per the [`RetryPolicy`], and parse it.
+    fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, ApiError> {
+        let url = format!("{}{}", self.base_url, path);
+        let policy = &self.retry_policy;
+        let value = policy.run(&url, || self.transport.get_json(&url, policy.timeout))?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// The views of the user the token belongs to, each with its movies and shows.
+    fn jellyfin_libraries(&self) -> Result<Vec<Library>, ApiError> {
+        let user: JellyfinUser = self.get_json("/Users/Me")?;
+        let views: JellyfinItems = self.get_json(&format!("/Users/{}/Views", user.id))?;
+        views
+            .items
+            .into_iter()
+            .map(|view| {
+                let path = format!(
+                    "/Users/{}/Items?ParentId={}&Recursive=true&IncludeItemTypes=Movie,Series,Video\
+                     &SortBy=SortName&Fields=PrimaryImageAspectRatio,ProductionYear,OfficialRating\
+                     &Limit={}",
+                    user.id, view.id, MAX_LIBRARY_ITEMS
+                );
+                let items: JellyfinItems = self.get_json(&path)?;
+                debug!("{} items in the view {}", items.items.len(), view.id);
+                Ok(Library {
+                    title: view.name.unwrap_or_default(),
+                    items: items
+                        .items
+                        .into_iter()
+                        .map(|item| self.jellyfin_item(item))
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    fn jellyfin_item(&self, item: JellyfinItem) -> LibraryItem {
+        let tile = item.image_tags.get("Primary").map(|tag| TileArt {
+            url: format!(
+                "{}/Items/{}/Images/Primary?tag={}",
+                self.base_url, item.id, tag
+            ),
+            aspect_ratio: item
+                .primary_image_aspect_ratio
+                .map_or(POSTER_ASPECT_RATIO, |ar| ar as f32),
+        });
+        LibraryItem {
+            metadata: ItemMetadata {
+                title: item.name,
+                content_id: Some(format!("jellyfin:{}", item.id)),
+                content_type: item.item_type,
+                release_year: item.production_year,
+                rating: item.official_rating,
+            },
+            tile,
+        }
+    }
+
+    /// The library sections, each with its movies or shows.
+    fn plex_libraries(&self) -> Result<Vec<Library>, ApiError> {
+        let sections: PlexResponse<PlexSections> = self.get_json("/library/sections")?;
+        sections
+            .media_container
+            .directories
+            .into_iter()
+            .map(|section| {
+                let path = format!(
+                    "/library/sections/{}/all?X-Plex-Container-Start=0&X-Plex-Container-Size={}",
+                    section.key, MAX_LIBRARY_ITEMS
+                );
+                let items: PlexResponse<PlexItems> = self.get_json(&path)?;
+                debug!(
+                    "{} items in the section {}",
+                    items.media_container.metadata.len(),
+                    section.key
+                );
+                Ok(Library {
+                    title: section.title,
+                    items: items
+                        .media_container
+                        .metadata
+                        .into_iter()
+                        .map(|item| self.plex_item(item))
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    fn plex_item(&self, item: PlexItem) -> LibraryItem {
+        let tile = item.thumb.as_ref().map(|thumb| TileArt {
+            url: format!("{}{}", self.base_url, thumb),
+            aspect_ratio: POSTER_ASPECT_RATIO,
+        });
+        let content_type = item.item_type.map(|item_type| match item_type.as_str() {
+            "movie" => String::from("Movie"),
+            "show" => String::from("Series"),
+            _ => item_type,
+        });
+        LibraryItem {
+            metadata: ItemMetadata {
+                title: item.title,
+                content_id: Some(format!("plex:{}", item.rating_key)),
+                content_type,
+                release_year: item.year,
+                rating: item.content_rating,
+            },
+            tile,
+        }
+    }
+}
+
+/// `/Users/Me` of a Jellyfin server.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct JellyfinUser {
+    id: String,
+}
+
+/// The views and the items of a Jellyfin server.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct JellyfinItems {
+    #[serde(default)]
+    items: Vec<JellyfinItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct JellyfinItem {
+    id: String,
+    name: Option<String>,
+    #[serde(rename = "Type")]
+    item_type: Option<String>,
+    production_year: Option<u32>,
+    official_rating: Option<String>,
+    /// Tag of each kind of image the item has, e.g. `Primary` for the poster.
+    #[serde(default)]
+    image_tags: HashMap<String, String>,
+    primary_image_aspect_ratio: Option<f64>,
+}
+
+/// Every json answer of a Plex server is wrapped in a `MediaContainer`.
+#[derive(Debug, Deserialize)]
+struct PlexResponse<T> {
+    #[serde(rename = "MediaContainer")]
+    media_container: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlexSections {
+    #[serde(rename = "Directory", default)]
+    directories: Vec<PlexSection>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlexSection {
+    key: String,
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlexItems {
+    #[serde(rename = "Metadata", default)]
+    metadata: Vec<PlexItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlexItem {
+    #[serde(rename = "ratingKey")]
+    rating_key: String,
+    title: Option<String>,
+    #[serde(rename = "type")]
+    item_type: Option<String>,
+    year: Option<u32>,
+    #[serde(rename = "contentRating")]
+    content_rating: Option<String>,
+    /// Path of the poster on the server.
+    thumb: Option<String>,
+}
@@ -0,0 +1,274 @@
+//! Typed representation of the home.json (and ref sets) payloads.
+//!
+//! Only the fields the UI cares about are typed, everything else is kept around as raw [`Value`]s
+//! in the `extra` maps so nothing is lost if the schema grows.
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Some fields are explicitly `null` instead of missing, treat both the same way.
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Top level of the home.json payload.
+#[derive(Debug, Deserialize)]
+pub struct HomeResponse {
+    pub data: HomeData,
+}
+
+/// The `data` object of the home.json payload.
+#[derive(Debug, Deserialize)]
+pub struct HomeData {
+    #[serde(rename = "StandardCollection")]
+    pub standard_collection: StandardCollection,
+}
+
+/// The collection holding every row of the home page.
+#[derive(Debug, Deserialize)]
+pub struct StandardCollection {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub containers: Vec<Container>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// A single row of the home page.
+#[derive(Debug, Deserialize)]
+pub struct Container {
+    pub set: Set,
+    pub style: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Payload returned when resolving a `SetRef`.
+///
+/// Looks like `{ "data": { "<SetType>": { ... } } }` where `SetType` varies
+/// (`CuratedSet`, `TrendingSet`, `PersonalizedCuratedSet`...).
+#[derive(Debug, Deserialize)]
+pub struct RefSetResponse {
+    pub data: HashMap<String, Set>,
+}
+
+/// A set of items. When `set_type` is `SetRef` the items live in a separate payload pointed to by `ref_id`.
+#[derive(Debug, Deserialize)]
+pub struct Set {
+    #[serde(rename = "type")]
+    pub set_type: Option<String>,
+    #[serde(rename = "setId")]
+    pub set_id: Option<String>,
+    #[serde(rename = "refId")]
+    pub ref_id: Option<String>,
+    #[serde(rename = "contentClass")]
+    pub content_class: Option<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub items: Vec<Item>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub text: TextBundle,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// A single item (tile) of a set.
+#[derive(Debug, Deserialize)]
+pub struct Item {
+    #[serde(rename = "contentId")]
+    pub content_id: Option<String>,
+    #[serde(rename = "type")]
+    pub item_type: Option<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub image: ImageVariants,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub text: TextBundle,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub releases: Vec<Release>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub ratings: Vec<Rating>,
+    #[serde(rename = "videoArt", default, deserialize_with = "null_as_default")]
+    pub video_art: Vec<VideoArt>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// A release of an [`Item`] (original, theatrical...).
+#[derive(Debug, Deserialize)]
+pub struct Release {
+    #[serde(rename = "releaseType")]
+    pub release_type: Option<String>,
+    #[serde(rename = "releaseDate")]
+    pub release_date: Option<String>,
+    #[serde(rename = "releaseYear")]
+    pub release_year: Option<u32>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// A content rating of an [`Item`] for a given rating `system` (MPAA, TVPG...).
+#[derive(Debug, Deserialize)]
+pub struct Rating {
+    pub system: Option<String>,
+    pub value: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// A background video loop of an [`Item`], an entry of its `videoArt`.
+#[derive(Debug, Deserialize)]
+pub struct VideoArt {
+    pub purpose: Option<String>,
+    #[serde(
+        rename = "mediaMetadata",
+        default,
+        deserialize_with = "null_as_default"
+    )]
+    pub media_metadata: MediaMetadata,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Where a [`VideoArt`] is served from.
+#[derive(Debug, Default, Deserialize)]
+pub struct MediaMetadata {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub urls: Vec<MediaUrl>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// One of the urls a [`VideoArt`] is served at.
+#[derive(Debug, Deserialize)]
+pub struct MediaUrl {
+    pub url: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Artwork of a given kind keyed by aspect ratio and then by tile type (`series`, `program`, `default`...).
+pub type AspectRatioMap = BTreeMap<String, BTreeMap<String, ImageSource>>;
+
+/// The `image` object of an [`Item`].
+#[derive(Debug, Default, Deserialize)]
+pub struct ImageVariants {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub tile: AspectRatioMap,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub background: AspectRatioMap,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub hero_tile: AspectRatioMap,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub title_treatment: AspectRatioMap,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Wrapper around the `default` entry of an image.
+#[derive(Debug, Deserialize)]
+pub struct ImageSource {
+    pub default: ImageAsset,
+}
+
+/// The actual artwork location.
+#[derive(Debug, Deserialize)]
+pub struct ImageAsset {
+    pub url: String,
+    #[serde(rename = "masterId")]
+    pub master_id: Option<String>,
+    #[serde(rename = "masterWidth")]
+    pub master_width: Option<u32>,
+    #[serde(rename = "masterHeight")]
+    pub master_height: Option<u32>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Text variants keyed by length (`full`, `slug`, `medium`...) and then by source entity (`set`, `series`, `program`...).
+pub type TextVariants = BTreeMap<String, BTreeMap<String, LocalizedText>>;
+
+/// The `text` object of a [`Set`] or an [`Item`].
+#[derive(Debug, Default, Deserialize)]
+pub struct TextBundle {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub title: TextVariants,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub description: TextVariants,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Text paths the title of a set is looked for at, in order, as `(length, source)`. Past these, the
+/// first `full` title of any other source is used, see [`TextBundle::set_title`].
+pub const SET_TITLE_PATHS: [(&str, &str); 4] = [
+    ("full", "set"),
+    ("full", "collection"),
+    ("full", "series"),
+    ("full", "program"),
+];
+
+/// Where a title was read from: `text.title[length][source]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TitleSource<'a> {
+    pub length: &'a str,
+    pub source: &'a str,
+}
+
+impl TextBundle {
+    /// Get the title for the given `length` and `source` if present.
+    pub fn title(&self, length: &str, source: &str) -> Option<&str> {
+        self.title
+            .get(length)
+            .and_then(|by_source| by_source.get(source))
+            .map(|t| t.default.content.as_str())
+    }
+
+    /// Get the first title for the given `length` regardless of its source.
+    pub fn any_title(&self, length: &str) -> Option<&str> {
+        self.title
+            .get(length)
+            .and_then(|by_source| by_source.values().next())
+            .map(|t| t.default.content.as_str())
+    }
+
+    /// Get the title of a set along with where it was found: the first of the [`SET_TITLE_PATHS`]
+    /// present, otherwise the first `full` title of any other source.
+    pub fn set_title(&self) -> Option<(&str, TitleSource<'_>)> {
+        let found = |length: &str, source: &str| {
+            let (length, by_source) = self.title.get_key_value(length)?;
+            let (source, text) = by_source.get_key_value(source)?;
+            Some((
+                text.default.content.as_str(),
+                TitleSource { length, source },
+            ))
+        };
+        SET_TITLE_PATHS
+            .iter()
+            .find_map(|(length, source)| found(length, source))
+            .or_else(|| {
+                let (length, by_source) = self.title.get_key_value("full")?;
+                let (source, text) = by_source.iter().next()?;
+                Some((
+                    text.default.content.as_str(),
+                    TitleSource { length, source },
+                ))
+            })
+    }
+}
+
+/// Wrapper around the `default` entry of a text.
+#[derive(Debug, Deserialize)]
+pub struct LocalizedText {
+    pub default: TextContent,
+}
+
+/// The actual text.
+#[derive(Debug, Deserialize)]
+pub struct TextContent {
+    pub content: String,
+    pub language: Option<String>,
+    #[serde(rename = "sourceEntity")]
+    pub source_entity: Option<String>,
+}
@@ -0,0 +1,83 @@
+//! Warming up the [`ImageCache`] ahead of time.
+use crate::ImageCache;
+use log::{debug, info};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Max number of images fetched at once by a single prefetch.
+pub const PREFETCH_PARALLELISM: usize = 4;
+
+/// State shared between a [`PrefetchHandle`] and its threads.
+#[derive(Debug, Default)]
+struct Progress {
+    completed: AtomicUsize,
+    failed: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+/// Handle to a batch of images being fetched into the [`ImageCache`], see [`Api::prefetch_images`](crate::Api::prefetch_images).
+///
+/// Dropping the handle cancels the images not started yet, the ones in flight still land in the cache.
+#[derive(Debug)]
+pub struct PrefetchHandle {
+    total: usize,
+    progress: Arc<Progress>,
+}
+
+impl PrefetchHandle {
+    /// Starts fetching the `urls` through the `image_cache` on up to [`PREFETCH_PARALLELISM`] threads.
+    pub(crate) fn spawn(image_cache: Arc<ImageCache>, urls: Vec<String>) -> Self {
+        let total = urls.len();
+        let progress = Arc::new(Progress::default());
+        let queue = Arc::new(Mutex::new(urls.into_iter()));
+        for _ in 0..PREFETCH_PARALLELISM.min(total) {
+            let image_cache = Arc::clone(&image_cache);
+            let progress = Arc::clone(&progress);
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || loop {
+                if progress.cancelled.load(Ordering::Relaxed) {
+                    break;
+                }
+                // The lock is released before fetching so the other threads can go on.
+                let url = match queue.lock().unwrap().next() {
+                    Some(url) => url,
+                    None => break,
+                };
+                if let Err(e) = image_cache.prefetch(&url) {
+                    info!("Could not prefetch {}: {}", url, e);
+                    progress.failed.fetch_add(1, Ordering::Relaxed);
+                }
+                progress.completed.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        debug!("Prefetching {} images", total);
+        Self { total, progress }
+    }
+
+    /// Number of images in the batch.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Number of images done, including the ones that failed.
+    pub fn completed(&self) -> usize {
+        self.progress.completed.load(Ordering::Relaxed)
+    }
+
+    /// Number of images that couldn't be fetched.
+    pub fn failed(&self) -> usize {
+        self.progress.failed.load(Ordering::Relaxed)
+    }
+
+    /// Returns true once every image of the batch is done.
+    pub fn is_done(&self) -> bool {
+        self.completed() >= self.total
+    }
+}
+
+impl Drop for PrefetchHandle {
+    fn drop(&mut self) {
+        self.progress.cancelled.store(true, Ordering::Relaxed);
+    }
+}
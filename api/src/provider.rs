@@ -0,0 +1,79 @@
+//! Catalog-agnostic view of the rows and their items, see [`ContentProvider`].
+use crate::{Api, ApiError, TileArt};
+use std::ops::Range;
+
+/// What is known about an item besides its artwork. Every field is optional, a catalog fills in
+/// the ones it has.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ItemMetadata {
+    /// Full title of the item.
+    pub title: Option<String>,
+    /// Identifies the item across sets, e.g. to keep it in the watchlist.
+    pub content_id: Option<String>,
+    /// What the item is, e.g. `Series` or `Program`, see [`ContentType`](crate::ContentType).
+    pub content_type: Option<String>,
+    /// Year the item came out.
+    pub release_year: Option<u32>,
+    /// Content rating, as given by the catalog (e.g. `TV-14`).
+    pub rating: Option<String>,
+}
+
+impl ItemMetadata {
+    /// The content type, release year and rating joined in a single line, e.g.
+    /// `Series  |  2019  |  TV-14`. The ones missing are left out, empty if none is known.
+    pub fn summary(&self) -> String {
+        self.content_type
+            .clone()
+            .into_iter()
+            .chain(self.release_year.map(|y| y.to_string()))
+            .chain(self.rating.clone())
+            .collect::<Vec<String>>()
+            .join("  |  ")
+    }
+}
+
+/// A catalog of rows of items, whatever they are fetched or read from.
+///
+/// The sets and their items are addressed by index, the same way as with [`Api::get_set`]. Any
+/// index out of range is treated as missing rather than as an error.
+pub trait ContentProvider {
+    /// Indices of the sets, empty until the catalog is loaded.
+    fn list_sets(&self) -> Range<usize>;
+
+    /// Title of the set `set_idx`, None if it has none.
+    fn set_title(&self, set_idx: usize) -> Option<&str>;
+
+    /// Number of items in the set `set_idx`, 0 for a set that doesn't exist.
+    fn item_count(&self, set_idx: usize) -> usize;
+
+    /// The tile artwork of the item `item_idx` of the set `set_idx`.
+    fn item_image_url(&self, set_idx: usize, item_idx: usize) -> Result<TileArt, ApiError>;
+
+    /// Whatever is known about the item `item_idx` of the set `set_idx`, None if it doesn't exist.
+    fn item_metadata(&self, set_idx: usize, item_idx: usize) -> Option<ItemMetadata>;
+}
+
+impl ContentProvider for Api {
+    fn list_sets(&self) -> Range<usize> {
+        0..self.get_num_of_sets().unwrap_or(0)
+    }
+
+    fn set_title(&self, set_idx: usize) -> Option<&str> {
+        self.get_set(set_idx)?.find_title()
+    }
+
+    fn item_count(&self, set_idx: usize) -> usize {
+        self.get_set(set_idx)
+            .map_or(0, |set_data| set_data.get_item_count())
+    }
+
+    fn item_image_url(&self, set_idx: usize, item_idx: usize) -> Result<TileArt, ApiError> {
+        self.get_set(set_idx)
+            .ok_or_else(|| ApiError::missing(format!("containers[{}]", set_idx)))?
+            .get_home_tile(item_idx)
+    }
+
+    fn item_metadata(&self, set_idx: usize, item_idx: usize) -> Option<ItemMetadata> {
+        self.get_set(set_idx)?.get_item_info(item_idx)
+    }
+}
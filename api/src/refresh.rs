@@ -0,0 +1,237 @@
+//! Reloading the home data while it's being shown, see [`Api::refresh_home_data`](crate::Api::refresh_home_data).
+use crate::home_cache::CachedHome;
+use crate::model::{HomeResponse, RefSetResponse, Set};
+use crate::{
+    first_set, ref_ids, ApiError, Conditional, HomeCache, RetryPolicy, SetData, Transport,
+    Validators,
+};
+use log::{debug, info};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Home data fetched by a [`HomeFetcher`], along with the `SetRef` sets it points to. Hand it to
+/// [`Api::set_home_data`](crate::Api::set_home_data) to start using it.
+#[derive(Debug)]
+pub struct HomeSnapshot {
+    pub(crate) home_data: HomeResponse,
+    /// Sets referenced by `SetRef` containers, keyed by their `refId`. The ones that couldn't be
+    /// fetched are missing.
+    pub(crate) ref_sets: HashMap<String, Set>,
+}
+
+/// Fetches the home data the same way the [`Api`](crate::Api) it comes from does, without borrowing
+/// it. That way the home data can be fetched again on another thread while the one already loaded is
+/// still in use. See [`Api::home_fetcher`](crate::Api::home_fetcher).
+#[derive(Clone)]
+pub struct HomeFetcher {
+    pub(crate) transport: Arc<dyn Transport>,
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) base_url: String,
+    /// Value of the `lang` query parameter, see [`Api::with_language`](crate::Api::with_language).
+    pub(crate) language: Option<String>,
+    /// See [`Api::with_home_cache`](crate::Api::with_home_cache).
+    pub(crate) home_cache: Option<Arc<HomeCache>>,
+}
+
+impl fmt::Debug for HomeFetcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HomeFetcher")
+            .field("retry_policy", &self.retry_policy)
+            .field("base_url", &self.base_url)
+            .field("language", &self.language)
+            .field("home_cache", &self.home_cache)
+            .finish()
+    }
+}
+
+impl HomeFetcher {
+    /// Fetch the home data. Containers of type `SetRef` are resolved eagerly, the ones that can't be
+    /// are left out of the [`HomeSnapshot`].
+    ///
+    /// With a [`HomeCache`] the request is conditional, the cached home data is used as is if the
+    /// home.json didn't change. Otherwise what was fetched replaces it in the cache.
+    pub fn fetch(&self) -> Result<HomeSnapshot, ApiError> {
+        let url = self.home_url();
+        let cached = self.home_cache.as_ref().and_then(|cache| cache.get(&url));
+        let validators = cached
+            .as_ref()
+            .map_or_else(Validators::default, |cached| cached.validators.clone());
+        let policy = &self.retry_policy;
+        let fetched = policy.run(&url, || {
+            self.transport
+                .get_json_if_changed(&url, policy.timeout, &validators)
+        })?;
+        let (home, validators) = match (fetched, cached) {
+            (Conditional::NotModified, Some(cached)) => {
+                debug!("{} not modified", url);
+                return cached.snapshot();
+            }
+            // Not asked for, the request had no validators.
+            (Conditional::NotModified, None) => return Err(ApiError::HttpStatus { code: 304 }),
+            (Conditional::Modified(home, validators), _) => (home, validators),
+        };
+        let home_data = serde_json::from_value::<HomeResponse>(home.clone())?;
+
+        let mut ref_sets = HashMap::new();
+        let mut cached_ref_sets = HashMap::new();
+        for ref_id in ref_ids(&home_data) {
+            let resolved = self.get_value(&self.ref_set_url(ref_id)).and_then(|value| {
+                let resp = serde_json::from_value::<RefSetResponse>(value.clone())?;
+                Ok((value, first_set(ref_id, resp)?))
+            });
+            match resolved {
+                Ok((value, resolved)) => {
+                    ref_sets.insert(ref_id.clone(), resolved);
+                    cached_ref_sets.insert(ref_id.clone(), value);
+                }
+                Err(e) => info!("Could not resolve ref set '{}': {}", ref_id, e),
+            }
+        }
+        if let Some(cache) = &self.home_cache {
+            let cached = CachedHome {
+                validators,
+                home,
+                ref_sets: cached_ref_sets,
+            };
+            cache.insert(&url, &cached);
+        }
+        Ok(HomeSnapshot {
+            home_data,
+            ref_sets,
+        })
+    }
+
+    /// The home data saved by the last [`fetch`](HomeFetcher::fetch) in the [`HomeCache`], if any.
+    pub fn cached(&self) -> Option<HomeSnapshot> {
+        let url = self.home_url();
+        let cached = self.home_cache.as_ref()?.get(&url)?;
+        match cached.snapshot() {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                info!("Ignoring the cached home data of {}: {}", url, e);
+                None
+            }
+        }
+    }
+
+    /// Fetch the set referenced by a `SetRef` container, see [`Api::resolve_ref_set`](crate::Api::resolve_ref_set).
+    pub fn resolve_ref_set(&self, ref_id: &str) -> Result<Set, ApiError> {
+        let resp =
+            serde_json::from_value::<RefSetResponse>(self.get_value(&self.ref_set_url(ref_id))?)?;
+        first_set(ref_id, resp)
+    }
+
+    pub(crate) fn home_url(&self) -> String {
+        self.with_language(format!("{}/home.json", self.base_url))
+    }
+
+    pub(crate) fn ref_set_url(&self, ref_id: &str) -> String {
+        self.with_language(format!("{}/sets/{}.json", self.base_url, ref_id))
+    }
+
+    /// The `url` with the `lang` query parameter, if a language was asked for.
+    fn with_language(&self, url: String) -> String {
+        match &self.language {
+            Some(language) => format!("{}?lang={}", url, language),
+            None => url,
+        }
+    }
+
+    /// Fetch the json found at `url` through the [`Transport`], as per the [`RetryPolicy`].
+    fn get_value(&self, url: &str) -> Result<Value, ApiError> {
+        let policy = &self.retry_policy;
+        policy.run(url, || self.transport.get_json(url, policy.timeout))
+    }
+}
+
+/// How the sets changed between two loads of the home data, see [`Api::diff_home_data`](crate::Api::diff_home_data).
+///
+/// Sets are told apart by their `setId`, or their `refId` for the `SetRef` containers. A set
+/// changed if the `contentId`s of its items did.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HomeDataDiff {
+    /// For each set of the new home data, its index in the previous one. None for the new sets.
+    pub previous_idx: Vec<Option<usize>>,
+    /// Indices, in the new home data, of the sets that were already there but with other items.
+    pub changed: Vec<usize>,
+    /// Number of sets of the previous home data that are gone.
+    pub removed: usize,
+}
+
+impl HomeDataDiff {
+    /// Diff of a home data of `num_of_sets` sets with itself.
+    pub fn unchanged(num_of_sets: usize) -> Self {
+        Self {
+            previous_idx: (0..num_of_sets).map(Some).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// True if the sets and their items are the same, in the same order.
+    pub fn is_unchanged(&self) -> bool {
+        self.changed.is_empty()
+            && self.removed == 0
+            && self
+                .previous_idx
+                .iter()
+                .enumerate()
+                .all(|(set_idx, previous_idx)| *previous_idx == Some(set_idx))
+    }
+
+    /// Index, in the new home data, of the set that was at `previous_idx`. None if it's gone.
+    pub fn current_idx(&self, previous_idx: usize) -> Option<usize> {
+        self.previous_idx
+            .iter()
+            .position(|idx| *idx == Some(previous_idx))
+    }
+
+    /// True if the set at `set_idx` in the new home data was already there, with the same items.
+    pub fn is_kept(&self, set_idx: usize) -> bool {
+        self.previous_idx.get(set_idx).is_some_and(Option::is_some)
+            && !self.changed.contains(&set_idx)
+    }
+
+    /// Compares the sets of `previous` to the ones of `current`.
+    pub(crate) fn between(previous: &[SetKey], current: &[SetKey]) -> Self {
+        let mut matched = vec![false; previous.len()];
+        let mut diff = HomeDataDiff::default();
+        for (set_idx, set) in current.iter().enumerate() {
+            let previous_idx = set.id.and_then(|id| {
+                (0..previous.len()).find(|idx| !matched[*idx] && previous[*idx].id == Some(id))
+            });
+            if let Some(previous_idx) = previous_idx {
+                matched[previous_idx] = true;
+                if previous[previous_idx].content_ids != set.content_ids {
+                    diff.changed.push(set_idx);
+                }
+            }
+            diff.previous_idx.push(previous_idx);
+        }
+        diff.removed = matched.iter().filter(|matched| !**matched).count();
+        diff
+    }
+}
+
+/// What [`HomeDataDiff`] compares a set on.
+#[derive(Debug)]
+pub(crate) struct SetKey<'a> {
+    /// `setId`, or `refId` for the `SetRef` containers.
+    id: Option<&'a str>,
+    content_ids: Vec<Option<&'a str>>,
+}
+
+impl<'a> SetKey<'a> {
+    /// Key of the set behind `set_data`, with the items it shows.
+    pub(crate) fn new(set_data: &SetData<'a>) -> Self {
+        let id = set_data
+            .set()
+            .and_then(|set| set.set_id.as_deref().or(set.ref_id.as_deref()));
+        let content_ids = set_data
+            .items()
+            .map(|item| item.content_id.as_deref())
+            .collect();
+        Self { id, content_ids }
+    }
+}
@@ -0,0 +1,125 @@
+//! Retrying the network calls that fail for transient reasons.
+use crate::ApiError;
+use log::info;
+#[cfg(feature = "async")]
+use reqwest::StatusCode;
+#[cfg(feature = "async")]
+use std::future::Future;
+use std::thread;
+use std::time::Duration;
+
+/// How many times and how patiently a request is attempted before giving up.
+///
+/// Only errors for which [`ApiError::is_retryable`] is true are retried, a 404 fails right away.
+/// The wait between two attempts doubles every time, starting at `initial_backoff` and capped
+/// at `max_backoff`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// Number of attempts, the first one included. 1 disables retrying.
+    pub max_attempts: u32,
+    /// Wait before the second attempt.
+    pub initial_backoff: Duration,
+    /// Longest wait between two attempts.
+    pub max_backoff: Duration,
+    /// Time allowed to each attempt, from connecting to reading the whole body.
+    pub timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(4),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A single attempt with the default timeout.
+    pub fn no_retry() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Wait after the failed attempt number `attempt`, counting from 1.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+
+    /// Calls `attempt` until it succeeds, fails with an error that isn't retryable or runs out of
+    /// attempts. `url` is only used for logging.
+    pub(crate) fn run<T>(
+        &self,
+        url: &str,
+        mut attempt: impl FnMut() -> Result<T, ApiError>,
+    ) -> Result<T, ApiError> {
+        let mut attempts = 1;
+        loop {
+            match attempt() {
+                Err(e) if e.is_retryable() && attempts < self.max_attempts => {
+                    let backoff = self.backoff(attempts);
+                    info!(
+                        "Attempt {} for {} failed ({}), retrying in {:?}",
+                        attempts, url, e, backoff
+                    );
+                    thread::sleep(backoff);
+                    attempts += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Same as [`RetryPolicy::run`] for futures, the backoff doesn't block the thread.
+    #[cfg(feature = "async")]
+    pub(crate) async fn run_async<T, F, Fut>(
+        &self,
+        url: &str,
+        mut attempt: F,
+    ) -> Result<T, ApiError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, ApiError>>,
+    {
+        let mut attempts = 1;
+        loop {
+            match attempt().await {
+                Err(e) if e.is_retryable() && attempts < self.max_attempts => {
+                    let backoff = self.backoff(attempts);
+                    info!(
+                        "Attempt {} for {} failed ({}), retrying in {:?}",
+                        attempts, url, e, backoff
+                    );
+                    tokio::time::sleep(backoff).await;
+                    attempts += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+/// Single `GET` of `url` through the async `client`, giving up after `timeout`. Anything but
+/// `200 OK` is an error, like with the [`ReqwestTransport`](crate::ReqwestTransport).
+#[cfg(feature = "async")]
+pub(crate) async fn get_async(
+    client: &reqwest::Client,
+    url: &str,
+    timeout: Duration,
+) -> Result<reqwest::Response, ApiError> {
+    let response = client.get(url).timeout(timeout).send().await?;
+    if response.status() != StatusCode::OK {
+        info!("Status not good for url {}", url);
+        return Err(ApiError::HttpStatus {
+            code: response.status().as_u16(),
+        });
+    }
+    Ok(response)
+}
@@ -0,0 +1,273 @@
+//! Rows read from The Movie Database, see [`TmdbProvider`].
+use crate::{
+    ApiError, ContentProvider, ItemMetadata, RetryPolicy, TileArt, Transport, POSTER_ASPECT_RATIO,
+};
+use log::{debug, info};
+use serde::de::{self, DeserializeOwned, Deserializer};
+use serde::Deserialize;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// Where the TMDB API v3 is served from.
+pub const TMDB_API_URL: &str = "https://api.themoviedb.org/3";
+/// Where the posters are served from, in the `w500` size: the closest to the tiles drawn.
+pub const TMDB_IMAGE_URL: &str = "https://image.tmdb.org/t/p/w500";
+/// Number of characters of a [`TmdbApiKey`].
+const API_KEY_LENGTH: usize = 32;
+
+/// The rows, each with its title and the path of the list it shows.
+const LISTS: [(&str, &str); 3] = [
+    ("Trending", "/trending/all/week"),
+    ("Popular", "/movie/popular"),
+    ("Top Rated", "/movie/top_rated"),
+];
+
+/// The 32 hexadecimal characters of a TMDB API v3 key, written as a string in the config, e.g.
+/// `tmdb_api_key = "0123456789abcdef0123456789abcdef"`. Kept out of the logs, its `Debug` doesn't
+/// show it.
+#[derive(Clone, Copy, PartialEq)]
+pub struct TmdbApiKey([u8; API_KEY_LENGTH]);
+
+impl TmdbApiKey {
+    /// The key as sent to the API.
+    pub fn as_str(&self) -> &str {
+        // Only ascii hex digits make it in, see `from_str`.
+        std::str::from_utf8(&self.0).unwrap_or_default()
+    }
+}
+
+impl FromStr for TmdbApiKey {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut key = [0; API_KEY_LENGTH];
+        if s.len() != API_KEY_LENGTH || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(());
+        }
+        key.copy_from_slice(s.as_bytes());
+        Ok(TmdbApiKey(key))
+    }
+}
+
+impl<'de> Deserialize<'de> for TmdbApiKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(|_| {
+            de::Error::invalid_value(de::Unexpected::Str(&s), &"32 hexadecimal characters")
+        })
+    }
+}
+
+impl fmt::Debug for TmdbApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("TmdbApiKey(..)")
+    }
+}
+
+/// Where and how to fetch the lists of The Movie Database, see [`Tmdb::fetch`].
+#[derive(Clone, Debug)]
+pub struct Tmdb {
+    api_key: TmdbApiKey,
+    base_url: String,
+    image_base_url: String,
+    language: Option<String>,
+}
+
+impl Tmdb {
+    /// Fetches from [`TMDB_API_URL`] with the `api_key`, in English.
+    pub fn new(api_key: TmdbApiKey) -> Self {
+        Self {
+            api_key,
+            base_url: TMDB_API_URL.to_string(),
+            image_base_url: TMDB_IMAGE_URL.to_string(),
+            language: None,
+        }
+    }
+
+    /// Fetches the lists from `base_url` instead of [`TMDB_API_URL`].
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Prefixes the poster paths with `image_base_url` instead of [`TMDB_IMAGE_URL`].
+    pub fn with_image_base_url(mut self, image_base_url: impl Into<String>) -> Self {
+        self.image_base_url = image_base_url.into();
+        self
+    }
+
+    /// Asks the titles in the `language`, e.g. `fr`. None for English.
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Fetches the Trending, Popular and Top Rated lists through the `transport`.
+    ///
+    /// TMDB answers `429 Too Many Requests` past its rate limit, the requests are then attempted
+    /// again as per the `retry_policy` like any other transient failure.
+    pub fn fetch(
+        &self,
+        transport: &dyn Transport,
+        retry_policy: RetryPolicy,
+    ) -> Result<TmdbProvider, ApiError> {
+        let lists = LISTS
+            .iter()
+            .map(|&(title, path)| {
+                let page: TmdbPage = self.get_json(path, transport, &retry_policy)?;
+                debug!("{} results in {}", page.results.len(), path);
+                Ok(TmdbList {
+                    title,
+                    items: page
+                        .results
+                        .into_iter()
+                        .filter_map(|result| self.item(result))
+                        .collect(),
+                })
+            })
+            .collect::<Result<Vec<_>, ApiError>>()?;
+        info!("{} lists fetched from {}", lists.len(), self.base_url);
+        Ok(TmdbProvider { lists })
+    }
+
+    /// Fetch the json found at `path`, as per the `retry_policy`, and parse it.
+    fn get_json<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        transport: &dyn Transport,
+        retry_policy: &RetryPolicy,
+    ) -> Result<T, ApiError> {
+        let language = self
+            .language
+            .as_ref()
+            .map_or_else(String::new, |language| format!("&language={}", language));
+        let url = format!(
+            "{}{}?api_key={}{}",
+            self.base_url,
+            path,
+            self.api_key.as_str(),
+            language
+        );
+        // The key is left out of the url logged by the retries.
+        let logged_url = format!("{}{}?api_key=..{}", self.base_url, path, language);
+        let value = retry_policy.run(&logged_url, || {
+            transport.get_json(&url, retry_policy.timeout)
+        })?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// The item of a `result`, None for a person.
+    fn item(&self, result: TmdbResult) -> Option<TmdbItem> {
+        let content_type = match result.media_type.as_deref() {
+            Some("person") => return None,
+            Some("tv") => "Series",
+            _ if result.name.is_some() && result.title.is_none() => "Series",
+            _ => "Movie",
+        };
+        let release_year = result
+            .release_date
+            .or(result.first_air_date)
+            .and_then(|date| date.get(..4)?.parse().ok());
+        Some(TmdbItem {
+            metadata: ItemMetadata {
+                title: result.title.or(result.name),
+                content_id: Some(format!(
+                    "tmdb:{}:{}",
+                    content_type.to_lowercase(),
+                    result.id
+                )),
+                content_type: Some(content_type.to_string()),
+                release_year,
+                rating: None,
+            },
+            poster: result.poster_path.map(|path| TileArt {
+                url: format!("{}{}", self.image_base_url, path),
+                aspect_ratio: POSTER_ASPECT_RATIO,
+            }),
+        })
+    }
+}
+
+/// A movie or show of a [`TmdbList`].
+#[derive(Debug)]
+struct TmdbItem {
+    metadata: ItemMetadata,
+    /// None if the item has no poster.
+    poster: Option<TileArt>,
+}
+
+/// One of the [`LISTS`].
+#[derive(Debug)]
+struct TmdbList {
+    title: &'static str,
+    items: Vec<TmdbItem>,
+}
+
+/// [`ContentProvider`] showing the Trending, Popular and Top Rated lists of
+/// [The Movie Database](https://www.themoviedb.org) as sets, with their posters for tiles. Only
+/// the first page of each list is fetched, 20 items. Built with [`Tmdb::fetch`].
+///
+/// The posters don't need the API key, the [`Api`](crate::Api) it's loaded into fetches them
+/// as usual.
+#[derive(Debug)]
+pub struct TmdbProvider {
+    lists: Vec<TmdbList>,
+}
+
+impl TmdbProvider {
+    fn item(&self, set_idx: usize, item_idx: usize) -> Option<&TmdbItem> {
+        self.lists.get(set_idx)?.items.get(item_idx)
+    }
+}
+
+impl ContentProvider for TmdbProvider {
+    fn list_sets(&self) -> Range<usize> {
+        0..self.lists.len()
+    }
+
+    fn set_title(&self, set_idx: usize) -> Option<&str> {
+        self.lists.get(set_idx).map(|list| list.title)
+    }
+
+    fn item_count(&self, set_idx: usize) -> usize {
+        self.lists.get(set_idx).map_or(0, |list| list.items.len())
+    }
+
+    fn item_image_url(&self, set_idx: usize, item_idx: usize) -> Result<TileArt, ApiError> {
+        self.item(set_idx, item_idx)
+            .and_then(|item| item.poster.clone())
+            .ok_or_else(|| {
+                ApiError::missing(format!(
+                    "lists[{}].results[{}].poster_path",
+                    set_idx, item_idx
+                ))
+            })
+    }
+
+    fn item_metadata(&self, set_idx: usize, item_idx: usize) -> Option<ItemMetadata> {
+        self.item(set_idx, item_idx)
+            .map(|item| item.metadata.clone())
+    }
+}
+
+/// A page of one of the [`LISTS`].
+#[derive(Debug, Deserialize)]
+struct TmdbPage {
+    #[serde(default)]
+    results: Vec<TmdbResult>,
+}
+
+/// A movie, show or person. The movies have a `title` and a `release_date`, the shows a `name`
+/// and a `first_air_date`. Only the trending list tells the `media_type`.
+#[derive(Debug, Deserialize)]
+struct TmdbResult {
+    id: u64,
+    media_type: Option<String>,
+    title: Option<String>,
+    name: Option<String>,
+    /// `YYYY-MM-DD`, sometimes empty.
+    release_date: Option<String>,
+    first_air_date: Option<String>,
+    poster_path: Option<String>,
+}
@@ -0,0 +1,154 @@
+//! Rewriting of the artwork urls.
+use std::str::FromStr;
+
+/// Encodings the CDN can serve the artwork in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArtworkFormat {
+    /// The default.
+    Jpeg,
+    /// Lossless, much larger.
+    Png,
+    /// About a third smaller than jpeg at the same quality.
+    Webp,
+    /// Smaller still. Decoding it needs the `avif` feature, see [`ArtworkFormat::is_decodable`].
+    Avif,
+}
+
+impl ArtworkFormat {
+    /// Value of the `format` query parameter.
+    fn as_param(self) -> &'static str {
+        match self {
+            ArtworkFormat::Jpeg => "jpeg",
+            ArtworkFormat::Png => "png",
+            ArtworkFormat::Webp => "webp",
+            ArtworkFormat::Avif => "avif",
+        }
+    }
+
+    /// Returns true if the artwork can be decoded in this format with the features enabled.
+    pub fn is_decodable(self) -> bool {
+        self != ArtworkFormat::Avif || cfg!(feature = "avif")
+    }
+
+    /// Returns true if the artwork keeps its transparency in this format.
+    pub fn has_alpha(self) -> bool {
+        self != ArtworkFormat::Jpeg
+    }
+
+    /// The decodable format making for the smallest downloads.
+    pub fn smallest() -> Self {
+        if ArtworkFormat::Avif.is_decodable() {
+            ArtworkFormat::Avif
+        } else {
+            ArtworkFormat::Webp
+        }
+    }
+
+    /// The format of the encoded image `buf`, told from its first bytes whatever the url asked for,
+    /// e.g. when the CDN falls back to jpeg. None for the formats it doesn't serve.
+    pub fn sniff(buf: &[u8]) -> Option<Self> {
+        match buf {
+            [0xff, 0xd8, 0xff, ..] => Some(ArtworkFormat::Jpeg),
+            [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', ..] => Some(ArtworkFormat::Png),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => {
+                Some(ArtworkFormat::Webp)
+            }
+            // An ISO media file whose major brand is AVIF, still image or sequence.
+            [_, _, _, _, b'f', b't', b'y', b'p', b'a', b'v', b'i', b'f' | b's', ..] => {
+                Some(ArtworkFormat::Avif)
+            }
+            _ => None,
+        }
+    }
+
+    /// The matching format of the `image` crate.
+    pub(crate) fn image_format(self) -> image::ImageFormat {
+        match self {
+            ArtworkFormat::Jpeg => image::ImageFormat::Jpeg,
+            ArtworkFormat::Png => image::ImageFormat::Png,
+            ArtworkFormat::Webp => image::ImageFormat::WebP,
+            ArtworkFormat::Avif => image::ImageFormat::Avif,
+        }
+    }
+}
+
+impl FromStr for ArtworkFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "jpeg" => Ok(ArtworkFormat::Jpeg),
+            "png" => Ok(ArtworkFormat::Png),
+            "webp" => Ok(ArtworkFormat::Webp),
+            "avif" => Ok(ArtworkFormat::Avif),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Rewrites the query parameters of an artwork url.
+///
+/// The artwork is delivered by an endpoint that scales and re-encodes it as per the `width`, `quality`
+/// and `format` parameters, so asking for exactly the size drawn keeps the downloads small.
+/// Parameters set through the builder replace the ones already in the url, the others are kept as is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UrlBuilder {
+    base: String,
+    /// `key=value` pairs, in order.
+    params: Vec<String>,
+}
+
+impl UrlBuilder {
+    /// Starts from `url`, query parameters included.
+    pub fn new(url: &str) -> Self {
+        let (base, query) = match url.find('?') {
+            Some(pos) => (&url[..pos], &url[pos + 1..]),
+            None => (url, ""),
+        };
+        Self {
+            base: base.to_string(),
+            params: query
+                .split('&')
+                .filter(|p| !p.is_empty())
+                .map(String::from)
+                .collect(),
+        }
+    }
+
+    /// The url without its query parameters, which tells the artwork apart whatever its size.
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// Sets the `key` query parameter to `value`, moving it last.
+    pub fn param(mut self, key: &str, value: impl ToString) -> Self {
+        let prefix = format!("{}=", key);
+        self.params.retain(|p| p != key && !p.starts_with(&prefix));
+        self.params.push(format!("{}{}", prefix, value.to_string()));
+        self
+    }
+
+    /// Width of the artwork, in pixels. The height follows the aspect ratio.
+    pub fn width(self, width: u32) -> Self {
+        self.param("width", width)
+    }
+
+    /// Jpeg quality, from 1 to 100.
+    pub fn quality(self, quality: u8) -> Self {
+        self.param("quality", quality)
+    }
+
+    /// Encoding of the artwork.
+    pub fn format(self, format: ArtworkFormat) -> Self {
+        self.param("format", format.as_param())
+    }
+
+    /// The rewritten url.
+    pub fn build(&self) -> String {
+        if self.params.is_empty() {
+            self.base.clone()
+        } else {
+            format!("{}?{}", self.base, self.params.join("&"))
+        }
+    }
+}
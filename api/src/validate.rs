@@ -0,0 +1,186 @@
+//! Checks of the loaded home data beyond what parsing it requires, see [`Api::anomalies`].
+use crate::model::{Item, Set};
+use crate::{is_file_url, pick_tile_type, Api, ContentRating, SET_TYPE_REF};
+use std::collections::HashMap;
+use std::fmt;
+use thiserror::Error;
+
+/// Something in the home data the UI copes with but that is likely a mistake of the backend, e.g.
+/// an item without a title. See [`Api::anomalies`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Anomaly {
+    /// Attribute path of the offending value, e.g. `containers[3].set.items[2]`.
+    pub path: String,
+    /// What is wrong with it.
+    pub kind: AnomalyKind,
+}
+
+/// The kinds of [`Anomaly`].
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum AnomalyKind {
+    /// A set or an item without a full title, the UI shows a placeholder instead.
+    #[error("no title")]
+    MissingTitle,
+    /// A `SetRef` container without the `refId` of its items.
+    #[error("SetRef without a refId")]
+    MissingRefId,
+    /// A `SetRef` whose set couldn't be fetched or parsed, the row is left empty.
+    #[error("the ref set '{0}' could not be resolved")]
+    UnresolvedRefSet(String),
+    /// A set without any item.
+    #[error("no items")]
+    EmptySet,
+    /// A `setId` already used by an earlier container.
+    #[error("setId '{0}' already used by {1}")]
+    DuplicateSetId(String, String),
+    /// An item without a `contentId`, it can't be added to the watchlist nor found again after
+    /// a refresh.
+    #[error("no contentId")]
+    MissingContentId,
+    /// A `contentId` found twice in the same set.
+    #[error("contentId '{0}' already used by {1}")]
+    DuplicateContentId(String, String),
+    /// An item without any tile artwork, or none with a numeric aspect ratio and a tile type. A
+    /// placeholder is drawn instead.
+    #[error("no tile artwork")]
+    MissingTileArt,
+    /// An artwork url that is neither http(s) nor a file.
+    #[error("invalid artwork url '{0}'")]
+    InvalidArtworkUrl(String),
+    /// A rating that isn't one of the [`ContentRating`]s, the parental controls ignore it.
+    #[error("unknown content rating '{0}'")]
+    UnknownRating(String),
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.kind)
+    }
+}
+
+impl Api {
+    /// Everything that looks wrong in the loaded home data and the `SetRef` sets it points to,
+    /// in the order found. Empty if nothing is loaded.
+    pub fn anomalies(&self) -> Vec<Anomaly> {
+        let home_data = match &self.home_data {
+            Some(home_data) => home_data,
+            None => return Vec::new(),
+        };
+        let mut anomalies = Vec::new();
+        let mut set_ids: HashMap<&str, String> = HashMap::new();
+        let containers = &home_data.data.standard_collection.containers;
+        for (idx, container) in containers.iter().enumerate() {
+            let path = format!("containers[{}].set", idx);
+            let set = &container.set;
+            let mut report = |path: &str, kind| {
+                anomalies.push(Anomaly {
+                    path: path.to_string(),
+                    kind,
+                })
+            };
+            if let Some(set_id) = &set.set_id {
+                match set_ids.get(set_id.as_str()) {
+                    Some(first) => report(
+                        &path,
+                        AnomalyKind::DuplicateSetId(set_id.clone(), first.clone()),
+                    ),
+                    None => {
+                        set_ids.insert(set_id.as_str(), path.clone());
+                    }
+                }
+            }
+            let items_set = if set.set_type.as_deref() == Some(SET_TYPE_REF) {
+                match &set.ref_id {
+                    Some(ref_id) => match self.ref_sets.get(ref_id) {
+                        Some(resolved) => resolved,
+                        None => {
+                            report(&path, AnomalyKind::UnresolvedRefSet(ref_id.clone()));
+                            continue;
+                        }
+                    },
+                    None => {
+                        report(&path, AnomalyKind::MissingRefId);
+                        continue;
+                    }
+                }
+            } else {
+                set
+            };
+            if set.text.set_title().is_none() && items_set.text.set_title().is_none() {
+                report(&path, AnomalyKind::MissingTitle);
+            }
+            check_items(&path, items_set, &mut anomalies);
+        }
+        anomalies
+    }
+}
+
+/// Adds the anomalies of the items of the `set` found at `path`.
+fn check_items(path: &str, set: &Set, anomalies: &mut Vec<Anomaly>) {
+    if set.items.is_empty() {
+        anomalies.push(Anomaly {
+            path: path.to_string(),
+            kind: AnomalyKind::EmptySet,
+        });
+    }
+    let mut content_ids: HashMap<&str, String> = HashMap::new();
+    for (idx, item) in set.items.iter().enumerate() {
+        let path = format!("{}.items[{}]", path, idx);
+        let mut kinds = item_anomalies(item);
+        if let Some(content_id) = &item.content_id {
+            match content_ids.get(content_id.as_str()) {
+                Some(first) => kinds.push(AnomalyKind::DuplicateContentId(
+                    content_id.clone(),
+                    first.clone(),
+                )),
+                None => {
+                    content_ids.insert(content_id.as_str(), path.clone());
+                }
+            }
+        }
+        anomalies.extend(kinds.into_iter().map(|kind| Anomaly {
+            path: path.clone(),
+            kind,
+        }));
+    }
+}
+
+/// What is wrong with the `item` on its own.
+fn item_anomalies(item: &Item) -> Vec<AnomalyKind> {
+    let mut kinds = Vec::new();
+    if item.content_id.is_none() {
+        kinds.push(AnomalyKind::MissingContentId);
+    }
+    if item.text.any_title("full").is_none() {
+        kinds.push(AnomalyKind::MissingTitle);
+    }
+    let usable_tile = item
+        .image
+        .tile
+        .iter()
+        .any(|(key, by_type)| key.parse::<f32>().is_ok() && pick_tile_type(by_type).is_some());
+    if !usable_tile {
+        kinds.push(AnomalyKind::MissingTileArt);
+    }
+    let images = [
+        &item.image.tile,
+        &item.image.background,
+        &item.image.hero_tile,
+    ];
+    for source in images
+        .iter()
+        .flat_map(|by_ratio| by_ratio.values())
+        .flat_map(|by_type| by_type.values())
+    {
+        let url = &source.default.url;
+        if !(url.starts_with("https://") || url.starts_with("http://") || is_file_url(url)) {
+            kinds.push(AnomalyKind::InvalidArtworkUrl(url.clone()));
+        }
+    }
+    for value in item.ratings.iter().filter_map(|r| r.value.as_ref()) {
+        if value.parse::<ContentRating>().is_err() {
+            kinds.push(AnomalyKind::UnknownRating(value.clone()));
+        }
+    }
+    kinds
+}
@@ -0,0 +1,1446 @@
+//! Runs the [`Api`] against a local server serving the recorded payloads under `tests/fixtures`.
+//!
+//! The odd shapes the home data comes in, under `tests/fixtures/shapes`, are served by a
+//! [`FixtureTransport`] instead, without going through the network.
+use api::model::{TextBundle, TitleSource};
+use api::{
+    decode_image, Anomaly, AnomalyKind, Api, ApiError, ArtworkFormat, AspectRatioPolicy,
+    ClientOptions, ContentProvider, ContentRating, ContentType, DiskCache, FolderProvider,
+    HomeCache, ImageCache, ImageOptions, ImageTier, ItemMetadata, Proxy, ReqwestTransport,
+    RetryPolicy, TierPolicy, TlsBackend, Tmdb, TmdbApiKey, Transport, VideoLoop,
+    DEFAULT_IMAGE_CACHE_BYTES,
+};
+use bytes::Bytes;
+use httpmock::prelude::*;
+use httpmock::Mock;
+use image::GenericImageView;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// Retries quickly so the tests don't wait on the backoff.
+fn fast_retries(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(20),
+        timeout: Duration::from_secs(5),
+    }
+}
+
+/// Contents of the fixture at `name`, with the artwork urls pointing to the `server`.
+fn fixture(server: &MockServer, name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    fs::read_to_string(&path)
+        .unwrap()
+        .replace("{{base}}", &server.base_url())
+}
+
+fn api_for(server: &MockServer) -> Api {
+    Api::new()
+        .with_base_url(server.base_url())
+        .with_retry_policy(fast_retries(3))
+}
+
+fn mock_json<'a>(server: &'a MockServer, path: &str, body: String) -> Mock<'a> {
+    server.mock(|when, then| {
+        when.method(GET).path(path);
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(body);
+    })
+}
+
+/// Serves the home.json, the `ref-1` set and the tile images. `ref-missing` is left unmocked.
+fn mock_backend(server: &MockServer) -> Mock {
+    mock_json(server, "/home.json", fixture(server, "home.json"));
+    mock_json(
+        server,
+        "/sets/ref-1.json",
+        fixture(server, "sets/ref-1.json"),
+    );
+    let tile = fs::read(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tile.png"))
+        .unwrap();
+    server.mock(|when, then| {
+        when.method(GET).path_contains("/images/tile-");
+        then.status(200)
+            .header("content-type", "image/png")
+            .body(tile);
+    })
+}
+
+#[test]
+fn loads_the_home_data() {
+    let server = MockServer::start();
+    mock_backend(&server);
+    let mut api = api_for(&server);
+
+    api.load_home_data().unwrap();
+
+    assert_eq!(api.get_num_of_sets(), Some(3));
+    let set = api.get_set(0).unwrap();
+    assert_eq!(set.get_title(), "New to Hello+");
+    assert_eq!(set.get_style(), Some("standard"));
+    assert_eq!(set.get_item_count(), 3);
+    assert_eq!(set.get_content_id(1), Some("tile-2"));
+}
+
+#[test]
+fn num_of_sets_bounds_the_set_indices() {
+    let server = MockServer::start();
+    mock_backend(&server);
+    let mut api = api_for(&server);
+    assert_eq!(api.get_num_of_sets(), None);
+    assert!(api.get_set(0).is_none());
+
+    api.load_home_data().unwrap();
+
+    let num_of_sets = api.get_num_of_sets().unwrap();
+    assert!((0..num_of_sets).all(|set_idx| api.get_set(set_idx).is_some()));
+    assert!(api.get_set(num_of_sets).is_none());
+}
+
+#[test]
+fn exposes_the_item_metadata() {
+    let server = MockServer::start();
+    mock_backend(&server);
+    let mut api = api_for(&server);
+
+    api.load_home_data().unwrap();
+
+    let set = api.get_set(0).unwrap();
+    assert_eq!(set.get_item_title(0), Some("First Item"));
+    assert_eq!(set.get_item_content_type(0), Some(ContentType::Program));
+    assert_eq!(set.get_item_release_year(0), Some(2019));
+    assert_eq!(set.get_item_rating(0), Some("PG-13"));
+    assert_eq!(set.get_item_metadata(0), "Program  |  2019  |  PG-13");
+    assert_eq!(set.get_item_release_year(1), None);
+    assert_eq!(set.get_item_metadata(1), "Program");
+    assert_eq!(set.get_item_title(3), None);
+}
+
+#[test]
+fn the_api_is_a_content_provider() {
+    let server = MockServer::start();
+    mock_backend(&server);
+    let mut api = api_for(&server);
+    assert!(ContentProvider::list_sets(&api).is_empty());
+
+    api.load_home_data().unwrap();
+
+    let provider: &dyn ContentProvider = &api;
+    assert_eq!(provider.list_sets(), 0..3);
+    assert_eq!(provider.set_title(0), Some("New to Hello+"));
+    assert_eq!(provider.item_count(1), 2);
+    assert_eq!(provider.item_count(3), 0);
+    let tile = provider.item_image_url(0, 1).unwrap();
+    assert!(tile.url.contains("/images/tile-2.png"));
+    assert!(provider.item_image_url(3, 0).is_err());
+    assert_eq!(
+        provider.item_metadata(0, 0),
+        Some(ItemMetadata {
+            title: Some(String::from("First Item")),
+            content_id: Some(String::from("tile-1")),
+            content_type: Some(String::from("Program")),
+            release_year: Some(2019),
+            rating: Some(String::from("PG-13")),
+        })
+    );
+    assert_eq!(provider.item_metadata(0, 3), None);
+}
+
+#[test]
+fn loads_a_folder_of_images() {
+    let root = std::env::temp_dir().join(format!("helloplus-library-{}", std::process::id()));
+    let tile = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tile.png");
+    fs::create_dir_all(root.join("Beaches")).unwrap();
+    fs::create_dir_all(root.join("Cities")).unwrap();
+    fs::create_dir_all(root.join(".thumbnails")).unwrap();
+    fs::copy(&tile, root.join("Beaches/sunny_day.png")).unwrap();
+    fs::copy(&tile, root.join("Beaches/b-side.PNG")).unwrap();
+    fs::write(root.join("Beaches/notes.txt"), "not an image").unwrap();
+    fs::write(root.join("Cities/broken.png"), "not an image either").unwrap();
+
+    let provider = FolderProvider::scan(&root).unwrap();
+    assert_eq!(provider.list_sets(), 0..2);
+    assert_eq!(provider.set_title(1), Some("Cities"));
+    assert_eq!(provider.item_count(0), 2);
+    assert_eq!(provider.item_count(1), 0);
+    let metadata = provider.item_metadata(0, 1).unwrap();
+    assert_eq!(metadata.title.as_deref(), Some("sunny day"));
+    assert!(provider
+        .item_image_url(0, 0)
+        .unwrap()
+        .url
+        .starts_with(api::FILE_SCHEME));
+
+    // No server involved, the tiles are read from their files.
+    let mut api = Api::new();
+    api.load_from_provider(&provider).unwrap();
+    let set = api.get_set(0).unwrap();
+    assert_eq!(set.get_title(), "Beaches");
+    assert_eq!(set.get_item_title(0), Some("b side"));
+    assert_eq!(api.find_item("Beaches/sunny_day.png"), Some((0, 1)));
+    assert!(set.get_home_tile_image(0).is_ok());
+    fs::remove_dir_all(&root).unwrap();
+}
+
+const TMDB_KEY: &str = "0123456789abcdef0123456789abcdef";
+
+/// Serves the three lists of TMDB, each with a single page. The trending one has a movie, a show
+/// and a person.
+fn mock_tmdb(server: &MockServer) {
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/trending/all/week")
+            .query_param("api_key", TMDB_KEY);
+        then.status(200)
+            .json_body(serde_json::json!({ "page": 1, "results": [
+            {
+                "id": 11, "media_type": "movie", "title": "Big Buck Bunny",
+                "release_date": "2008-05-20", "poster_path": "/bunny.jpg"
+            },
+            {
+                "id": 22, "media_type": "tv", "name": "Elephants Dream",
+                "first_air_date": "", "poster_path": null
+            },
+            { "id": 33, "media_type": "person", "name": "Someone" }
+        ] }));
+    });
+    for path in ["/movie/popular", "/movie/top_rated"].iter() {
+        server.mock(|when, then| {
+            when.method(GET)
+                .path(*path)
+                .query_param("api_key", TMDB_KEY);
+            then.status(200)
+                .json_body(serde_json::json!({ "page": 1, "results": [
+                { "id": 44, "title": "Sintel", "release_date": "2010-09-27" }
+            ] }));
+        });
+    }
+}
+
+fn tmdb_for(server: &MockServer) -> Tmdb {
+    Tmdb::new(TMDB_KEY.parse().unwrap())
+        .with_base_url(server.base_url())
+        .with_image_base_url(server.url("/t/p/w500"))
+}
+
+#[test]
+fn loads_the_tmdb_lists() {
+    let server = MockServer::start();
+    mock_tmdb(&server);
+
+    let provider = tmdb_for(&server)
+        .fetch(&ReqwestTransport::default(), fast_retries(1))
+        .unwrap();
+    assert_eq!(provider.list_sets(), 0..3);
+    assert_eq!(provider.set_title(2), Some("Top Rated"));
+    // The person is left out.
+    assert_eq!(provider.item_count(0), 2);
+    assert_eq!(
+        provider.item_metadata(0, 0),
+        Some(ItemMetadata {
+            title: Some(String::from("Big Buck Bunny")),
+            content_id: Some(String::from("tmdb:movie:11")),
+            content_type: Some(String::from("Movie")),
+            release_year: Some(2008),
+            rating: None,
+        })
+    );
+    let show = provider.item_metadata(0, 1).unwrap();
+    assert_eq!(show.content_type.as_deref(), Some("Series"));
+    assert_eq!(show.release_year, None);
+    assert_eq!(
+        provider.item_image_url(0, 0).unwrap().url,
+        server.url("/t/p/w500/bunny.jpg")
+    );
+    assert!(provider.item_image_url(0, 1).is_err());
+
+    let mut api = Api::new();
+    api.load_from_provider(&provider).unwrap();
+    assert_eq!(api.get_set(1).unwrap().get_title(), "Popular");
+    assert_eq!(api.find_item("tmdb:movie:44"), Some((1, 0)));
+}
+
+#[test]
+fn tmdb_titles_are_asked_in_the_language() {
+    let server = MockServer::start();
+    let trending = server.mock(|when, then| {
+        when.method(GET)
+            .path("/trending/all/week")
+            .query_param("language", "fr");
+        then.status(200)
+            .json_body(serde_json::json!({ "results": [] }));
+    });
+    server.mock(|when, then| {
+        when.method(GET).path_contains("/movie/");
+        then.status(200)
+            .json_body(serde_json::json!({ "results": [] }));
+    });
+
+    tmdb_for(&server)
+        .with_language(Some(String::from("fr")))
+        .fetch(&ReqwestTransport::default(), fast_retries(1))
+        .unwrap();
+    trending.assert();
+}
+
+#[test]
+fn tmdb_rate_limit_is_retried() {
+    let server = MockServer::start();
+    let trending = server.mock(|when, then| {
+        when.method(GET).path("/trending/all/week");
+        then.status(429).header("retry-after", "1");
+    });
+
+    let res = tmdb_for(&server).fetch(&ReqwestTransport::default(), fast_retries(3));
+    assert!(matches!(res, Err(ApiError::HttpStatus { code: 429 })));
+    trending.assert_hits(3);
+}
+
+#[test]
+fn tmdb_api_keys_are_32_hex_digits() {
+    assert!(TMDB_KEY.parse::<TmdbApiKey>().is_ok());
+    assert!("0123".parse::<TmdbApiKey>().is_err());
+    assert!("0123456789abcdef0123456789abcdeg"
+        .parse::<TmdbApiKey>()
+        .is_err());
+    let key: TmdbApiKey = TMDB_KEY.parse().unwrap();
+    assert_eq!(key.as_str(), TMDB_KEY);
+    assert!(!format!("{:?}", key).contains(TMDB_KEY));
+}
+
+/// Serves a Jellyfin user with a "Movies" view holding two movies, the second without a poster.
+/// Every request must carry the `secret` token.
+#[cfg(feature = "media-server")]
+fn mock_jellyfin(server: &MockServer) -> Mock {
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/Users/Me")
+            .header("X-Emby-Token", "secret");
+        then.status(200)
+            .json_body(serde_json::json!({ "Id": "u1" }));
+    });
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/Users/u1/Views")
+            .header("X-Emby-Token", "secret");
+        then.status(200)
+            .json_body(serde_json::json!({ "Items": [{ "Id": "v1", "Name": "Movies" }] }));
+    });
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/Users/u1/Items")
+            .query_param("ParentId", "v1")
+            .header("X-Emby-Token", "secret");
+        then.status(200).json_body(serde_json::json!({ "Items": [
+            {
+                "Id": "m1", "Name": "Big Buck Bunny", "Type": "Movie", "ProductionYear": 2008,
+                "OfficialRating": "G", "ImageTags": { "Primary": "t1" },
+                "PrimaryImageAspectRatio": 0.6667
+            },
+            { "Id": "m2", "Name": "Sintel", "Type": "Movie" }
+        ] }));
+    });
+    let tile = fs::read(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tile.png"))
+        .unwrap();
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/Items/m1/Images/Primary")
+            .header("X-Emby-Token", "secret");
+        then.status(200)
+            .header("content-type", "image/png")
+            .body(tile);
+    })
+}
+
+#[cfg(feature = "media-server")]
+#[test]
+fn loads_the_libraries_of_a_jellyfin_server() {
+    use api::{MediaServer, MediaServerProvider};
+    let server = MockServer::start();
+    let poster = mock_jellyfin(&server);
+    let transport = MediaServer::Jellyfin.transport("secret");
+
+    let provider = MediaServerProvider::connect(
+        MediaServer::Jellyfin,
+        &format!("{}/", server.base_url()),
+        &transport,
+        fast_retries(1),
+    )
+    .unwrap();
+    assert_eq!(provider.list_sets(), 0..1);
+    assert_eq!(provider.set_title(0), Some("Movies"));
+    assert_eq!(provider.item_count(0), 2);
+    assert_eq!(
+        provider.item_metadata(0, 0),
+        Some(ItemMetadata {
+            title: Some(String::from("Big Buck Bunny")),
+            content_id: Some(String::from("jellyfin:m1")),
+            content_type: Some(String::from("Movie")),
+            release_year: Some(2008),
+            rating: Some(String::from("G")),
+        })
+    );
+    let tile = provider.item_image_url(0, 0).unwrap();
+    assert!(tile.url.ends_with("/Items/m1/Images/Primary?tag=t1"));
+    assert!((tile.aspect_ratio - 0.6667).abs() < 1e-4);
+    assert!(provider.item_image_url(0, 1).is_err());
+
+    // The posters are fetched with the token too.
+    let mut api = Api::new().with_retry_policy(fast_retries(1));
+    api.set_transport(transport);
+    api.load_from_provider(&provider).unwrap();
+    assert_eq!(api.find_item("jellyfin:m2"), Some((0, 1)));
+    assert!(api.get_set(0).unwrap().get_home_tile_image(0).is_ok());
+    poster.assert();
+}
+
+#[cfg(feature = "media-server")]
+#[test]
+fn loads_the_sections_of_a_plex_server() {
+    use api::{MediaServer, MediaServerProvider};
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/library/sections")
+            .header("X-Plex-Token", "secret")
+            .header("accept", "application/json");
+        then.status(200)
+            .json_body(serde_json::json!({ "MediaContainer": {
+            "Directory": [{ "key": "1", "title": "TV Shows" }]
+        } }));
+    });
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/library/sections/1/all")
+            .header("X-Plex-Token", "secret");
+        then.status(200)
+            .json_body(serde_json::json!({ "MediaContainer": {
+            "Metadata": [{
+                "ratingKey": "42", "title": "Elephants Dream", "type": "show", "year": 2006,
+                "contentRating": "TV-PG", "thumb": "/library/metadata/42/thumb/1"
+            }]
+        } }));
+    });
+
+    let provider = MediaServerProvider::connect(
+        MediaServer::Plex,
+        &server.base_url(),
+        &MediaServer::Plex.transport("secret"),
+        fast_retries(1),
+    )
+    .unwrap();
+    assert_eq!(provider.set_title(0), Some("TV Shows"));
+    let metadata = provider.item_metadata(0, 0).unwrap();
+    assert_eq!(metadata.content_id.as_deref(), Some("plex:42"));
+    assert_eq!(metadata.summary(), "Series  |  2006  |  TV-PG");
+    assert_eq!(
+        provider.item_image_url(0, 0).unwrap().url,
+        server.url("/library/metadata/42/thumb/1")
+    );
+}
+
+#[cfg(feature = "media-server")]
+#[test]
+fn media_servers_reject_a_wrong_token() {
+    use api::{MediaServer, MediaServerProvider};
+    let server = MockServer::start();
+    let me = server.mock(|when, then| {
+        when.method(GET).path("/Users/Me");
+        then.status(401);
+    });
+
+    let res = MediaServerProvider::connect(
+        MediaServer::Jellyfin,
+        &server.base_url(),
+        &MediaServer::Jellyfin.transport("wrong"),
+        fast_retries(3),
+    );
+    assert!(matches!(res, Err(ApiError::HttpStatus { code: 401 })));
+    me.assert_hits(1);
+}
+
+#[cfg(feature = "chaos")]
+#[test]
+fn injected_server_errors_are_retried() {
+    use api::{ChaosTransport, FaultRates};
+    let server = MockServer::start();
+    let home = mock_backend(&server);
+    let rates: FaultRates = "error=1,seed=7".parse().unwrap();
+    let mut api =
+        api_for(&server).with_transport(ChaosTransport::new(ReqwestTransport::default(), rates));
+
+    let err = api.load_home_data().unwrap_err();
+
+    assert!(matches!(err, ApiError::HttpStatus { code: 500 }), "{}", err);
+    // The faults are injected before reaching the server.
+    home.assert_hits(0);
+}
+
+#[cfg(feature = "chaos")]
+#[test]
+fn injected_truncated_bodies_fail_to_parse() {
+    use api::{ChaosTransport, FaultRates};
+    let server = MockServer::start();
+    mock_backend(&server);
+    let rates = FaultRates {
+        truncated: 1.0,
+        seed: Some(7),
+        ..FaultRates::default()
+    };
+    let mut api =
+        api_for(&server).with_transport(ChaosTransport::new(ReqwestTransport::default(), rates));
+
+    let err = api.load_home_data().unwrap_err();
+
+    assert!(matches!(err, ApiError::Parse(_)), "{}", err);
+    assert!(!err.is_retryable());
+}
+
+#[cfg(feature = "chaos")]
+#[test]
+fn fault_rates_add_up_to_one_at_most() {
+    use api::FaultRates;
+    let rates: FaultRates = "latency=800, timeout=0.1, error=0.2".parse().unwrap();
+    assert_eq!(rates.max_latency, Duration::from_millis(800));
+    assert_eq!(rates.server_error, 0.2);
+    assert_eq!(rates.truncated, 0.0);
+    assert!("timeout=0.6,error=0.6".parse::<FaultRates>().is_err());
+    assert!("error=2".parse::<FaultRates>().is_err());
+    assert!("jitter=5".parse::<FaultRates>().is_err());
+}
+
+#[test]
+fn reports_the_unresolved_ref_sets() {
+    let server = MockServer::start();
+    mock_backend(&server);
+    let mut api = api_for(&server);
+    assert!(api.anomalies().is_empty());
+
+    api.load_home_data().unwrap();
+
+    assert_eq!(
+        api.anomalies(),
+        vec![Anomaly {
+            path: String::from("containers[2].set"),
+            kind: AnomalyKind::UnresolvedRefSet(String::from("ref-missing")),
+        }]
+    );
+}
+
+#[test]
+fn reports_the_home_data_anomalies() {
+    let item = |content_id: Option<&str>, url: &str, rating: &str| {
+        serde_json::json!({
+            "contentId": content_id,
+            "image": { "tile": { "1.78": { "program": { "default": { "url": url } } } } },
+            "text": { "title": { "full": { "program": { "default": { "content": "Title" } } } } },
+            "ratings": [{ "value": rating }],
+        })
+    };
+    let title = serde_json::json!({
+        "title": { "full": { "set": { "default": { "content": "Row" } } } },
+    });
+    let home = serde_json::json!({ "data": { "StandardCollection": { "containers": [
+        { "set": { "type": "CuratedSet", "setId": "a", "text": title, "items": [
+            item(Some("x"), "https://cdn/x.jpg", "PG"),
+            item(None, "ftp://cdn/y.jpg", "PG"),
+            item(Some("x"), "https://cdn/z.jpg", "NOT-A-RATING"),
+            { "contentId": "w" },
+        ] } },
+        { "set": { "type": "CuratedSet", "setId": "a", "items": [] } },
+        { "set": { "type": "SetRef", "text": title } },
+    ] } } });
+    let dir = std::env::temp_dir().join(format!("helloplus-anomalies-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("home.json");
+    fs::write(&path, home.to_string()).unwrap();
+
+    let mut api = Api::new();
+    api.load_from_file(&path).unwrap();
+    let anomalies = api
+        .anomalies()
+        .into_iter()
+        .map(|anomaly| anomaly.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        anomalies,
+        vec![
+            "containers[0].set.items[1]: no contentId",
+            "containers[0].set.items[1]: invalid artwork url 'ftp://cdn/y.jpg'",
+            "containers[0].set.items[2]: unknown content rating 'NOT-A-RATING'",
+            "containers[0].set.items[2]: contentId 'x' already used by containers[0].set.items[0]",
+            "containers[0].set.items[3]: no title",
+            "containers[0].set.items[3]: no tile artwork",
+            "containers[1].set: setId 'a' already used by containers[0].set",
+            "containers[1].set: no title",
+            "containers[1].set: no items",
+            "containers[2].set: SetRef without a refId",
+        ]
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn refreshing_the_same_home_data_changes_nothing() {
+    let server = MockServer::start();
+    mock_backend(&server);
+    let mut api = api_for(&server);
+    api.load_home_data().unwrap();
+
+    let diff = api.refresh_home_data().unwrap();
+
+    assert!(diff.is_unchanged(), "{:?}", diff);
+}
+
+#[test]
+fn refreshing_reports_the_changed_sets() {
+    let server = MockServer::start();
+    let mut home = mock_json(&server, "/home.json", fixture(&server, "home.json"));
+    let mut ref_set = mock_json(
+        &server,
+        "/sets/ref-1.json",
+        fixture(&server, "sets/ref-1.json"),
+    );
+    let mut api = api_for(&server);
+    api.load_home_data().unwrap();
+    home.delete();
+    ref_set.delete();
+    let home = fixture(&server, "home.json").replace("\"tile-2\"", "\"tile-9\"");
+    mock_json(&server, "/home.json", home);
+
+    let diff = api.refresh_home_data().unwrap();
+
+    assert_eq!(diff.previous_idx, vec![Some(0), Some(1), Some(2)]);
+    assert_eq!(diff.changed, vec![0]);
+    assert_eq!(diff.removed, 0);
+    assert_eq!(api.get_set(0).unwrap().get_content_id(1), Some("tile-9"));
+    // `ref-1` is gone from the server, it keeps the items fetched the first time.
+    assert_eq!(api.get_set(1).unwrap().get_item_count(), 2);
+}
+
+#[test]
+fn failed_refreshes_keep_the_home_data() {
+    let server = MockServer::start();
+    let mut home = mock_json(&server, "/home.json", fixture(&server, "home.json"));
+    mock_json(
+        &server,
+        "/sets/ref-1.json",
+        fixture(&server, "sets/ref-1.json"),
+    );
+    let mut api = api_for(&server);
+    api.load_home_data().unwrap();
+    home.delete();
+
+    let err = api.refresh_home_data().unwrap_err();
+
+    assert!(err.is_not_found(), "{}", err);
+    assert_eq!(api.get_num_of_sets(), Some(3));
+    assert_eq!(api.get_set(1).unwrap().get_title(), "Trending");
+}
+
+#[test]
+fn cached_home_data_is_revalidated() {
+    let dir = std::env::temp_dir().join(format!("helloplus-home-{}", std::process::id()));
+    let server = MockServer::start();
+    let mut home = server.mock(|when, then| {
+        when.method(GET).path("/home.json");
+        then.status(200)
+            .header("content-type", "application/json")
+            .header("etag", "\"v1\"")
+            .body(fixture(&server, "home.json"));
+    });
+    mock_json(
+        &server,
+        "/sets/ref-1.json",
+        fixture(&server, "sets/ref-1.json"),
+    );
+    let mut api = api_for(&server).with_home_cache(Some(HomeCache::new(&dir).unwrap()));
+    api.load_home_data().unwrap();
+    home.delete();
+    let not_modified = server.mock(|when, then| {
+        when.method(GET)
+            .path("/home.json")
+            .header("if-none-match", "\"v1\"");
+        then.status(304);
+    });
+
+    let mut api = api_for(&server).with_home_cache(Some(HomeCache::new(&dir).unwrap()));
+    assert!(api.load_cached_home_data());
+    assert_eq!(api.get_num_of_sets(), Some(3));
+    assert_eq!(api.get_set(1).unwrap().get_item_count(), 2);
+    let diff = api.refresh_home_data().unwrap();
+
+    assert!(diff.is_unchanged(), "{:?}", diff);
+    not_modified.assert();
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn nothing_is_cached_before_the_first_fetch() {
+    let dir = std::env::temp_dir().join(format!("helloplus-no-home-{}", std::process::id()));
+    let mut api = Api::new().with_home_cache(Some(HomeCache::new(&dir).unwrap()));
+
+    assert!(!api.load_cached_home_data());
+    assert_eq!(api.get_num_of_sets(), None);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rating_ceiling_leaves_out_the_items_rated_above_it() {
+    let server = MockServer::start();
+    mock_backend(&server);
+    let mut api = api_for(&server).with_rating_ceiling(Some(ContentRating::Pg));
+    api.load_home_data().unwrap();
+
+    let set = api.get_set(0).unwrap();
+    assert_eq!(set.get_item_count(), 2);
+    assert_eq!(set.get_content_id(0), Some("tile-2"));
+    assert_eq!(api.find_item("tile-1"), None);
+
+    let diff = api.set_rating_ceiling(None);
+
+    assert_eq!(diff.changed, vec![0]);
+    assert_eq!(
+        api.get_set(0).unwrap().get_item_content_rating(0),
+        Some(ContentRating::Pg13)
+    );
+}
+
+#[test]
+fn image_options_apply_to_the_loaded_sets() {
+    let server = MockServer::start();
+    mock_backend(&server);
+    let mut api = api_for(&server);
+    api.load_home_data().unwrap();
+
+    api.set_image_options(ImageOptions {
+        quality: 40,
+        ..ImageOptions::default()
+    });
+
+    let url = api.get_set(0).unwrap().get_home_tile_url(0).unwrap();
+    assert!(url.contains("quality=40"), "{}", url);
+}
+
+#[test]
+fn artwork_is_asked_in_the_format() {
+    let server = MockServer::start();
+    mock_backend(&server);
+    let mut api = api_for(&server);
+    api.load_home_data().unwrap();
+
+    api.set_image_options(ImageOptions {
+        format: ArtworkFormat::Webp,
+        ..ImageOptions::default()
+    });
+
+    let url = api.get_set(0).unwrap().get_home_tile_url(0).unwrap();
+    assert!(url.contains("format=webp"), "{}", url);
+    // The mock serves png whatever the format asked for, it's told from the bytes.
+    let img = api.get_set(0).unwrap().get_home_tile_image(0).unwrap();
+    assert_eq!((img.width(), img.height()), (16, 9));
+}
+
+#[test]
+fn artwork_format_is_told_from_the_bytes() {
+    let tile = fs::read(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tile.png"))
+        .unwrap();
+
+    assert_eq!(ArtworkFormat::sniff(&tile), Some(ArtworkFormat::Png));
+    assert_eq!(
+        ArtworkFormat::sniff(b"RIFF\x24\0\0\0WEBPVP8 "),
+        Some(ArtworkFormat::Webp)
+    );
+    assert_eq!(
+        ArtworkFormat::sniff(b"\0\0\0\x1cftypavif"),
+        Some(ArtworkFormat::Avif)
+    );
+    assert_eq!(ArtworkFormat::sniff(b"<html>"), None);
+}
+
+#[test]
+fn decodes_jpeg_artwork() {
+    let tile = image::RgbImage::from_pixel(16, 9, image::Rgb([200, 40, 40]));
+    let mut jpeg = Vec::new();
+    image::DynamicImage::ImageRgb8(tile)
+        .write_to(&mut jpeg, image::ImageOutputFormat::Jpeg(90))
+        .unwrap();
+
+    let img = decode_image(&jpeg).unwrap();
+
+    assert_eq!((img.width(), img.height()), (16, 9));
+    let [r, g, b, _] = img.get_pixel(8, 4).0;
+    assert!(r > 180 && g < 60 && b < 60, "{:?}", (r, g, b));
+}
+
+#[test]
+fn artwork_tier_follows_the_throughput() {
+    const MB: usize = 1024 * 1024;
+    let server = MockServer::start();
+    mock_backend(&server);
+    let mut api = api_for(&server);
+    api.load_home_data().unwrap();
+    let cache = api.image_cache();
+    let bandwidth = cache.bandwidth();
+
+    // Just past the threshold isn't enough to move up.
+    for _ in 0..4 {
+        bandwidth.record(MB * 22 / 10, Duration::from_secs(1));
+    }
+    assert_eq!(api.image_tier(), ImageTier::Medium);
+    for _ in 0..12 {
+        bandwidth.record(10 * MB, Duration::from_secs(1));
+    }
+
+    assert_eq!(api.image_tier(), ImageTier::High);
+    let url = api.get_set(0).unwrap().get_home_tile_url(0).unwrap();
+    assert!(url.contains("width=750"), "{}", url);
+}
+
+#[test]
+fn fixed_artwork_tier_ignores_the_throughput() {
+    let server = MockServer::start();
+    mock_backend(&server);
+    let mut api = api_for(&server).with_image_options(ImageOptions {
+        tier: TierPolicy::Fixed(ImageTier::Low),
+        ..ImageOptions::default()
+    });
+    api.load_home_data().unwrap();
+
+    for _ in 0..16 {
+        api.image_cache()
+            .bandwidth()
+            .record(10 * 1024 * 1024, Duration::from_secs(1));
+    }
+
+    assert_eq!(api.image_tier(), ImageTier::Low);
+    let url = api.get_set(0).unwrap().get_home_tile_url(0).unwrap();
+    assert!(url.contains("width=250"), "{}", url);
+    assert!(url.contains("quality=60"), "{}", url);
+}
+
+#[test]
+fn language_is_asked_for_in_the_json_requests() {
+    let server = MockServer::start();
+    let home = server.mock(|when, then| {
+        when.method(GET)
+            .path("/home.json")
+            .query_param("lang", "fr-FR");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(fixture(&server, "home.json").replace("New to Hello+", "Nouveau sur Hello+"));
+    });
+    let ref_set = server.mock(|when, then| {
+        when.method(GET)
+            .path("/sets/ref-1.json")
+            .query_param("lang", "fr-FR");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(fixture(&server, "sets/ref-1.json"));
+    });
+    let mut api = api_for(&server).with_language(Some(String::from("fr-FR")));
+
+    api.load_home_data().unwrap();
+
+    assert_eq!(home.hits(), 1);
+    assert_eq!(ref_set.hits(), 1);
+    assert_eq!(api.get_set(0).unwrap().get_title(), "Nouveau sur Hello+");
+}
+
+#[test]
+fn resolves_the_ref_sets() {
+    let server = MockServer::start();
+    mock_backend(&server);
+    let mut api = api_for(&server);
+
+    api.load_home_data().unwrap();
+
+    let set = api.get_set(1).unwrap();
+    assert_eq!(set.get_title(), "Trending");
+    assert_eq!(set.get_item_count(), 2);
+    assert_eq!(set.get_content_id(0), Some("tile-3"));
+}
+
+#[test]
+fn unresolved_ref_sets_are_left_empty() {
+    let server = MockServer::start();
+    mock_backend(&server);
+    let mut api = api_for(&server);
+
+    api.load_home_data().unwrap();
+
+    let set = api.get_set(2).unwrap();
+    assert_eq!(set.get_title(), "Gone");
+    assert_eq!(set.get_item_count(), 0);
+    assert!(api
+        .resolve_ref_set("ref-missing")
+        .unwrap_err()
+        .is_not_found());
+}
+
+#[test]
+fn malformed_home_data_is_not_retried() {
+    let server = MockServer::start();
+    let home = mock_json(&server, "/home.json", String::from("{\"data\": [1, 2"));
+    let mut api = api_for(&server);
+
+    let err = api.load_home_data().unwrap_err();
+
+    assert!(!err.is_retryable(), "{}", err);
+    assert_eq!(home.hits(), 1);
+    assert_eq!(api.get_num_of_sets(), None);
+}
+
+#[test]
+fn unexpected_home_data_shape_is_a_parse_error() {
+    let server = MockServer::start();
+    mock_json(&server, "/home.json", String::from("{\"data\": {}}"));
+    let mut api = api_for(&server);
+
+    let err = api.load_home_data().unwrap_err();
+
+    assert!(matches!(err, ApiError::Parse(_)), "{}", err);
+}
+
+#[test]
+fn missing_home_data_is_not_retried() {
+    let server = MockServer::start();
+    let home = server.mock(|when, then| {
+        when.method(GET).path("/home.json");
+        then.status(404);
+    });
+    let mut api = api_for(&server);
+
+    let err = api.load_home_data().unwrap_err();
+
+    assert!(err.is_not_found(), "{}", err);
+    assert_eq!(home.hits(), 1);
+}
+
+#[test]
+fn server_errors_are_retried() {
+    let server = MockServer::start();
+    let home = server.mock(|when, then| {
+        when.method(GET).path("/home.json");
+        then.status(503);
+    });
+    let mut api = api_for(&server);
+
+    let err = api.load_home_data().unwrap_err();
+
+    assert!(err.is_retryable(), "{}", err);
+    assert_eq!(home.hits(), 3);
+}
+
+#[test]
+fn slow_responses_time_out() {
+    let server = MockServer::start();
+    let home = server.mock(|when, then| {
+        when.method(GET).path("/home.json");
+        then.status(200)
+            .delay(Duration::from_millis(500))
+            .body(fixture(&server, "home.json"));
+    });
+    let mut api = Api::new()
+        .with_base_url(server.base_url())
+        .with_retry_policy(RetryPolicy {
+            timeout: Duration::from_millis(100),
+            ..fast_retries(2)
+        });
+
+    let err = api.load_home_data().unwrap_err();
+
+    assert!(
+        matches!(&err, ApiError::Network(e) if e.is_timeout()),
+        "{}",
+        err
+    );
+    assert_eq!(home.hits(), 2);
+}
+
+#[test]
+fn fetches_the_tiles_once() {
+    let server = MockServer::start();
+    let images = mock_backend(&server);
+    let mut api = api_for(&server);
+    api.load_home_data().unwrap();
+    let set = api.get_set(0).unwrap();
+
+    let img = set.get_home_tile_image(0).unwrap();
+    set.get_home_tile_image(0).unwrap();
+
+    assert_eq!((img.width(), img.height()), (16, 9));
+    assert_eq!(images.hits(), 1);
+    assert_eq!(api.image_cache().len(), 1);
+}
+
+/// Serves a tile at `/images/tile-1.png` with the extra `headers`.
+fn mock_tile<'a>(server: &'a MockServer, headers: &[(&str, &str)]) -> Mock<'a> {
+    let tile = fs::read(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tile.png"))
+        .unwrap();
+    server.mock(|when, then| {
+        when.method(GET).path("/images/tile-1.png");
+        let then = then.status(200).header("content-type", "image/png");
+        headers
+            .iter()
+            .fold(then, |then, (name, value)| then.header(*name, *value))
+            .body(tile);
+    })
+}
+
+/// An [`ImageCache`] backed by a [`DiskCache`] at `dir` whose entries are stale right away.
+fn stale_disk_cache(dir: &Path) -> ImageCache {
+    ImageCache::with_disk_cache(
+        DEFAULT_IMAGE_CACHE_BYTES,
+        Some(DiskCache::new(dir, Duration::ZERO).unwrap()),
+    )
+    .with_retry_policy(fast_retries(1))
+}
+
+#[test]
+fn stale_tiles_are_revalidated() {
+    let dir = std::env::temp_dir().join(format!("helloplus-revalidate-{}", std::process::id()));
+    let server = MockServer::start();
+    let url = server.url("/images/tile-1.png");
+    let mut tile = mock_tile(&server, &[("etag", "\"v1\"")]);
+    let fetched = stale_disk_cache(&dir).fetch_bytes(&url).unwrap();
+    tile.delete();
+    let not_modified = server.mock(|when, then| {
+        when.method(GET)
+            .path("/images/tile-1.png")
+            .header("if-none-match", "\"v1\"");
+        then.status(304);
+    });
+
+    let revalidated = stale_disk_cache(&dir).fetch_bytes(&url).unwrap();
+
+    assert_eq!(revalidated, fetched);
+    not_modified.assert();
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn tiles_are_not_asked_again_before_their_max_age() {
+    let dir = std::env::temp_dir().join(format!("helloplus-max-age-{}", std::process::id()));
+    let server = MockServer::start();
+    let url = server.url("/images/tile-1.png");
+    let tile = mock_tile(&server, &[("cache-control", "public, max-age=3600")]);
+    stale_disk_cache(&dir).fetch_bytes(&url).unwrap();
+
+    stale_disk_cache(&dir).fetch_bytes(&url).unwrap();
+
+    assert_eq!(tile.hits(), 1);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn concurrent_fetches_of_a_tile_share_the_download() {
+    let server = MockServer::start();
+    mock_json(&server, "/home.json", fixture(&server, "home.json"));
+    let tile = fs::read(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tile.png"))
+        .unwrap();
+    let images = server.mock(|when, then| {
+        when.method(GET).path_contains("/images/tile-");
+        then.status(200)
+            .header("content-type", "image/png")
+            .delay(Duration::from_millis(200))
+            .body(tile);
+    });
+    let mut api = api_for(&server);
+    api.load_home_data().unwrap();
+    let url = api.get_set(0).unwrap().get_home_tile_url(0).unwrap();
+
+    let fetches = (0..4)
+        .map(|_| {
+            let (cache, url) = (api.image_cache(), url.clone());
+            thread::spawn(move || cache.fetch_image(&url))
+        })
+        .collect::<Vec<_>>();
+
+    for fetch in fetches {
+        let img = fetch.join().unwrap().unwrap();
+        assert_eq!((img.width(), img.height()), (16, 9));
+    }
+    assert_eq!(images.hits(), 1);
+}
+
+#[test]
+fn requests_go_through_the_configured_client() {
+    let server = MockServer::start();
+    mock_json(
+        &server,
+        "/sets/ref-1.json",
+        fixture(&server, "sets/ref-1.json"),
+    );
+    let home = server.mock(|when, then| {
+        when.method(GET)
+            .path("/home.json")
+            .header("user-agent", "kiosk/1.0");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(fixture(&server, "home.json"));
+    });
+    let options = ClientOptions {
+        user_agent: String::from("kiosk/1.0"),
+        ..ClientOptions::default()
+    };
+    let mut api = api_for(&server).with_transport(ReqwestTransport::new(&options).unwrap());
+
+    api.load_home_data().unwrap();
+    api.refresh_home_data().unwrap();
+
+    assert_eq!(home.hits(), 2);
+}
+
+#[test]
+fn built_apis_go_through_their_client() {
+    let server = MockServer::start();
+    mock_backend(&server);
+    let mut api = Api::builder()
+        .proxy(Proxy::Direct)
+        .tls_backend(TlsBackend::Rustls)
+        .build()
+        .unwrap()
+        .with_base_url(server.base_url());
+
+    api.load_home_data().unwrap();
+
+    assert_eq!(api.get_num_of_sets(), Some(3));
+}
+
+#[test]
+fn unreadable_certificates_fail_the_build() {
+    let res = Api::builder()
+        .root_certificate("tests/fixtures/missing-ca.pem")
+        .build();
+
+    assert!(matches!(res, Err(ApiError::Io(_))));
+}
+
+#[test]
+fn invalid_proxy_urls_fail_the_build() {
+    let res = Api::builder()
+        .proxy(Proxy::Url(String::from("not a url")))
+        .build();
+
+    assert!(matches!(res, Err(ApiError::Network(_))));
+}
+
+#[test]
+fn missing_tiles_are_not_found() {
+    let server = MockServer::start();
+    mock_backend(&server);
+    let mut api = api_for(&server);
+    api.load_home_data().unwrap();
+
+    let err = api.get_set(0).unwrap().get_home_tile_image(2).unwrap_err();
+
+    assert!(err.is_not_found(), "{}", err);
+    assert_eq!(api.image_cache().len(), 0);
+}
+
+/// Base url of the [`FixtureTransport`], never resolved.
+const FIXTURE_BASE: &str = "http://fixtures.invalid";
+
+/// Serves the json files under `tests/fixtures/<dir>` and `tile.png` for any artwork, at the paths
+/// the [`Api`] asks for under [`FIXTURE_BASE`]. Anything else is a `404`.
+struct FixtureTransport {
+    root: PathBuf,
+}
+
+impl FixtureTransport {
+    fn new(dir: &str) -> Self {
+        Self {
+            root: PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures")
+                .join(dir),
+        }
+    }
+
+    /// Path of the `url` relative to the [`FIXTURE_BASE`], without the query.
+    fn path(url: &str) -> Result<&str, ApiError> {
+        let path = url
+            .strip_prefix(FIXTURE_BASE)
+            .ok_or(ApiError::HttpStatus { code: 404 })?;
+        Ok(path
+            .split('?')
+            .next()
+            .unwrap_or(path)
+            .trim_start_matches('/'))
+    }
+}
+
+impl Transport for FixtureTransport {
+    fn get_json(&self, url: &str, _timeout: Duration) -> Result<Value, ApiError> {
+        let body = fs::read_to_string(self.root.join(Self::path(url)?))
+            .map_err(|_| ApiError::HttpStatus { code: 404 })?;
+        Ok(serde_json::from_str(
+            &body.replace("{{base}}", FIXTURE_BASE),
+        )?)
+    }
+
+    fn get_bytes(&self, url: &str, _timeout: Duration) -> Result<Bytes, ApiError> {
+        if !Self::path(url)?.starts_with("images/") {
+            return Err(ApiError::HttpStatus { code: 404 });
+        }
+        let tile =
+            fs::read(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tile.png"))?;
+        Ok(Bytes::from(tile))
+    }
+}
+
+/// The home data of `tests/fixtures/shapes`: a set whose items have odd artwork, a `SetRef` titled
+/// by the set it refers to, and sets with empty, missing and `null` items.
+fn shapes_api() -> Api {
+    let mut api = Api::new()
+        .with_base_url(FIXTURE_BASE)
+        .with_retry_policy(fast_retries(1))
+        .with_transport(FixtureTransport::new("shapes"));
+    api.load_home_data().unwrap();
+    api
+}
+
+/// Path of the [`ApiError::MissingField`] `err`, panics on any other error.
+fn missing_path(err: ApiError) -> String {
+    match err {
+        ApiError::MissingField { path } => path,
+        err => panic!("expected a missing field, got {}", err),
+    }
+}
+
+/// What `err` names as found instead of usable artwork.
+fn available_tiles(err: ApiError) -> Vec<String> {
+    match err {
+        ApiError::NoUsableArtwork { available, .. } => available,
+        err => panic!("expected no usable artwork, got {}", err),
+    }
+}
+
+#[test]
+fn parses_every_container_shape() {
+    let api = shapes_api();
+
+    assert_eq!(api.get_num_of_sets(), Some(5));
+    let sets: Vec<_> = (0..5)
+        .map(|set_idx| {
+            let set = api.get_set(set_idx).unwrap();
+            (set.get_title(), set.get_item_count())
+        })
+        .collect();
+    assert_eq!(
+        sets,
+        vec![
+            ("Odd Tiles", 7),
+            ("Because You Watched", 2),
+            ("Coming Soon", 0),
+            ("Nothing Yet", 0),
+            ("Title not found", 0),
+        ]
+    );
+    assert_eq!(api.get_set(4).unwrap().find_title(), None);
+    assert_eq!(
+        api.get_set(1).unwrap().get_content_id(1),
+        Some("ref-shape-2")
+    );
+    assert!(api
+        .anomalies()
+        .iter()
+        .all(|anomaly| !matches!(anomaly.kind, AnomalyKind::UnresolvedRefSet(_))));
+}
+
+#[test]
+fn finds_the_titles_at_every_text_path() {
+    let api = shapes_api();
+    let sources: Vec<_> = (0..5)
+        .map(|set_idx| {
+            let source = api.get_set(set_idx).unwrap().get_title_source();
+            source.map(|TitleSource { length, source }| format!("{}.{}", length, source))
+        })
+        .collect();
+    assert_eq!(
+        sources,
+        vec![
+            Some("full.set".to_string()),
+            Some("full.set".to_string()),
+            Some("full.collection".to_string()),
+            Some("full.series".to_string()),
+            None,
+        ]
+    );
+
+    let title = |sources: &[&str]| {
+        let mut by_source = serde_json::Map::new();
+        for source in sources {
+            by_source.insert(
+                source.to_string(),
+                serde_json::json!({ "default": { "content": source.to_uppercase() } }),
+            );
+        }
+        let text: TextBundle =
+            serde_json::from_value(serde_json::json!({ "title": { "full": by_source } })).unwrap();
+        text.set_title()
+            .map(|(title, found)| (title.to_string(), found.source.to_string()))
+    };
+    let found = |source: &str| Some((source.to_uppercase(), source.to_string()));
+    assert_eq!(title(&["program", "collection", "set"]), found("set"));
+    assert_eq!(
+        title(&["program", "series", "collection"]),
+        found("collection")
+    );
+    assert_eq!(title(&["program", "series"]), found("series"));
+    assert_eq!(title(&["brand", "program"]), found("program"));
+    assert_eq!(title(&["brand"]), found("brand"));
+    assert_eq!(title(&[]), None);
+}
+
+#[test]
+fn extracts_the_tile_urls() {
+    let api = shapes_api();
+    let set = api.get_set(0).unwrap();
+
+    // The aspect ratio closest to 16:9 by default, whatever the tile type.
+    let url = set.get_home_tile_url(0).unwrap();
+    assert!(
+        url.starts_with(&format!("{}/images/shape-1.png?", FIXTURE_BASE)),
+        "{}",
+        url
+    );
+    assert_eq!(set.get_tile_aspect_ratios(0), vec![0.71, 1.78]);
+    let url = api.get_set(1).unwrap().get_home_tile_url(0).unwrap();
+    assert!(url.contains("/images/ref-shape-1.png?"), "{}", url);
+
+    assert_eq!(
+        missing_path(set.get_home_tile_url(1).unwrap_err()),
+        "items[1].image.tile"
+    );
+    assert_eq!(
+        available_tiles(set.get_home_tile_url(2).unwrap_err()),
+        vec!["1.78"]
+    );
+    assert_eq!(
+        available_tiles(set.get_home_tile_url(3).unwrap_err()),
+        vec!["wide/program"]
+    );
+    assert!(set.get_tile_aspect_ratios(3).is_empty());
+    assert_eq!(
+        missing_path(set.get_home_tile_url(4).unwrap_err()),
+        "items[4].image.tile"
+    );
+    assert_eq!(
+        missing_path(set.get_home_tile_url(7).unwrap_err()),
+        "items[7]"
+    );
+    for set_idx in 2..5 {
+        let err = api
+            .get_set(set_idx)
+            .unwrap()
+            .get_home_tile_url(0)
+            .unwrap_err();
+        assert_eq!(missing_path(err), "items[0]");
+    }
+}
+
+#[test]
+fn picks_the_tile_type() {
+    let api = shapes_api();
+    let set = api.get_set(0).unwrap();
+
+    // `default` over any other type, then whatever type there is.
+    let url = set.get_home_tile_url(5).unwrap();
+    assert!(url.contains("/images/default-type.png?"), "{}", url);
+    let url = set.get_home_tile_url(6).unwrap();
+    assert!(url.contains("/images/custom-type.png?"), "{}", url);
+
+    let err = set
+        .get_home_tile_with(0, AspectRatioPolicy::Exact(1.33))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "no usable artwork at 'items[0].image.tile', found: [0.71/program, 1.78/series]"
+    );
+    assert!(!err.is_retryable());
+}
+
+#[test]
+fn extracts_the_video_art() {
+    let api = shapes_api();
+    let set = api.get_set(0).unwrap();
+
+    // The entries without any url are passed over.
+    assert_eq!(
+        set.get_item_video_art(0).unwrap(),
+        VideoLoop {
+            url: format!("{}/video/shape-1.mp4", FIXTURE_BASE),
+            fallback_urls: vec![format!("{}/video/shape-1-backup.mp4", FIXTURE_BASE)],
+            purpose: Some("background".to_string()),
+        }
+    );
+    for item_num in 1..5 {
+        assert_eq!(
+            missing_path(set.get_item_video_art(item_num).unwrap_err()),
+            format!("items[{}].videoArt", item_num)
+        );
+    }
+    assert_eq!(
+        missing_path(set.get_item_video_art(7).unwrap_err()),
+        "items[7]"
+    );
+}
+
+#[test]
+fn extracts_the_title_treatment() {
+    let mut api = shapes_api();
+
+    // The widest logo, in png whatever the format of the rest of the artwork.
+    let logo = api.get_set(0).unwrap().get_title_treatment(0).unwrap();
+    assert!(
+        logo.url
+            .starts_with(&format!("{}/images/logo-1.png?", FIXTURE_BASE)),
+        "{}",
+        logo.url
+    );
+    assert!(logo.url.contains("width=2112"), "{}", logo.url);
+    assert!(logo.url.contains("format=png"), "{}", logo.url);
+    assert_eq!(logo.aspect_ratio, 3.91);
+    let set = api.get_set(0).unwrap();
+    for item_num in 1..5 {
+        assert_eq!(
+            missing_path(set.get_title_treatment(item_num).unwrap_err()),
+            format!("items[{}].image.title_treatment", item_num)
+        );
+    }
+
+    // Webp keeps the transparency.
+    api.set_image_options(ImageOptions {
+        format: ArtworkFormat::Webp,
+        ..ImageOptions::default()
+    });
+    let logo = api.get_set(0).unwrap().get_title_treatment(0).unwrap();
+    assert!(logo.url.contains("format=webp"), "{}", logo.url);
+    let img = api.image_cache().fetch_image(&logo.url).unwrap();
+    assert_eq!((img.width(), img.height()), (16, 9));
+}
+
+#[test]
+fn tiles_come_through_the_transport() {
+    let api = shapes_api();
+    let set = api.get_set(0).unwrap();
+
+    let img = set.get_home_tile_image(0).unwrap();
+
+    assert_eq!((img.width(), img.height()), (16, 9));
+    assert_eq!(api.image_cache().len(), 1);
+    // Nothing to fetch without an url.
+    assert_eq!(
+        missing_path(set.get_home_tile_image(1).unwrap_err()),
+        "items[1].image.tile"
+    );
+    assert_eq!(api.image_cache().len(), 1);
+}
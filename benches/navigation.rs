@@ -0,0 +1,73 @@
+//! Time spent every frame on the row virtualization: finding the rows in view as the cursor moves
+//! down and back up, and looking up the cached tiles of a row, the first thing
+//! `populate_cache_if_needed` does for each tile in view.
+//!
+//! The binary has no library to link against, the modules are included as they are.
+#![allow(dead_code)]
+
+#[path = "../src/navigation.rs"]
+mod navigation;
+#[path = "../src/virtualization.rs"]
+mod virtualization;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use navigation::NavigationModel;
+use virtualization::{RowScroll, TileRing, Viewport};
+
+/// Rows in view, as in a 1080p window.
+const NUM_ROWS: usize = 4;
+/// Rows of a large home screen.
+const NUM_OF_SETS: usize = 200;
+/// Tiles drawn for a row, see `Layout::row_stride`.
+const ROW_STRIDE: usize = 8;
+
+fn visible_rows(c: &mut Criterion) {
+    let mut group = c.benchmark_group("visible_set_range");
+    group.bench_function("viewport", |b| {
+        let mut viewport = Viewport::new(NUM_ROWS);
+        b.iter(|| {
+            for true_set_idx in (0..NUM_OF_SETS).chain((0..NUM_OF_SETS).rev()) {
+                black_box(viewport.visible_set_range(black_box(true_set_idx), NUM_ROWS));
+            }
+        })
+    });
+    group.bench_function("navigation_model", |b| {
+        let mut nav = NavigationModel::new(NUM_ROWS);
+        b.iter(|| {
+            // Down to the last row and back up to the first one, short of the banner.
+            for _ in 1..NUM_OF_SETS {
+                nav.move_down(NUM_OF_SETS);
+                black_box(nav.visible_set_range());
+            }
+            for _ in 1..NUM_OF_SETS {
+                nav.move_up(NUM_OF_SETS);
+                black_box(nav.visible_set_range());
+            }
+        })
+    });
+    group.finish();
+}
+
+fn cached_tiles(c: &mut Criterion) {
+    // A row scrolled right by half its stride: the tiles scrolled past are still in the ring, the
+    // half in view that came after aren't cached yet.
+    let scroll = RowScroll {
+        left_right_idx_adjustment: ROW_STRIDE / 2,
+    };
+    let mut ring = TileRing::new(ROW_STRIDE);
+    for true_item_idx in 0..ROW_STRIDE {
+        ring.insert(true_item_idx, true_item_idx);
+    }
+
+    c.bench_function("tile_ring/get", |b| {
+        b.iter(|| {
+            for adjusted_item_idx in 0..ROW_STRIDE {
+                let true_item_idx = scroll.true_item_idx(adjusted_item_idx);
+                black_box(ring.get(black_box(true_item_idx)));
+            }
+        })
+    });
+}
+
+criterion_group!(benches, visible_rows, cached_tiles);
+criterion_main!(benches);
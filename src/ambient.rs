@@ -0,0 +1,134 @@
+//! Background tinted after the artwork of the focused tile.
+//!
+//! The dominant color of each tile is picked on the decoding threads, see [`dominant_color`], and
+//! kept along its texture in the [`CachedImgData`](crate::CachedImgData). The home screen is drawn
+//! over a backdrop which moves toward a mix of the theme background and the dominant color of the
+//! focused tile, back to the plain background when the focus goes to the banner or to a tile
+//! without one.
+use crate::animation::Easing;
+use conrod::{widget, Colorable, Positionable, Sizeable, UiCell, Widget};
+use image::{Rgba, RgbaImage};
+use std::time::{Duration, Instant};
+
+/// Time the backdrop takes to change color.
+const AMBIENT_DURATION: Duration = Duration::from_millis(600);
+/// How much of the dominant color goes into the backdrop, the rest being the theme background.
+const AMBIENT_STRENGTH: f32 = 0.3;
+/// Bits kept per channel when grouping the pixels by color.
+const QUANTIZE_BITS: u32 = 4;
+/// Pixels more transparent than this are left out.
+const MIN_ALPHA: u8 = 128;
+
+widget_ids!(
+    /// Hold the [`Id`](conrod::widget::Id)s for the backdrop widgets.
+    pub struct AmbientIds {
+        backdrop,
+    }
+);
+
+/// The dominant color of `img`, as sRGB components from 0 to 1. None if the image is transparent.
+///
+/// The pixels are grouped by color, the saturated ones weighting more so the greys of a poster's
+/// background don't win over its subject, and the group weighting the most is averaged.
+pub fn dominant_color(img: &RgbaImage) -> Option<[f32; 3]> {
+    let shift = 8 - QUANTIZE_BITS;
+    // Weight and weighted sum of the components of each group.
+    let mut groups = vec![(0.0f32, [0.0f32; 3]); 1 << (3 * QUANTIZE_BITS)];
+    for Rgba([r, g, b, a]) in img.pixels() {
+        if *a < MIN_ALPHA {
+            continue;
+        }
+        let idx = ((*r as usize >> shift) << (2 * QUANTIZE_BITS))
+            | ((*g as usize >> shift) << QUANTIZE_BITS)
+            | (*b as usize >> shift);
+        let saturation = (*r.max(g).max(b) - *r.min(g).min(b)) as f32 / 255.0;
+        let weight = 1.0 + 4.0 * saturation;
+        let (total, sum) = &mut groups[idx];
+        *total += weight;
+        for (sum, value) in sum.iter_mut().zip(&[*r, *g, *b]) {
+            *sum += *value as f32 * weight;
+        }
+    }
+    let (total, sum) = groups
+        .into_iter()
+        .max_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap())?;
+    if total == 0.0 {
+        return None;
+    }
+    Some([
+        sum[0] / total / 255.0,
+        sum[1] / total / 255.0,
+        sum[2] / total / 255.0,
+    ])
+}
+
+/// The backdrop of the home screen and its transition between two colors.
+pub struct Ambient {
+    ids: AmbientIds,
+    from: [f32; 3],
+    to: [f32; 3],
+    start: Instant,
+    enabled: bool,
+}
+
+impl Ambient {
+    /// Constructor. The backdrop starts as the theme `background`. When `enabled` is false it
+    /// changes color at once.
+    pub fn new(ids: AmbientIds, background: [f32; 3], enabled: bool) -> Self {
+        Self {
+            ids,
+            from: background,
+            to: background,
+            start: Instant::now(),
+            enabled,
+        }
+    }
+
+    /// Turns the transition on or off, see [`Ambient::new`].
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Moves the backdrop toward the theme `background` tinted by the dominant `color` of the
+    /// focused tile, or to the plain `background` without one.
+    pub fn set_target(&mut self, background: [f32; 3], color: Option<[f32; 3]>, now: Instant) {
+        let mut to = background;
+        if let Some(color) = color {
+            for (to, color) in to.iter_mut().zip(&color) {
+                *to += (color - *to) * AMBIENT_STRENGTH;
+            }
+        }
+        if to == self.to {
+            return;
+        }
+        self.from = if self.enabled { self.color(now) } else { to };
+        self.to = to;
+        self.start = now;
+    }
+
+    /// Color of the backdrop at `now`.
+    fn color(&self, now: Instant) -> [f32; 3] {
+        let elapsed = now.saturating_duration_since(self.start).as_secs_f64();
+        let t = Easing::EaseInOutQuad.apply(elapsed / AMBIENT_DURATION.as_secs_f64()) as f32;
+        let mut color = self.from;
+        for (color, to) in color.iter_mut().zip(&self.to) {
+            *color += (to - *color) * t;
+        }
+        color
+    }
+
+    /// Returns true while the backdrop is changing color.
+    pub fn is_animating(&self, now: Instant) -> bool {
+        self.from != self.to && now.saturating_duration_since(self.start) < AMBIENT_DURATION
+    }
+
+    /// Sets the backdrop over the whole window. Meant to be set before the other widgets so they
+    /// are drawn on top of it.
+    pub fn show(&self, ui: &mut UiCell, now: Instant) {
+        let [r, g, b] = self.color(now);
+        widget::Rectangle::fill(ui.window_dim())
+            .middle_of(ui.window)
+            .color(conrod::color::rgb(r, g, b))
+            .set(self.ids.backdrop, ui);
+    }
+}
@@ -0,0 +1,263 @@
+//! Time based tweening used to smooth out the navigation.
+//!
+//! Nothing here knows about widgets. The [`DisplayController`](crate::DisplayController) feeds the
+//! targets (row scroll, vertical scroll, focus) and reads back interpolated values every tick.
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Duration of every transition.
+pub const ANIMATION_DURATION: Duration = Duration::from_millis(150);
+/// Time a loaded image takes to fade in over its placeholder.
+pub const CROSSFADE_DURATION: Duration = Duration::from_millis(200);
+/// Time the shimmer takes to sweep once across a tile still loading.
+pub const SHIMMER_PERIOD: Duration = Duration::from_millis(1200);
+
+/// Easing curves available to a [`Tween`].
+#[derive(Clone, Copy, Debug)]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+    /// Fast start, slow end.
+    EaseOutCubic,
+    /// Slow start and end.
+    EaseInOutQuad,
+}
+
+impl Easing {
+    /// Maps the linear progress `t` (0 to 1) through the curve.
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.max(0.0).min(1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOutQuad => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// Interpolates a single value from `from` to `to` over `duration`.
+#[derive(Clone, Debug)]
+pub struct Tween {
+    from: f64,
+    to: f64,
+    start: Instant,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl Tween {
+    /// A tween resting at `value`.
+    pub fn at(value: f64) -> Self {
+        Self {
+            from: value,
+            to: value,
+            start: Instant::now(),
+            duration: ANIMATION_DURATION,
+            easing: Easing::EaseOutCubic,
+        }
+    }
+
+    /// A tween going from `from` to `to` starting `now`.
+    pub fn between(from: f64, to: f64, now: Instant) -> Self {
+        Self {
+            from,
+            to,
+            start: now,
+            duration: ANIMATION_DURATION,
+            easing: Easing::EaseOutCubic,
+        }
+    }
+
+    /// Changes the destination. The transition starts from wherever the tween currently is so
+    /// quick consecutive moves don't jump.
+    pub fn retarget(&mut self, to: f64, now: Instant) {
+        if (to - self.to).abs() < f64::EPSILON {
+            return;
+        }
+        self.from = self.value(now);
+        self.to = to;
+        self.start = now;
+    }
+
+    /// Current value.
+    pub fn value(&self, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.start).as_secs_f64();
+        let t = elapsed / self.duration.as_secs_f64();
+        self.from + (self.to - self.from) * self.easing.apply(t)
+    }
+
+    /// Destination value.
+    pub fn target(&self) -> f64 {
+        self.to
+    }
+
+    /// Returns true once the destination has been reached.
+    pub fn is_done(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.start) >= self.duration
+    }
+}
+
+/// Loops over the frames of the shimmer drawn in place of the tiles still loading, one sweep every
+/// [`SHIMMER_PERIOD`]. The frames themselves come from [`build_shimmer_frames`](crate::helpers::build_shimmer_frames).
+#[derive(Clone, Debug)]
+pub struct Shimmer {
+    num_frames: usize,
+    started: Instant,
+}
+
+impl Shimmer {
+    /// Shimmer of `num_frames` frames, at least 1, starting at the first one `now`.
+    pub fn new(num_frames: usize, now: Instant) -> Self {
+        Self {
+            num_frames: num_frames.max(1),
+            started: now,
+        }
+    }
+
+    /// Index of the frame to draw at `now`.
+    pub fn frame(&self, now: Instant) -> usize {
+        let period = SHIMMER_PERIOD.as_secs_f64();
+        let elapsed = now.saturating_duration_since(self.started).as_secs_f64();
+        let t = (elapsed % period) / period;
+        ((t * self.num_frames as f64) as usize).min(self.num_frames - 1)
+    }
+}
+
+/// Identifies the focused element: `(true_set_idx, true_item_idx)`, None for the banner.
+pub type Focus = Option<(usize, usize)>;
+
+/// Keeps every transition of the home grid.
+pub struct Animator {
+    /// Horizontal scroll of each row in items, keyed by `true_set_idx`.
+    row_scroll: HashMap<usize, Tween>,
+    /// Vertical scroll in rows, follows the start of the visible range.
+    vertical_scroll: Tween,
+    /// Extra top offset in pixels, follows the banner visibility.
+    top_shift: Tween,
+    /// Scale of the focused tile.
+    grow: Tween,
+    /// Scale of the previously focused tile.
+    shrink: Tween,
+    focus: Focus,
+    prev_focus: Focus,
+    enabled: bool,
+}
+
+impl Animator {
+    /// Constructor. When `enabled` is false every transition is instant.
+    pub fn new(enabled: bool, initial_top_shift: f64, highlight_scale: f64) -> Self {
+        Self {
+            row_scroll: HashMap::new(),
+            vertical_scroll: Tween::at(0.0),
+            top_shift: Tween::at(initial_top_shift),
+            grow: Tween::at(highlight_scale),
+            shrink: Tween::at(1.0),
+            focus: Some((0, 0)),
+            prev_focus: None,
+            enabled,
+        }
+    }
+
+    /// Turns the transitions on or off, see [`Animator::new`]. The ones in progress run to the end.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn tween(&self, from: f64, to: f64, now: Instant) -> Tween {
+        if self.enabled {
+            Tween::between(from, to, now)
+        } else {
+            Tween::at(to)
+        }
+    }
+
+    /// Sets the horizontal destination (the row's `left_right_idx_adjustment`) of the given row.
+    /// `prev_adjustment` is only used the first time the row scrolls.
+    pub fn scroll_row(
+        &mut self,
+        true_set_idx: usize,
+        prev_adjustment: usize,
+        adjustment: usize,
+        now: Instant,
+    ) {
+        let to = adjustment as f64;
+        let enabled = self.enabled;
+        let tween = self
+            .row_scroll
+            .entry(true_set_idx)
+            .or_insert_with(|| Tween::at(prev_adjustment as f64));
+        if enabled {
+            tween.retarget(to, now);
+        } else {
+            *tween = Tween::at(to);
+        }
+    }
+
+    /// Offset, in items, to add to the row's resting position.
+    pub fn row_offset(&self, true_set_idx: usize, now: Instant) -> f64 {
+        self.row_scroll
+            .get(&true_set_idx)
+            .map(|t| t.target() - t.value(now))
+            .unwrap_or(0.0)
+    }
+
+    /// Sets the vertical destination (start of the visible range) and the top shift in pixels.
+    pub fn scroll_vertically(&mut self, range_start: usize, top_shift: f64, now: Instant) {
+        if self.enabled {
+            self.vertical_scroll.retarget(range_start as f64, now);
+            self.top_shift.retarget(top_shift, now);
+        } else {
+            self.vertical_scroll = Tween::at(range_start as f64);
+            self.top_shift = Tween::at(top_shift);
+        }
+    }
+
+    /// Offset, in rows, to add to every row's resting position.
+    pub fn vertical_offset(&self, now: Instant) -> f64 {
+        self.vertical_scroll.target() - self.vertical_scroll.value(now)
+    }
+
+    /// Current top shift in pixels.
+    pub fn top_shift(&self, now: Instant) -> f64 {
+        self.top_shift.value(now)
+    }
+
+    /// Notifies the focus moved. The new focus grows to `highlight_scale` while the old one shrinks back.
+    pub fn set_focus(&mut self, focus: Focus, highlight_scale: f64, now: Instant) {
+        if focus == self.focus {
+            return;
+        }
+        self.shrink = self.tween(self.grow.value(now), 1.0, now);
+        self.grow = self.tween(1.0, highlight_scale, now);
+        self.prev_focus = self.focus;
+        self.focus = focus;
+    }
+
+    /// Scale of the focused tile.
+    pub fn focus_scale(&self, now: Instant) -> f64 {
+        self.grow.value(now)
+    }
+
+    /// Scale of the tile that previously had the focus, if it's still shrinking.
+    pub fn shrinking(&self, now: Instant) -> Option<((usize, usize), f64)> {
+        if self.shrink.is_done(now) {
+            return None;
+        }
+        self.prev_focus.map(|f| (f, self.shrink.value(now)))
+    }
+
+    /// Returns true while any transition is in progress.
+    pub fn is_animating(&self, now: Instant) -> bool {
+        !self.vertical_scroll.is_done(now)
+            || !self.top_shift.is_done(now)
+            || !self.grow.is_done(now)
+            || !self.shrink.is_done(now)
+            || self.row_scroll.values().any(|t| !t.is_done(now))
+    }
+}
@@ -0,0 +1,252 @@
+//! Packs the tile images into a few large textures.
+//!
+//! Giving each tile a texture of its own means a texture switch per tile drawn and the driver
+//! overhead of a texture each. The tiles are instead copied into the pages of a [`TextureAtlas`]
+//! and drawn with the part of the page they take, see [`AtlasRegion::source_rect`].
+//!
+//! The pages are split into shelves, rows as tall as the first image put on them. An image goes on
+//! the first shelf close enough to its height with room left, or where an image was removed, else
+//! on a new shelf. The tiles of a row share their height so they end up on the same shelves. The
+//! images that don't fit, e.g. once [`MAX_PAGES`] are full, get a texture of their own.
+use crate::ui_core::SourceRect;
+use conrod::backend::glium::glium::{self, Surface};
+use conrod::glium::Display;
+use conrod::image::{Id, Map};
+use image::RgbaImage;
+use tracing::{debug, info, info_span};
+
+/// Width and height of a page.
+pub const ATLAS_SIZE: u32 = 4096;
+/// Pages created at most, each takes [`PAGE_BYTES`] of GPU memory.
+const MAX_PAGES: usize = 2;
+/// GPU memory taken by a page, 64MB.
+pub const PAGE_BYTES: u64 = ATLAS_SIZE as u64 * ATLAS_SIZE as u64 * 4;
+/// Space left between two images so the sampling at the edge of one doesn't bleed into the next.
+const PADDING: u32 = 2;
+/// How much taller than an image a shelf can be and still take it, as a fraction of its height.
+const SHELF_SLACK: f64 = 0.25;
+
+/// Where an image was put in the [`TextureAtlas`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtlasRegion {
+    /// The page texture in the image map.
+    pub page: Id,
+    /// Width of the image, in pixels.
+    pub w: u32,
+    /// Height of the image, in pixels.
+    pub h: u32,
+    page_idx: usize,
+    shelf_idx: usize,
+    slot_idx: usize,
+    x: u32,
+    y: u32,
+}
+
+impl AtlasRegion {
+    /// The part of the page to draw the image with.
+    pub fn source_rect(&self) -> SourceRect {
+        SourceRect {
+            left: self.x as f64,
+            bottom: self.y as f64,
+            w: self.w as f64,
+            h: self.h as f64,
+        }
+    }
+}
+
+/// Room for an image on a [`Shelf`], free again once the image is removed.
+#[derive(Debug)]
+struct Slot {
+    x: u32,
+    w: u32,
+    used: bool,
+}
+
+#[derive(Debug)]
+struct Shelf {
+    y: u32,
+    h: u32,
+    slots: Vec<Slot>,
+    /// Where the next slot starts.
+    end: u32,
+}
+
+impl Shelf {
+    /// Returns true if an image `h` tall (padding included) can go on this shelf.
+    fn fits(&self, h: u32) -> bool {
+        h <= self.h && (self.h - h) as f64 <= self.h as f64 * SHELF_SLACK
+    }
+}
+
+#[derive(Debug)]
+struct Page {
+    id: Id,
+    shelves: Vec<Shelf>,
+    /// Where the next shelf starts.
+    end: u32,
+}
+
+impl Page {
+    fn new(id: Id) -> Self {
+        Self {
+            id,
+            shelves: Vec::new(),
+            end: 0,
+        }
+    }
+
+    /// Finds room for an image of `w` x `h` (padding included), returns the shelf and slot it goes in.
+    fn allocate(&mut self, w: u32, h: u32) -> Option<(usize, usize)> {
+        for (shelf_idx, shelf) in self.shelves.iter_mut().enumerate() {
+            if !shelf.fits(h) {
+                continue;
+            }
+            if let Some(slot_idx) = shelf.slots.iter().position(|s| !s.used && s.w >= w) {
+                shelf.slots[slot_idx].used = true;
+                return Some((shelf_idx, slot_idx));
+            }
+            if shelf.end + w <= ATLAS_SIZE {
+                shelf.slots.push(Slot {
+                    x: shelf.end,
+                    w,
+                    used: true,
+                });
+                shelf.end += w;
+                return Some((shelf_idx, shelf.slots.len() - 1));
+            }
+        }
+        if self.end + h > ATLAS_SIZE {
+            return None;
+        }
+        self.shelves.push(Shelf {
+            y: self.end,
+            h,
+            slots: vec![Slot {
+                x: 0,
+                w,
+                used: true,
+            }],
+            end: w,
+        });
+        self.end += h;
+        Some((self.shelves.len() - 1, 0))
+    }
+
+    /// Frees the slot at `slot_idx` of the shelf at `shelf_idx`. Shelves left empty take any
+    /// width again, the last one is given back to the page.
+    fn free(&mut self, shelf_idx: usize, slot_idx: usize) {
+        let shelf = &mut self.shelves[shelf_idx];
+        shelf.slots[slot_idx].used = false;
+        if shelf.slots.iter().any(|slot| slot.used) {
+            return;
+        }
+        if shelf_idx + 1 == self.shelves.len() {
+            self.end = shelf.y;
+            self.shelves.pop();
+        } else {
+            shelf.slots.clear();
+            shelf.end = 0;
+        }
+    }
+}
+
+/// The pages the tiles are packed into. The page textures are kept in the image map the widgets
+/// are drawn from, under [`AtlasRegion::page`].
+#[derive(Debug, Default)]
+pub struct TextureAtlas {
+    pages: Vec<Page>,
+}
+
+impl TextureAtlas {
+    /// Copies `img`, rows bottom to top as per [`helpers::to_gl_rgba`](crate::helpers::to_gl_rgba),
+    /// into a page with room for it. A page is created in the `image_map` if none has room and the
+    /// atlas `may_grow`, see [`GpuUsage::fits`](crate::gpu_budget::GpuUsage::fits).
+    ///
+    /// Gives `img` back if it doesn't fit, e.g. when all the pages are full.
+    pub fn insert(
+        &mut self,
+        display: &Display,
+        image_map: &mut Map<glium::texture::SrgbTexture2d>,
+        img: RgbaImage,
+        may_grow: bool,
+    ) -> Result<AtlasRegion, RgbaImage> {
+        let (w, h) = img.dimensions();
+        if w + PADDING > ATLAS_SIZE || h + PADDING > ATLAS_SIZE {
+            return Err(img);
+        }
+        let found = self
+            .pages
+            .iter_mut()
+            .enumerate()
+            .find_map(|(page_idx, page)| {
+                page.allocate(w + PADDING, h + PADDING)
+                    .map(|(shelf_idx, slot_idx)| (page_idx, shelf_idx, slot_idx))
+            });
+        let (page_idx, shelf_idx, slot_idx) = match found {
+            Some(found) => found,
+            None if may_grow && self.pages.len() < MAX_PAGES => {
+                match self.add_page(display, image_map) {
+                    Some(page) => match page.allocate(w + PADDING, h + PADDING) {
+                        Some((shelf_idx, slot_idx)) => (self.pages.len() - 1, shelf_idx, slot_idx),
+                        None => return Err(img),
+                    },
+                    None => return Err(img),
+                }
+            }
+            None => return Err(img),
+        };
+
+        let page = &self.pages[page_idx];
+        let shelf = &page.shelves[shelf_idx];
+        let region = AtlasRegion {
+            page: page.id,
+            w,
+            h,
+            page_idx,
+            shelf_idx,
+            slot_idx,
+            x: shelf.slots[slot_idx].x,
+            y: shelf.y,
+        };
+        let _span = info_span!("texture_upload", width = w, height = h, atlas = true).entered();
+        let texture = image_map
+            .get(&page.id)
+            .expect("the atlas pages stay in the image map");
+        let rect = glium::Rect {
+            left: region.x,
+            bottom: region.y,
+            width: w,
+            height: h,
+        };
+        texture.write(
+            rect,
+            glium::texture::RawImage2d::from_raw_rgba(img.into_raw(), (w, h)),
+        );
+        Ok(region)
+    }
+
+    /// Frees the room taken by the image at `region`, the page texture is kept.
+    pub fn remove(&mut self, region: &AtlasRegion) {
+        self.pages[region.page_idx].free(region.shelf_idx, region.slot_idx);
+    }
+
+    /// Creates a page texture cleared to transparent, None if the texture can't be created.
+    fn add_page(
+        &mut self,
+        display: &Display,
+        image_map: &mut Map<glium::texture::SrgbTexture2d>,
+    ) -> Option<&mut Page> {
+        let texture = match glium::texture::SrgbTexture2d::empty(display, ATLAS_SIZE, ATLAS_SIZE) {
+            Ok(texture) => texture,
+            Err(e) => {
+                info!("Could not create an atlas page: {:?}", e);
+                return None;
+            }
+        };
+        texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+        let id = image_map.insert(texture);
+        debug!("atlas page {} is {:?}", self.pages.len(), id);
+        self.pages.push(Page::new(id));
+        self.pages.last_mut()
+    }
+}
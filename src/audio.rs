@@ -0,0 +1,111 @@
+//! Short sounds played as the focus moves around, using [`rodio`].
+//!
+//! The samples are in the `sounds` folder of the assets, decoded once at startup so playing one
+//! only hands a buffer to the output stream. Their volume follows
+//! [`Config::sound_volume`](crate::config::Config::sound_volume), 0 mutes them. Without an audio
+//! device, or when a sample can't be decoded, the app goes on without it.
+use rodio::buffer::SamplesBuffer;
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Source};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use tracing::info;
+
+/// The sounds, each with its file in the `sounds` folder.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sound {
+    /// The focus moved to another tile of the row.
+    Move,
+    /// The focus moved to another row.
+    RowChange,
+    /// An item was opened.
+    Select,
+    /// A move going nowhere, or a wrong PIN.
+    Error,
+}
+
+const SOUNDS: [(Sound, &str); 4] = [
+    (Sound::Move, "move.wav"),
+    (Sound::RowChange, "row.wav"),
+    (Sound::Select, "select.wav"),
+    (Sound::Error, "error.wav"),
+];
+
+/// A decoded sound.
+struct Sample {
+    channels: u16,
+    sample_rate: u32,
+    data: Vec<f32>,
+}
+
+impl Sample {
+    fn decode(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let decoder = Decoder::new(BufReader::new(File::open(path)?))?;
+        Ok(Self {
+            channels: decoder.channels(),
+            sample_rate: decoder.sample_rate(),
+            data: decoder.convert_samples().collect(),
+        })
+    }
+}
+
+/// Where the sounds are played, see [`Audio::play`].
+pub struct Audio {
+    /// Kept alive for the `handle` to work, None without an audio device.
+    output: Option<(OutputStream, OutputStreamHandle)>,
+    /// By [`Sound`], in the order of [`SOUNDS`].
+    samples: Vec<Option<Sample>>,
+}
+
+impl Audio {
+    /// Opens the default audio device and decodes the sounds of the assets folder.
+    pub fn load() -> Self {
+        let output = match OutputStream::try_default() {
+            Ok(output) => output,
+            Err(e) => {
+                info!("Sounds disabled: {}", e);
+                return Self::silent();
+            }
+        };
+        let dir = crate::helpers::sounds_dir();
+        let samples = SOUNDS
+            .iter()
+            .map(|(_, file_name)| {
+                Sample::decode(&dir.join(file_name))
+                    .map_err(|e| info!("Could not decode the sound {}: {}", file_name, e))
+                    .ok()
+            })
+            .collect();
+        Self {
+            output: Some(output),
+            samples,
+        }
+    }
+
+    /// Plays nothing, e.g. when rendering [`headless`](crate::headless).
+    pub fn silent() -> Self {
+        Self {
+            output: None,
+            samples: Vec::new(),
+        }
+    }
+
+    /// Starts playing the `sound` at `volume`, from 0 (muted) to 100. Returns right away, the
+    /// sound overlaps with the ones still playing.
+    pub fn play(&self, sound: Sound, volume: u8) {
+        let handle = match &self.output {
+            Some((_, handle)) if volume > 0 => handle,
+            _ => return,
+        };
+        let idx = SOUNDS.iter().position(|(s, _)| *s == sound);
+        let sample = match idx.and_then(|idx| self.samples.get(idx)?.as_ref()) {
+            Some(sample) => sample,
+            None => return,
+        };
+        let source = SamplesBuffer::new(sample.channels, sample.sample_rate, sample.data.clone())
+            .amplify(f32::from(volume.min(100)) / 100.0);
+        if let Err(e) = handle.play_raw(source) {
+            info!("Could not play {:?}: {}", sound, e);
+        }
+    }
+}
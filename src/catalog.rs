@@ -0,0 +1,95 @@
+//! Where the rows come from, picked on the command line: the home data by default, or one of the
+//! [`ContentProvider`](api::ContentProvider)s loaded in its place.
+use crate::config::ConfigError;
+use crate::headless;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Environment variable holding the access token of the media server. Kept off the command line
+/// so it doesn't show up in the process list.
+#[cfg(feature = "media-server")]
+pub const MEDIA_TOKEN_VAR: &str = "HELLOPLUS_MEDIA_TOKEN";
+
+/// The catalog the rows are built from.
+#[derive(Clone, PartialEq)]
+pub enum Catalog {
+    /// The home data, from the network or the assets folder when [`offline`](crate::Config::offline).
+    Home,
+    /// `--library <dir>`, the images found under `dir`, see [`FolderProvider`](api::FolderProvider).
+    Folder(PathBuf),
+    /// `--tmdb`, the Trending, Popular and Top Rated lists of The Movie Database, see
+    /// [`TmdbProvider`](api::TmdbProvider). Needs the
+    /// [`tmdb_api_key`](crate::Config::tmdb_api_key).
+    Tmdb,
+    /// `--jellyfin <url>` or `--plex <url>`, the libraries of a media server, see
+    /// [`MediaServerProvider`](api::MediaServerProvider).
+    #[cfg(feature = "media-server")]
+    MediaServer {
+        /// Which kind of server is at the `url`.
+        server: api::MediaServer,
+        /// Where the server is, e.g. `http://localhost:8096`.
+        url: String,
+        /// Read from [`MEDIA_TOKEN_VAR`].
+        token: String,
+    },
+}
+
+impl Catalog {
+    /// Takes `--library <dir>`, `--tmdb`, and `--jellyfin <url>` or `--plex <url>` with the
+    /// `media-server` feature, out of `args` so the rest can be handed to
+    /// [`Config::load`](crate::Config::load). The first one found wins, [`Catalog::Home`] without
+    /// any.
+    pub fn take_from_args(args: &mut Vec<String>) -> Result<Self, ConfigError> {
+        let library = headless::take_value(args, "--library")?;
+        let tmdb = match args.iter().position(|a| a == "--tmdb") {
+            Some(pos) => {
+                args.remove(pos);
+                true
+            }
+            None => false,
+        };
+        #[cfg(feature = "media-server")]
+        {
+            let mut servers = Vec::new();
+            for server in [api::MediaServer::Jellyfin, api::MediaServer::Plex].iter() {
+                if let Some(url) = headless::take_value(args, &format!("--{}", server))? {
+                    servers.push((*server, url));
+                }
+            }
+            if let (None, false, Some((server, url))) = (&library, tmdb, servers.into_iter().next())
+            {
+                let token = std::env::var(MEDIA_TOKEN_VAR)
+                    .map_err(|_| ConfigError::MissingValue(MEDIA_TOKEN_VAR.to_string()))?;
+                return Ok(Catalog::MediaServer { server, url, token });
+            }
+        }
+        Ok(match library {
+            Some(dir) => Catalog::Folder(PathBuf::from(dir)),
+            None if tmdb => Catalog::Tmdb,
+            None => Catalog::Home,
+        })
+    }
+
+    /// Returns true if the catalog changes over time and is worth reloading every
+    /// [`home_refresh_minutes`](crate::Config::home_refresh_minutes), see [`refresh`](crate::refresh).
+    pub fn refreshes(&self) -> bool {
+        *self == Catalog::Home
+    }
+}
+
+/// Leaves the access token out.
+impl fmt::Debug for Catalog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Catalog::Home => f.write_str("Home"),
+            Catalog::Folder(dir) => f.debug_tuple("Folder").field(dir).finish(),
+            Catalog::Tmdb => f.write_str("Tmdb"),
+            #[cfg(feature = "media-server")]
+            Catalog::MediaServer { server, url, .. } => f
+                .debug_struct("MediaServer")
+                .field("server", server)
+                .field("url", url)
+                .finish(),
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! Subcommands of the command line, parsed with [`clap`].
+//!
+//! `helloplus run` opens the window, the default when no subcommand is given. The others only go
+//! through the [`api`] crate, no window nor OpenGL needed:
+//!  - `helloplus dump` prints the sets and their items, as a table or as json with `--format json`.
+//!  - `helloplus prefetch` fetches the tiles of the first `--rows <n>` rows into the disk cache,
+//!    so the next start (or an `--offline` one) shows them right away.
+//!  - `helloplus validate` loads the home data and reports what looks wrong in it, see
+//!    [`Api::anomalies`]. Exits with an error if anything was found.
+//!
+//! Every subcommand takes the config flags and the catalog flags after its own ones, e.g.
+//! `helloplus dump --format json --offline --locale fr`, see [`config`](crate::config) and
+//! [`Catalog`].
+use crate::catalog::Catalog;
+use crate::config::Config;
+use crate::layout::{RowStyle, TileShape};
+use api::{Api, AspectRatioPolicy, ContentProvider, POSTER_ASPECT_RATIO};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::json;
+use std::error::Error;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+/// How often `prefetch` checks whether a row is done.
+const PREFETCH_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The command line, see the [module](self) documentation.
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about = "Clone of a streaming service homepage",
+    long_about = None,
+    args_conflicts_with_subcommands = true
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Config flags of `run`, when no subcommand is given.
+    #[arg(
+        trailing_var_arg = true,
+        allow_hyphen_values = true,
+        value_name = "FLAGS"
+    )]
+    flags: Vec<String>,
+}
+
+impl Cli {
+    /// The subcommand given, [`Command::Run`] when there is none.
+    pub fn into_command(self) -> Command {
+        self.command.unwrap_or(Command::Run { flags: self.flags })
+    }
+}
+
+/// The subcommands.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Opens the window (the default).
+    Run {
+        /// Config, catalog, `--headless`, `--tui`, `--demo`, `--record`, `--replay` and `--chaos`
+        /// flags, e.g. `--num-rows 5`.
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            value_name = "FLAGS"
+        )]
+        flags: Vec<String>,
+    },
+    /// Prints the sets and their items.
+    Dump {
+        /// How the sets are printed.
+        #[arg(long, value_enum, default_value_t = DumpFormat::Table)]
+        format: DumpFormat,
+        /// Config and catalog flags, e.g. `--offline`.
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            value_name = "FLAGS"
+        )]
+        flags: Vec<String>,
+    },
+    /// Fetches the tiles of the first rows into the disk cache.
+    Prefetch {
+        /// Number of rows, the number of visible rows of the config by default.
+        #[arg(long)]
+        rows: Option<usize>,
+        /// Config and catalog flags, e.g. `--image-quality 80`.
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            value_name = "FLAGS"
+        )]
+        flags: Vec<String>,
+    },
+    /// Loads the home data and reports what looks wrong in it.
+    Validate {
+        /// Config flags, e.g. `--offline` to check the bundled home data.
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            value_name = "FLAGS"
+        )]
+        flags: Vec<String>,
+    },
+}
+
+impl Command {
+    /// The flags following the subcommand, handed to [`Config::load`].
+    pub fn flags_mut(&mut self) -> &mut Vec<String> {
+        match self {
+            Command::Run { flags }
+            | Command::Dump { flags, .. }
+            | Command::Prefetch { flags, .. }
+            | Command::Validate { flags } => flags,
+        }
+    }
+}
+
+/// Output of `dump`.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum DumpFormat {
+    /// A line per set, then one per item, for reading.
+    Table,
+    /// An array of the sets with their items, for scripts.
+    Json,
+}
+
+/// Loads the catalog given in the `flags`, with the config they resolve to.
+fn load(mut flags: Vec<String>) -> Result<(Api, Config), Box<dyn Error>> {
+    let catalog = Catalog::take_from_args(&mut flags)?;
+    let config = Config::load(&flags)?;
+    let mut api = crate::build_api(&config)?;
+    crate::load_home_data(&mut api, &config, &catalog)?;
+    Ok((api, config))
+}
+
+/// Prints the sets of the catalog and their items to stdout, in the `format`.
+pub fn dump(format: DumpFormat, flags: Vec<String>) -> Result<(), Box<dyn Error>> {
+    let (api, _) = load(flags)?;
+    let provider: &dyn ContentProvider = &api;
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    match format {
+        DumpFormat::Table => {
+            for set_idx in provider.list_sets() {
+                let count = provider.item_count(set_idx);
+                let title = provider.set_title(set_idx).unwrap_or("-");
+                writeln!(out, "{:>3}  {} ({} items)", set_idx, title, count)?;
+                for item_idx in 0..count {
+                    let metadata = provider
+                        .item_metadata(set_idx, item_idx)
+                        .unwrap_or_default();
+                    writeln!(
+                        out,
+                        "     {:>3}  {:<40}  {:<24}  {}",
+                        item_idx,
+                        metadata.title.as_deref().unwrap_or("-"),
+                        metadata.content_id.as_deref().unwrap_or("-"),
+                        metadata.summary()
+                    )?;
+                }
+            }
+        }
+        DumpFormat::Json => {
+            let sets = provider
+                .list_sets()
+                .map(|set_idx| {
+                    let items = (0..provider.item_count(set_idx))
+                        .map(|item_idx| {
+                            let metadata = provider
+                                .item_metadata(set_idx, item_idx)
+                                .unwrap_or_default();
+                            let tile = provider.item_image_url(set_idx, item_idx).ok();
+                            json!({
+                                "title": metadata.title,
+                                "content_id": metadata.content_id,
+                                "content_type": metadata.content_type,
+                                "release_year": metadata.release_year,
+                                "rating": metadata.rating,
+                                "tile": tile.map(|tile| tile.url),
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    json!({ "title": provider.set_title(set_idx), "items": items })
+                })
+                .collect::<Vec<_>>();
+            serde_json::to_writer_pretty(&mut out, &sets)?;
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Fetches the tiles of the first `rows` rows into the disk cache, in the shape the rows show
+/// them. Prints how each row went.
+pub fn prefetch(rows: Option<usize>, flags: Vec<String>) -> Result<(), Box<dyn Error>> {
+    let (api, config) = load(flags)?;
+    let rows = rows.unwrap_or(config.num_rows);
+    let (mut fetched, mut failed) = (0, 0);
+    for set_idx in 0..rows.min(api.get_num_of_sets().unwrap_or(0)) {
+        let set_data = match api.get_set(set_idx) {
+            Some(set_data) => set_data,
+            None => continue,
+        };
+        let set_data = match RowStyle::for_set(&set_data, config.poster_rows).shape() {
+            TileShape::Landscape => set_data,
+            TileShape::Poster => {
+                set_data.with_tile_aspect_ratio(AspectRatioPolicy::Closest(POSTER_ASPECT_RATIO))
+            }
+        };
+        let handle = set_data.prefetch_images(0..set_data.get_item_count());
+        while !handle.is_done() {
+            thread::sleep(PREFETCH_POLL_INTERVAL);
+        }
+        println!(
+            "{:>3}  {}: {} tiles, {} failed",
+            set_idx,
+            set_data.get_title(),
+            handle.total(),
+            handle.failed()
+        );
+        fetched += handle.total() - handle.failed();
+        failed += handle.failed();
+    }
+    println!("{} tiles fetched, {} failed", fetched, failed);
+    Ok(())
+}
+
+/// Loads the home data and prints its [`Anomaly`](api::Anomaly)s, one per line. Fails if there is
+/// any.
+pub fn validate(flags: Vec<String>) -> Result<(), Box<dyn Error>> {
+    let config = Config::load(&flags)?;
+    let mut api = crate::build_api(&config)?;
+    crate::load_home_data(&mut api, &config, &Catalog::Home)?;
+    let anomalies = api.anomalies();
+    for anomaly in &anomalies {
+        println!("{}", anomaly);
+    }
+    let num_of_sets = api.get_num_of_sets().unwrap_or(0);
+    if anomalies.is_empty() {
+        println!("{} sets, no anomaly found", num_of_sets);
+        Ok(())
+    } else {
+        Err(format!(
+            "{} anomalies found in {} sets",
+            anomalies.len(),
+            num_of_sets
+        )
+        .into())
+    }
+}
@@ -0,0 +1,153 @@
+//! [`Renderer`] drawing the [`DrawCommand`]s with conrod widgets.
+use crate::theme::Theme;
+use crate::ui_core::{DrawCommand, Renderer};
+use conrod::image::Id;
+use conrod::{widget, Colorable, Positionable, Sizeable, UiCell, Widget};
+use tracing::debug;
+
+/// Radius of the corners of a [`DrawCommand::Glow`].
+const GLOW_RADIUS: f64 = 10.0;
+
+widget_ids!(
+    /// Hold the [`Id`](conrod::widget::Id)s the [`DrawCommand`]s are drawn with.
+    ///
+    /// The n-th [`DrawCommand::Image`] of a frame uses `images[n]`, same for the texts. The buffers
+    /// grow as needed but should be reserved up front, see [`RendererIds::reserve`].
+    pub struct RendererIds {
+        images[],
+        texts[],
+        backdrops[],
+        glows[]
+    }
+);
+
+impl RendererIds {
+    /// Makes room for `images` images, `texts` texts, `backdrops` backdrops and `glows` glows per frame.
+    ///
+    /// Widgets created later are drawn on top of the ones created before, reserving the ids right
+    /// away keeps the tiles below the widgets of the other screens.
+    pub fn reserve(
+        &mut self,
+        images: usize,
+        texts: usize,
+        backdrops: usize,
+        glows: usize,
+        ui: &mut conrod::Ui,
+    ) {
+        if self.images.len() < images {
+            self.images.resize(images, &mut ui.widget_id_generator());
+        }
+        if self.texts.len() < texts {
+            self.texts.resize(texts, &mut ui.widget_id_generator());
+        }
+        if self.backdrops.len() < backdrops {
+            self.backdrops
+                .resize(backdrops, &mut ui.widget_id_generator());
+        }
+        if self.glows.len() < glows {
+            self.glows.resize(glows, &mut ui.widget_id_generator());
+        }
+    }
+}
+
+/// Sets one conrod widget per [`DrawCommand`].
+pub struct ConrodRenderer<'a, 'b: 'a> {
+    ui: &'a mut UiCell<'b>,
+    ids: &'a mut RendererIds,
+    theme: &'a Theme,
+}
+
+impl<'a, 'b> ConrodRenderer<'a, 'b> {
+    /// Constructor. The texts are drawn with the colors, fonts and sizes of the `theme`.
+    pub fn new(ui: &'a mut UiCell<'b>, ids: &'a mut RendererIds, theme: &'a Theme) -> Self {
+        Self { ui, ids, theme }
+    }
+}
+
+impl<'a, 'b> Renderer for ConrodRenderer<'a, 'b> {
+    type Image = Id;
+
+    fn draw(&mut self, commands: &[DrawCommand<Id>]) {
+        let (num_images, num_texts, num_backdrops, num_glows) =
+            commands
+                .iter()
+                .fold((0, 0, 0, 0), |(i, t, b, g), cmd| match cmd {
+                    DrawCommand::Image { .. } => (i + 1, t, b, g),
+                    DrawCommand::Text { .. } => (i, t + 1, b, g),
+                    DrawCommand::Backdrop { .. } => (i, t, b + 1, g),
+                    DrawCommand::Glow { .. } => (i, t, b, g + 1),
+                });
+        if num_images > self.ids.images.len()
+            || num_texts > self.ids.texts.len()
+            || num_backdrops > self.ids.backdrops.len()
+            || num_glows > self.ids.glows.len()
+        {
+            debug!(
+                "growing the renderer ids to {}/{}/{}/{}",
+                num_images, num_texts, num_backdrops, num_glows
+            );
+            let mut generator = self.ui.widget_id_generator();
+            self.ids
+                .images
+                .resize(num_images.max(self.ids.images.len()), &mut generator);
+            self.ids
+                .texts
+                .resize(num_texts.max(self.ids.texts.len()), &mut generator);
+            self.ids
+                .backdrops
+                .resize(num_backdrops.max(self.ids.backdrops.len()), &mut generator);
+            self.ids
+                .glows
+                .resize(num_glows.max(self.ids.glows.len()), &mut generator);
+        }
+
+        let (mut image_idx, mut text_idx, mut backdrop_idx, mut glow_idx) = (0, 0, 0, 0);
+        for cmd in commands {
+            match cmd {
+                DrawCommand::Image {
+                    image,
+                    source,
+                    rect,
+                    alpha,
+                } => {
+                    let mut img = widget::Image::new(*image)
+                        .w_h(rect.w, rect.h)
+                        .top_left_with_margins_on(self.ui.window, rect.y, rect.x);
+                    if let Some(source) = source {
+                        img = img.source_rectangle(conrod::Rect::from_corners(
+                            [source.left, source.bottom],
+                            [source.left + source.w, source.bottom + source.h],
+                        ));
+                    }
+                    if *alpha < 1.0 {
+                        img = img.color(conrod::color::WHITE.alpha(*alpha));
+                    }
+                    img.set(self.ids.images[image_idx], self.ui);
+                    image_idx += 1;
+                }
+                DrawCommand::Text { text, x, y, style } => {
+                    self.theme
+                        .text_widget(text, self.theme.text_color(*style))
+                        .top_left_with_margins_on(self.ui.window, *y, *x)
+                        .font_size(self.theme.font_size(*style))
+                        .set(self.ids.texts[text_idx], self.ui);
+                    text_idx += 1;
+                }
+                DrawCommand::Backdrop { rect, alpha } => {
+                    widget::Rectangle::fill([rect.w, rect.h])
+                        .top_left_with_margins_on(self.ui.window, rect.y, rect.x)
+                        .color(conrod::color::BLACK.alpha(*alpha))
+                        .set(self.ids.backdrops[backdrop_idx], self.ui);
+                    backdrop_idx += 1;
+                }
+                DrawCommand::Glow { rect, alpha } => {
+                    widget::RoundedRectangle::fill([rect.w, rect.h], GLOW_RADIUS)
+                        .top_left_with_margins_on(self.ui.window, rect.y, rect.x)
+                        .color(conrod::color::LIGHT_BLUE.alpha(*alpha))
+                        .set(self.ids.glows[glow_idx], self.ui);
+                    glow_idx += 1;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Debug overlay toggled with F3: frame rate, frame times, textures against their budget, downloads
+//! and artwork tier.
+//!
+//! Meant to tell where the stutters come from while scrolling, e.g. a burst of texture uploads
+//! or a [`image_map`](crate::DisplayController::image_map) that keeps growing. The frame times
+//! cover a whole iteration of the [`EventLoop`](crate::event_loop::EventLoop) that ended up drawing,
+//! the uploads of the images loaded in the background included.
+use crate::gpu_budget::GpuUsage;
+use crate::ui_core::Cursor;
+use api::ImageTier;
+use conrod::{widget, Colorable, Positionable, Sizeable, UiCell, Widget};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Number of frames the frame times are computed over.
+const FRAME_WINDOW: usize = 240;
+/// Time between two updates of the overlay while it's shown.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(250);
+const MARGIN: f64 = 16.0;
+const FONT_SIZE: u32 = 18;
+
+widget_ids!(
+    /// Hold the [`Id`](conrod::widget::Id)s for the overlay widgets.
+    pub struct HudIds {
+        backdrop,
+        text,
+    }
+);
+
+/// What the overlay shows besides the frame times, gathered by the
+/// [`DisplayController`](crate::DisplayController).
+#[derive(Debug)]
+pub struct HudStats<'c> {
+    /// Textures in the `image_map`, the memory they take and their budget.
+    pub gpu: GpuUsage,
+    /// Images being fetched in the background, or waiting for their upload.
+    pub in_flight: usize,
+    /// Images waiting for their upload, see [`UploadBudget`](crate::loader::UploadBudget).
+    pub queued_uploads: usize,
+    /// Tier the tile artwork is requested at, see [`Api::image_tier`](api::Api::image_tier).
+    pub image_tier: ImageTier,
+    /// Throughput of the recent downloads in bytes per second, see
+    /// [`BandwidthMonitor`](api::BandwidthMonitor). None until enough images were downloaded.
+    pub throughput: Option<f64>,
+    /// Where the focus is.
+    pub cursor: &'c Cursor,
+}
+
+/// State of the overlay, see [`DebugHud::record_frame`] for the frame times.
+pub struct DebugHud {
+    ids: HudIds,
+    shown: bool,
+    /// End and duration of the last [`FRAME_WINDOW`] frames, oldest first.
+    frames: VecDeque<(Instant, Duration)>,
+    /// When the overlay was last set, see [`DebugHud::is_due`].
+    updated_at: Option<Instant>,
+}
+
+impl DebugHud {
+    /// Constructor. The overlay starts hidden.
+    pub fn new(ids: HudIds) -> Self {
+        Self {
+            ids,
+            shown: false,
+            frames: VecDeque::with_capacity(FRAME_WINDOW),
+            updated_at: None,
+        }
+    }
+
+    /// Shows the overlay if it's hidden, hides it otherwise.
+    pub fn toggle(&mut self) {
+        self.shown = !self.shown;
+        self.updated_at = None;
+    }
+
+    /// Returns true while the overlay is shown.
+    pub fn is_shown(&self) -> bool {
+        self.shown
+    }
+
+    /// Returns true if the overlay is shown and its numbers are due for an update at `now`.
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.shown
+            && self
+                .updated_at
+                .map_or(true, |updated_at| now - updated_at >= UPDATE_INTERVAL)
+    }
+
+    /// Remembers a frame drawn in `duration`, ending at `end`.
+    pub fn record_frame(&mut self, end: Instant, duration: Duration) {
+        if self.frames.len() == FRAME_WINDOW {
+            self.frames.pop_front();
+        }
+        self.frames.push_back((end, duration));
+    }
+
+    /// Number of frames that ended in the second before `now`.
+    fn fps(&self, now: Instant) -> usize {
+        self.frames
+            .iter()
+            .rev()
+            .take_while(|(end, _)| now - *end <= Duration::from_secs(1))
+            .count()
+    }
+
+    /// Frame time at the `percentile` (0 to 100) of the last [`FRAME_WINDOW`] frames, None if no
+    /// frame was drawn yet.
+    fn frame_time(&self, percentile: usize) -> Option<Duration> {
+        let mut durations: Vec<Duration> = self.frames.iter().map(|(_, d)| *d).collect();
+        durations.sort_unstable();
+        let idx = (durations.len() * percentile / 100).min(durations.len().checked_sub(1)?);
+        Some(durations[idx])
+    }
+
+    /// Sets the overlay widgets in the top right corner, on top of whatever was set before. Does
+    /// nothing if it's hidden.
+    pub fn show(&mut self, ui: &mut UiCell, stats: &HudStats) {
+        if !self.shown {
+            return;
+        }
+        let now = Instant::now();
+        self.updated_at = Some(now);
+        let millis = |percentile| {
+            self.frame_time(percentile)
+                .map_or(0.0, |d| d.as_secs_f64() * 1000.0)
+        };
+        let cursor = stats.cursor;
+        let throughput = stats.throughput.map_or_else(
+            || String::from("n/a"),
+            |throughput| format!("{:.0} KB/s", throughput / 1024.0),
+        );
+        let text = format!(
+            "{} fps\nframe p50 {:.1} ms  p95 {:.1} ms  p99 {:.1} ms\n\
+             textures {}  ~{:.1} of {:.0} MB\ndownloads {} ({} to upload)  {}  artwork {}\n\
+             cursor set {} item {} (slot {}){}",
+            self.fps(now),
+            millis(50),
+            millis(95),
+            millis(99),
+            stats.gpu.textures,
+            stats.gpu.bytes as f64 / (1024.0 * 1024.0),
+            stats.gpu.budget as f64 / (1024.0 * 1024.0),
+            stats.in_flight,
+            stats.queued_uploads,
+            throughput,
+            stats.image_tier,
+            cursor.true_set_idx,
+            cursor.true_item_idx,
+            cursor.adjusted_item_idx,
+            if cursor.on_banner { " on banner" } else { "" },
+        );
+
+        widget::Rectangle::fill([520.0, 175.0])
+            .top_right_with_margin_on(ui.window, MARGIN)
+            .color(conrod::color::BLACK.alpha(0.7))
+            .set(self.ids.backdrop, ui);
+        widget::Text::new(&text)
+            .top_left_with_margins_on(self.ids.backdrop, 10.0, 12.0)
+            .color(conrod::color::LIGHT_GREEN)
+            .font_size(FONT_SIZE)
+            .set(self.ids.text, ui);
+    }
+}
@@ -0,0 +1,270 @@
+//! Kiosk mode driving the UI on its own, started with `--demo`.
+//!
+//! The [`Demo`] hands [`Action`]s to the [`DisplayController`](crate::DisplayController) the same
+//! way the keyboard does, pausing between them like someone looking at the tiles would. By default
+//! it walks around the rows at random: mostly along the row, now and then to another row, and
+//! sometimes opening the detail page of an item for a few seconds. `--demo-seed <n>` makes the walk
+//! the same on every run, e.g. to soak-test the loader and the eviction of the textures.
+//!
+//! `--demo-script <path>` plays a script instead, over and over. Each line is an action named as in
+//! `keys.toml` (see [`keymap`](crate::keymap)), optionally followed by the pause after it in
+//! milliseconds. Blank lines and lines starting with `#` are skipped:
+//! ```text
+//! # down to the second row and along it
+//! down 2000
+//! right
+//! right
+//! select 5000
+//! back
+//! ```
+//!
+//! Someone pressing a key takes over, the demo goes on after [`USER_GRACE`] without input.
+use crate::config::ConfigError;
+use crate::headless::take_value;
+use crate::keymap;
+use crate::screens::Action;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Wait before the first action.
+const START_DELAY: Duration = Duration::from_secs(2);
+/// Time without input from the user before the demo takes over again.
+const USER_GRACE: Duration = Duration::from_secs(30);
+/// Pause after a move along a row, in milliseconds.
+const MOVE_PAUSE_MS: Range<u64> = 600..1800;
+/// Pause after a move to another row, in milliseconds.
+const ROW_PAUSE_MS: Range<u64> = 1200..2500;
+/// Time the detail page stays open, in milliseconds.
+const DETAIL_PAUSE_MS: Range<u64> = 3000..6000;
+/// Pause after a scripted action without one of its own, in milliseconds.
+const SCRIPT_PAUSE_MS: u64 = 1000;
+/// The actions of the random walk, with their weights.
+const WALK: [(Action, u64); 5] = [
+    (Action::Right, 50),
+    (Action::Left, 15),
+    (Action::Down, 15),
+    (Action::Up, 10),
+    (Action::Select, 10),
+];
+
+/// How the [`Demo`] picks its actions, given on the command line.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DemoOptions {
+    /// A random walk, seeded with the value of `--demo-seed` if given.
+    Random(Option<u64>),
+    /// The script at the path of `--demo-script`.
+    Script(PathBuf),
+}
+
+impl DemoOptions {
+    /// Takes `--demo`, `--demo-seed <n>` and `--demo-script <path>` out of `args` so the rest can be
+    /// handed to [`Config::load`](crate::Config::load). `--demo-script` needs no `--demo`, `--demo-seed` is
+    /// ignored without it.
+    ///
+    /// Returns None when neither `--demo` nor `--demo-script` is given.
+    pub fn take_from_args(args: &mut Vec<String>) -> Result<Option<Self>, ConfigError> {
+        let demo = match args.iter().position(|a| a == "--demo") {
+            Some(pos) => {
+                args.remove(pos);
+                true
+            }
+            None => false,
+        };
+        let seed = match take_value(args, "--demo-seed")? {
+            Some(value) => Some(value.parse().map_err(|_| ConfigError::InvalidValue {
+                key: String::from("demo-seed"),
+                value,
+            })?),
+            None => None,
+        };
+        if let Some(path) = take_value(args, "--demo-script")? {
+            return Ok(Some(DemoOptions::Script(PathBuf::from(path))));
+        }
+        Ok(Some(DemoOptions::Random(seed)).filter(|_| demo))
+    }
+}
+
+/// An action of a script, with the pause after it.
+#[derive(Clone, Copy, Debug)]
+struct Step {
+    action: Action,
+    pause: Duration,
+}
+
+/// Where the actions come from.
+enum Steps {
+    Random {
+        rng: Rng,
+        /// True after a [`Action::Select`], the next action goes back.
+        opened: bool,
+    },
+    Script {
+        steps: Vec<Step>,
+        /// Index in `steps` of the next action.
+        next: usize,
+    },
+}
+
+/// Synthetic input source, see [`Demo::poll`].
+pub struct Demo {
+    steps: Steps,
+    /// When the demo was started, the input from before doesn't hold it back.
+    started: Instant,
+    /// When the next action is due.
+    next_at: Instant,
+}
+
+impl Demo {
+    /// Starts the demo described by the `options`, its first action coming after [`START_DELAY`].
+    /// Fails if the script can't be read or has an unknown action.
+    pub fn new(options: &DemoOptions, now: Instant) -> Result<Self, ConfigError> {
+        let steps = match options {
+            DemoOptions::Random(seed) => Steps::Random {
+                rng: Rng::new(seed.unwrap_or_else(time_seed)),
+                opened: false,
+            },
+            DemoOptions::Script(path) => {
+                let contents =
+                    fs::read_to_string(path).map_err(|e| ConfigError::Io(path.clone(), e))?;
+                Steps::Script {
+                    steps: parse_script(&contents)?,
+                    next: 0,
+                }
+            }
+        };
+        Ok(Self {
+            steps,
+            started: now,
+            next_at: now + START_DELAY,
+        })
+    }
+
+    /// The action due at `now`, if any. `idle` is the time since the user last did something, see
+    /// [`EventLoop::idle_for`](crate::event_loop::EventLoop::idle_for). Once they did, nothing is
+    /// due until they have been idle for [`USER_GRACE`].
+    pub fn poll(&mut self, now: Instant, idle: Duration) -> Option<Action> {
+        let last_input = now.checked_sub(idle).unwrap_or(now);
+        if last_input > self.started && idle < USER_GRACE {
+            self.next_at = self.next_at.max(now + USER_GRACE - idle);
+            return None;
+        }
+        if now < self.next_at {
+            return None;
+        }
+        let step = self.next_step();
+        self.next_at = now + step.pause;
+        Some(step.action)
+    }
+
+    /// When the next action is due, for the [`EventLoop`](crate::event_loop::EventLoop) to wake up.
+    pub fn next_at(&self) -> Instant {
+        self.next_at
+    }
+
+    fn next_step(&mut self) -> Step {
+        match &mut self.steps {
+            Steps::Random { rng, opened } => {
+                let action = if *opened { Action::Back } else { walk(rng) };
+                *opened = action == Action::Select;
+                let pause_ms = match action {
+                    Action::Select => DETAIL_PAUSE_MS,
+                    Action::Up | Action::Down | Action::Back => ROW_PAUSE_MS,
+                    _ => MOVE_PAUSE_MS,
+                };
+                Step {
+                    action,
+                    pause: Duration::from_millis(rng.within(pause_ms)),
+                }
+            }
+            Steps::Script { steps, next } => {
+                let step = steps[*next];
+                *next = (*next + 1) % steps.len();
+                step
+            }
+        }
+    }
+}
+
+/// Picks the next action of the random walk as per the weights of [`WALK`].
+fn walk(rng: &mut Rng) -> Action {
+    let total = WALK.iter().map(|(_, weight)| weight).sum();
+    let mut pick = rng.below(total);
+    for (action, weight) in WALK.iter() {
+        if pick < *weight {
+            return *action;
+        }
+        pick -= weight;
+    }
+    Action::Right
+}
+
+/// The steps of a script, see the [module docs](self). Fails on an unknown action or pause, or if
+/// there is no action at all.
+fn parse_script(contents: &str) -> Result<Vec<Step>, ConfigError> {
+    let invalid = |line: &str| ConfigError::InvalidValue {
+        key: String::from("demo-script"),
+        value: line.to_string(),
+    };
+    let mut steps = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let action = fields
+            .next()
+            .and_then(keymap::action_named)
+            .ok_or_else(|| invalid(line))?;
+        let pause_ms = match fields.next() {
+            Some(pause) => pause.parse().map_err(|_| invalid(line))?,
+            None => SCRIPT_PAUSE_MS,
+        };
+        if fields.next().is_some() {
+            return Err(invalid(line));
+        }
+        steps.push(Step {
+            action,
+            pause: Duration::from_millis(pause_ms),
+        });
+    }
+    if steps.is_empty() {
+        return Err(invalid(""));
+    }
+    Ok(steps)
+}
+
+/// Seed of the random walk when none is given.
+fn time_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
+
+/// Small xorshift generator, good enough to pick where to go next.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // Xorshift gets stuck on 0.
+        Self(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number from 0 to `n` excluded, `n` being above 0.
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    /// A number within the `range`, which isn't empty.
+    fn within(&mut self, range: Range<u64>) -> u64 {
+        range.start + self.below(range.end - range.start)
+    }
+}
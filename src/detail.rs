@@ -0,0 +1,178 @@
+//! Detail page shown on top of the grid when the user presses Enter on a tile.
+use crate::i18n::{self, Locale, Text};
+use crate::CachedImgData;
+use api::ItemMetadata;
+use conrod::image::Id;
+use conrod::{widget, Colorable, Positionable, Sizeable, UiCell, Widget};
+
+/// Width used to draw the hero image. The height follows the image's aspect ratio.
+const HERO_WIDTH: f64 = 1280.0;
+/// Margin between the top of the window and the hero image.
+const HERO_TOP_MARGIN: f64 = 60.0;
+/// Largest size the title logo is drawn at, its aspect ratio kept.
+const LOGO_MAX_SIZE: (f64, f64) = (560.0, 200.0);
+/// Margin between the title logo and the bottom left corner of the hero image.
+const LOGO_MARGIN: f64 = 40.0;
+
+widget_ids!(
+    /// Hold the [`Id`]s for the detail page widgets.
+    pub struct DetailIds {
+        backdrop,
+        hero,
+        logo,
+        title,
+        info,
+    }
+);
+
+/// The text data shown in the detail page for a given item.
+pub struct DetailContent {
+    /// Index of the set the item belongs to.
+    pub true_set_idx: usize,
+    /// Index of the item within the set.
+    pub true_item_idx: usize,
+    /// Full title of the item.
+    pub title: String,
+    /// Content type, release year and rating joined in a single line.
+    pub info: String,
+}
+
+impl DetailContent {
+    /// Pulls the text data of the item `true_item_idx` out of its `metadata`, as given by the
+    /// [`ContentProvider`](api::ContentProvider). An item without a title gets a placeholder in the
+    /// language of the `locale`.
+    pub fn from_metadata(
+        true_set_idx: usize,
+        true_item_idx: usize,
+        metadata: &ItemMetadata,
+        locale: Locale,
+    ) -> Self {
+        let title = metadata
+            .title
+            .clone()
+            .unwrap_or_else(|| i18n::tr(locale, Text::TitleNotFound).to_string());
+        Self {
+            true_set_idx,
+            true_item_idx,
+            title,
+            info: metadata.summary(),
+        }
+    }
+}
+
+/// Overlay drawing a large hero image along with the item's title and metadata.
+///
+/// The hero image is loaded in the background so the page first shows the "image-not-found"
+/// placeholder until [`DetailView::set_hero`] is called. Once both the hero image and the logo of
+/// the title (see [`DetailView::set_logo`]) are there, the logo is drawn over the hero image in
+/// place of the title.
+pub struct DetailView {
+    ids: DetailIds,
+    content: Option<DetailContent>,
+    hero: Option<CachedImgData>,
+    logo: Option<CachedImgData>,
+}
+
+impl DetailView {
+    /// Constructor.
+    pub fn new(ids: DetailIds) -> Self {
+        Self {
+            ids,
+            content: None,
+            hero: None,
+            logo: None,
+        }
+    }
+
+    /// Indices of the item shown, None while the page is closed.
+    pub fn item(&self) -> Option<(usize, usize)> {
+        self.content
+            .as_ref()
+            .map(|c| (c.true_set_idx, c.true_item_idx))
+    }
+
+    /// Returns true if the detail page is showing the given item.
+    pub fn is_showing(&self, true_set_idx: usize, true_item_idx: usize) -> bool {
+        matches!(self.content, Some(ref c) if c.true_set_idx == true_set_idx && c.true_item_idx == true_item_idx)
+    }
+
+    /// Show the page for the given content.
+    pub fn open(&mut self, content: DetailContent) {
+        self.content = Some(content);
+        self.hero = None;
+        self.logo = None;
+    }
+
+    /// Hide the page. Returns the data of the hero image and of the logo so their textures can be
+    /// released.
+    pub fn close(&mut self) -> Vec<CachedImgData> {
+        self.content = None;
+        self.hero
+            .take()
+            .into_iter()
+            .chain(self.logo.take())
+            .collect()
+    }
+
+    /// Sets the loaded hero image. `w` and `h` are the texture dimensions.
+    pub fn set_hero(&mut self, img_id: Id, w: f64, h: f64) {
+        let scale = HERO_WIDTH / w;
+        self.hero = Some(CachedImgData::new(img_id, HERO_WIDTH, h * scale));
+    }
+
+    /// Sets the loaded logo of the title. `w` and `h` are the texture dimensions, the logo is
+    /// shrunk to fit in [`LOGO_MAX_SIZE`].
+    pub fn set_logo(&mut self, img_id: Id, w: f64, h: f64) {
+        let (max_w, max_h) = LOGO_MAX_SIZE;
+        let scale = (max_w / w).min(max_h / h).min(1.0);
+        self.logo = Some(CachedImgData::new(img_id, w * scale, h * scale));
+    }
+
+    /// Sets the widgets for the page. Does nothing if the page is not open.
+    pub fn show(&self, ui: &mut UiCell, nf_id: &Id) {
+        let content = match self.content {
+            Some(ref c) => c,
+            None => return,
+        };
+
+        widget::Rectangle::fill_with([ui.win_w, ui.win_h], conrod::color::BLACK.alpha(0.9))
+            .middle_of(ui.window)
+            .set(self.ids.backdrop, ui);
+
+        let (img_id, w, h) = match self.hero {
+            Some(ref hero) => (hero.img_id, hero.w, hero.h),
+            None => (nf_id.clone(), 500.0, 220.0),
+        };
+        widget::Image::new(img_id)
+            .w_h(w, h)
+            .mid_top_with_margin_on(ui.window, HERO_TOP_MARGIN)
+            .set(self.ids.hero, ui);
+
+        // The logo alone on the placeholder would look out of place, the title is written instead.
+        let above_info = match (&self.hero, &self.logo) {
+            (Some(_), Some(logo)) => {
+                widget::Image::new(logo.img_id)
+                    .w_h(logo.w, logo.h)
+                    .bottom_left_with_margins_on(self.ids.hero, LOGO_MARGIN, LOGO_MARGIN)
+                    .set(self.ids.logo, ui);
+                self.ids.hero
+            }
+            _ => {
+                widget::Text::new(&content.title)
+                    .down_from(self.ids.hero, 30.0)
+                    .align_left_of(self.ids.hero)
+                    .color(conrod::color::WHITE)
+                    .font_size(48)
+                    .set(self.ids.title, ui);
+                self.ids.title
+            }
+        };
+
+        widget::Text::new(&content.info)
+            .down_from(above_info, 16.0)
+            .align_left_of(self.ids.hero)
+            .color(conrod::color::LIGHT_GREY)
+            .font_size(28)
+            .set(self.ids.info, ui);
+    }
+}
@@ -0,0 +1,399 @@
+//! Window and gamepad event loop.
+//!
+//! The rest of the app is written against the callback model of the newer `winit::event_loop::EventLoop`:
+//! [`EventLoop::run`] hands every [`Event`] to a handler which steers the loop through a [`ControlFlow`].
+//! Only this module (plus the window creation in [`helpers`](crate::helpers)) knows about the old
+//! glutin `EventsLoop`, its events are translated into [`Input`]s so moving to a newer winit only
+//! means rewriting the translation.
+//!
+//! The iterations are paced by a [`FrameScheduler`]: while something moves on screen the frames
+//! follow the refresh rate of the display, sleeping only what is left of each frame once it's
+//! drawn. Otherwise the loop blocks until an event comes, the threads with results to hand over
+//! wake it up, see [`Waker`].
+//!
+//! It also keeps track of the last time the user did something, see [`EventLoop::idle_for`].
+use crate::gamepad::{self, Gamepad, GamepadAction};
+use conrod::glium::glutin;
+use std::cell::Cell;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Keys are named the same in every winit version so they are used as is.
+pub use conrod::glium::glutin::VirtualKeyCode;
+
+/// Time between two frames at the [`DEFAULT_REFRESH_RATE`], about 60fps.
+pub const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+/// Refresh rate of the display assumed when it isn't configured, in Hz. The window library doesn't
+/// tell it.
+pub const DEFAULT_REFRESH_RATE: u32 = 60;
+
+/// Window events the app reacts to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Input {
+    /// The window was asked to close.
+    CloseRequested,
+    /// The window was resized, new inner size in pixels.
+    Resized(u32, u32),
+    /// The pointer moved to `x`, `y`, in pixels from the top left corner of the window.
+    CursorMoved(f64, f64),
+    /// The left mouse button was pressed.
+    LeftClick,
+    /// The mouse wheel or the trackpad scrolled vertically.
+    Scrolled(ScrollDelta),
+    /// A key was pressed or released.
+    Key {
+        /// The key.
+        key: VirtualKeyCode,
+        /// False when the key was released.
+        pressed: bool,
+        /// True while Alt is held.
+        alt: bool,
+    },
+    /// A character was typed.
+    Char(char),
+    /// The window lost the keyboard focus, the keys held won't be reported as released.
+    FocusLost,
+    /// [`Waker::wake`] was called, e.g. by a thread with results to hand over.
+    Awakened,
+}
+
+impl Input {
+    /// Returns true if the input comes from the user, as opposed to the window or the app itself.
+    pub fn is_user_input(self) -> bool {
+        matches!(
+            self,
+            Input::CursorMoved(..)
+                | Input::LeftClick
+                | Input::Scrolled(_)
+                | Input::Key { .. }
+                | Input::Char(_)
+        )
+    }
+}
+
+/// Amount scrolled by a [`Input::Scrolled`] event, positive values scroll up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScrollDelta {
+    /// In lines, as reported by a mouse wheel.
+    Lines(f32),
+    /// In pixels, as reported by a trackpad.
+    Pixels(f64),
+}
+
+/// What the handler given to [`EventLoop::run`] receives.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    /// Something happened to the window.
+    Input(Input),
+    /// Navigation produced by a gamepad.
+    Gamepad(GamepadAction),
+    /// Every pending event has been handled, time to update and draw.
+    MainEventsCleared,
+}
+
+/// Set by the handler given to [`EventLoop::run`] to tell what to do once the current events are handled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ControlFlow {
+    /// Keep iterating even if there are no events, once the next frame is due. For the animations,
+    /// see [`FrameScheduler`].
+    Poll,
+    /// Park the thread until the next event, e.g. while images are being loaded in the background
+    /// by threads which wake it up once done.
+    Wait,
+    /// Park the thread until the next event or until the deadline, whichever comes first.
+    WaitUntil(Instant),
+    /// Return from [`EventLoop::run`].
+    Exit,
+}
+
+/// Runs the main loop, paced by a [`FrameScheduler`] so it doesn't spin endlessly.
+///
+/// It also polls the connected [`Gamepad`]s, their actions are delivered as [`Event::Gamepad`].
+pub struct EventLoop {
+    events_loop: glutin::EventsLoop,
+    scheduler: FrameScheduler,
+    gamepad: Option<Gamepad>,
+    /// When the user last pressed a key, moved the mouse or used a gamepad.
+    last_input: Cell<Instant>,
+    /// Started the first time the loop waits until a deadline, see [`ControlFlow::WaitUntil`].
+    timer: Option<Timer>,
+}
+
+impl EventLoop {
+    /// Constructor.
+    pub fn new() -> Self {
+        Self::with_gamepad_config(gamepad::DEFAULT_DEADZONE, gamepad::DEFAULT_REPEAT_INTERVAL)
+    }
+
+    /// Same as [`EventLoop::new`] with a custom stick `deadzone` and `repeat_interval` for held directions.
+    pub fn with_gamepad_config(deadzone: f32, repeat_interval: Duration) -> Self {
+        EventLoop {
+            events_loop: glutin::EventsLoop::new(),
+            scheduler: FrameScheduler::new(DEFAULT_REFRESH_RATE, Instant::now()),
+            gamepad: Gamepad::new(deadzone, repeat_interval),
+            last_input: Cell::new(Instant::now()),
+            timer: None,
+        }
+    }
+
+    /// Paces the frames of the animations after a display refreshing `refresh_rate` times per
+    /// second rather than [`DEFAULT_REFRESH_RATE`].
+    pub fn with_refresh_rate(mut self, refresh_rate: u32) -> Self {
+        self.scheduler = FrameScheduler::new(refresh_rate, Instant::now());
+        self
+    }
+
+    /// The underlying glutin loop, needed to create the window.
+    pub fn events_loop(&self) -> &glutin::EventsLoop {
+        &self.events_loop
+    }
+
+    /// Handle for other threads to wake the loop up while it's parked, see [`ControlFlow::Wait`].
+    pub fn waker(&self) -> Waker {
+        Waker(self.events_loop.create_proxy())
+    }
+
+    /// The monitor the window goes fullscreen on.
+    pub fn primary_monitor(&self) -> glutin::MonitorId {
+        self.events_loop.get_primary_monitor()
+    }
+
+    /// Time since the user last did something, or since the loop was created if they didn't yet.
+    /// Only the [`Input`]s from the user and the gamepad actions count, see [`Input::is_user_input`].
+    pub fn idle_for(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_input.get())
+    }
+
+    /// Counts as the user doing something, for the input that doesn't go through the loop, e.g. the
+    /// `remote`.
+    pub fn record_input(&self) {
+        self.last_input.set(Instant::now());
+    }
+
+    /// Runs the loop until the `handler` sets [`ControlFlow::Exit`].
+    ///
+    /// Every iteration the `handler` gets the pending [`Event::Input`]s and [`Event::Gamepad`]s followed
+    /// by a single [`Event::MainEventsCleared`]. Unlike winit's `run` this returns once done, like its
+    /// `run_return`. The loop starts with [`ControlFlow::Poll`].
+    pub fn run<F>(&mut self, mut handler: F)
+    where
+        F: FnMut(Event, &EventLoop, &mut ControlFlow),
+    {
+        let mut control_flow = ControlFlow::Poll;
+        loop {
+            let inputs = self.next(control_flow);
+            let actions = match self.gamepad.as_mut() {
+                Some(gamepad) => gamepad.poll(),
+                None => Vec::new(),
+            };
+            if !actions.is_empty() || inputs.iter().any(|input| input.is_user_input()) {
+                self.record_input();
+            }
+            let events = inputs
+                .into_iter()
+                .map(Event::Input)
+                .chain(actions.into_iter().map(Event::Gamepad))
+                .chain(std::iter::once(Event::MainEventsCleared));
+            for event in events {
+                handler(event, self, &mut control_flow);
+                if control_flow == ControlFlow::Exit {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Collects the pending events, waiting for them as per the `control_flow`, see
+    /// [`FrameScheduler::next_wake`].
+    fn next(&mut self, control_flow: ControlFlow) -> Vec<Input> {
+        let mut events = Vec::new();
+        self.events_loop
+            .poll_events(|event| events.extend(translate(event)));
+
+        if events.is_empty() {
+            let can_park = self.gamepad.is_none();
+            match self
+                .scheduler
+                .next_wake(control_flow, can_park, Instant::now())
+            {
+                Wake::Now => (),
+                Wake::At(at) => {
+                    thread::sleep(at.saturating_duration_since(Instant::now()));
+                    self.events_loop
+                        .poll_events(|event| events.extend(translate(event)));
+                }
+                Wake::OnEvent(deadline) => {
+                    if let Some(deadline) = deadline {
+                        let waker = self.waker();
+                        self.timer
+                            .get_or_insert_with(|| Timer::spawn(waker))
+                            .wake_at(deadline);
+                    }
+                    self.events_loop
+                        .run_forever(|event| match translate(event) {
+                            Some(input) => {
+                                events.push(input);
+                                glutin::ControlFlow::Break
+                            }
+                            None => glutin::ControlFlow::Continue,
+                        });
+                }
+            }
+        }
+
+        self.scheduler.frame_started(Instant::now());
+        events
+    }
+}
+
+/// When the [`EventLoop`] goes on, see [`FrameScheduler::next_wake`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Wake {
+    /// Right away.
+    Now,
+    /// At the given time, events or not.
+    At(Instant),
+    /// With the next event, or at the deadline if one is given.
+    OnEvent(Option<Instant>),
+}
+
+/// Decides how long the [`EventLoop`] waits between two iterations.
+///
+/// Frames are only drawn as often as the display refreshes: the loop sleeps until one frame
+/// interval after the start of the last iteration, whatever time the iteration took is taken off.
+/// An iteration late for its frame doesn't sleep at all, the frames after it aren't hurried to catch
+/// up either. Sleeping a fixed time on top of the iteration would make it miss the next refresh, the
+/// buffers being swapped on the vertical sync.
+#[derive(Debug)]
+struct FrameScheduler {
+    /// Time between two refreshes of the display.
+    interval: Duration,
+    /// When the last iteration started.
+    frame_start: Instant,
+}
+
+impl FrameScheduler {
+    /// Paces the frames after a display refreshing `refresh_rate` times per second, at least once.
+    fn new(refresh_rate: u32, now: Instant) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / refresh_rate.max(1),
+            frame_start: now,
+        }
+    }
+
+    /// When the iteration after the one started last is due.
+    fn frame_due(&self) -> Instant {
+        self.frame_start + self.interval
+    }
+
+    /// Remembers an iteration started at `now`.
+    fn frame_started(&mut self, now: Instant) {
+        self.frame_start = now;
+    }
+
+    /// When to go on once the events are handled, given the `control_flow` and that there are no
+    /// events pending at `now`. Unless the loop `can_park`, e.g. while the gamepads are polled since
+    /// their events don't wake the window up, it wakes up every frame to look for them.
+    fn next_wake(&self, control_flow: ControlFlow, can_park: bool, now: Instant) -> Wake {
+        let deadline = match control_flow {
+            ControlFlow::Poll => Some(self.frame_due()),
+            ControlFlow::Wait => None,
+            ControlFlow::WaitUntil(deadline) => Some(deadline),
+            ControlFlow::Exit => return Wake::Now,
+        };
+        match deadline {
+            Some(deadline) if deadline <= now => Wake::Now,
+            _ if control_flow == ControlFlow::Poll => Wake::At(self.frame_due()),
+            Some(deadline) if can_park => Wake::OnEvent(Some(deadline)),
+            None if can_park => Wake::OnEvent(None),
+            deadline => Wake::At(deadline.map_or(self.frame_due(), |at| at.min(self.frame_due()))),
+        }
+    }
+}
+
+/// Wakes the [`EventLoop`] up from another thread, see [`EventLoop::waker`].
+pub struct Waker(glutin::EventsLoopProxy);
+
+impl Waker {
+    /// Delivers an [`Input::Awakened`] to the loop. Does nothing if the loop is gone.
+    pub fn wake(&self) {
+        let _ = self.0.wakeup();
+    }
+}
+
+/// Wakes the [`EventLoop`] up at a deadline from a thread of its own, see [`ControlFlow::WaitUntil`].
+/// The thread stops once the loop is dropped.
+struct Timer(Sender<Instant>);
+
+impl Timer {
+    fn spawn(waker: Waker) -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut deadline: Option<Instant> = None;
+            loop {
+                let next = match deadline {
+                    Some(at) => rx.recv_timeout(at.saturating_duration_since(Instant::now())),
+                    None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                match next {
+                    Ok(at) => deadline = Some(at),
+                    Err(RecvTimeoutError::Timeout) => {
+                        deadline = None;
+                        waker.wake();
+                    }
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+        });
+        Timer(tx)
+    }
+
+    /// Wakes the loop up at `deadline`, instead of the deadline given before if it's still to come.
+    fn wake_at(&self, deadline: Instant) {
+        let _ = self.0.send(deadline);
+    }
+}
+
+/// Turns a glutin event into an [`Input`], None for the events the app doesn't use.
+fn translate(event: glutin::Event) -> Option<Input> {
+    let event = match event {
+        glutin::Event::WindowEvent { event, .. } => event,
+        glutin::Event::Awakened => return Some(Input::Awakened),
+        _ => return None,
+    };
+    let input = match event {
+        glutin::WindowEvent::Closed => Input::CloseRequested,
+        glutin::WindowEvent::Resized(w, h) => Input::Resized(w, h),
+        glutin::WindowEvent::CursorMoved {
+            position: (x, y), ..
+        } => Input::CursorMoved(x, y),
+        glutin::WindowEvent::MouseInput {
+            state: glutin::ElementState::Pressed,
+            button: glutin::MouseButton::Left,
+            ..
+        } => Input::LeftClick,
+        glutin::WindowEvent::MouseWheel { delta, .. } => Input::Scrolled(match delta {
+            glutin::MouseScrollDelta::LineDelta(_, y) => ScrollDelta::Lines(y),
+            glutin::MouseScrollDelta::PixelDelta(_, y) => ScrollDelta::Pixels(y as f64),
+        }),
+        glutin::WindowEvent::KeyboardInput {
+            input:
+                glutin::KeyboardInput {
+                    state,
+                    virtual_keycode: Some(key),
+                    modifiers,
+                    ..
+                },
+            ..
+        } => Input::Key {
+            key,
+            pressed: state == glutin::ElementState::Pressed,
+            alt: modifiers.alt,
+        },
+        glutin::WindowEvent::ReceivedCharacter(c) => Input::Char(c),
+        glutin::WindowEvent::Focused(false) => Input::FocusLost,
+        _ => return None,
+    };
+    Some(input)
+}
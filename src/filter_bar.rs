@@ -0,0 +1,455 @@
+//! Sorting and filtering of the items of a row, picked on the [`FilterBar`] pushed with `F` from the
+//! home screen.
+//!
+//! The [`SetData`] is left as is: the [`RowOrder`] of a row maps the `true_item_idx` of its tiles,
+//! the places the cursor and the [`RowScroll`](crate::ui_core::RowScroll) deal with, to the
+//! `item_idx` of their items in the set, the one the artwork and the metadata are looked up with.
+//! The artwork urls, hence the keys of the [`ImageCache`](api::ImageCache), don't depend on the
+//! order: the tiles of a row sorted again are decoded from the cache, not fetched again.
+//!
+//! Up/down move the focus between the sort and the filter, left/right change the focused one and
+//! the row follows right away, the focus staying on the same item while it's shown. Select, `F` or
+//! back close the bar. The order is kept until the rows are rebuilt, e.g. on a
+//! [refresh](crate#refresh).
+use crate::i18n::{self, Locale, Text};
+use crate::theme::Theme;
+use api::{ContentType, SetData};
+use conrod::{widget, Colorable, Labelable, Positionable, Sizeable, UiCell, Widget};
+use std::cmp::Reverse;
+
+const BAR_HEIGHT: f64 = 160.0;
+const OPTION_WIDTH: f64 = 420.0;
+const OPTION_HEIGHT: f64 = 64.0;
+const OPTION_MARGIN: f64 = 24.0;
+
+widget_ids!(
+    /// Hold the [`Id`](conrod::widget::Id)s for the filter bar widgets.
+    pub struct FilterBarIds {
+        backdrop,
+        sort,
+        filter,
+        hint,
+    }
+);
+
+/// How the items of a row are sorted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RowSort {
+    /// The order of the set.
+    Default,
+    /// By title, ignoring case. The items without a title come last.
+    Title,
+    /// Newest first. The items without a release year come last.
+    ReleaseYear,
+}
+
+const ROW_SORTS: [RowSort; 3] = [RowSort::Default, RowSort::Title, RowSort::ReleaseYear];
+
+/// Which items of a row are shown, from their [`ContentType`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RowFilter {
+    /// Every item.
+    All,
+    /// The [`ContentType::Series`] only.
+    Series,
+    /// The [`ContentType::Program`] only.
+    Movies,
+}
+
+const ROW_FILTERS: [RowFilter; 3] = [RowFilter::All, RowFilter::Series, RowFilter::Movies];
+
+impl RowFilter {
+    /// Returns true if an item of `content_type` is shown.
+    fn keeps(self, content_type: Option<ContentType>) -> bool {
+        match self {
+            RowFilter::All => true,
+            RowFilter::Series => content_type == Some(ContentType::Series),
+            RowFilter::Movies => content_type == Some(ContentType::Program),
+        }
+    }
+}
+
+/// The items of a row as sorted and filtered, see the [module](self) documentation.
+#[derive(Debug)]
+pub struct RowOrder {
+    sort: RowSort,
+    filter: RowFilter,
+    /// `item_idx` of each `true_item_idx`, None while every item is shown in the order of the set.
+    item_idxs: Option<Vec<usize>>,
+    /// Number of items of the set.
+    set_len: usize,
+}
+
+impl RowOrder {
+    /// The items of `set_data` sorted and filtered as per `sort` and `filter`. Ties keep the order of
+    /// the set.
+    pub fn new(set_data: &SetData, sort: RowSort, filter: RowFilter) -> Self {
+        let set_len = set_data.get_item_count();
+        let item_idxs = if (sort, filter) == (RowSort::Default, RowFilter::All) {
+            None
+        } else {
+            let mut item_idxs: Vec<usize> = (0..set_len)
+                .filter(|item_idx| filter.keeps(set_data.get_item_content_type(*item_idx)))
+                .collect();
+            match sort {
+                RowSort::Default => (),
+                RowSort::Title => item_idxs.sort_by_cached_key(|item_idx| {
+                    let title = set_data.get_item_title(*item_idx);
+                    (title.is_none(), title.map(str::to_lowercase))
+                }),
+                RowSort::ReleaseYear => item_idxs.sort_by_key(|item_idx| {
+                    let year = set_data.get_item_release_year(*item_idx);
+                    (year.is_none(), Reverse(year))
+                }),
+            }
+            Some(item_idxs)
+        };
+        Self {
+            sort,
+            filter,
+            item_idxs,
+            set_len,
+        }
+    }
+
+    /// How the items are sorted.
+    pub fn sort(&self) -> RowSort {
+        self.sort
+    }
+
+    /// Which items are shown.
+    pub fn filter(&self) -> RowFilter {
+        self.filter
+    }
+
+    /// Returns true if some items of the set are left out.
+    pub fn is_filtered(&self) -> bool {
+        self.filter != RowFilter::All
+    }
+
+    /// Number of items shown.
+    pub fn item_count(&self) -> usize {
+        self.item_idxs.as_ref().map_or(self.set_len, Vec::len)
+    }
+
+    /// The `item_idx` of the tile `true_item_idx`. Past the last item shown, it's past the end of
+    /// the set too.
+    pub fn item_idx(&self, true_item_idx: usize) -> usize {
+        match &self.item_idxs {
+            Some(item_idxs) => item_idxs
+                .get(true_item_idx)
+                .copied()
+                .unwrap_or_else(|| self.set_len + true_item_idx - item_idxs.len()),
+            None => true_item_idx,
+        }
+    }
+
+    /// The `true_item_idx` of the item `item_idx`, None if it's left out.
+    pub fn true_item_idx(&self, item_idx: usize) -> Option<usize> {
+        match &self.item_idxs {
+            Some(item_idxs) => item_idxs.iter().position(|idx| *idx == item_idx),
+            None => Some(item_idx).filter(|item_idx| *item_idx < self.set_len),
+        }
+    }
+}
+
+/// The two lines of the [`FilterBar`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Field {
+    Sort,
+    Filter,
+}
+
+/// Overlay picking the [`RowSort`] and the [`RowFilter`] of the row with the focus, drawn at the
+/// bottom of the home screen.
+pub struct FilterBar {
+    ids: FilterBarIds,
+    /// `true_set_idx` of the row, None while the bar is closed.
+    true_set_idx: Option<usize>,
+    sort: RowSort,
+    filter: RowFilter,
+    focus: Field,
+}
+
+impl FilterBar {
+    /// Constructor.
+    pub fn new(ids: FilterBarIds) -> Self {
+        Self {
+            ids,
+            true_set_idx: None,
+            sort: RowSort::Default,
+            filter: RowFilter::All,
+            focus: Field::Sort,
+        }
+    }
+
+    /// Opens the bar for the row `true_set_idx`, starting from its current `order`.
+    pub fn open(&mut self, true_set_idx: usize, order: &RowOrder) {
+        self.true_set_idx = Some(true_set_idx);
+        self.sort = order.sort();
+        self.filter = order.filter();
+        self.focus = Field::Sort;
+    }
+
+    /// Hides the bar.
+    pub fn close(&mut self) {
+        self.true_set_idx = None;
+    }
+
+    /// The row the bar is open for, None while it's closed.
+    pub fn true_set_idx(&self) -> Option<usize> {
+        self.true_set_idx
+    }
+
+    /// The sort and the filter picked.
+    pub fn picked(&self) -> (RowSort, RowFilter) {
+        (self.sort, self.filter)
+    }
+
+    /// Moves the focus to the other line.
+    pub fn move_focus(&mut self) {
+        self.focus = match self.focus {
+            Field::Sort => Field::Filter,
+            Field::Filter => Field::Sort,
+        };
+    }
+
+    /// Changes the focused line to its next value, or the previous one when `forward` is false.
+    /// The values wrap around.
+    pub fn change(&mut self, forward: bool) {
+        match self.focus {
+            Field::Sort => self.sort = cycle(&ROW_SORTS, self.sort, forward),
+            Field::Filter => self.filter = cycle(&ROW_FILTERS, self.filter, forward),
+        }
+    }
+
+    /// Sets the widgets of the bar, the texts following the `theme` and in the language of the
+    /// `locale`. Does nothing while the bar is closed.
+    pub fn show(&self, ui: &mut UiCell, theme: &Theme, locale: Locale) {
+        if self.true_set_idx.is_none() {
+            return;
+        }
+
+        widget::Rectangle::fill_with([ui.win_w, BAR_HEIGHT], conrod::color::BLACK.alpha(0.85))
+            .mid_bottom_of(ui.window)
+            .set(self.ids.backdrop, ui);
+
+        let lines = [
+            (
+                Field::Sort,
+                Text::SortBy,
+                sort_name(self.sort),
+                self.ids.sort,
+            ),
+            (
+                Field::Filter,
+                Text::Show,
+                filter_name(self.filter),
+                self.ids.filter,
+            ),
+        ];
+        for (idx, (field, name, value, id)) in lines.iter().enumerate() {
+            let label = format!("{}:  {}", i18n::tr(locale, *name), i18n::tr(locale, *value));
+            let left = (ui.win_w - 2.0 * OPTION_WIDTH - OPTION_MARGIN) / 2.0
+                + idx as f64 * (OPTION_WIDTH + OPTION_MARGIN);
+            widget::Button::new()
+                .w_h(OPTION_WIDTH, OPTION_HEIGHT)
+                .top_left_with_margins_on(self.ids.backdrop, OPTION_MARGIN, left)
+                .color(if *field == self.focus {
+                    conrod::color::LIGHT_BLUE
+                } else {
+                    conrod::color::DARK_CHARCOAL
+                })
+                .label(&label)
+                .label_color(conrod::color::WHITE)
+                .label_font_size(26)
+                .set(*id, ui);
+        }
+
+        theme
+            .text_widget(i18n::tr(locale, Text::FilterBarHint), theme.hint_text)
+            .down_from(self.ids.sort, OPTION_MARGIN)
+            .align_middle_x_of(self.ids.backdrop)
+            .font_size(22)
+            .set(self.ids.hint, ui);
+    }
+}
+
+/// The value after `current` among `values`, or before it when `forward` is false.
+fn cycle<T: Copy + PartialEq>(values: &[T], current: T, forward: bool) -> T {
+    let idx = values.iter().position(|v| *v == current).unwrap_or(0);
+    let next = if forward {
+        (idx + 1) % values.len()
+    } else {
+        (idx + values.len() - 1) % values.len()
+    };
+    values[next]
+}
+
+fn sort_name(sort: RowSort) -> Text {
+    match sort {
+        RowSort::Default => Text::DefaultOrder,
+        RowSort::Title => Text::ByTitle,
+        RowSort::ReleaseYear => Text::NewestFirst,
+    }
+}
+
+fn filter_name(filter: RowFilter) -> Text {
+    match filter {
+        RowFilter::All => Text::Everything,
+        RowFilter::Series => Text::Series,
+        RowFilter::Movies => Text::Movies,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use api::{Api, ApiError, ContentProvider, ItemMetadata, TileArt};
+    use std::io;
+    use std::ops::Range;
+
+    const SERIES: &str = "DmcSeries";
+    const MOVIE: &str = "DmcVideo";
+
+    /// A single set of items given as their title, release year and `type`, without artwork.
+    struct Items(Vec<(Option<&'static str>, Option<u32>, &'static str)>);
+
+    impl ContentProvider for Items {
+        fn list_sets(&self) -> Range<usize> {
+            0..1
+        }
+
+        fn set_title(&self, _: usize) -> Option<&str> {
+            Some("Row")
+        }
+
+        fn item_count(&self, _: usize) -> usize {
+            self.0.len()
+        }
+
+        fn item_image_url(&self, _: usize, _: usize) -> Result<TileArt, ApiError> {
+            Err(io::Error::from(io::ErrorKind::NotFound).into())
+        }
+
+        fn item_metadata(&self, _: usize, item_idx: usize) -> Option<ItemMetadata> {
+            let (title, release_year, content_type) = self.0.get(item_idx)?;
+            Some(ItemMetadata {
+                title: title.map(String::from),
+                content_id: Some(format!("item-{}", item_idx)),
+                content_type: Some(content_type.to_string()),
+                release_year: *release_year,
+                rating: None,
+            })
+        }
+    }
+
+    fn api_with(items: Items) -> Api {
+        let mut api = Api::new();
+        api.load_from_provider(&items).unwrap();
+        api
+    }
+
+    /// The `item_idx` of every tile of the row.
+    fn item_idxs(order: &RowOrder) -> Vec<usize> {
+        (0..order.item_count())
+            .map(|true_item_idx| order.item_idx(true_item_idx))
+            .collect()
+    }
+
+    #[test]
+    fn ties_keep_the_set_order() {
+        let api = api_with(Items(vec![
+            (Some("b"), Some(2000), MOVIE),
+            (Some("A"), Some(2010), MOVIE),
+            (Some("a"), Some(2000), MOVIE),
+            (Some("B"), Some(2010), MOVIE),
+        ]));
+        let set_data = api.get_set(0).unwrap();
+
+        let by_title = RowOrder::new(&set_data, RowSort::Title, RowFilter::All);
+        assert_eq!(item_idxs(&by_title), vec![1, 2, 0, 3]);
+        let by_year = RowOrder::new(&set_data, RowSort::ReleaseYear, RowFilter::All);
+        assert_eq!(item_idxs(&by_year), vec![1, 3, 0, 2]);
+        let kept = RowOrder::new(&set_data, RowSort::Default, RowFilter::Movies);
+        assert_eq!(item_idxs(&kept), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn items_without_a_title_or_a_year_come_last() {
+        let api = api_with(Items(vec![
+            (None, None, MOVIE),
+            (Some("b"), Some(1999), MOVIE),
+            (None, None, MOVIE),
+            (Some("a"), Some(2020), MOVIE),
+        ]));
+        let set_data = api.get_set(0).unwrap();
+
+        let by_title = RowOrder::new(&set_data, RowSort::Title, RowFilter::All);
+        assert_eq!(item_idxs(&by_title), vec![3, 1, 0, 2]);
+        let by_year = RowOrder::new(&set_data, RowSort::ReleaseYear, RowFilter::All);
+        assert_eq!(item_idxs(&by_year), vec![3, 1, 0, 2]);
+    }
+
+    #[test]
+    fn tiles_past_the_items_shown_are_past_the_end_of_the_set() {
+        let api = api_with(Items(vec![
+            (Some("a"), None, SERIES),
+            (Some("b"), None, MOVIE),
+            (Some("c"), None, SERIES),
+            (Some("d"), None, MOVIE),
+            (Some("e"), None, MOVIE),
+        ]));
+        let set_data = api.get_set(0).unwrap();
+
+        let series = RowOrder::new(&set_data, RowSort::Default, RowFilter::Series);
+        assert_eq!(series.item_count(), 2);
+        assert!(series.is_filtered());
+        assert_eq!(item_idxs(&series), vec![0, 2]);
+        assert_eq!(series.item_idx(2), 5);
+        assert_eq!(series.item_idx(3), 6);
+
+        let all = RowOrder::new(&set_data, RowSort::Default, RowFilter::All);
+        assert_eq!(all.item_count(), 5);
+        assert!(!all.is_filtered());
+        assert_eq!(all.item_idx(7), 7);
+    }
+
+    #[test]
+    fn items_left_out_have_no_tile() {
+        let api = api_with(Items(vec![
+            (Some("c"), None, SERIES),
+            (Some("b"), None, MOVIE),
+            (Some("a"), None, MOVIE),
+        ]));
+        let set_data = api.get_set(0).unwrap();
+
+        let movies = RowOrder::new(&set_data, RowSort::Title, RowFilter::Movies);
+        assert_eq!(movies.true_item_idx(0), None);
+        assert_eq!(movies.true_item_idx(1), Some(1));
+        assert_eq!(movies.true_item_idx(2), Some(0));
+        assert_eq!(movies.true_item_idx(3), None);
+
+        let all = RowOrder::new(&set_data, RowSort::Default, RowFilter::All);
+        assert_eq!(all.true_item_idx(2), Some(2));
+        assert_eq!(all.true_item_idx(3), None);
+    }
+
+    #[test]
+    fn the_values_wrap_around_both_ways() {
+        assert_eq!(
+            cycle(&ROW_SORTS, RowSort::ReleaseYear, true),
+            RowSort::Default
+        );
+        assert_eq!(
+            cycle(&ROW_SORTS, RowSort::Default, false),
+            RowSort::ReleaseYear
+        );
+        assert_eq!(cycle(&ROW_SORTS, RowSort::Default, true), RowSort::Title);
+        assert_eq!(cycle(&ROW_FILTERS, RowFilter::Movies, true), RowFilter::All);
+        assert_eq!(
+            cycle(&ROW_FILTERS, RowFilter::All, false),
+            RowFilter::Movies
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! Fallback fonts for the texts the font of the [`Theme`](crate::theme::Theme) can't draw, e.g.
+//! titles in Japanese or Arabic.
+//!
+//! conrod draws a `widget::Text` with a single font, so the fallback is picked per text rather
+//! than per character: the first font of the [`FontChain`] having a glyph for every character,
+//! or failing that the one missing the fewest. A text mixing scripts no single font covers still
+//! shows some blanks.
+//!
+//! The fallback fonts are not bundled, they are looked up in the `fonts` folder of the assets and
+//! the missing ones are skipped, see [`helpers::load_fonts`](crate::helpers::load_fonts). The list
+//! can be changed per theme with its [`fallback_fonts`](crate::theme::Theme::fallback_fonts).
+use conrod::text::{font, Font};
+use std::fmt;
+
+/// Fonts tried, in order, for the characters the font of a theme doesn't cover. Relative to the
+/// `fonts` folder of the assets.
+pub const FALLBACK_FONTS: [&str; 3] = [
+    "NotoSansJP/NotoSansJP-Regular.ttf",
+    "NotoSansArabic/NotoSansArabic-Regular.ttf",
+    "NotoSansSymbols2/NotoSansSymbols2-Regular.ttf",
+];
+
+/// Loaded fonts a text can be drawn with, in order of preference.
+#[derive(Clone, Default)]
+pub struct FontChain {
+    fonts: Vec<(font::Id, Font)>,
+}
+
+impl FontChain {
+    /// Adds the loaded font `id` at the end of the chain.
+    pub fn push(&mut self, id: font::Id, font: Font) {
+        self.fonts.push((id, font));
+    }
+
+    /// The font to draw `text` with: the first one having a glyph for each of its characters, or
+    /// else the one missing the fewest. None if the chain is empty.
+    pub fn pick(&self, text: &str) -> Option<font::Id> {
+        self.pick_font(text).map(|(id, _)| *id)
+    }
+
+    /// Width of `text` drawn at `font_size` in the font [`pick`](FontChain::pick)ed for it, in
+    /// pixels. Kerning is left out. None if the chain is empty.
+    pub fn width(&self, text: &str, font_size: u32) -> Option<f64> {
+        let (_, font) = self.pick_font(text)?;
+        let scale = conrod::text::pt_to_scale(font_size);
+        let width: f32 = text
+            .chars()
+            .filter_map(|c| font.glyph(c))
+            .map(|glyph| glyph.scaled(scale).h_metrics().advance_width)
+            .sum();
+        Some(width as f64)
+    }
+
+    fn pick_font(&self, text: &str) -> Option<&(font::Id, Font)> {
+        let mut best: Option<(&(font::Id, Font), usize)> = None;
+        for entry in &self.fonts {
+            let missing = missing_glyphs(&entry.1, text).len();
+            if missing == 0 {
+                return Some(entry);
+            }
+            if best.map_or(true, |(_, fewest)| missing < fewest) {
+                best = Some((entry, missing));
+            }
+        }
+        best.map(|(entry, _)| entry)
+    }
+
+    /// The characters of `text` no font of the chain has a glyph for, without duplicates.
+    pub fn uncovered(&self, text: &str) -> Vec<char> {
+        let mut uncovered: Vec<char> = text
+            .chars()
+            .filter(|c| {
+                !c.is_whitespace() && self.fonts.iter().all(|(_, font)| !has_glyph(font, *c))
+            })
+            .collect();
+        uncovered.sort_unstable();
+        uncovered.dedup();
+        uncovered
+    }
+}
+
+impl fmt::Debug for FontChain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.fonts.iter().map(|(id, _)| id))
+            .finish()
+    }
+}
+
+impl PartialEq for FontChain {
+    fn eq(&self, other: &Self) -> bool {
+        self.fonts.len() == other.fonts.len()
+            && self
+                .fonts
+                .iter()
+                .zip(&other.fonts)
+                .all(|((id, _), (other_id, _))| id == other_id)
+    }
+}
+
+/// True if the `font` has a glyph for `c`. Fonts map the characters they lack to the glyph 0,
+/// drawn as a blank or a box.
+fn has_glyph(font: &Font, c: char) -> bool {
+    font.glyph(c).map_or(false, |glyph| glyph.id().0 != 0)
+}
+
+/// The characters of `text` the `font` has no glyph for, whitespace aside.
+fn missing_glyphs(font: &Font, text: &str) -> Vec<char> {
+    text.chars()
+        .filter(|c| !c.is_whitespace() && !has_glyph(font, *c))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Titles as found in the home data of other regions, with whether the bundled NotoSans covers
+    /// them.
+    const TITLES: [(&str, bool); 8] = [
+        ("The Mandalorian", true),
+        ("Amélie", true),
+        ("La Casa de Papel", true),
+        ("Ελληνικά", true),
+        ("Брат", true),
+        ("千と千尋の神隠し", false),
+        ("وجدة", false),
+        ("Pokémon: ポケモン", false),
+    ];
+
+    fn bundled(name: &str) -> Font {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("assets/fonts")
+            .join(name);
+        font::from_file(path).unwrap()
+    }
+
+    /// The bundled regular and bold fonts, in that order.
+    fn chain() -> (FontChain, font::Id, font::Id) {
+        let mut map = font::Map::new();
+        let regular = bundled("NotoSans/NotoSans-Regular.ttf");
+        let bold = bundled("NotoSans/NotoSans-Bold.ttf");
+        let regular_id = map.insert(regular.clone());
+        let bold_id = map.insert(bold.clone());
+        let mut chain = FontChain::default();
+        chain.push(regular_id, regular);
+        chain.push(bold_id, bold);
+        (chain, regular_id, bold_id)
+    }
+
+    #[test]
+    fn multilingual_titles_go_to_the_first_font_missing_the_fewest_glyphs() {
+        let (chain, regular_id, _) = chain();
+        for (title, covered) in TITLES.iter() {
+            assert_eq!(chain.uncovered(title).is_empty(), *covered, "{}", title);
+            assert_eq!(chain.pick(title), Some(regular_id), "{}", title);
+        }
+    }
+
+    #[test]
+    fn fonts_are_tried_in_order() {
+        let (chain, regular_id, bold_id) = chain();
+        let mut reversed = FontChain::default();
+        for (id, font) in chain.fonts.into_iter().rev() {
+            reversed.push(id, font);
+        }
+
+        assert_eq!(reversed.pick("Amélie"), Some(bold_id));
+        assert_ne!(reversed.pick("Amélie"), Some(regular_id));
+    }
+
+    #[test]
+    fn empty_chain_picks_nothing() {
+        let chain = FontChain::default();
+
+        assert_eq!(chain.pick("Amélie"), None);
+        assert_eq!(
+            chain.uncovered("Amélie"),
+            vec!['A', 'e', 'i', 'l', 'm', 'é']
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Gamepad/controller input using [`gilrs`].
+//!
+//! The D-pad and the left stick produce the same navigation as the arrow keys, `A` (south button)
+//! opens the focused item, `B` (east button) backs out and `Y` (north button) opens the search
+//! screen. Holding a direction repeats it every `repeat_interval`.
+use gilrs::{Axis, Button, EventType, Gilrs};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Default dead zone for the left stick. Values below this are treated as 0.
+pub const DEFAULT_DEADZONE: f32 = 0.5;
+/// Default time between repeated moves while a direction is held.
+pub const DEFAULT_REPEAT_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Navigation produced by the gamepad.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GamepadAction {
+    /// D-pad/stick left.
+    Left,
+    /// D-pad/stick right.
+    Right,
+    /// D-pad/stick up.
+    Up,
+    /// D-pad/stick down.
+    Down,
+    /// `A` button.
+    Select,
+    /// `B` button.
+    Back,
+    /// `Y` button.
+    Search,
+}
+
+/// Polls the connected gamepads and turns their events into [`GamepadAction`]s.
+pub struct Gamepad {
+    gilrs: Gilrs,
+    deadzone: f32,
+    repeat_interval: Duration,
+    /// Direction held on the D-pad.
+    dpad_held: Option<GamepadAction>,
+    /// Direction the left stick is pushed towards, if beyond the dead zone.
+    stick_held: Option<GamepadAction>,
+    stick: (f32, f32),
+    last_emit: Instant,
+}
+
+impl Gamepad {
+    /// Constructor. Returns None if the gamepad subsystem is not available on this platform.
+    pub fn new(deadzone: f32, repeat_interval: Duration) -> Option<Self> {
+        match Gilrs::new() {
+            Ok(gilrs) => Some(Self {
+                gilrs,
+                deadzone,
+                repeat_interval,
+                dpad_held: None,
+                stick_held: None,
+                stick: (0.0, 0.0),
+                last_emit: Instant::now(),
+            }),
+            Err(e) => {
+                info!("Gamepad support disabled: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Drain the pending gamepad events. Meant to be called once per loop.
+    pub fn poll(&mut self) -> Vec<GamepadAction> {
+        let mut actions = Vec::new();
+        let now = Instant::now();
+
+        while let Some(ev) = self.gilrs.next_event() {
+            match ev.event {
+                EventType::ButtonPressed(Button::South, _) => actions.push(GamepadAction::Select),
+                EventType::ButtonPressed(Button::East, _) => actions.push(GamepadAction::Back),
+                EventType::ButtonPressed(Button::North, _) => actions.push(GamepadAction::Search),
+                EventType::ButtonPressed(button, _) => {
+                    if let Some(dir) = dpad_direction(button) {
+                        self.dpad_held = Some(dir);
+                        self.last_emit = now;
+                        actions.push(dir);
+                    }
+                }
+                EventType::ButtonReleased(button, _) => {
+                    if dpad_direction(button).is_some() && dpad_direction(button) == self.dpad_held
+                    {
+                        self.dpad_held = None;
+                    }
+                }
+                EventType::AxisChanged(Axis::LeftStickX, value, _) => self.stick.0 = value,
+                EventType::AxisChanged(Axis::LeftStickY, value, _) => self.stick.1 = value,
+                _ => (),
+            }
+        }
+
+        let stick_dir = self.stick_direction();
+        if stick_dir != self.stick_held {
+            self.stick_held = stick_dir;
+            if let Some(dir) = stick_dir {
+                self.last_emit = now;
+                actions.push(dir);
+            }
+        }
+
+        if let Some(dir) = self.dpad_held.or(self.stick_held) {
+            if now.duration_since(self.last_emit) >= self.repeat_interval {
+                self.last_emit = now;
+                actions.push(dir);
+            }
+        }
+
+        actions
+    }
+
+    fn stick_direction(&self) -> Option<GamepadAction> {
+        let (x, y) = self.stick;
+        if x.abs() < self.deadzone && y.abs() < self.deadzone {
+            None
+        } else if x.abs() > y.abs() {
+            Some(if x > 0.0 {
+                GamepadAction::Right
+            } else {
+                GamepadAction::Left
+            })
+        } else {
+            // Positive Y is up for gilrs.
+            Some(if y > 0.0 {
+                GamepadAction::Up
+            } else {
+                GamepadAction::Down
+            })
+        }
+    }
+}
+
+fn dpad_direction(button: Button) -> Option<GamepadAction> {
+    match button {
+        Button::DPadLeft => Some(GamepadAction::Left),
+        Button::DPadRight => Some(GamepadAction::Right),
+        Button::DPadUp => Some(GamepadAction::Up),
+        Button::DPadDown => Some(GamepadAction::Down),
+        _ => None,
+    }
+}
@@ -0,0 +1,223 @@
+//! Keeps the textures under a budget of GPU memory.
+//!
+//! The memory a texture takes is estimated from its size as 4 bytes per pixel, the driver overhead
+//! aside. Once the textures of the [`image_map`](crate::DisplayController::image_map) take more
+//! than the [`gpu_budget_mb`](crate::config::Config::gpu_budget_mb), the tiles of the rows drawn
+//! the longest ago are released, those in view excepted, see [`GpuBudget::pick_evictions`]. They
+//! are loaded again once scrolled back to.
+//!
+//! Only the tiles with a texture of their own give memory back that way: a page of the
+//! [`TextureAtlas`](crate::atlas::TextureAtlas) keeps its size however few tiles are left on it.
+//! The atlas doesn't take a new page past the budget instead, see [`GpuUsage::fits`].
+use conrod::backend::glium::glium;
+use conrod::image::{Id, Map};
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// What the textures take, see [`GpuBudget::usage`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GpuUsage {
+    /// Number of textures in the `image_map`.
+    pub textures: usize,
+    /// Memory taken by these textures, estimated from their size.
+    pub bytes: u64,
+    /// Memory they are allowed to take.
+    pub budget: u64,
+}
+
+impl GpuUsage {
+    /// Returns true if the textures take more than the budget.
+    pub fn is_over(&self) -> bool {
+        self.bytes > self.budget
+    }
+
+    /// Returns true if a texture of `bytes` more still fits in the budget.
+    pub fn fits(&self, bytes: u64) -> bool {
+        self.bytes + bytes <= self.budget
+    }
+
+    /// Bytes to give back to get under the budget, 0 unless it's exceeded.
+    pub fn excess(&self) -> u64 {
+        self.bytes.saturating_sub(self.budget)
+    }
+}
+
+/// Estimated size of `texture` on the GPU.
+pub fn texture_bytes(texture: &glium::texture::SrgbTexture2d) -> u64 {
+    let (w, h) = (texture.get_width(), texture.get_height().unwrap_or(1));
+    w as u64 * h as u64 * 4
+}
+
+/// A texture of the `image_map` whose memory on the GPU can be estimated.
+pub trait TextureBytes {
+    /// Estimated size of the texture on the GPU, see [`texture_bytes`].
+    fn bytes(&self) -> u64;
+}
+
+impl TextureBytes for glium::texture::SrgbTexture2d {
+    fn bytes(&self) -> u64 {
+        texture_bytes(self)
+    }
+}
+
+/// The budget, and when each texture was last drawn.
+#[derive(Debug)]
+pub struct GpuBudget {
+    budget: u64,
+    drawn_at: HashMap<Id, Instant>,
+}
+
+impl GpuBudget {
+    /// The textures are allowed `budget` bytes.
+    pub fn new(budget: u64) -> Self {
+        Self {
+            budget,
+            drawn_at: HashMap::new(),
+        }
+    }
+
+    /// What the textures of the `image_map` take.
+    pub fn usage<T: TextureBytes>(&self, image_map: &Map<T>) -> GpuUsage {
+        GpuUsage {
+            textures: image_map.len(),
+            bytes: image_map.values().map(T::bytes).sum(),
+            budget: self.budget,
+        }
+    }
+
+    /// Remembers the texture `id` was drawn at `now`.
+    pub fn drawn(&mut self, id: Id, now: Instant) {
+        self.drawn_at.insert(id, now);
+    }
+
+    /// Forgets the textures no longer in the `image_map`.
+    pub fn forget_released<T>(&mut self, image_map: &Map<T>) {
+        self.drawn_at.retain(|id, _| image_map.get(id).is_some());
+    }
+
+    /// Picks which of the `candidates`, given as a key, their texture and its size, to release to
+    /// get `excess` bytes back. The ones never drawn go first, then the ones drawn the longest ago.
+    /// Returns fewer than needed if the `candidates` don't add up to `excess`.
+    pub fn pick_evictions<K>(
+        &self,
+        candidates: impl IntoIterator<Item = (K, Id, u64)>,
+        excess: u64,
+    ) -> Vec<K> {
+        let mut candidates: Vec<_> = candidates.into_iter().collect();
+        candidates.sort_by_key(|(_, id, _)| self.drawn_at.get(id).copied());
+        let mut freed = 0;
+        candidates
+            .into_iter()
+            .take_while(|(_, _, bytes)| {
+                let more = freed < excess;
+                freed += bytes;
+                more
+            })
+            .map(|(key, _, _)| key)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Stands for a texture of the given size, no GPU needed.
+    struct Texture(u64);
+
+    impl TextureBytes for Texture {
+        fn bytes(&self) -> u64 {
+            self.0
+        }
+    }
+
+    /// Three textures of 100 bytes: `a` drawn first, `b` never drawn and `c` drawn last.
+    fn drawn_textures() -> (GpuBudget, Vec<(Id, Id, u64)>) {
+        let mut image_map = Map::new();
+        let (a, b, c) = (
+            image_map.insert(Texture(100)),
+            image_map.insert(Texture(100)),
+            image_map.insert(Texture(100)),
+        );
+        let mut gpu = GpuBudget::new(250);
+        let now = Instant::now();
+        gpu.drawn(a, now);
+        gpu.drawn(c, now + Duration::from_secs(1));
+        let candidates = [a, b, c].iter().map(|id| (*id, *id, 100)).collect();
+        (gpu, candidates)
+    }
+
+    #[test]
+    fn evicts_only_over_the_budget() {
+        let (gpu, candidates) = drawn_textures();
+        let under = GpuUsage {
+            textures: 3,
+            bytes: 250,
+            budget: 250,
+        };
+        assert!(!under.is_over());
+        assert_eq!(under.excess(), 0);
+        assert!(gpu.pick_evictions(candidates.clone(), 0).is_empty());
+
+        let over = GpuUsage {
+            bytes: 300,
+            ..under
+        };
+        assert!(over.is_over());
+        assert_eq!(over.excess(), 50);
+        assert_eq!(gpu.pick_evictions(candidates, over.excess()).len(), 1);
+    }
+
+    #[test]
+    fn evicts_the_never_drawn_then_the_least_recently_drawn() {
+        let (gpu, candidates) = drawn_textures();
+        let (a, b, c) = (candidates[0].0, candidates[1].0, candidates[2].0);
+        assert_eq!(gpu.pick_evictions(candidates.clone(), 1), vec![b]);
+        assert_eq!(gpu.pick_evictions(candidates.clone(), 101), vec![b, a]);
+        assert_eq!(gpu.pick_evictions(candidates, 201), vec![b, a, c]);
+    }
+
+    #[test]
+    fn stops_once_the_excess_is_freed() {
+        let (gpu, candidates) = drawn_textures();
+        assert_eq!(gpu.pick_evictions(candidates.clone(), 100).len(), 1);
+        assert_eq!(gpu.pick_evictions(candidates.clone(), 200).len(), 2);
+        // Not enough to free, every candidate goes.
+        assert_eq!(gpu.pick_evictions(candidates, 1000).len(), 3);
+    }
+
+    #[test]
+    fn forgets_the_released_textures() {
+        let mut image_map = Map::new();
+        let kept = image_map.insert(Texture(100));
+        let released = image_map.insert(Texture(200));
+        let mut gpu = GpuBudget::new(1000);
+        let now = Instant::now();
+        gpu.drawn(kept, now);
+        gpu.drawn(released, now + Duration::from_secs(1));
+        assert_eq!(
+            gpu.usage(&image_map),
+            GpuUsage {
+                textures: 2,
+                bytes: 300,
+                budget: 1000,
+            }
+        );
+        let candidates = vec![(kept, kept, 100), (released, released, 200)];
+        assert_eq!(gpu.pick_evictions(candidates.clone(), 1), vec![kept]);
+
+        image_map.remove(released);
+        gpu.forget_released(&image_map);
+        assert_eq!(
+            gpu.usage(&image_map),
+            GpuUsage {
+                textures: 1,
+                bytes: 100,
+                budget: 1000,
+            }
+        );
+        // No longer known to be drawn, it goes first.
+        assert_eq!(gpu.pick_evictions(candidates, 1), vec![released]);
+    }
+}
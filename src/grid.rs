@@ -0,0 +1,349 @@
+//! "See all" screen: every item of a row in a grid scrolled vertically.
+//!
+//! Browsing a row of 30+ items one tile at a time is tedious, `G` on a row opens its items
+//! [`GRID_COLUMNS`] per line instead. Only the lines in view plus [`BUFFERED_GRID_ROWS`] above and
+//! below have their textures, the ones scrolled further away are released. The images go through
+//! the same [`ImageLoader`] as the rows so the artwork already fetched for them comes from the
+//! [`ImageCache`](api::ImageCache). Selecting an item opens its detail page.
+use crate::i18n::{self, Locale, Text};
+use crate::loader::{ImageLoader, LoadKind, LoadRequest};
+use crate::theme::{to_color, Theme};
+use crate::ui_core::TextStyle;
+use crate::CachedImgData;
+use api::SetData;
+use conrod::image::Id;
+use conrod::{widget, Colorable, Positionable, Sizeable, UiCell, Widget};
+use std::collections::HashMap;
+use std::ops::Range;
+use tracing::debug;
+
+/// Number of tiles per line.
+const GRID_COLUMNS: usize = 5;
+/// Lines above and below the ones in view that keep their textures.
+const BUFFERED_GRID_ROWS: usize = 1;
+const GRID_LEFT: f64 = 60.0;
+const GRID_TOP: f64 = 140.0;
+const TILE_MARGIN: f64 = 30.0;
+/// Room below each tile for the title of its item.
+const TITLE_HEIGHT: f64 = 36.0;
+
+widget_ids!(
+    /// Hold the [`Id`]s for the grid widgets. `tiles` and `titles` are sized for the lines in view
+    /// by [`GridScreen::show`].
+    pub struct GridIds {
+        title,
+        position,
+        outline,
+        tiles[],
+        titles[],
+    }
+);
+
+/// State of the grid screen.
+pub struct GridScreen {
+    ids: GridIds,
+    /// Row whose items are shown, as in [`DisplayController::rows`](crate::DisplayController::rows).
+    true_set_idx: usize,
+    item_count: usize,
+    /// Width over height of the tiles.
+    aspect_ratio: f64,
+    /// Size of the window.
+    window: (f64, f64),
+    focus: usize,
+    /// First line in view.
+    top_line: usize,
+    /// Textures of the items, keyed by `true_item_idx`.
+    imgs: HashMap<usize, CachedImgData>,
+    /// Textures no longer in view, waiting to be released.
+    evicted: Vec<CachedImgData>,
+}
+
+impl GridScreen {
+    /// Constructor. The grid is empty until [`open`](GridScreen::open)ed.
+    pub fn new(ids: GridIds, window: (f64, f64)) -> Self {
+        Self {
+            ids,
+            true_set_idx: 0,
+            item_count: 0,
+            aspect_ratio: api::DEFAULT_TILE_ASPECT_RATIO as f64,
+            window,
+            focus: 0,
+            top_line: 0,
+            imgs: HashMap::new(),
+            evicted: Vec::new(),
+        }
+    }
+
+    /// Shows the items of the row `true_set_idx`, from its `set_data`, with `focus` on one of them.
+    /// The tiles take the aspect ratio of the artwork of the focused item.
+    pub fn open(&mut self, true_set_idx: usize, set_data: &SetData, focus: usize) {
+        self.close();
+        self.true_set_idx = true_set_idx;
+        self.item_count = set_data.get_item_count();
+        self.aspect_ratio = set_data
+            .get_home_tile(focus)
+            .map_or(api::DEFAULT_TILE_ASPECT_RATIO, |tile| tile.aspect_ratio)
+            as f64;
+        self.focus = focus.min(self.item_count.saturating_sub(1));
+        self.top_line = 0;
+        self.scroll_to_focus();
+    }
+
+    /// Moves every texture to the evicted list.
+    pub fn close(&mut self) {
+        self.evicted.extend(self.imgs.drain().map(|(_, data)| data));
+    }
+
+    /// Follows a new window size. The textures are evicted since the tiles change size.
+    pub fn resize(&mut self, width: f64, height: f64) {
+        if self.window == (width, height) {
+            return;
+        }
+        self.window = (width, height);
+        self.close();
+        self.scroll_to_focus();
+    }
+
+    /// Row whose items are shown.
+    pub fn true_set_idx(&self) -> usize {
+        self.true_set_idx
+    }
+
+    /// `true_item_idx` of the focused item.
+    pub fn focused(&self) -> usize {
+        self.focus
+    }
+
+    /// Size of a tile, in pixels.
+    fn tile_size(&self) -> (f64, f64) {
+        let columns = GRID_COLUMNS as f64;
+        let w = (self.window.0 - 2.0 * GRID_LEFT - (columns - 1.0) * TILE_MARGIN) / columns;
+        (w, w / self.aspect_ratio)
+    }
+
+    /// Height of a line of tiles, their titles included.
+    fn line_height(&self) -> f64 {
+        self.tile_size().1 + TITLE_HEIGHT + TILE_MARGIN
+    }
+
+    /// Number of lines entirely in view, at least 1.
+    fn full_lines(&self) -> usize {
+        (((self.window.1 - GRID_TOP) / self.line_height()).floor() as usize).max(1)
+    }
+
+    /// Lines in view, the last one possibly cut by the bottom of the window.
+    fn visible_lines(&self) -> Range<usize> {
+        let count = ((self.window.1 - GRID_TOP) / self.line_height())
+            .ceil()
+            .max(1.0) as usize;
+        self.top_line..self.top_line + count
+    }
+
+    /// Items whose textures are kept, see [`BUFFERED_GRID_ROWS`].
+    fn buffered_items(&self) -> Range<usize> {
+        let lines = self.visible_lines();
+        let start = lines.start.saturating_sub(BUFFERED_GRID_ROWS) * GRID_COLUMNS;
+        let end = (lines.end + BUFFERED_GRID_ROWS) * GRID_COLUMNS;
+        start.min(self.item_count)..end.min(self.item_count)
+    }
+
+    /// Scrolls the least needed for the focused line to be entirely in view.
+    fn scroll_to_focus(&mut self) {
+        let line = self.focus / GRID_COLUMNS;
+        let full_lines = self.full_lines();
+        if line < self.top_line {
+            self.top_line = line;
+        } else if line >= self.top_line + full_lines {
+            self.top_line = line + 1 - full_lines;
+        }
+    }
+
+    fn set_focus(&mut self, focus: usize) {
+        self.focus = focus.min(self.item_count.saturating_sub(1));
+        self.scroll_to_focus();
+    }
+
+    /// Moves the focus left, staying on its line.
+    pub fn move_left(&mut self) {
+        if self.focus % GRID_COLUMNS > 0 {
+            self.set_focus(self.focus - 1);
+        }
+    }
+
+    /// Moves the focus right, staying on its line.
+    pub fn move_right(&mut self) {
+        if (self.focus + 1) % GRID_COLUMNS > 0 && self.focus + 1 < self.item_count {
+            self.set_focus(self.focus + 1);
+        }
+    }
+
+    /// Moves the focus up a line.
+    pub fn move_up(&mut self) {
+        if self.focus >= GRID_COLUMNS {
+            self.set_focus(self.focus - GRID_COLUMNS);
+        }
+    }
+
+    /// Moves the focus down a line, to the last item if the next line is shorter.
+    pub fn move_down(&mut self) {
+        if self.focus / GRID_COLUMNS < self.item_count.saturating_sub(1) / GRID_COLUMNS {
+            self.set_focus(self.focus + GRID_COLUMNS);
+        }
+    }
+
+    /// Moves the focus up by the lines in view.
+    pub fn page_up(&mut self) {
+        let lines = self.full_lines().min(self.focus / GRID_COLUMNS);
+        self.set_focus(self.focus - lines * GRID_COLUMNS);
+    }
+
+    /// Moves the focus down by the lines in view.
+    pub fn page_down(&mut self) {
+        self.set_focus(self.focus + self.full_lines() * GRID_COLUMNS);
+    }
+
+    /// Moves the focus to the first item.
+    pub fn first(&mut self) {
+        self.set_focus(0);
+    }
+
+    /// Moves the focus to the last item.
+    pub fn last(&mut self) {
+        self.set_focus(self.item_count.saturating_sub(1));
+    }
+
+    /// Evicts the textures scrolled away and submits the [`LoadRequest`]s for the items kept
+    /// around that don't have one yet. The items of `set_data` without artwork get the `nf_id`.
+    pub fn request_images(&mut self, set_data: &SetData, loader: &mut ImageLoader, nf_id: &Id) {
+        let buffered = self.buffered_items();
+        let stale: Vec<usize> = self
+            .imgs
+            .keys()
+            .filter(|true_item_idx| !buffered.contains(true_item_idx))
+            .copied()
+            .collect();
+        for true_item_idx in stale {
+            if let Some(data) = self.imgs.remove(&true_item_idx) {
+                self.evicted.push(data);
+            }
+        }
+
+        let (w, h) = self.tile_size();
+        for true_item_idx in buffered {
+            if self.imgs.contains_key(&true_item_idx)
+                || loader
+                    .notifier()
+                    .is_in_flight(LoadKind::Grid, self.true_set_idx, true_item_idx)
+            {
+                continue;
+            }
+            match set_data.get_home_tile_url(true_item_idx) {
+                Ok(url) => loader.submit(LoadRequest {
+                    kind: LoadKind::Grid,
+                    true_set_idx: self.true_set_idx,
+                    true_item_idx,
+                    url,
+                    size: Some((w.round() as u32, h.round() as u32)),
+                    preview_url: None,
+                }),
+                Err(e) => {
+                    debug!("No artwork for grid item {}: {}", true_item_idx, e);
+                    self.imgs
+                        .insert(true_item_idx, CachedImgData::not_found(nf_id.clone()));
+                }
+            }
+        }
+    }
+
+    /// Returns true if the image of `true_item_idx` of the row `true_set_idx` is still wanted.
+    pub fn wants(&self, true_set_idx: usize, true_item_idx: usize) -> bool {
+        true_set_idx == self.true_set_idx && self.buffered_items().contains(&true_item_idx)
+    }
+
+    /// Stores the texture data of an item image loaded in the background.
+    pub fn insert_loaded(&mut self, true_item_idx: usize, data: CachedImgData) {
+        if let Some(old) = self.imgs.insert(true_item_idx, data) {
+            self.evicted.push(old);
+        }
+    }
+
+    /// Takes the textures that need to be released.
+    pub fn take_evicted(&mut self) -> Vec<CachedImgData> {
+        std::mem::take(&mut self.evicted)
+    }
+
+    /// Sets the grid widgets for the items of `set_data` under the row `title`, the texts following
+    /// the `theme` and in the language of the `locale`.
+    pub fn show(
+        &mut self,
+        ui: &mut UiCell,
+        set_data: &SetData,
+        title: &str,
+        nf_id: &Id,
+        theme: &Theme,
+        locale: Locale,
+    ) {
+        let lines = self.visible_lines();
+        let num_of_tiles = lines.len() * GRID_COLUMNS;
+        if self.ids.tiles.len() < num_of_tiles {
+            let mut generator = ui.widget_id_generator();
+            self.ids.tiles.resize(num_of_tiles, &mut generator);
+            self.ids.titles.resize(num_of_tiles, &mut generator);
+        }
+
+        let heading = format!("{}  {}", title, i18n::item_count(locale, self.item_count));
+        theme
+            .text_widget(&heading, theme.text)
+            .top_left_with_margins_on(ui.window, 60.0, GRID_LEFT)
+            .font_size(theme.font_size(TextStyle::Title))
+            .set(self.ids.title, ui);
+        if self.item_count > 0 {
+            let position = i18n::trf_all(
+                locale,
+                Text::RowPosition,
+                &[
+                    &(self.focus / GRID_COLUMNS + 1).to_string(),
+                    &((self.item_count - 1) / GRID_COLUMNS + 1).to_string(),
+                ],
+            );
+            theme
+                .text_widget(&position, theme.hint_text)
+                .top_right_with_margins_on(ui.window, 60.0, GRID_LEFT)
+                .font_size(theme.font_size(TextStyle::Hint))
+                .set(self.ids.position, ui);
+        }
+
+        // Items still loading show the not found image, same as the search results.
+        let placeholder = CachedImgData::not_found(nf_id.clone());
+        let (w, h) = self.tile_size();
+        let line_height = self.line_height();
+        let items = lines.start * GRID_COLUMNS..(lines.end * GRID_COLUMNS).min(self.item_count);
+        for (widget_idx, true_item_idx) in items.enumerate() {
+            let data = self.imgs.get(&true_item_idx).unwrap_or(&placeholder);
+            let line = true_item_idx / GRID_COLUMNS - lines.start;
+            let top = GRID_TOP + line as f64 * line_height;
+            let left = GRID_LEFT + (true_item_idx % GRID_COLUMNS) as f64 * (w + TILE_MARGIN);
+            widget::Image::new(data.img_id)
+                .w_h(w, h)
+                .top_left_with_margins_on(ui.window, top, left)
+                .set(self.ids.tiles[widget_idx], ui);
+            let item_title = set_data.get_item_title(true_item_idx).unwrap_or_default();
+            let item_title = theme.truncate(TextStyle::Hint, item_title, w);
+            theme
+                .text_widget(&item_title, theme.text)
+                .down_from(self.ids.tiles[widget_idx], 8.0)
+                .font_size(theme.font_size(TextStyle::Hint))
+                .set(self.ids.titles[widget_idx], ui);
+
+            if true_item_idx == self.focus {
+                widget::Rectangle::outline_styled(
+                    [w + 8.0, h + 8.0],
+                    widget::line::Style::new().thickness(4.0),
+                )
+                .color(to_color(theme.text))
+                .middle_of(self.ids.tiles[widget_idx])
+                .set(self.ids.outline, ui);
+            }
+        }
+    }
+}
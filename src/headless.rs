@@ -0,0 +1,204 @@
+//! Off-screen rendering, to check the layout without looking at a window.
+//!
+//! `helloplus --headless --screenshot out.png` loads the home data, renders `--frames` frames (60 by
+//! default) of the home screen into an off-screen texture and writes the last one to `out.png`.
+//! Once the frames are rendered it keeps going while rows are streaming or images are loading, so
+//! the screenshot doesn't catch the placeholders, up to [`MAX_WAIT`].
+//!
+//! With `--replay <path>` the recorded actions are replayed first, the screenshot is taken once
+//! the last one is handled, see [`recording`](crate::recording). Their times are counted in frames
+//! of [`FRAME_INTERVAL`] rather than on the clock, so a slow machine replays them the same way.
+//!
+//! The window is hidden but a display server is still needed, on CI run it with `xvfb-run`. Without a
+//! GPU, Mesa falls back to software rendering (llvmpipe), `LIBGL_ALWAYS_SOFTWARE=1` forces it.
+use crate::audio::Audio;
+use crate::catalog::Catalog;
+use crate::config::{Config, ConfigError};
+use crate::event_loop::FRAME_INTERVAL;
+use crate::local_rows::LocalRows;
+use crate::profiles::ProfileStore;
+use crate::recording::Replay;
+use crate::theme::Themes;
+use crate::{helpers, DisplayController, ExitReason};
+use conrod::backend::glium::glium::{self, glutin, Surface};
+use conrod::backend::glium::Renderer;
+use std::error::Error;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{info, info_span, warn};
+
+/// Number of frames rendered when `--frames` isn't given.
+pub const DEFAULT_FRAMES: usize = 60;
+/// Past the requested frames, rendering stops after this long even if images are still loading.
+const MAX_WAIT: Duration = Duration::from_secs(30);
+
+/// What to render when running headless.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeadlessOptions {
+    /// Where the last frame is written, the format is picked from the extension.
+    pub screenshot: PathBuf,
+    /// Number of frames to render.
+    pub frames: usize,
+}
+
+impl HeadlessOptions {
+    /// Takes `--headless`, `--screenshot <path>` and `--frames <n>` out of `args` so the rest can be
+    /// handed to [`Config::load`].
+    ///
+    /// Returns None, leaving `args` untouched, when `--headless` isn't given. Otherwise `--screenshot`
+    /// is mandatory.
+    pub fn take_from_args(args: &mut Vec<String>) -> Result<Option<Self>, ConfigError> {
+        match args.iter().position(|a| a == "--headless") {
+            Some(pos) => args.remove(pos),
+            None => return Ok(None),
+        };
+
+        let screenshot = take_value(args, "--screenshot")?
+            .ok_or_else(|| ConfigError::MissingValue(String::from("--screenshot")))?;
+        let frames = match take_value(args, "--frames")? {
+            Some(value) => match value.parse() {
+                Ok(frames) if frames > 0 => frames,
+                _ => {
+                    return Err(ConfigError::InvalidValue {
+                        key: String::from("frames"),
+                        value,
+                    })
+                }
+            },
+            None => DEFAULT_FRAMES,
+        };
+
+        Ok(Some(Self {
+            screenshot: PathBuf::from(screenshot),
+            frames,
+        }))
+    }
+}
+
+/// Removes `flag` and the value following it from `args`.
+pub(crate) fn take_value(
+    args: &mut Vec<String>,
+    flag: &str,
+) -> Result<Option<String>, ConfigError> {
+    let pos = match args.iter().position(|a| a == flag) {
+        Some(pos) => pos,
+        None => return Ok(None),
+    };
+    if pos + 1 >= args.len() {
+        return Err(ConfigError::MissingValue(flag.to_string()));
+    }
+    let value = args.remove(pos + 1);
+    args.remove(pos);
+    Ok(Some(value))
+}
+
+/// Renders the home screen off-screen as per the `options` and writes the screenshot. The rows
+/// come from the `catalog`, see [`load_home_data`](crate::load_home_data). The actions of the
+/// `replay` are handled before.
+pub fn run(
+    config: Config,
+    options: &HeadlessOptions,
+    catalog: &Catalog,
+    mut replay: Option<Replay>,
+) -> Result<(), Box<dyn Error>> {
+    let events_loop = glutin::EventsLoop::new();
+    let mut themes = Themes::load()?;
+    let (display, mut ui) = helpers::build_hidden_display(&config, &events_loop, &mut themes)?;
+    let mut renderer = Renderer::new(&display).unwrap();
+    let (width, height) = (config.display_width, config.display_height);
+    let frame = glium::texture::SrgbTexture2d::empty(&display, width, height)?;
+
+    // Rendered as the profile picked last, without showing the picker.
+    let mut profiles = ProfileStore::load_default();
+    let profile = profiles.select(profiles.last_selected());
+    let config = profile.map_or(config, |profile| profile.apply(config));
+    let local = profile.map_or_else(
+        || LocalRows::empty(config.locale),
+        |profile| LocalRows::load(profile, config.locale),
+    );
+
+    let mut api_handle = crate::build_api(&config)?;
+    crate::load_home_data(&mut api_handle, &config, catalog)?;
+
+    let audio = Audio::silent();
+    let mut controller = DisplayController::new(
+        &display,
+        &api_handle,
+        &mut ui,
+        config,
+        &themes,
+        local,
+        &audio,
+    );
+    controller.initialize(&mut ui);
+
+    let started = Instant::now();
+    if let Some(replay) = replay.as_mut() {
+        replay.start(started);
+    }
+    let max_wait = MAX_WAIT
+        + replay
+            .as_ref()
+            .map_or_else(Duration::default, Replay::duration);
+    let mut rendered = 0;
+    loop {
+        let _frame = info_span!("frame").entered();
+        let frame_time = started + FRAME_INTERVAL * rendered as u32;
+        for action in replay.iter_mut().flat_map(|replay| replay.poll(frame_time)) {
+            match controller.handle(action, &mut ui) {
+                Some(ExitReason::Quit) => warn!("quitting is ignored when headless"),
+                Some(ExitReason::Reload(_)) => warn!("the rows are not rebuilt when headless"),
+                None => (),
+            }
+        }
+        controller.stream_rows(&mut ui);
+        controller.poll_loaded_images(&mut ui);
+        controller.tick(&mut ui);
+
+        if let Some(primitives) = ui.draw_if_changed() {
+            let _render = info_span!("render").entered();
+            renderer.fill(&display, primitives, &controller.image_map);
+            let mut target = frame.as_surface();
+            let [r, g, b] = controller.theme().background;
+            target.clear_color_srgb(r, g, b, 1.0);
+            renderer
+                .draw(&display, &mut target, &controller.image_map)
+                .unwrap();
+        }
+        rendered += 1;
+
+        let replaying = replay.as_ref().map_or(false, |replay| !replay.is_done());
+        let busy = controller.is_loading()
+            || controller.is_animating()
+            || controller.is_streaming()
+            || replaying;
+        if rendered >= options.frames && !busy {
+            break;
+        }
+        if rendered >= options.frames && !replaying && started.elapsed() > max_wait {
+            warn!(
+                "still loading after {:?}, the screenshot may show placeholders",
+                max_wait
+            );
+            break;
+        }
+        thread::sleep(FRAME_INTERVAL);
+    }
+
+    let raw: glium::texture::RawImage2d<u8> = frame.read();
+    let image = image::RgbaImage::from_raw(raw.width, raw.height, raw.data.into_owned())
+        .expect("texture data matches its dimensions");
+    // OpenGL rows go bottom to top.
+    image::imageops::flip_vertical(&image).save(&options.screenshot)?;
+    let gpu = controller.gpu_usage();
+    info!(
+        "rendered {} frames to {}, {} textures take ~{} of {} MB",
+        rendered,
+        options.screenshot.display(),
+        gpu.textures,
+        gpu.bytes / (1024 * 1024),
+        gpu.budget / (1024 * 1024)
+    );
+    Ok(())
+}
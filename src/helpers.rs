@@ -1,33 +1,166 @@
 //! Helper functions
+use crate::config::{Config, WindowMode};
+use crate::event_loop::EventLoop;
+use crate::fonts::FontChain;
+use crate::i18n::Locale;
+use crate::theme::Themes;
 use conrod::backend::glium::glium;
-use conrod::glium::glutin::EventsLoop;
 use conrod::glium::Display;
+use conrod::text::font;
 use conrod::Ui;
 use find_folder;
-use image::imageops::FilterType;
-use image::DynamicImage;
-
-/// Load the given `dyn_image` as a [`glium Texture2d`](glium::texture::Texture2d) struct.
-pub fn load_img(display: &glium::Display, dyn_img: DynamicImage) -> glium::texture::Texture2d {
-    let rgba_image = dyn_img.to_rgba8();
-    let image_dimensions = rgba_image.dimensions();
-    let raw_image = glium::texture::RawImage2d::from_raw_rgba_reversed(
-        &rgba_image.into_raw(),
-        image_dimensions,
-    );
-    let texture = glium::texture::Texture2d::new(display, raw_image).unwrap();
-    texture
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{info, info_span, warn};
+
+/// Font the widgets are drawn with unless their [`Theme`](crate::theme::Theme) picks another one.
+const DEFAULT_FONT: &str = "NotoSans/NotoSans-Regular.ttf";
+
+/// Load the given `dyn_image` as a [`glium SrgbTexture2d`](glium::texture::SrgbTexture2d) struct.
+pub fn load_img(display: &glium::Display, dyn_img: DynamicImage) -> glium::texture::SrgbTexture2d {
+    upload_rgba(display, to_gl_rgba(dyn_img))
+}
+
+/// Resizes `dyn_img` to `size`, if given and not its size already. Meant to be done off the main
+/// thread, see [`LoadRequest::size`](crate::loader::LoadRequest::size).
+pub fn resize_to(dyn_img: DynamicImage, size: Option<(u32, u32)>) -> DynamicImage {
+    match size {
+        Some((width, height)) if (width, height) != dyn_img.dimensions() && width * height > 0 => {
+            let _span = info_span!("resize", width, height).entered();
+            dyn_img.resize_exact(width, height, FilterType::Lanczos3)
+        }
+        _ => dyn_img,
+    }
+}
+
+/// Converts `dyn_img` to RGBA with its rows bottom to top, the order OpenGL expects them in. Meant
+/// to be done off the main thread, leaving only the [`upload_rgba`] to it.
+pub fn to_gl_rgba(dyn_img: DynamicImage) -> RgbaImage {
+    let mut rgba_image = dyn_img.into_rgba8();
+    imageops::flip_vertical_in_place(&mut rgba_image);
+    rgba_image
+}
+
+/// Raises the color channels of `rgba_image` to the power of `1 / gamma`, the alpha is left as is.
+/// Does nothing for a `gamma` of 1, see [`Config::gamma`].
+pub fn apply_gamma(rgba_image: &mut RgbaImage, gamma: f32) {
+    if (gamma - 1.0).abs() < f32::EPSILON {
+        return;
+    }
+    let mut lut = [0u8; 256];
+    for (value, mapped) in lut.iter_mut().enumerate() {
+        *mapped = ((value as f32 / 255.0).powf(1.0 / gamma) * 255.0).round() as u8;
+    }
+    for Rgba([r, g, b, _]) in rgba_image.pixels_mut() {
+        *r = lut[*r as usize];
+        *g = lut[*g as usize];
+        *b = lut[*b as usize];
+    }
+}
+
+/// Uploads the `rgba_image` coming from [`to_gl_rgba`] as a [`glium SrgbTexture2d`](glium::texture::SrgbTexture2d).
+///
+/// The artwork is sRGB encoded, the texture being sRGB too the GPU decodes it to linear when
+/// sampling and the sRGB framebuffer of [`build_display`] encodes it back when drawing. Uploaded as
+/// a linear texture it would be encoded twice, looking washed out.
+pub fn upload_rgba(
+    display: &glium::Display,
+    rgba_image: RgbaImage,
+) -> glium::texture::SrgbTexture2d {
+    let (width, height) = rgba_image.dimensions();
+    let _span = info_span!("texture_upload", width, height).entered();
+    let raw_image =
+        glium::texture::RawImage2d::from_raw_rgba(rgba_image.into_raw(), (width, height));
+    glium::texture::SrgbTexture2d::new(display, raw_image).unwrap()
 }
 
-/// Load the fonts for this ui.
+/// Writes `rgba_image` over the whole of `texture`, which has the same size. Cheaper than uploading
+/// a new texture, e.g. for the frames of a video loop.
+pub fn write_rgba(texture: &glium::texture::SrgbTexture2d, rgba_image: RgbaImage) {
+    let (width, height) = rgba_image.dimensions();
+    let raw_image =
+        glium::texture::RawImage2d::from_raw_rgba(rgba_image.into_raw(), (width, height));
+    let rect = glium::Rect {
+        left: 0,
+        bottom: 0,
+        width,
+        height,
+    };
+    texture.write(rect, raw_image);
+}
+
+/// Load the fonts for this ui: the [`DEFAULT_FONT`], then the font and the fallback fonts of each
+/// of the `themes`, see [`Theme::fonts`](crate::theme::Theme::fonts). A theme whose font can't be
+/// loaded keeps the default one, the fallback fonts that can't be loaded are skipped.
+///
+/// Each theme is checked against the texts of the `locale`, see [`check_font_coverage`].
 ///
 /// Fonts are located in the assets folder.
-pub fn load_fonts(ui: &mut Ui) {
-    let assets = find_folder::Search::KidsThenParents(3, 5)
+pub fn load_fonts(ui: &mut Ui, themes: &mut Themes, locale: Locale) {
+    let fonts = find_folder::Search::KidsThenParents(3, 5)
         .for_folder("assets")
-        .unwrap();
-    let font_path = assets.join("fonts/NotoSans/NotoSans-Regular.ttf");
-    ui.fonts.insert_from_file(font_path).unwrap();
+        .unwrap()
+        .join("fonts");
+    let default_id = ui.fonts.insert_from_file(fonts.join(DEFAULT_FONT)).unwrap();
+    let mut loaded = HashMap::new();
+    loaded.insert(DEFAULT_FONT.to_string(), Some(default_id));
+    for theme in themes.iter_mut() {
+        let mut ids = vec![load_font(ui, &fonts, &theme.font, &mut loaded).unwrap_or(default_id)];
+        for name in &theme.fallback_fonts {
+            ids.extend(load_font(ui, &fonts, name, &mut loaded));
+        }
+        let mut chain = FontChain::default();
+        for id in ids {
+            if let Some(font) = ui.fonts.get(id) {
+                chain.push(id, font.clone());
+            }
+        }
+        check_font_coverage(&chain, &theme.font, locale);
+        theme.fonts = chain;
+    }
+}
+
+/// Loads the font file `name` from the `fonts` folder, once: the fonts already `loaded` are reused
+/// and the ones that failed aren't tried again. None if it can't be loaded.
+fn load_font(
+    ui: &mut Ui,
+    fonts: &Path,
+    name: &str,
+    loaded: &mut HashMap<String, Option<font::Id>>,
+) -> Option<font::Id> {
+    if let Some(font_id) = loaded.get(name) {
+        return *font_id;
+    }
+    let font_id = match ui.fonts.insert_from_file(fonts.join(name)) {
+        Ok(font_id) => Some(font_id),
+        Err(e) => {
+            info!("Could not load the font {}: {}", name, e);
+            None
+        }
+    };
+    loaded.insert(name.to_string(), font_id);
+    font_id
+}
+
+/// Warns about the characters of the texts of the `locale` no font of the `chain`, the one of the
+/// theme whose font is `name`, has a glyph for. They would be drawn as blanks. That's the case of
+/// the non-latin scripts when the fallback fonts are missing, see [`fonts`](crate::fonts).
+///
+/// Only the texts of the app are checked, the titles coming from the home data may need more.
+pub fn check_font_coverage(chain: &FontChain, name: &str, locale: Locale) {
+    let missing = chain.uncovered(&locale.all_texts());
+    if missing.is_empty() {
+        return;
+    }
+    warn!(
+        "The font {} and its fallbacks have no glyph for {} characters of the '{}' texts, e.g. {}",
+        name,
+        missing.len(),
+        locale,
+        missing.iter().take(10).collect::<String>()
+    );
 }
 
 /// Load the "image-not-found" png to use when artwork can't be found.
@@ -42,22 +175,154 @@ pub fn load_img_not_found() -> DynamicImage {
     img.resize(500, 220, FilterType::Lanczos3)
 }
 
-/// Build the [`glium Display`](Display) and [`EventsLoop`] for the window.
-pub fn build_display() -> (Display, EventsLoop, Ui) {
-    let events_loop = glium::glutin::EventsLoop::new();
+/// Builds the `count` frames of the shimmer drawn while a tile is loading: a lighter band sweeping
+/// diagonally across a dark grey tile, one sweep over the whole sequence.
+///
+/// The frames are `width` x `height` and get stretched to the tile size, the gradient is smooth
+/// enough for them to be kept small.
+pub fn build_shimmer_frames(width: u32, height: u32, count: usize) -> Vec<DynamicImage> {
+    let band = width as f32 * 0.4;
+    // Slanted by half a pixel per line, the band starts fully left of the tile and ends fully right of it.
+    let travel = width as f32 + height as f32 * 0.5 + 2.0 * band;
+    (0..count)
+        .map(|frame| {
+            let center = -band + travel * frame as f32 / count as f32;
+            let img = RgbaImage::from_fn(width, height, |x, y| {
+                let dist = (x as f32 + y as f32 * 0.5 - center).abs();
+                let t = (1.0 - dist / band).max(0.0);
+                // Smoothstep so the band has soft edges.
+                let glow = t * t * (3.0 - 2.0 * t);
+                let v = (38.0 + 34.0 * glow) as u8;
+                Rgba([v, v, v, 255])
+            });
+            DynamicImage::ImageRgba8(img)
+        })
+        .collect()
+}
+
+/// The home.json read when offline. The `SetRef` sets it points to are in the `sets` folder next to it.
+///
+/// Located in the assets folder.
+pub fn offline_home_path() -> PathBuf {
+    let assets = find_folder::Search::ParentsThenKids(3, 3)
+        .for_folder("assets")
+        .unwrap();
+    assets.join("home.json")
+}
+
+/// The artwork shown when offline for the images that aren't in the disk cache.
+///
+/// Located in the assets folder.
+pub fn offline_samples_dir() -> PathBuf {
+    let assets = find_folder::Search::ParentsThenKids(3, 3)
+        .for_folder("assets")
+        .unwrap();
+    assets.join("images/samples")
+}
+
+/// The sounds played as the focus moves around, see [`audio`](crate::audio).
+///
+/// Located in the assets folder.
+pub fn sounds_dir() -> PathBuf {
+    let assets = find_folder::Search::ParentsThenKids(3, 3)
+        .for_folder("assets")
+        .unwrap();
+    assets.join("sounds")
+}
+
+/// Build the [`glium Display`](Display) and the [`Ui`] for the window of the `event_loop`.
+///
+/// The window is sized as per the `config` when `window_mode` is [`WindowMode::Windowed`],
+/// otherwise it takes the size of the primary monitor. Its framebuffer is sRGB, see [`upload_rgba`].
+pub fn build_display(
+    config: &Config,
+    window_mode: WindowMode,
+    event_loop: &EventLoop,
+    themes: &mut Themes,
+) -> (Display, Ui) {
+    let (width, height) = match window_mode {
+        WindowMode::Windowed => (config.display_width, config.display_height),
+        _ => event_loop.primary_monitor().get_dimensions(),
+    };
     let window = glium::glutin::WindowBuilder::new()
         .with_title("Hello +")
-        .with_dimensions(crate::DISPLAY_WIDTH, crate::DISPLAY_HEIGHT);
+        .with_dimensions(width, height);
     let context = glium::glutin::ContextBuilder::new()
         .with_vsync(true)
-        .with_multisampling(4);
-
-    let mut ui =
-        conrod::UiBuilder::new([crate::DISPLAY_WIDTH as f64, crate::DISPLAY_HEIGHT as f64]).build();
-    load_fonts(&mut ui);
-    (
-        glium::Display::new(window, context, &events_loop).unwrap(),
-        events_loop,
-        ui,
-    )
+        .with_multisampling(4)
+        .with_srgb(true);
+
+    let ui = build_ui(width, height, themes, config.locale);
+    let display = glium::Display::new(window, context, event_loop.events_loop()).unwrap();
+    if window_mode != WindowMode::Windowed {
+        set_window_mode(&display, event_loop, config, window_mode);
+    }
+    (display, ui)
+}
+
+/// Build a [`glium Display`](Display) backed by a hidden window, along with its [`Ui`], for off-screen
+/// rendering. Its size is taken from the `config`.
+///
+/// The conrod renderer needs a [`Display`] so a hidden window is used rather than a glutin headless
+/// context. It still needs a display server, e.g. `xvfb-run` on CI.
+pub fn build_hidden_display(
+    config: &Config,
+    events_loop: &glium::glutin::EventsLoop,
+    themes: &mut Themes,
+) -> Result<(Display, Ui), glium::backend::glutin::DisplayCreationError> {
+    let (width, height) = (config.display_width, config.display_height);
+    let window = glium::glutin::WindowBuilder::new()
+        .with_title("Hello +")
+        .with_dimensions(width, height)
+        .with_visibility(false);
+    let context = glium::glutin::ContextBuilder::new().with_srgb(true);
+
+    let display = glium::Display::new(window, context, events_loop)?;
+    Ok((display, build_ui(width, height, themes, config.locale)))
+}
+
+/// Build a `width` x `height` [`Ui`] with the fonts of the `themes` loaded, see [`load_fonts`].
+fn build_ui(width: u32, height: u32, themes: &mut Themes, locale: Locale) -> Ui {
+    let mut ui = conrod::UiBuilder::new([width as f64, height as f64]).build();
+    load_fonts(&mut ui, themes, locale);
+    ui
+}
+
+/// Switches the window of the `display` to `window_mode`.
+///
+/// The window gets resized along the way, the new size is delivered as a regular
+/// [`Input::Resized`](crate::event_loop::Input::Resized) event.
+pub fn set_window_mode(
+    display: &Display,
+    event_loop: &EventLoop,
+    config: &Config,
+    window_mode: WindowMode,
+) {
+    let window = display.gl_window();
+    let monitor = event_loop.primary_monitor();
+    match window_mode {
+        WindowMode::Windowed => {
+            window.set_fullscreen(None);
+            window.set_decorations(true);
+            window.set_inner_size(config.display_width, config.display_height);
+        }
+        WindowMode::Borderless => {
+            let (width, height) = monitor.get_dimensions();
+            window.set_fullscreen(None);
+            window.set_decorations(false);
+            window.set_position(0, 0);
+            window.set_inner_size(width, height);
+        }
+        WindowMode::Exclusive => window.set_fullscreen(Some(monitor)),
+    }
+}
+
+/// Directory where the app keeps its state, `$XDG_DATA_HOME/helloplus` (or `~/.local/share/helloplus`).
+///
+/// Returns None if neither `XDG_DATA_HOME` nor `HOME` are set.
+pub fn data_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))?;
+    Some(base.join("helloplus"))
 }
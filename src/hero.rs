@@ -0,0 +1,103 @@
+//! Billboard banner drawn above the tile rows.
+use crate::loader::{ImageLoader, LoadKind, LoadRequest};
+use crate::CachedImgData;
+use api::Api;
+use conrod::image::Id;
+use conrod::{widget, Colorable, Positionable, Sizeable, UiCell, Widget};
+use tracing::debug;
+
+/// Height of the banner. The tile rows are shifted down by this amount while the banner is visible.
+pub const HERO_BANNER_HEIGHT: f64 = 400.0;
+/// Thickness of the outline drawn when the banner has the focus.
+const FOCUS_OUTLINE_THICKNESS: f64 = 4.0;
+
+widget_ids!(
+    /// Hold the [`Id`]s for the banner widgets.
+    pub struct HeroIds {
+        image,
+        outline,
+    }
+);
+
+/// Full-width banner showing the hero/background artwork of the first item of the first container.
+///
+/// The texture is cropped to the banner's aspect ratio using the vertical center of the artwork.
+pub struct HeroBanner {
+    ids: HeroIds,
+    img: Option<CachedImgData>,
+    requested: bool,
+}
+
+impl HeroBanner {
+    /// Constructor.
+    pub fn new(ids: HeroIds) -> Self {
+        Self {
+            ids,
+            img: None,
+            requested: false,
+        }
+    }
+
+    /// Submits the [`LoadRequest`] for the banner artwork. Only the first call does anything.
+    pub fn request(&mut self, api_handle: &Api, loader: &mut ImageLoader) {
+        if self.requested {
+            return;
+        }
+        self.requested = true;
+        let url = api_handle
+            .get_set(0)
+            .ok_or_else(|| String::from("no sets loaded"))
+            .and_then(|set| set.get_hero_image_url(0).map_err(|e| e.to_string()));
+        match url {
+            Ok(url) => loader.submit(LoadRequest {
+                kind: LoadKind::Banner,
+                true_set_idx: 0,
+                true_item_idx: 0,
+                url,
+                size: None,
+                preview_url: None,
+            }),
+            Err(e) => debug!("No banner artwork: {}", e),
+        }
+    }
+
+    /// Sets the loaded artwork. `w` and `h` are the texture dimensions.
+    pub fn set_image(&mut self, img_id: Id, w: f64, h: f64) {
+        self.img = Some(CachedImgData::new(img_id, w, h));
+    }
+
+    /// Sets the banner widgets at the top of the window.
+    pub fn show(&self, ui: &mut UiCell, focused: bool, nf_id: &Id) {
+        let banner_w = ui.win_w;
+
+        let image = match self.img {
+            Some(ref img) => {
+                // Crop the middle band of the artwork matching the banner's aspect ratio.
+                let src_h = (img.w * HERO_BANNER_HEIGHT / banner_w).min(img.h);
+                let src_y = (img.h - src_h) / 2.0;
+                widget::Image::new(img.img_id).source_rectangle(conrod::Rect::from_corners(
+                    [0.0, src_y],
+                    [img.w, src_y + src_h],
+                ))
+            }
+            None => widget::Image::new(nf_id.clone()),
+        };
+        image
+            .w_h(banner_w, HERO_BANNER_HEIGHT)
+            .mid_top_of(ui.window)
+            .set(self.ids.image, ui);
+
+        if focused {
+            widget::Rectangle::outline_styled(
+                [
+                    banner_w - FOCUS_OUTLINE_THICKNESS,
+                    HERO_BANNER_HEIGHT - FOCUS_OUTLINE_THICKNESS,
+                ],
+                widget::line::Style::new().thickness(FOCUS_OUTLINE_THICKNESS),
+            )
+            .color(conrod::color::WHITE)
+            .middle_of(self.ids.image)
+            .set(self.ids.outline, ui);
+        }
+    }
+}
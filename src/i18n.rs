@@ -0,0 +1,391 @@
+//! Translations of the texts drawn by the app, picked with [`Config::locale`](crate::config::Config::locale).
+//!
+//! Each [`Text`] has an entry per [`Locale`] in [`tr`]. The titles coming from the home data are
+//! translated by the backend instead, the [`Api`](api::Api) asks for them in the
+//! [`language_tag`](Locale::language_tag) of the locale.
+//!
+//! The fonts of the themes are checked against the texts of the locale at startup, see
+//! [`helpers::check_font_coverage`](crate::helpers::check_font_coverage). The bundled NotoSans fonts
+//! only cover the latin, greek and cyrillic scripts: [`Locale::Ja`] relies on the fallback fonts,
+//! see [`fonts`](crate::fonts).
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// Languages the app is translated to.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    /// English, the default.
+    En,
+    /// French.
+    Fr,
+    /// Spanish.
+    Es,
+    /// Japanese.
+    Ja,
+}
+
+impl Locale {
+    /// Language tag the home data is requested with, see [`Api::with_language`](api::Api::with_language).
+    pub fn language_tag(self) -> &'static str {
+        match self {
+            Locale::En => "en-US",
+            Locale::Fr => "fr-FR",
+            Locale::Es => "es-ES",
+            Locale::Ja => "ja-JP",
+        }
+    }
+
+    /// Every text of the locale joined together, to check a font has a glyph for each of their
+    /// characters.
+    pub fn all_texts(self) -> String {
+        TEXTS.iter().map(|text| tr(self, *text)).collect()
+    }
+}
+
+impl FromStr for Locale {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "en" => Ok(Locale::En),
+            "fr" => Ok(Locale::Fr),
+            "es" => Ok(Locale::Es),
+            "ja" => Ok(Locale::Ja),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Locale::En => write!(f, "en"),
+            Locale::Fr => write!(f, "fr"),
+            Locale::Es => write!(f, "es"),
+            Locale::Ja => write!(f, "ja"),
+        }
+    }
+}
+
+/// The texts to translate. The ones containing `{}` are filled in with [`trf`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Text {
+    /// Placeholder for the items and sets without a title.
+    TitleNotFound,
+    /// Title of the [`WATCHLIST_ROW`](crate::local_rows::WATCHLIST_ROW).
+    MyWatchlist,
+    /// Title of the [`CONTINUE_WATCHING_ROW`](crate::local_rows::CONTINUE_WATCHING_ROW).
+    ContinueWatching,
+    /// Shown while the [`CONTINUE_WATCHING_ROW`](crate::local_rows::CONTINUE_WATCHING_ROW) is empty.
+    ContinueWatchingHint,
+    /// Shown while the [`WATCHLIST_ROW`](crate::local_rows::WATCHLIST_ROW) is empty.
+    WatchlistHint,
+    /// Shown in place of the tiles of a set without items, see [`Config::skip_empty_rows`](crate::config::Config::skip_empty_rows).
+    NothingHereYet,
+    /// Shown while the home data is fetched, see [`startup`](crate::startup).
+    Loading,
+    /// Title of the [`PinEntry`](crate::parental::PinEntry).
+    EnterPin,
+    /// Shown once a wrong PIN was typed.
+    WrongPin,
+    /// How to close the [`PinEntry`](crate::parental::PinEntry).
+    EscapeToCancel,
+    /// The query being typed on the search screen, `{}` is the query.
+    SearchPrompt,
+    /// Shown when a search finds nothing, `{}` is the query.
+    NoResults,
+    /// Title of the profile picker.
+    WhoIsWatching,
+    /// Last entry of the profile picker.
+    AddProfile,
+    /// Title of the settings screen.
+    Settings,
+    /// How to use the settings screen.
+    SettingsHint,
+    /// Name of [`Setting::Animations`](crate::settings::Setting::Animations).
+    Animations,
+    /// Name of [`Setting::ImageQuality`](crate::settings::Setting::ImageQuality).
+    ImageQuality,
+    /// Name of [`Setting::RowDensity`](crate::settings::Setting::RowDensity).
+    RowDensity,
+    /// Name of [`Setting::Theme`](crate::settings::Setting::Theme).
+    Theme,
+    /// Name of [`Setting::Sounds`](crate::settings::Setting::Sounds).
+    Sounds,
+    /// A setting turned on.
+    On,
+    /// A setting turned off.
+    Off,
+    /// An image quality tier.
+    Low,
+    /// An image quality tier.
+    Medium,
+    /// An image quality tier.
+    High,
+    /// A row density.
+    Spacious,
+    /// A row density.
+    Comfortable,
+    /// A row density.
+    Compact,
+    /// A value set by hand in the config file, `{}` is the value.
+    Custom,
+    /// Number of items of a row, next to its title. `{}` is the number.
+    ItemCount,
+    /// [`Text::ItemCount`] of a row with a single item.
+    OneItem,
+    /// Which row has the focus, `{}` being its number then the number of rows.
+    RowPosition,
+    /// [`ThemeName::Dark`](crate::config::ThemeName::Dark).
+    Dark,
+    /// [`ThemeName::Light`](crate::config::ThemeName::Light).
+    Light,
+    /// Name of the [`RowSort`](crate::filter_bar::RowSort) on the filter bar.
+    SortBy,
+    /// Name of the [`RowFilter`](crate::filter_bar::RowFilter) on the filter bar.
+    Show,
+    /// [`RowSort::Default`](crate::filter_bar::RowSort::Default).
+    DefaultOrder,
+    /// [`RowSort::Title`](crate::filter_bar::RowSort::Title).
+    ByTitle,
+    /// [`RowSort::ReleaseYear`](crate::filter_bar::RowSort::ReleaseYear).
+    NewestFirst,
+    /// [`RowFilter::All`](crate::filter_bar::RowFilter::All).
+    Everything,
+    /// [`RowFilter::Series`](crate::filter_bar::RowFilter::Series).
+    Series,
+    /// [`RowFilter::Movies`](crate::filter_bar::RowFilter::Movies).
+    Movies,
+    /// How to use the filter bar.
+    FilterBarHint,
+    /// Shown in place of the tiles of a row whose filter leaves out every item.
+    NothingMatches,
+}
+
+/// Every [`Text`], see [`Locale::all_texts`].
+const TEXTS: [Text; 45] = [
+    Text::TitleNotFound,
+    Text::MyWatchlist,
+    Text::ContinueWatching,
+    Text::ContinueWatchingHint,
+    Text::WatchlistHint,
+    Text::NothingHereYet,
+    Text::Loading,
+    Text::EnterPin,
+    Text::WrongPin,
+    Text::EscapeToCancel,
+    Text::SearchPrompt,
+    Text::NoResults,
+    Text::WhoIsWatching,
+    Text::AddProfile,
+    Text::Settings,
+    Text::SettingsHint,
+    Text::Animations,
+    Text::ImageQuality,
+    Text::RowDensity,
+    Text::Theme,
+    Text::Sounds,
+    Text::On,
+    Text::Off,
+    Text::Low,
+    Text::Medium,
+    Text::High,
+    Text::Spacious,
+    Text::Comfortable,
+    Text::Compact,
+    Text::Custom,
+    Text::ItemCount,
+    Text::OneItem,
+    Text::RowPosition,
+    Text::Dark,
+    Text::Light,
+    Text::SortBy,
+    Text::Show,
+    Text::DefaultOrder,
+    Text::ByTitle,
+    Text::NewestFirst,
+    Text::Everything,
+    Text::Series,
+    Text::Movies,
+    Text::FilterBarHint,
+    Text::NothingMatches,
+];
+
+/// The `text` in the language of the `locale`.
+pub fn tr(locale: Locale, text: Text) -> &'static str {
+    let [en, fr, es, ja] = translations(text);
+    match locale {
+        Locale::En => en,
+        Locale::Fr => fr,
+        Locale::Es => es,
+        Locale::Ja => ja,
+    }
+}
+
+/// The `text` in the language of the `locale`, with its `{}` replaced by `arg`.
+pub fn trf(locale: Locale, text: Text, arg: &str) -> String {
+    trf_all(locale, text, &[arg])
+}
+
+/// The `text` in the language of the `locale`, with each `{}` replaced by the next of the `args`.
+pub fn trf_all(locale: Locale, text: Text, args: &[&str]) -> String {
+    args.iter().fold(tr(locale, text).to_string(), |text, arg| {
+        text.replacen("{}", arg, 1)
+    })
+}
+
+/// [`Text::ItemCount`] of `count` items in the language of the `locale`, [`Text::OneItem`] for one.
+pub fn item_count(locale: Locale, count: usize) -> String {
+    match count {
+        1 => tr(locale, Text::OneItem).to_string(),
+        count => trf(locale, Text::ItemCount, &count.to_string()),
+    }
+}
+
+/// The `text` in English, French, Spanish and Japanese.
+fn translations(text: Text) -> [&'static str; 4] {
+    match text {
+        Text::TitleNotFound => [
+            "Title not found",
+            "Titre introuvable",
+            "Título no encontrado",
+            "タイトルが見つかりません",
+        ],
+        Text::MyWatchlist => ["My Watchlist", "Ma liste", "Mi lista", "マイリスト"],
+        Text::ContinueWatching => [
+            "Continue Watching",
+            "Reprendre la lecture",
+            "Seguir viendo",
+            "続きを見る",
+        ],
+        Text::ContinueWatchingHint => [
+            "Titles you open show up here",
+            "Les titres que vous ouvrez apparaissent ici",
+            "Los títulos que abras aparecen aquí",
+            "開いたタイトルがここに表示されます",
+        ],
+        Text::WatchlistHint => [
+            "Press W on a title to add it to your watchlist",
+            "Appuyez sur W sur un titre pour l'ajouter à votre liste",
+            "Pulsa W en un título para añadirlo a tu lista",
+            "タイトルでWを押すとマイリストに追加されます",
+        ],
+        Text::NothingHereYet => [
+            "Nothing here yet",
+            "Rien ici pour l'instant",
+            "Nada por aquí todavía",
+            "まだ何もありません",
+        ],
+        Text::Loading => ["Loading…", "Chargement…", "Cargando…", "読み込み中…"],
+        Text::EnterPin => [
+            "Enter the parental PIN",
+            "Saisissez le code parental",
+            "Introduce el PIN parental",
+            "ペアレンタルPINを入力してください",
+        ],
+        Text::WrongPin => [
+            "Wrong PIN",
+            "Code incorrect",
+            "PIN incorrecto",
+            "PINが違います",
+        ],
+        Text::EscapeToCancel => [
+            "Escape to cancel",
+            "Échap pour annuler",
+            "Escape para cancelar",
+            "Escapeでキャンセル",
+        ],
+        Text::SearchPrompt => ["Search: {}_", "Recherche : {}_", "Buscar: {}_", "検索: {}_"],
+        Text::NoResults => [
+            "No results for \"{}\"",
+            "Aucun résultat pour « {} »",
+            "No hay resultados para \"{}\"",
+            "「{}」の検索結果はありません",
+        ],
+        Text::WhoIsWatching => [
+            "Who's watching?",
+            "Qui regarde ?",
+            "¿Quién está viendo?",
+            "誰が視聴しますか？",
+        ],
+        Text::AddProfile => [
+            "+ Add profile",
+            "+ Ajouter un profil",
+            "+ Añadir perfil",
+            "+ プロフィールを追加",
+        ],
+        Text::Settings => ["Settings", "Paramètres", "Ajustes", "設定"],
+        Text::SettingsHint => [
+            "Left/Right to change, Escape to save and close",
+            "Gauche/Droite pour changer, Échap pour enregistrer et fermer",
+            "Izquierda/Derecha para cambiar, Escape para guardar y cerrar",
+            "左右で変更、Escapeで保存して閉じる",
+        ],
+        Text::Animations => ["Animations", "Animations", "Animaciones", "アニメーション"],
+        Text::ImageQuality => [
+            "Image quality",
+            "Qualité d'image",
+            "Calidad de imagen",
+            "画質",
+        ],
+        Text::RowDensity => [
+            "Row density",
+            "Densité des rangées",
+            "Densidad de filas",
+            "行の密度",
+        ],
+        Text::Theme => ["Theme", "Thème", "Tema", "テーマ"],
+        Text::Sounds => ["Sounds", "Sons", "Sonidos", "効果音"],
+        Text::On => ["On", "Activées", "Activadas", "オン"],
+        Text::Off => ["Off", "Désactivées", "Desactivadas", "オフ"],
+        Text::Low => ["Low", "Basse", "Baja", "低"],
+        Text::Medium => ["Medium", "Moyenne", "Media", "中"],
+        Text::High => ["High", "Haute", "Alta", "高"],
+        Text::Spacious => ["Spacious", "Aérée", "Amplia", "ゆったり"],
+        Text::Comfortable => ["Comfortable", "Confortable", "Cómoda", "標準"],
+        Text::Compact => ["Compact", "Compacte", "Compacta", "コンパクト"],
+        Text::Custom => [
+            "Custom ({})",
+            "Personnalisée ({})",
+            "Personalizada ({})",
+            "カスタム ({})",
+        ],
+        Text::ItemCount => ["({} items)", "({} éléments)", "({} elementos)", "({}件)"],
+        Text::OneItem => ["(1 item)", "(1 élément)", "(1 elemento)", "(1件)"],
+        Text::RowPosition => [
+            "Row {} of {}",
+            "Rangée {} sur {}",
+            "Fila {} de {}",
+            "{} / {} 行目",
+        ],
+        Text::Dark => ["Dark", "Sombre", "Oscuro", "ダーク"],
+        Text::Light => ["Light", "Clair", "Claro", "ライト"],
+        Text::SortBy => ["Sort", "Tri", "Orden", "並べ替え"],
+        Text::Show => ["Show", "Afficher", "Mostrar", "表示"],
+        Text::DefaultOrder => ["Default", "Par défaut", "Predeterminado", "標準"],
+        Text::ByTitle => ["A to Z", "De A à Z", "De la A a la Z", "タイトル順"],
+        Text::NewestFirst => [
+            "Newest first",
+            "Plus récents d'abord",
+            "Más recientes primero",
+            "新しい順",
+        ],
+        Text::Everything => ["Everything", "Tout", "Todo", "すべて"],
+        Text::Series => ["Series", "Séries", "Series", "シリーズ"],
+        Text::Movies => ["Movies", "Films", "Películas", "映画"],
+        Text::FilterBarHint => [
+            "Up/Down to switch, Left/Right to change, Escape to close",
+            "Haut/Bas pour passer, Gauche/Droite pour changer, Échap pour fermer",
+            "Arriba/Abajo para pasar, Izquierda/Derecha para cambiar, Escape para cerrar",
+            "上下で切り替え、左右で変更、Escapeで閉じる",
+        ],
+        Text::NothingMatches => [
+            "Nothing matches the filter, press F to change it",
+            "Rien ne correspond au filtre, appuyez sur F pour le changer",
+            "Nada coincide con el filtro, pulsa F para cambiarlo",
+            "フィルターに一致するものはありません。Fで変更できます",
+        ],
+    }
+}
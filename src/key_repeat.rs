@@ -0,0 +1,95 @@
+//! Hold-to-repeat for the keys moving the focus, the arrow keys unless they were remapped, see
+//! [`KeyMap::repeats`](crate::keymap::KeyMap::repeats).
+//!
+//! The OS key repeat is ignored, repeats are driven by the loop instead: once such a key has been
+//! held for `delay` it fires again every `interval`, the interval shrinking with every repeat down
+//! to `min_interval` so holding Right skims across a long row.
+use crate::event_loop::VirtualKeyCode;
+use std::time::{Duration, Instant};
+
+/// Each repeat waits this fraction of the previous wait.
+const ACCELERATION: f64 = 0.8;
+
+/// Key being repeated.
+#[derive(Clone, Copy, Debug)]
+struct Held {
+    key: VirtualKeyCode,
+    /// When it fires next.
+    next: Instant,
+    /// Wait after the next repeat, before accelerating.
+    interval: Duration,
+}
+
+/// Tracks which keys are down and when the held key is due to repeat.
+#[derive(Debug)]
+pub struct KeyRepeat {
+    delay: Duration,
+    interval: Duration,
+    min_interval: Duration,
+    /// Keys currently down, to tell a new press from the OS repeating it.
+    down: Vec<VirtualKeyCode>,
+    held: Option<Held>,
+}
+
+impl KeyRepeat {
+    /// Constructor.
+    /// # Arguments
+    /// * `delay` - How long a key is held before it starts repeating.
+    /// * `interval` - Time between the first two repeats.
+    /// * `min_interval` - Shortest time between two repeats.
+    pub fn new(delay: Duration, interval: Duration, min_interval: Duration) -> Self {
+        Self {
+            delay,
+            interval,
+            min_interval: min_interval.min(interval),
+            down: Vec::new(),
+            held: None,
+        }
+    }
+
+    /// Records `key` going down at `now`, it's repeated while held if `repeats`. Returns false if it
+    /// was already down, i.e. the event is the OS repeating it and should be ignored.
+    pub fn press(&mut self, key: VirtualKeyCode, repeats: bool, now: Instant) -> bool {
+        if self.down.contains(&key) {
+            return false;
+        }
+        self.down.push(key);
+        // The last key pressed wins, like on the gamepad D-pad.
+        if repeats {
+            self.held = Some(Held {
+                key,
+                next: now + self.delay,
+                interval: self.interval,
+            });
+        }
+        true
+    }
+
+    /// Records `key` going up.
+    pub fn release(&mut self, key: VirtualKeyCode) {
+        self.down.retain(|k| *k != key);
+        if self.held.map_or(false, |held| held.key == key) {
+            self.held = None;
+        }
+    }
+
+    /// Forgets every key, e.g. when the window loses the focus and the releases won't be seen.
+    pub fn release_all(&mut self) {
+        self.down.clear();
+        self.held = None;
+    }
+
+    /// True while a repeating key is held, the loop has to keep polling for [`KeyRepeat::poll`] to fire.
+    pub fn is_repeating(&self) -> bool {
+        self.held.is_some()
+    }
+
+    /// The held key if it's due to repeat at `now`. Meant to be called once per loop.
+    pub fn poll(&mut self, now: Instant) -> Option<VirtualKeyCode> {
+        let min_interval = self.min_interval;
+        let held = self.held.as_mut().filter(|held| now >= held.next)?;
+        held.next = now + held.interval;
+        held.interval = held.interval.mul_f64(ACCELERATION).max(min_interval);
+        Some(held.key)
+    }
+}
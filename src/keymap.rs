@@ -0,0 +1,167 @@
+//! Which keys trigger which [`Action`].
+//!
+//! The defaults are the arrow keys, `Enter`, `Escape` and the letters listed on each [`Action`].
+//! They can be changed with a `keys.toml` file next to the config file, e.g.
+//! `~/.config/helloplus/keys.toml`, listing the keys of some actions. The actions missing from the
+//! file keep their default keys, the ones in it only get the keys listed:
+//! ```toml
+//! # vim-style
+//! left = ["Left", "H"]
+//! down = ["Down", "J"]
+//! up = ["Up", "K"]
+//! right = ["Right", "L"]
+//! # TV remote
+//! select = ["Return", "MediaSelect"]
+//! back = ["Back", "WebBack"]
+//! ```
+//! The actions are named after the [`Action`]s in snake case, e.g. `toggle_watchlist`, and the keys
+//! after the variants of [`VirtualKeyCode`], see [`KEYS`] for the ones that can be bound. A key
+//! bound to a letter still types it on the search screen.
+//!
+//! F3, F11 and Alt+Enter are not actions, they toggle the [`debug_hud`](crate::debug_hud) and the
+//! [`WindowMode`](crate::config::WindowMode) whatever the bindings.
+use crate::config::{self, ConfigError};
+use crate::event_loop::VirtualKeyCode;
+use crate::screens::Action;
+use std::collections::HashMap;
+use std::{fs, io};
+
+/// Lists the keys along with their names, which are the same as the variants.
+macro_rules! keys {
+    ($($key:ident),* $(,)?) => {
+        &[$((stringify!($key), VirtualKeyCode::$key)),*]
+    };
+}
+
+/// The keys that can be bound, with the names they go by in `keys.toml`.
+#[rustfmt::skip]
+pub const KEYS: &[(&str, VirtualKeyCode)] = keys![
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9,
+    Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+    F1, F2, F4, F5, F6, F7, F8, F9, F10, F12,
+    Left, Right, Up, Down, Home, End, PageUp, PageDown, Insert, Delete,
+    Return, NumpadEnter, Escape, Back, Space, Tab,
+    Slash, Backslash, Comma, Period, Minus, Equals, Semicolon, Apostrophe, LBracket, RBracket,
+    MediaSelect, PlayPause, Stop, NextTrack, PrevTrack,
+    WebBack, WebForward, WebHome, WebSearch, NavigateBackward, NavigateForward,
+];
+
+/// The actions that can be bound, with the names they go by in `keys.toml` and their default keys.
+const DEFAULT_BINDINGS: &[(&str, Action, &[VirtualKeyCode])] = &[
+    ("left", Action::Left, &[VirtualKeyCode::Left]),
+    ("right", Action::Right, &[VirtualKeyCode::Right]),
+    ("up", Action::Up, &[VirtualKeyCode::Up]),
+    ("down", Action::Down, &[VirtualKeyCode::Down]),
+    ("page_up", Action::PageUp, &[VirtualKeyCode::PageUp]),
+    ("page_down", Action::PageDown, &[VirtualKeyCode::PageDown]),
+    ("first", Action::First, &[VirtualKeyCode::Home]),
+    ("last", Action::Last, &[VirtualKeyCode::End]),
+    ("select", Action::Select, &[VirtualKeyCode::Return]),
+    ("escape", Action::Escape, &[VirtualKeyCode::Escape]),
+    ("back", Action::Back, &[]),
+    ("backspace", Action::Backspace, &[VirtualKeyCode::Back]),
+    ("search", Action::Search, &[VirtualKeyCode::Slash]),
+    (
+        "toggle_watchlist",
+        Action::ToggleWatchlist,
+        &[VirtualKeyCode::W],
+    ),
+    ("parental", Action::Parental, &[VirtualKeyCode::P]),
+    ("settings", Action::Settings, &[VirtualKeyCode::S]),
+    ("see_all", Action::SeeAll, &[VirtualKeyCode::G]),
+    ("filter", Action::Filter, &[VirtualKeyCode::F]),
+    ("play", Action::Play, &[VirtualKeyCode::PlayPause]),
+];
+
+/// The [`Action`] of each bound key.
+#[derive(Clone, Debug)]
+pub struct KeyMap {
+    actions: HashMap<VirtualKeyCode, Action>,
+}
+
+impl Default for KeyMap {
+    /// The default keys of every action.
+    fn default() -> Self {
+        let actions = DEFAULT_BINDINGS
+            .iter()
+            .flat_map(|(_, action, keys)| keys.iter().map(move |key| (*key, *action)))
+            .collect();
+        Self { actions }
+    }
+}
+
+impl KeyMap {
+    /// The default keys, with the actions found in `keys.toml` bound to the keys it lists instead.
+    pub fn load() -> Result<Self, ConfigError> {
+        let path = match config::config_dir() {
+            Some(dir) => dir.join("keys.toml"),
+            None => return Ok(Self::default()),
+        };
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(ConfigError::Io(path, e)),
+        };
+        let bindings: HashMap<String, Vec<String>> =
+            toml::from_str(&contents).map_err(|e| ConfigError::Parse(path, e))?;
+        Self::with_bindings(&bindings)
+    }
+
+    /// The default keys, with each action of `bindings` bound to the keys it lists instead. A key
+    /// listed in `bindings` is taken away from the action it's bound to by default.
+    fn with_bindings(bindings: &HashMap<String, Vec<String>>) -> Result<Self, ConfigError> {
+        let invalid = |key: &str, value: &str| ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        if let Some(name) = bindings
+            .keys()
+            .find(|name| !DEFAULT_BINDINGS.iter().any(|(n, _, _)| *n == name.as_str()))
+        {
+            return Err(invalid("keys.toml", name));
+        }
+        let mut actions = HashMap::new();
+        for (name, action, default_keys) in DEFAULT_BINDINGS {
+            if !bindings.contains_key(*name) {
+                actions.extend(default_keys.iter().map(|key| (*key, *action)));
+            }
+        }
+        for (name, action, _) in DEFAULT_BINDINGS {
+            for key_name in bindings.get(*name).into_iter().flatten() {
+                let key = key_named(key_name).ok_or_else(|| invalid(name, key_name))?;
+                actions.insert(key, *action);
+            }
+        }
+        Ok(Self { actions })
+    }
+
+    /// The action bound to the `key`, if any.
+    pub fn action(&self, key: VirtualKeyCode) -> Option<Action> {
+        self.actions.get(&key).copied()
+    }
+
+    /// Returns true if holding the `key` repeats its action, see [`KeyRepeat`](crate::key_repeat::KeyRepeat).
+    /// Only the keys moving the focus around do.
+    pub fn repeats(&self, key: VirtualKeyCode) -> bool {
+        matches!(
+            self.action(key),
+            Some(Action::Left | Action::Right | Action::Up | Action::Down)
+        )
+    }
+}
+
+/// The action going by `name` in `keys.toml`, e.g. `page_down`.
+pub fn action_named(name: &str) -> Option<Action> {
+    DEFAULT_BINDINGS
+        .iter()
+        .find(|(action_name, _, _)| *action_name == name)
+        .map(|(_, action, _)| *action)
+}
+
+/// The key going by `name` in `keys.toml`, ignoring case.
+fn key_named(name: &str) -> Option<VirtualKeyCode> {
+    KEYS.iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|(_, key)| *key)
+}
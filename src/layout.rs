@@ -0,0 +1,266 @@
+//! Sizes used to place the rows, derived from the [`Config`] and the window size.
+//!
+//! At the configured window size the [`Layout`] matches the [`Config`] as is. Once the window is
+//! resized every pixel size is scaled by the width ratio and the number of rows/tiles is recomputed
+//! so they fill the window, see [`Layout::for_window`].
+//!
+//! Rows showing posters get their own [`Layout`], with narrower and taller tiles, see [`TileShape`].
+//! So do the rows of brands and the hero rows, see [`RowStyle`].
+use crate::config::{Config, PosterRows};
+use api::SetData;
+
+/// Margin to space out the thumbnails. Used to the left and right of the images.
+const ITEMS_MARGIN: f64 = 20.0;
+/// Width of the tile artwork, before applying the `image_scale_down_factor`.
+const IMAGE_WIDTH: f64 = 500.0;
+const IMAGE_WIDTH_PLUS_MARGIN: f64 = IMAGE_WIDTH + 15.0;
+/// Width of the poster artwork, before applying the `image_scale_down_factor`.
+const POSTER_IMAGE_WIDTH: f64 = 260.0;
+const POSTER_IMAGE_WIDTH_PLUS_MARGIN: f64 = POSTER_IMAGE_WIDTH + 15.0;
+/// Content classes of the sets shown as posters with [`PosterRows::Auto`], matched as substrings.
+const POSTER_CONTENT_CLASSES: [&str; 2] = ["poster", "character"];
+/// Width of the brand tiles, before applying the `image_scale_down_factor`.
+const BRAND_IMAGE_WIDTH: f64 = 300.0;
+/// Smallest space between two brand tiles, before applying the `image_scale_down_factor`.
+const BRAND_MIN_GAP: f64 = 60.0;
+/// Width of the tiles of the hero rows, before applying the `image_scale_down_factor`.
+const HERO_IMAGE_WIDTH: f64 = 800.0;
+const HERO_IMAGE_WIDTH_PLUS_MARGIN: f64 = HERO_IMAGE_WIDTH + 15.0;
+/// Styles and content classes of the sets shown as [`RowStyle::Brands`], matched as substrings.
+const BRAND_STYLES: [&str; 1] = ["brand"];
+/// Styles and content classes of the sets shown as [`RowStyle::Hero`], matched as substrings.
+const HERO_STYLES: [&str; 1] = ["hero"];
+/// The navigation needs a few tiles per row, see [`Config::row_stride`].
+const MIN_ROW_STRIDE: usize = 4;
+const ROW_TOP_MARGIN: f64 = 70.0;
+const ROW_HEIGHT: f64 = 290.0;
+/// Bounds of the ratio used to scale the pixel sizes so the tiles don't get unreadable or huge.
+const MIN_SCALE: f64 = 0.5;
+const MAX_SCALE: f64 = 1.5;
+
+/// Pixel sizes and counts used to lay out the rows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Layout {
+    /// Number of visible rows, see [`Config::num_rows`].
+    pub num_rows: usize,
+    /// Number of tiles drawn for a given row, see [`Config::row_stride`].
+    pub row_stride: usize,
+    /// Factor applied to the fetched artwork to get the size of the tiles.
+    pub image_scale_down_factor: f64,
+    /// Factor applied to the focused tile so it looks larger.
+    pub image_scale_up_factor: f64,
+    /// Margin to space out the thumbnails. Used to the left and right of the images.
+    pub items_margin: f64,
+    /// Width of a tile, before applying the `image_scale_down_factor`.
+    pub image_width: f64,
+    /// Width of a tile plus the space between tiles, before applying the `image_scale_down_factor`.
+    pub image_width_plus_margin: f64,
+    /// Space above the first row.
+    pub row_top_margin: f64,
+    /// Height of a row, including its title.
+    pub row_height: f64,
+    /// Which rows use the [`TileShape::Poster`] layout.
+    pub poster_rows: PosterRows,
+}
+
+/// Shape of the tiles of a row.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TileShape {
+    /// Wide tiles, the artwork closest to the [`Config::tile_aspect_ratio`].
+    Landscape,
+    /// Tall tiles showing the [`POSTER_ASPECT_RATIO`](api::POSTER_ASPECT_RATIO) artwork.
+    Poster,
+}
+
+impl TileShape {
+    /// The shape of the row showing `set_data` as per the `poster_rows` setting.
+    ///
+    /// With [`PosterRows::Auto`] posters are used for the sets whose content class matches one of the
+    /// [`POSTER_CONTENT_CLASSES`], provided their first item has poster artwork.
+    pub fn for_set(set_data: &SetData, poster_rows: PosterRows) -> Self {
+        let poster = match poster_rows {
+            PosterRows::Always => true,
+            PosterRows::Never => false,
+            PosterRows::Auto => {
+                let class = set_data.get_content_class().unwrap_or("").to_lowercase();
+                POSTER_CONTENT_CLASSES.iter().any(|c| class.contains(c))
+                    && set_data
+                        .get_tile_aspect_ratios(0)
+                        .iter()
+                        .any(|ar| (ar - api::POSTER_ASPECT_RATIO).abs() < 0.01)
+            }
+        };
+        if poster {
+            TileShape::Poster
+        } else {
+            TileShape::Landscape
+        }
+    }
+}
+
+/// How a row is laid out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RowStyle {
+    /// Regular tiles of the given shape.
+    Standard(TileShape),
+    /// Smaller tiles (Disney, Pixar, Marvel...) evenly spaced across the window, the focused one glows.
+    Brands,
+    /// Larger tiles, a few per row.
+    Hero,
+}
+
+impl RowStyle {
+    /// The style of the row showing `set_data`, from the `style` of its container or its content
+    /// class. Rows that are neither brands nor heroes are [`RowStyle::Standard`], with the [`TileShape`]
+    /// picked as per the `poster_rows` setting.
+    pub fn for_set(set_data: &SetData, poster_rows: PosterRows) -> Self {
+        let style = set_data.get_style().unwrap_or("").to_lowercase();
+        let class = set_data.get_content_class().unwrap_or("").to_lowercase();
+        let matches = |styles: &[&str]| {
+            styles
+                .iter()
+                .any(|s| style.contains(s) || class.contains(s))
+        };
+        if matches(&BRAND_STYLES) {
+            RowStyle::Brands
+        } else if matches(&HERO_STYLES) {
+            RowStyle::Hero
+        } else {
+            RowStyle::Standard(TileShape::for_set(set_data, poster_rows))
+        }
+    }
+
+    /// The [`TileShape`] of the row, the artwork is picked accordingly.
+    pub fn shape(self) -> TileShape {
+        match self {
+            RowStyle::Standard(shape) => shape,
+            RowStyle::Brands | RowStyle::Hero => TileShape::Landscape,
+        }
+    }
+}
+
+impl Layout {
+    /// The layout for the window size in the `config`.
+    pub fn new(config: &Config) -> Self {
+        Self {
+            num_rows: config.num_rows,
+            row_stride: config.row_stride,
+            image_scale_down_factor: config.image_scale_down_factor,
+            image_scale_up_factor: config.image_scale_up_factor,
+            items_margin: ITEMS_MARGIN,
+            image_width: IMAGE_WIDTH,
+            image_width_plus_margin: IMAGE_WIDTH_PLUS_MARGIN,
+            row_top_margin: ROW_TOP_MARGIN,
+            row_height: ROW_HEIGHT,
+            poster_rows: config.poster_rows,
+        }
+    }
+
+    /// The layout for a window of `width` x `height` pixels.
+    ///
+    /// Pixel sizes are scaled by `width` over [`Config::display_width`]. The number of rows is the
+    /// amount of (even partially) visible rows and the row stride is the amount of visible tiles
+    /// plus one kept out of view.
+    pub fn for_window(config: &Config, width: f64, height: f64) -> Self {
+        if width as u32 == config.display_width && height as u32 == config.display_height {
+            return Self::new(config);
+        }
+        let scale = (width / config.display_width as f64)
+            .max(MIN_SCALE)
+            .min(MAX_SCALE);
+        let image_scale_down_factor = config.image_scale_down_factor * scale;
+        let items_margin = ITEMS_MARGIN * scale;
+        let row_top_margin = ROW_TOP_MARGIN * scale;
+        let row_height = ROW_HEIGHT * scale;
+
+        let tile_w = IMAGE_WIDTH_PLUS_MARGIN * image_scale_down_factor;
+        let visible_tiles = ((width - items_margin) / tile_w).ceil().max(0.0) as usize;
+        let visible_rows = ((height - row_top_margin) / row_height).ceil().max(0.0) as usize;
+
+        Self {
+            num_rows: visible_rows.max(2),
+            row_stride: (visible_tiles + 1).max(MIN_ROW_STRIDE),
+            image_scale_down_factor,
+            image_scale_up_factor: config.image_scale_up_factor,
+            items_margin,
+            image_width: IMAGE_WIDTH,
+            image_width_plus_margin: IMAGE_WIDTH_PLUS_MARGIN,
+            row_top_margin,
+            row_height,
+            poster_rows: config.poster_rows,
+        }
+    }
+
+    /// Size of a tile showing artwork of the given `aspect_ratio` (width over height).
+    pub fn tile_size(&self, aspect_ratio: f64) -> (f64, f64) {
+        let w = self.image_width * self.image_scale_down_factor;
+        (w, w / aspect_ratio)
+    }
+
+    /// The layout of a row of `shape` tiles.
+    ///
+    /// Poster rows are taller by the difference in height between a poster and a regular tile,
+    /// and hold more tiles so they cover the same width.
+    pub fn for_shape(&self, shape: TileShape) -> Self {
+        match shape {
+            TileShape::Landscape => *self,
+            TileShape::Poster => self.resized(
+                POSTER_IMAGE_WIDTH,
+                POSTER_IMAGE_WIDTH_PLUS_MARGIN,
+                api::POSTER_ASPECT_RATIO,
+            ),
+        }
+    }
+
+    /// The layout of a row of the given `style`.
+    ///
+    /// Like for the posters the brand and hero rows cover the same width as the regular rows, with
+    /// a height following the one of their tiles. The brand tiles are spread evenly over that width.
+    pub fn for_style(&self, style: RowStyle) -> Self {
+        match style {
+            RowStyle::Standard(shape) => self.for_shape(shape),
+            RowStyle::Brands => {
+                let width = (self.row_stride - 1) as f64 * self.image_width_plus_margin;
+                let visible = (width / (BRAND_IMAGE_WIDTH + BRAND_MIN_GAP))
+                    .floor()
+                    .max(1.0);
+                self.resized(
+                    BRAND_IMAGE_WIDTH,
+                    width / visible,
+                    api::DEFAULT_TILE_ASPECT_RATIO,
+                )
+            }
+            RowStyle::Hero => self.resized(
+                HERO_IMAGE_WIDTH,
+                HERO_IMAGE_WIDTH_PLUS_MARGIN,
+                api::DEFAULT_TILE_ASPECT_RATIO,
+            ),
+        }
+    }
+
+    /// This layout with tiles of `image_width` showing artwork of `aspect_ratio`, `image_width_plus_margin`
+    /// apart. The row holds as many tiles as needed to cover the same width, and its height changes
+    /// by the difference in height between these tiles and the regular ones.
+    fn resized(&self, image_width: f64, image_width_plus_margin: f64, aspect_ratio: f32) -> Self {
+        let resized = Self {
+            image_width,
+            image_width_plus_margin,
+            row_stride: ((self.row_stride as f64 * self.image_width_plus_margin
+                / image_width_plus_margin)
+                .ceil() as usize)
+                .max(MIN_ROW_STRIDE),
+            ..*self
+        };
+        let (_, tile_h) = self.tile_size(api::DEFAULT_TILE_ASPECT_RATIO as f64);
+        let (_, resized_h) = resized.tile_size(aspect_ratio as f64);
+        Self {
+            row_height: self.row_height + resized_h - tile_h,
+            ..resized
+        }
+    }
+
+    /// Total number of tiles drawn for the rows in view.
+    pub fn num_of_cached_images(&self) -> usize {
+        self.num_rows * self.row_stride
+    }
+}
@@ -0,0 +1,580 @@
+//! Background image loading.
+//!
+//! Fetching and decoding the artwork is the slowest part of drawing a row so it's done off the main
+//! thread: a small pool of worker threads waits on the network, and hands the bytes over to a
+//! [`rayon`] pool decoding them into RGBA buffers ready to be uploaded. The tiles are also resized
+//! there to the size they are drawn at, see [`LoadRequest::size`], the focused one getting its own
+//! larger copy (see [`LoadRequest::focused`]). The tiles not cached yet first get a blurred preview,
+//! see [`LoadRequest::with_preview`]. The [`DisplayController`](crate::DisplayController) submits
+//! [`LoadRequest`]s and polls for [`LoadResult`]s every loop, which the results wake up (see
+//! [`ImageLoader::wake_with`]). The texture upload itself still happens on the main thread since
+//! the glium [`Display`](conrod::glium::Display) can't be shared.
+//!
+//! Requests no longer wanted, e.g. the tiles of the rows scrolled past, can be
+//! [cancelled](ImageLoader::cancel). The ones still queued are then skipped, the ones being
+//! fetched aren't decoded and nothing comes back for either of them.
+//!
+//! The workers don't take the requests in the order they came but closest to the cursor first,
+//! see [`LoadFocus`]: the focused tile, then the other tiles in view outward from it, then the
+//! tiles of the rows out of view. The other kinds of images come before the tiles, there is only
+//! one or two of them at a time and they are what the user is looking at.
+use crate::ambient;
+use crate::event_loop::Waker;
+use crate::helpers;
+use api::{ApiError, ImageCache, UrlBuilder};
+use image::RgbaImage;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use tracing::{debug, info, info_span};
+
+/// Width of the previews, see [`LoadRequest::with_preview`].
+const PREVIEW_WIDTH: u32 = 32;
+/// Jpeg quality of the previews, see [`LoadRequest::with_preview`].
+const PREVIEW_QUALITY: u8 = 50;
+/// Blur applied to the previews so they don't look blocky once stretched to the size of the tile.
+const PREVIEW_BLUR: f32 = 1.5;
+
+/// Number of worker threads fetching images.
+const NUM_OF_WORKERS: usize = 4;
+/// Number of threads decoding the fetched images.
+const NUM_OF_DECODERS: usize = 2;
+/// Textures uploaded per frame at most, see [`UploadBudget`].
+const UPLOADS_PER_FRAME: usize = 4;
+/// Bytes uploaded per frame past which the next uploads wait for the next frame, see [`UploadBudget`].
+const UPLOAD_BYTES_PER_FRAME: usize = 4 * 1024 * 1024;
+
+/// What the loaded image is going to be used for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LoadKind {
+    /// A thumbnail in one of the rows.
+    Tile,
+    /// The large artwork of the detail page.
+    Hero,
+    /// The logo of the title drawn over the [`Hero`](LoadKind::Hero) artwork.
+    Logo,
+    /// The artwork of the billboard banner.
+    Banner,
+    /// A thumbnail in the search results.
+    Search,
+    /// A tile of the see-all grid, see [`GridScreen`](crate::grid::GridScreen).
+    Grid,
+    /// The artwork shown full screen, see [`Screensaver`](crate::screensaver::Screensaver).
+    Screensaver,
+    /// The artwork of the focused tile at the size it's drawn at once scaled up, see
+    /// [`LoadRequest::focused`].
+    Focus,
+}
+
+/// Identifies a single image to load.
+pub struct LoadRequest {
+    /// What the image is for.
+    pub kind: LoadKind,
+    /// Index of the set this tile belongs to.
+    pub true_set_idx: usize,
+    /// Index of the item within the set.
+    pub true_item_idx: usize,
+    /// Url of the artwork.
+    pub url: String,
+    /// Size the image is drawn at, in pixels. It's resized to it once decoded rather than scaled
+    /// when drawn, the GPU only samples the base level of the textures which makes the tiles shrunk
+    /// by the `image_scale_down_factor` shimmer. None to keep the image as it comes.
+    pub size: Option<(u32, u32)>,
+    /// Url of a low resolution variant of the artwork, see [`LoadRequest::with_preview`].
+    pub preview_url: Option<String>,
+}
+
+impl LoadRequest {
+    /// Fetches a [`PREVIEW_WIDTH`] wide variant of the artwork first, delivered blurred as a
+    /// [`preview`](LoadResult::preview) result before the artwork itself. The preview is skipped if
+    /// the artwork is already cached, when offline, or when the artwork is read from disk (see
+    /// [`FolderProvider`](api::FolderProvider)), there being no smaller variant of it then.
+    pub fn with_preview(self) -> Self {
+        let preview_url = UrlBuilder::new(&self.url)
+            .width(PREVIEW_WIDTH)
+            .quality(PREVIEW_QUALITY)
+            .build();
+        Self {
+            preview_url: Some(preview_url),
+            ..self
+        }
+    }
+
+    /// Turns the request of a tile into the one of its [`LoadKind::Focus`] artwork, `scale` times
+    /// larger. The artwork is asked for at that width rather than the tile being stretched, which
+    /// blurs it. The artwork read from disk is only resized, see [`api::is_file_url`].
+    pub fn focused(self, scale: f64) -> Self {
+        let size = self.size.map(|(w, h)| {
+            (
+                (w as f64 * scale).round() as u32,
+                (h as f64 * scale).round() as u32,
+            )
+        });
+        let url = match size {
+            Some((w, _)) if !api::is_file_url(&self.url) => {
+                UrlBuilder::new(&self.url).width(w).build()
+            }
+            _ => self.url,
+        };
+        Self {
+            kind: LoadKind::Focus,
+            url,
+            size,
+            preview_url: None,
+            ..self
+        }
+    }
+
+    fn into_result(self, img: Result<RgbaImage, ApiError>) -> LoadResult {
+        LoadResult {
+            kind: self.kind,
+            true_set_idx: self.true_set_idx,
+            true_item_idx: self.true_item_idx,
+            url: self.url,
+            img,
+            dominant_color: None,
+            preview: false,
+        }
+    }
+
+    fn preview_result(&self, img: RgbaImage) -> LoadResult {
+        LoadResult {
+            kind: self.kind,
+            true_set_idx: self.true_set_idx,
+            true_item_idx: self.true_item_idx,
+            url: self.url.clone(),
+            dominant_color: ambient::dominant_color(&img),
+            img: Ok(img),
+            preview: true,
+        }
+    }
+}
+
+/// Outcome of a [`LoadRequest`].
+pub struct LoadResult {
+    /// What the image is for.
+    pub kind: LoadKind,
+    /// Index of the set this tile belongs to.
+    pub true_set_idx: usize,
+    /// Index of the item within the set.
+    pub true_item_idx: usize,
+    /// Url the image was fetched from. Lets the receiver check the slot still shows the same item.
+    pub url: String,
+    /// The decoded image, rows flipped for the upload (see [`helpers::to_gl_rgba`]), or the reason
+    /// it couldn't be loaded, see [`ApiError::is_retryable`].
+    pub img: Result<RgbaImage, ApiError>,
+    /// Dominant color of the tiles, see [`ambient::dominant_color`]. None for the other kinds.
+    pub dominant_color: Option<[f32; 3]>,
+    /// True for the preview of a request made [`with_preview`](LoadRequest::with_preview). The
+    /// artwork itself comes in a later result.
+    pub preview: bool,
+}
+
+/// What's left to upload in the current frame. Uploading a lot of textures at once, e.g. a whole
+/// row coming in while scrolling fast, takes longer than a frame, past the budget the uploads wait
+/// for the next frame.
+#[derive(Debug)]
+pub struct UploadBudget {
+    textures: usize,
+    bytes: usize,
+}
+
+impl UploadBudget {
+    /// The budget of a whole frame.
+    pub fn per_frame() -> Self {
+        Self {
+            textures: UPLOADS_PER_FRAME,
+            bytes: UPLOAD_BYTES_PER_FRAME,
+        }
+    }
+
+    /// Returns true once nothing more should be uploaded this frame.
+    pub fn is_spent(&self) -> bool {
+        self.textures == 0 || self.bytes == 0
+    }
+
+    /// Takes the upload of `img` out of the budget. The last one may go over it.
+    pub fn spend(&mut self, img: &RgbaImage) {
+        self.textures = self.textures.saturating_sub(1);
+        self.bytes = self.bytes.saturating_sub(img.as_raw().len());
+    }
+}
+
+/// Shared between the [`ImageLoader`] and the threads working on a request, set once the request
+/// is [cancelled](ImageLoader::cancel).
+#[derive(Clone, Debug, Default)]
+struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Keeps track of the requests that were submitted but haven't come back yet.
+#[derive(Default)]
+pub struct ImgLoadingNotifier {
+    in_flight: HashMap<(LoadKind, usize, usize), CancelToken>,
+}
+
+impl ImgLoadingNotifier {
+    /// Returns true if the image of `kind` for `true_set_idx`, `true_item_idx` is currently being loaded.
+    pub fn is_in_flight(&self, kind: LoadKind, true_set_idx: usize, true_item_idx: usize) -> bool {
+        self.in_flight
+            .contains_key(&(kind, true_set_idx, true_item_idx))
+    }
+
+    /// Returns true if any image of `kind` for the row `true_set_idx` is currently being loaded.
+    pub fn is_row_in_flight(&self, kind: LoadKind, true_set_idx: usize) -> bool {
+        self.in_flight
+            .keys()
+            .any(|(k, set_idx, _)| *k == kind && *set_idx == true_set_idx)
+    }
+
+    /// Number of requests still pending.
+    pub fn in_flight_count(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    fn started(
+        &mut self,
+        kind: LoadKind,
+        true_set_idx: usize,
+        true_item_idx: usize,
+    ) -> CancelToken {
+        let token = CancelToken::default();
+        self.in_flight
+            .insert((kind, true_set_idx, true_item_idx), token.clone());
+        token
+    }
+
+    fn finished(&mut self, kind: LoadKind, true_set_idx: usize, true_item_idx: usize) {
+        self.in_flight.remove(&(kind, true_set_idx, true_item_idx));
+    }
+}
+
+/// Where the cursor is, to pick which request is loaded next. See [`ImageLoader::focus`].
+#[derive(Clone, Debug, Default)]
+pub struct LoadFocus {
+    /// Row of the cursor.
+    pub true_set_idx: usize,
+    /// Column of the cursor, its `adjusted_item_idx`.
+    pub column: usize,
+    /// Rows in view.
+    pub visible: Range<usize>,
+    /// `left_right_idx_adjustment` of the rows built, by `true_set_idx`, turning the
+    /// `true_item_idx` of their tiles into a column. See [`RowScroll`](crate::ui_core::RowScroll).
+    pub row_offsets: HashMap<usize, usize>,
+}
+
+impl LoadFocus {
+    /// Lowest first: the other kinds of images, then the tiles in view, then the others. The tiles
+    /// go by their distance to the cursor, counted in rows and columns.
+    fn rank(&self, req: &LoadRequest) -> (u8, usize) {
+        if req.kind != LoadKind::Tile {
+            return (0, 0);
+        }
+        let offset = self
+            .row_offsets
+            .get(&req.true_set_idx)
+            .copied()
+            .unwrap_or(0);
+        let column = req.true_item_idx.saturating_sub(offset);
+        let distance = req.true_set_idx.abs_diff(self.true_set_idx) + column.abs_diff(self.column);
+        if self.visible.contains(&req.true_set_idx) {
+            (1, distance)
+        } else {
+            (2, distance)
+        }
+    }
+}
+
+/// The requests waiting for a worker, see [`LoadFocus::rank`].
+#[derive(Default)]
+struct RequestQueue {
+    state: Mutex<QueueState>,
+    /// Signaled when a request is pushed or the queue is closed.
+    pushed: Condvar,
+}
+
+#[derive(Default)]
+struct QueueState {
+    /// The requests in the order they were pushed, which breaks the ties between them.
+    pending: Vec<(LoadRequest, CancelToken)>,
+    focus: LoadFocus,
+    /// Set when the loader is dropped, the workers then exit.
+    closed: bool,
+}
+
+impl RequestQueue {
+    fn push(&self, req: LoadRequest, token: CancelToken) {
+        self.state.lock().unwrap().pending.push((req, token));
+        self.pushed.notify_one();
+    }
+
+    /// Waits for a request and takes the one ranked first. The cancelled ones are dropped on the
+    /// way. None once closed.
+    fn pop(&self) -> Option<(LoadRequest, CancelToken)> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.closed {
+                return None;
+            }
+            state.pending.retain(|(_, token)| !token.is_cancelled());
+            let focus = &state.focus;
+            let next = state
+                .pending
+                .iter()
+                .enumerate()
+                .min_by_key(|(idx, (req, _))| (focus.rank(req), *idx))
+                .map(|(idx, _)| idx);
+            match next {
+                Some(idx) => return Some(state.pending.remove(idx)),
+                None => state = self.pushed.wait(state).unwrap(),
+            }
+        }
+    }
+
+    fn set_focus(&self, focus: LoadFocus) {
+        self.state.lock().unwrap().focus = focus;
+    }
+
+    fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.pushed.notify_all();
+    }
+}
+
+/// Hands the [`LoadResult`]s over to the [`ImageLoader`], waking the event loop up if it's parked.
+/// See [`ImageLoader::wake_with`].
+#[derive(Clone)]
+struct ResultSender {
+    tx: Sender<(LoadResult, CancelToken)>,
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl ResultSender {
+    /// Returns false if the loader is gone.
+    fn send(&self, res: LoadResult, token: CancelToken) -> bool {
+        if self.tx.send((res, token)).is_err() {
+            return false;
+        }
+        if let Some(waker) = self.waker.lock().unwrap().as_ref() {
+            waker.wake();
+        }
+        true
+    }
+}
+
+/// Thread pools that fetch and decode the images.
+pub struct ImageLoader {
+    requests: Arc<RequestQueue>,
+    result_rx: Receiver<(LoadResult, CancelToken)>,
+    /// Woken up by the [`ResultSender`]s.
+    waker: Arc<Mutex<Option<Waker>>>,
+    workers: Vec<JoinHandle<()>>,
+    notifier: ImgLoadingNotifier,
+}
+
+impl ImageLoader {
+    /// Constructor. Spawns the worker threads which fetch through the given `image_cache`, and the
+    /// decoding threads which apply the `gamma` to the images, see [`helpers::apply_gamma`].
+    pub fn new(image_cache: Arc<ImageCache>, gamma: f32) -> Self {
+        let requests = Arc::new(RequestQueue::default());
+        let (tx, result_rx) = channel::<(LoadResult, CancelToken)>();
+        let waker = Arc::new(Mutex::new(None));
+        let result_tx = ResultSender {
+            tx,
+            waker: Arc::clone(&waker),
+        };
+        let decoders: Arc<ThreadPool> = Arc::new(
+            ThreadPoolBuilder::new()
+                .num_threads(NUM_OF_DECODERS)
+                .thread_name(|idx| format!("decoder-{}", idx))
+                .build()
+                .expect("decoding threads to start"),
+        );
+
+        let workers = (0..NUM_OF_WORKERS)
+            .map(|worker_idx| {
+                let requests = Arc::clone(&requests);
+                let result_tx = result_tx.clone();
+                let image_cache = Arc::clone(&image_cache);
+                let decoders = Arc::clone(&decoders);
+                std::thread::spawn(move || loop {
+                    let (req, token) = match requests.pop() {
+                        Some(request) => request,
+                        None => break,
+                    };
+                    let fetched = {
+                        let _span = info_span!(
+                            "fetch_image",
+                            worker = worker_idx,
+                            kind = ?req.kind,
+                            set = req.true_set_idx,
+                            item = req.true_item_idx,
+                        )
+                        .entered();
+                        if let Some(preview_url) = req.preview_url.as_ref().filter(|_| {
+                            !image_cache.is_offline()
+                                && !api::is_file_url(&req.url)
+                                && image_cache.get(&req.url).is_none()
+                        }) {
+                            // Small enough to be decoded right away, it's then sure to come first.
+                            let preview = image_cache
+                                .fetch_bytes(preview_url)
+                                .and_then(|buf| api::decode_image(&buf));
+                            match preview {
+                                Ok(img) => {
+                                    let mut img = helpers::to_gl_rgba(img.blur(PREVIEW_BLUR));
+                                    helpers::apply_gamma(&mut img, gamma);
+                                    let res = req.preview_result(img);
+                                    if !result_tx.send(res, token.clone()) {
+                                        break;
+                                    }
+                                }
+                                Err(e) => debug!("No preview for {}: {}", req.url, e),
+                            }
+                        }
+                        if token.is_cancelled() {
+                            continue;
+                        }
+                        debug!("Worker {} loading {}", worker_idx, req.url);
+                        image_cache.fetch_bytes(&req.url)
+                    };
+                    match fetched {
+                        Ok(buf) => {
+                            let result_tx = result_tx.clone();
+                            decoders.spawn(move || {
+                                if token.is_cancelled() {
+                                    return;
+                                }
+                                let img = {
+                                    let _span = info_span!("decode", bytes = buf.len()).entered();
+                                    api::decode_image(&buf)
+                                        .map(|img| helpers::resize_to(img, req.size))
+                                        .map(helpers::to_gl_rgba)
+                                        .map(|mut img| {
+                                            helpers::apply_gamma(&mut img, gamma);
+                                            img
+                                        })
+                                };
+                                let dominant_color = match (&img, req.kind) {
+                                    (Ok(img), LoadKind::Tile) => ambient::dominant_color(img),
+                                    _ => None,
+                                };
+                                let res = LoadResult {
+                                    dominant_color,
+                                    ..req.into_result(img)
+                                };
+                                // The loader may be gone by then, the image is of no use anymore.
+                                result_tx.send(res, token);
+                            });
+                        }
+                        Err(e) => {
+                            if !result_tx.send(req.into_result(Err(e)), token) {
+                                break;
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            requests,
+            result_rx,
+            waker,
+            workers,
+            notifier: ImgLoadingNotifier::default(),
+        }
+    }
+
+    /// Queue a request. Requests for tiles that are already in flight are ignored.
+    pub fn submit(&mut self, req: LoadRequest) {
+        if self
+            .notifier
+            .is_in_flight(req.kind, req.true_set_idx, req.true_item_idx)
+        {
+            return;
+        }
+        let token = self
+            .notifier
+            .started(req.kind, req.true_set_idx, req.true_item_idx);
+        self.requests.push(req, token);
+    }
+
+    /// Wakes the event loop up with the `waker` whenever a result comes, so it doesn't have to keep
+    /// polling while images are loading.
+    pub fn wake_with(&self, waker: Waker) {
+        *self.waker.lock().unwrap() = Some(waker);
+    }
+
+    /// Moves the requests closest to the cursor `focus` to the front of the queue, the ones already
+    /// queued included. Called whenever the cursor moves.
+    pub fn focus(&mut self, focus: LoadFocus) {
+        self.requests.set_focus(focus);
+    }
+
+    /// Cancels the requests in flight for which `stale` returns true, given their kind,
+    /// `true_set_idx` and `true_item_idx`. They are no longer in flight, so they can be submitted
+    /// again, and whatever was already on its way back for them is dropped. Returns how many were
+    /// cancelled.
+    pub fn cancel(&mut self, mut stale: impl FnMut(LoadKind, usize, usize) -> bool) -> usize {
+        let before = self.notifier.in_flight.len();
+        self.notifier
+            .in_flight
+            .retain(|(kind, true_set_idx, true_item_idx), token| {
+                let keep = !stale(*kind, *true_set_idx, *true_item_idx);
+                if !keep {
+                    token.cancel();
+                }
+                keep
+            });
+        before - self.notifier.in_flight.len()
+    }
+
+    /// Drain every result available without blocking. They are still in flight until
+    /// [`handled`](ImageLoader::handled), so they aren't requested again while waiting to be uploaded.
+    /// The results of the [cancelled](ImageLoader::cancel) requests are left out.
+    pub fn poll(&mut self) -> Vec<LoadResult> {
+        self.result_rx
+            .try_iter()
+            .filter(|(_, token)| !token.is_cancelled())
+            .map(|(res, _)| res)
+            .collect()
+    }
+
+    /// Marks the request `res` answers as done, unless it's only its [`preview`](LoadResult::preview).
+    pub fn handled(&mut self, res: &LoadResult) {
+        if !res.preview {
+            self.notifier
+                .finished(res.kind, res.true_set_idx, res.true_item_idx);
+        }
+    }
+
+    /// Access the in-flight tracking.
+    pub fn notifier(&self) -> &ImgLoadingNotifier {
+        &self.notifier
+    }
+}
+
+impl Drop for ImageLoader {
+    fn drop(&mut self) {
+        // The requests left are dropped, the workers exit once done with the one they are on.
+        self.requests.close();
+        for worker in self.workers.drain(..) {
+            if worker.join().is_err() {
+                info!("Image loader worker panicked");
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Rows assembled locally from the state persisted on disk.
+//!
+//! They are drawn ahead of the sets coming from the [`Api`], which is why the `true_set_idx` of
+//! the rows built from the [`Api`] sets is shifted by [`LOCAL_ROWS`].
+use crate::i18n::{self, Locale, Text};
+use crate::profiles::Profile;
+use crate::progress::ProgressStore;
+use crate::watchlist::Watchlist;
+use api::{Api, SetData};
+
+/// `true_set_idx` of the row built from the [`ProgressStore`].
+pub const CONTINUE_WATCHING_ROW: usize = 0;
+/// `true_set_idx` of the row built from the [`Watchlist`].
+pub const WATCHLIST_ROW: usize = 1;
+/// Number of rows assembled locally.
+pub const LOCAL_ROWS: usize = 2;
+
+/// The persisted state backing the local rows.
+pub struct LocalRows {
+    /// Backs the [`WATCHLIST_ROW`].
+    pub watchlist: Watchlist,
+    /// Backs the [`CONTINUE_WATCHING_ROW`].
+    pub progress: ProgressStore,
+    /// Language of the titles and hints of the rows, and of the placeholder title of the [`Api`]
+    /// sets without one.
+    pub locale: Locale,
+}
+
+impl LocalRows {
+    /// Empty rows only kept in memory, they stand in until a [`Profile`] is picked.
+    pub fn empty(locale: Locale) -> Self {
+        Self {
+            watchlist: Watchlist::default(),
+            progress: ProgressStore::default(),
+            locale,
+        }
+    }
+
+    /// Loads the state of the `profile` from the data dir.
+    pub fn load(profile: &Profile, locale: Locale) -> Self {
+        Self {
+            watchlist: Watchlist::load(profile),
+            progress: ProgressStore::load(profile),
+            locale,
+        }
+    }
+
+    /// Builds the [`SetData`] of the local row `true_set_idx`. Items no longer found in the home data
+    /// are skipped. Returns None if `true_set_idx` is not a local row.
+    pub fn get_set<'a>(&self, api_handle: &'a Api, true_set_idx: usize) -> Option<SetData<'a>> {
+        match true_set_idx {
+            CONTINUE_WATCHING_ROW => Some(api_handle.custom_set(
+                i18n::tr(self.locale, Text::ContinueWatching),
+                &self.progress.recent(),
+            )),
+            WATCHLIST_ROW => Some(api_handle.custom_set(
+                i18n::tr(self.locale, Text::MyWatchlist),
+                self.watchlist.content_ids(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Text shown instead of the tiles while the row `true_set_idx` is empty: how to fill it for the
+    /// local rows, a placeholder for the [`Api`] sets.
+    pub fn empty_hint(&self, true_set_idx: usize) -> &'static str {
+        match true_set_idx {
+            CONTINUE_WATCHING_ROW => i18n::tr(self.locale, Text::ContinueWatchingHint),
+            WATCHLIST_ROW => i18n::tr(self.locale, Text::WatchlistHint),
+            _ => i18n::tr(self.locale, Text::NothingHereYet),
+        }
+    }
+}
@@ -8,7 +8,7 @@
 //! The idea is to keep around as few resources as possible while still allowing fluid navigation.
 //! To achieve this we are running vectors with fixed sizes to cache the minimum amount of data.
 //!
-//! It also uses an indexing scheme to allow the reuse slots inside the [Ids::imgs] and [Ids::titles] buffers.
+//! It also uses an indexing scheme so only the tiles in view are drawn and cached.
 //! It consists of:
 //!  - having an `adjusted_set` index for the data set (corresponds to the rows) and an `adjusted_item` index
 //!    for an item within a dataset (corresponds to a specific image). These `adjusted`
@@ -17,353 +17,870 @@
 //!    is always in view.
 //!
 //! ### Improvements
-//! - [`DisplayController::rows`] and [`DisplayController::image_map`] are currently "unbounded".
-//!   They are both technically bound by how many sets/items are fetched from the json data. Note
-//!   that the [`rows`](DisplayController::rows) has an accompanying [`fetch_row`](DisplayController::fetch_row)
-//!   function meant to be used as a way to dynamically load the rows and bound it like [Ids::imgs]
-//! - [`SetRow::cached_img_id`] is also unbounded and could be set so that it follows the [Ids::imgs] pattern.
-//! - Currently, at start time, everything is loaded in one shot. It would be better to
-//!   break that out to work alongside the [`EventLoop`] to load rows dynamically to give the user some quick feedback.
+//! - [`DisplayController::rows`] is currently "unbounded". It's technically bound by how many sets are
+//!   fetched from the json data. Note that the [`rows`](DisplayController::rows) has an accompanying
+//!   [`fetch_row`](DisplayController::fetch_row) function meant to be used as a way to dynamically load
+//!   the rows and bound it like the tiles in view. The textures in [`DisplayController::image_map`] on the
+//!   other hand are bound: [`SetRow::cached_img_id`] is a [`TileRing`] of [`row_stride`](Layout::row_stride) slots and rows further
+//!   than [`BUFFERED_ROWS`] from the visible ones release theirs. The tiles of the rows are packed
+//!   into the pages of a [`TextureAtlas`] rather than getting a texture each, see [`atlas`]. The
+//!   textures are kept under the [`gpu_budget_mb`](Config::gpu_budget_mb), see [`gpu_budget`].
+//!
+//! ### Rendering
+//! The layout of the tiles, the [`Cursor`] and which rows/tiles are in view live in [`ui_core`], which
+//! doesn't depend on conrod. A frame of the home rows is a list of [`DrawCommand`]s turned into conrod
+//! widgets by the [`ConrodRenderer`]. The hero banner, the detail page and the search screen still set
+//! their conrod widgets directly. Moving the cursor around is handled by the [`NavigationModel`], which
+//! only deals with indices.
+//!
+//! ### Startup
+//! The window shows up right away with a loading message while the home data is fetched in the background
+//! (see [`startup`]). Once it's available the rows stream in, at most [`ROWS_PER_LOOP`] per iteration of the
+//! [`EventLoop`], and each row title is dimmed while its images are still in flight.
+//!
+//! ### Screens
+//! The [`DisplayController`] keeps a stack of [`Screen`]s. Pressing `/` (or `Y` on a gamepad) pushes
+//! the [`search`] screen on top of the home rows, `Enter` (or `A`) pushes the [`detail`] page of the
+//! focused item, `G` pushes the [`grid`] of every item of the focused row and `Escape` (or `B`)
+//! pops the screen on top. `F` opens the [`filter_bar`] sorting the focused row by title or
+//! release year and keeping only its series or movies. Keys and gamepad buttons are turned into
+//! [`Action`]s and routed to the screen on top by [`DisplayController::handle`], each screen
+//! drawing with its own widget ids. The main loop only deals with the window, the mouse and the
+//! [`ExitReason`]s. Which key is which action can be changed in a `keys.toml` file, e.g. for
+//! vim-style keys, see [`keymap`]. Built with the `remote` feature on Linux, infrared and HDMI-CEC
+//! remotes go through the same actions, see `remote`.
+//!
+//! ### Profiles
+//! Each of the [`profiles`] has its own local rows, parental settings and cursor, which is put back
+//! where it was the last time the app was closed. The [`profile_picker`] is pushed on top of the
+//! home rows at startup, picking a profile rebuilds the rows the same way as on a [refresh](#refresh).
+//! The scroll of the rows and the detail page, search, settings or grid that was open come back too,
+//! unless [`resume_session`](Config::resume_session) is turned off.
+//!
+//! ### Settings
+//! Pressing `S` on the home screen pushes the [`settings`] screen: animations, image quality, row
+//! density, [`Theme`] and sound volume. The animations, the density, the theme and the volume apply
+//! right away, the image quality once the screen is closed, which also writes them to the config
+//! file. The colors, fonts and spacing of each theme can be tweaked, see [`theme`].
+//!
+//! ### Screensaver
+//! After [`screensaver_minutes`](Config::screensaver_minutes) without input the hero artwork of the
+//! items is shown full screen, one after the other, see [`screensaver`]. Any key brings back the
+//! screen that was shown, exactly as it was left.
+//!
+//! ### Video previews
+//! Once the focus rests on a tile for [`video_preview_ms`](Config::video_preview_ms), its video loop
+//! plays in place of the artwork, decoded by `ffmpeg`. The artwork stays when it can't be played,
+//! see [`preview`].
+//!
+//! ### External player
+//! With a [`player_command`](Config::player_command), `PlayPause` on a tile or `Enter` on the detail
+//! page hands the item over to an external player, e.g. `mpv`. The app is paused until it exits,
+//! see [`player`].
+//!
+//! ### Sounds
+//! Moving the focus, changing rows and opening an item play a short sound, a move going nowhere a
+//! duller one, see [`audio`]. They are muted with a [`sound_volume`](Config::sound_volume) of 0.
+//!
+//! ### Command line
+//! `helloplus run` (or no subcommand at all) opens the window. `dump`, `prefetch` and `validate`
+//! print the catalog, warm the disk cache and check the home data without opening one, see [`cli`].
+//!
+//! ### Configuration
+//! The window size, the number of rows and tiles kept around, the scale factors and key repeat timings
+//! come from a [`Config`], see the [`config`] module for where it's read from. When the window is
+//! resized the rows are laid out again following a [`Layout`] computed for the new size.
+//! F11 (or Alt+Enter) toggles fullscreen, see [`WindowMode`](config::WindowMode).
+//! F3 toggles the [`debug_hud`]: frame rate and times, textures and their budget, downloads and
+//! cursor indices.
+//!
+//! Behind a corporate proxy, or on a device whose certificate store is lacking, the
+//! [`proxy`](Config::proxy), [`ca_certificate`](Config::ca_certificate) and
+//! [`tls_backend`](Config::tls_backend) keys set up the client every request goes through.
+//!
+//! ### Offline
+//! `--offline` (or `offline = true` in the config) reads the home data bundled in the assets folder
+//! instead of fetching it. Tiles come from the disk cache when they were seen before, otherwise one of
+//! the bundled sample images stands in for them.
+//!
+//! ### Local library
+//! `--library <dir>` shows the images found under `dir` instead of the home data, each subfolder
+//! being a row, see [`FolderProvider`]. The tiles are titled after their EXIF description or their
+//! file name. Handy to demo the app without network access, or to try it with huge catalogs.
+//!
+//! `--tmdb` shows the Trending, Popular and Top Rated lists of The Movie Database, given a
+//! [`tmdb_api_key`](Config::tmdb_api_key) in the config.
+//!
+//! Built with the `media-server` feature, `--jellyfin <url>` or `--plex <url>` shows the libraries
+//! of that server instead, its access token read from the `HELLOPLUS_MEDIA_TOKEN` environment
+//! variable, see [`Catalog`]. Only the home data is refreshed.
+//!
+//! ### Refresh
+//! The home data is fetched again every [`home_refresh_minutes`](Config::home_refresh_minutes), see
+//! [`refresh`]. When the sets changed the rows are rebuilt from it once the home screen is shown, the
+//! cursor staying on the same item (or the same spot if the item is gone), see
+//! [`DisplayController::restore_position`].
+//!
+//! The home data of the last run is saved in a [`HomeCache`](api::HomeCache) and shown at startup
+//! without waiting for the network. It's then fetched again in the background, with a conditional
+//! request, and swapped in the same way if it changed.
+//!
+//! ### Parental controls
+//! With a [`max_rating`](Config::max_rating) the items rated above it are left out of the rows, the
+//! search results and the local rows. Pressing `P` on the home screen asks for the
+//! [`parental_pin`](Config::parental_pin) and lifts the filter, pressing it again puts it back. The
+//! rows are then rebuilt the same way as on a [refresh](#refresh). See [`parental`].
+//!
+//! ### Headless
+//! `--headless --screenshot out.png` renders the home screen off-screen and writes it to `out.png`
+//! instead of opening a window, see [`headless`].
+//!
+//! ### Terminal
+//! `--tui` draws the row and item titles in the terminal instead of opening a window, moving around
+//! with the same keys. Handy over SSH, without OpenGL or to debug the navigation, see [`tui`].
+//!
+//! ### Demo
+//! `--demo` walks around the rows on its own, pausing on the tiles and opening some of them, for a
+//! kiosk or to soak-test the loader. `--demo-script <path>` follows a script instead, see [`demo`].
+//!
+//! ### Record and replay
+//! `--record events.jsonl` writes the actions handled and their times to `events.jsonl`,
+//! `--replay events.jsonl` hands them over again at the same times. Along with `--headless`, the
+//! screenshot is taken once they are replayed, see [`recording`].
+//!
+//! ### Fault injection
+//! Built with the `chaos` feature, `--chaos latency=800,timeout=0.05,error=0.1,truncate=0.05` makes
+//! the requests slow and fail at those rates, to check the placeholders and retries, see
+//! `api::FaultRates`. `seed=<n>` injects the same faults on every run. The media servers send
+//! their own token, their requests are left alone.
+//!
+//! ### Tracing
+//! `--trace-json trace.json` writes spans around the home data loading, the image fetches, the texture
+//! uploads and the frames to `trace.json`, to be opened in `chrome://tracing`, see [`telemetry`].
+//!
+//! ### Local rows
+//! Some rows are assembled locally from state saved to disk (see [`persistence`]) and shown ahead of
+//! the sets coming from the [`Api`], see [`local_rows`]:
+//!  - "Continue Watching": the items whose detail page was opened, most recent first, see [`progress`].
+//!  - "My Watchlist": pressing `W` on a tile adds it to (or removes it from) the [`watchlist`].
 //!
 #![allow(rustdoc::private_intra_doc_links)]
 #![warn(missing_docs)]
 
 #[macro_use]
 extern crate conrod;
-use api::{Api, SetData};
-use conrod::backend::glium::glium::backend::glutin::glutin::VirtualKeyCode;
+use ambient::{Ambient, AmbientIds};
+use animation::{Animator, Easing, Shimmer, CROSSFADE_DURATION};
+use api::{
+    Api, AspectRatioPolicy, ContentProvider, ContentRating, FolderProvider, HomeDataDiff,
+    HomeSnapshot, PrefetchHandle, SetData, UrlBuilder,
+};
+use atlas::{AtlasRegion, TextureAtlas};
+use audio::{Audio, Sound};
+use catalog::Catalog;
+use clap::Parser;
+use cli::{Cli, Command};
+use config::Config;
 use conrod::backend::glium::glium::{self, Surface};
 use conrod::glium::Display;
 use conrod::image::Id;
 use conrod::image::Map;
-use conrod::{widget, Colorable, Positionable, Sizeable, Ui, UiCell, Widget};
-use log::{debug, info};
+use conrod::Ui;
+use conrod_renderer::{ConrodRenderer, RendererIds};
+use debug_hud::{DebugHud, HudIds, HudStats};
+use demo::{Demo, DemoOptions};
+use detail::{DetailContent, DetailIds, DetailView};
+use event_loop::{ControlFlow, Event, EventLoop, Input, VirtualKeyCode, Waker};
+use filter_bar::{FilterBar, FilterBarIds, RowFilter, RowOrder, RowSort};
+use gpu_budget::{GpuBudget, GpuUsage};
+use grid::{GridIds, GridScreen};
+use headless::HeadlessOptions;
+use hero::{HeroBanner, HeroIds, HERO_BANNER_HEIGHT};
+use i18n::{Locale, Text};
+use keymap::KeyMap;
+use layout::{Layout, RowStyle, TileShape};
+use loader::{ImageLoader, LoadFocus, LoadKind, LoadRequest, LoadResult, UploadBudget};
+use local_rows::{LocalRows, CONTINUE_WATCHING_ROW, LOCAL_ROWS, WATCHLIST_ROW};
+use mouse::{MouseAction, MouseState};
+use navigation::{NavigationModel, RowExtent};
+use parental::{PinEntry, PinIds};
+use player::{ExternalPlayer, PLAYER_POLL_INTERVAL};
+use preview::VideoPreview;
+use profile_picker::{PickerChoice, PickerIds, ProfilePicker};
+use profiles::ProfileStore;
+use recording::{Recorder, Replay};
+use refresh::HomeRefresher;
+use screens::{Action, Screen};
+use screensaver::{Screensaver, ScreensaverIds};
+use search::{SearchIds, SearchScreen};
+use serde::{Deserialize, Serialize};
+use settings::{Setting, SettingsIds, SettingsScreen};
+use startup::Startup;
+use std::collections::{HashMap, VecDeque};
 use std::ops::Range;
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use theme::{Theme, Themes};
+use tracing::{debug, info, info_span, instrument};
+use ui_core::{
+    Cursor, DrawCommand, Rect, Renderer, RowPlacement, RowScroll, SourceRect, TextStyle, TileRing,
+};
+mod ambient;
+mod animation;
+mod atlas;
+mod audio;
+mod catalog;
+mod cli;
+mod config;
+mod conrod_renderer;
+mod debug_hud;
+mod demo;
+mod detail;
+mod event_loop;
+mod filter_bar;
+mod fonts;
+mod gamepad;
+mod gpu_budget;
+mod grid;
+mod headless;
 mod helpers;
+mod hero;
+mod i18n;
+mod key_repeat;
+mod keymap;
+mod layout;
+mod loader;
+mod local_rows;
+mod mouse;
+mod navigation;
+mod parental;
+mod persistence;
+mod player;
+mod preview;
+mod profile_picker;
+mod profiles;
+mod progress;
+mod recording;
+mod refresh;
+#[cfg(all(feature = "remote", target_os = "linux"))]
+mod remote;
+mod screens;
+mod screensaver;
+mod search;
+mod settings;
+mod startup;
+mod telemetry;
+mod theme;
+mod tui;
+mod ui_core;
+mod virtualization;
+mod watchlist;
 
-const DISPLAY_WIDTH: u32 = 1920;
-const DISPLAY_HEIGHT: u32 = 1080;
-
-/// Debounce value for handling the Left, Right, Up Down key strokes.
-const NAVIGATION_KEYS_DEBOUNCE_THRESHOLD: u128 = 180;
-/// This field represents the number of visible rows given the [`ROW_HEIGHT`],the [`ROW_TOP_MARGIN`] and the [`DISPLAY_HEIGHT`]
-const NUM_ROWS: usize = 4;
-/// This field serves as the number of spaces reserved in the [Ids::imgs] field for a given row.
-/// This is adjusted to keep at least one out of view image in memory so the user doesn't see a placeholder.
-const ROW_STRIDE: usize = 6;
-/// This represents the number of images available to draw. Used for various alignments and as the total size of the [Ids::imgs] field.
-const NUM_OF_CACHED_IMAGES: usize = NUM_ROWS * ROW_STRIDE;
-
-// **** Start of pixel alignment consts.
-/// Margin to space out the thumbnails. Used to the left and right of the images.
-const ITEMS_MARGIN: f64 = 20.0;
-const IMAGE_WIDTH_PLUS_MARGIN: f64 = 500.0 + 15.0;
-const IMAGE_SCALE_DOWN_FACTOR: f64 = 0.75;
-/// Used to scale the image up so that it looks 15% larger.
-const IMAGE_SCALE_UP_FACTOR: f64 = 1.15;
-const ROW_TOP_MARGIN: f64 = 70.0;
-const ROW_HEIGHT: f64 = 290.0;
-
-widget_ids!(
-    /// Hold the [`Id`]s for the row titles and the images.
-    /// Note that `imgs` length is [`NUM_OF_CACHED_IMAGES`].
-    ///
-    /// The scheme used for the `imgs` field is that continuous chunks (sized [`ROW_STRIDE`]) of data are used
-    /// to store the images in view.
-    ///
-    /// For example:
-    ///  - With  [`NUM_ROWS`] set to 4 and [`ROW_STRIDE`] set to 6, `imgs` will have 24 elements.
-    ///  - This produces an array that looks like:
-    ///
-    /// | 0, 1, 2, 3, 4, 5, | 6, 7, 8, 9, 10, 11,| 12, 13, 14, 15, 16, 17,| 18, 19, 20, 21, 22, 23 |
-    /// | ----------------- | ------------------ | ---------------------- | ---------------------- |
-    /// | indices for row 0 |  indices for row 1 |   indices for row 2    |   indices for row 3    |
-    ///
-    struct Ids {
-        titles[],
-        imgs[]
-    }
-);
-
-/// In order to not spin endlessly this struct will throttle the main loop and queue incoming events.
-/// It will throttle to target 60fps rate.
-pub struct EventLoop {
-    last_update: std::time::Instant,
-}
-
-impl EventLoop {
-    /// Constructor.
-    pub fn new() -> Self {
-        EventLoop {
-            last_update: std::time::Instant::now(),
-        }
-    }
-
-    /// Produce an iterator yielding all available events.
-    pub fn next(
-        &mut self,
-        events_loop: &mut glium::glutin::EventsLoop,
-    ) -> Vec<glium::glutin::Event> {
-        // We don't want to loop any faster than 60 FPS, so wait until it has been at least 16ms
-        // since the last yield.
-        let last_update = self.last_update;
-        let sixteen_ms = std::time::Duration::from_millis(16);
-        let duration_since_last_update = std::time::Instant::now().duration_since(last_update);
-        if duration_since_last_update < sixteen_ms {
-            std::thread::sleep(sixteen_ms - duration_since_last_update);
-        }
-
-        // Collect all pending events.
-        let mut events = Vec::new();
-        events_loop.poll_events(|event| events.push(event));
-
-        if events.is_empty() {
-            events_loop.run_forever(|event| {
-                events.push(event);
-                glium::glutin::ControlFlow::Break
-            });
-        }
+/// Number of rows above and below the visible ones that keep their textures around.
+/// Rows further away release them, see [`DisplayController::evict_far_rows`].
+const BUFFERED_ROWS: usize = 1;
+/// Max number of rows built per iteration of the [`EventLoop`] while the visible rows stream in.
+const ROWS_PER_LOOP: usize = 1;
+/// Wait before requesting again a tile whose image failed to load for a transient reason. The
+/// [`Api`] already retried a few times by then, see [`api::RetryPolicy`].
+const TILE_RETRY_DELAY: Duration = Duration::from_secs(5);
+/// Number of frames of the loading shimmer, see [`Shimmer`].
+const SHIMMER_FRAMES: usize = 24;
 
-        self.last_update = std::time::Instant::now();
-
-        events
-    }
-}
+// The pixel alignment values live in the [`Layout`] since they follow the window size.
 
 /// Simple holder to keep track of the img_ids we've already placed in the [`image_map`](DisplayController::image_map)
 struct CachedImgData {
     img_id: Id,
     w: f64,
     h: f64,
+    /// When the image came in, it fades in over the placeholder for [`CROSSFADE_DURATION`] from then.
+    /// None for the images shown as is, e.g. the placeholder itself.
+    loaded_at: Option<Instant>,
+    /// Where the image is in the [`TextureAtlas`], `img_id` being its page. None for the images
+    /// with a texture of their own.
+    region: Option<AtlasRegion>,
+    /// Dominant color of the image the backdrop is tinted with when it has the focus, see [`Ambient`].
+    dominant_color: Option<[f32; 3]>,
+    /// True for the blurred preview shown until the image itself comes, see
+    /// [`LoadRequest::with_preview`]. It's swapped for the image without a crossfade.
+    preview: bool,
 }
 
 impl CachedImgData {
     fn new(img_id: Id, w: f64, h: f64) -> Self {
-        Self { img_id, w, h }
+        Self {
+            img_id,
+            w,
+            h,
+            loaded_at: None,
+            region: None,
+            dominant_color: None,
+            preview: false,
+        }
+    }
+
+    /// The part of the `img_id` texture to draw, see [`CachedImgData::region`].
+    fn source(&self) -> Option<SourceRect> {
+        self.region.as_ref().map(AtlasRegion::source_rect)
+    }
+
+    /// Same image, fading in over the placeholder starting `now`.
+    fn fading_in(mut self, now: Instant) -> Self {
+        self.loaded_at = Some(now);
+        self
+    }
+
+    /// Opacity of the image at `now`, below 1 while it's fading in.
+    fn opacity(&self, now: Instant) -> f32 {
+        match self.loaded_at {
+            Some(loaded_at) => {
+                let elapsed = now.saturating_duration_since(loaded_at).as_secs_f64();
+                Easing::Linear.apply(elapsed / CROSSFADE_DURATION.as_secs_f64()) as f32
+            }
+            None => 1.0,
+        }
+    }
+
+    /// Data used for the "image-not-found" tile.
+    fn not_found(nf_id: Id) -> Self {
+        Self::new(nf_id, 500.0 * 0.75, 220.0 * 0.75)
+    }
+
+    /// Data used for the placeholder shown while loading, a frame of the [`Shimmer`]. Same size as
+    /// the "image-not-found" tile.
+    fn loading(shimmer_id: Id) -> Self {
+        Self::new(shimmer_id, 500.0 * 0.75, 220.0 * 0.75)
     }
 }
 
 /// Holds the necessary data needed to draw a single row.
 ///
 /// Its responsibilities include:
-/// - fetching the resources (title and thumbnail images) and caching them.
-/// - place the visible widgets in the appropriate locations given the combination of `true` and `adjusted` indices.
-/// - controls the navigation for this row via the [`shift_right`](SetRow::shift_right) and [`shift_left`](SetRow::shift_left) methods.
+/// - requesting the resources (title and thumbnail images) and caching them once loaded.
+/// - produce the [`DrawCommand`]s for the visible tiles given the combination of `true` and `adjusted` indices.
+/// - keeps the horizontal [`RowScroll`] of this row.
 struct SetRow<'a> {
     /// Title for this set of data.
     title: &'a str,
     /// The fetched data retrieved by the [`Api`].
     set_data: SetData<'a>,
-    /// Unique id for this set of data.
+    /// Unique id for this set of data. Rows coming from the [`Api`] are shifted by [`LOCAL_ROWS`].
     true_set_idx: usize,
-    /// Cached [`Id`] keys used to map the image data stored in the [`image_map`](DisplayController::image_map)
-    ///
-    /// IMPROVEMENT: treat as a fixed sized array to only keep the items in view.
-    cached_img_id: Vec<CachedImgData>,
-    /// Combined with the `adjusted_item_idx` it produces the `true_item_idx` for this specific row.
-    left_right_idx_adjustment: usize,
+    /// Cached [`Id`] keys used to map the image data stored in the [`image_map`](DisplayController::image_map),
+    /// [`row_stride`](Layout::row_stride) of them at most.
+    cached_img_id: TileRing<CachedImgData>,
+    /// Entries pushed out of [`SetRow::cached_img_id`] whose textures need to be released.
+    evicted: Vec<CachedImgData>,
+    /// Items whose image failed with a retryable error and when to request them again. The
+    /// placeholder is shown meanwhile, only the errors that aren't retryable (e.g. a 404) get the
+    /// "image-not-found" tile cached.
+    retry_at: HashMap<usize, Instant>,
+    /// Which items are in view.
+    scroll: RowScroll,
+    /// `true_item_idx` the cursor was on when it last left the row, it goes back there when the row
+    /// gets the focus again. See [`DisplayController::leave_row`].
+    last_item_idx: Option<usize>,
+    /// Extra top offset, in pixels, applied to the row. Set by the [`DisplayController`] from the
+    /// [`Animator`] to account for the [`HeroBanner`] and the vertical scroll transitions.
+    top_shift: f64,
+    /// Extra left offset, in items, applied to the row. Set by the [`DisplayController`] from the
+    /// [`Animator`] while the row is scrolling horizontally.
+    left_shift: f64,
+    /// Offset from the first row in view, in pixels. Set by the [`DisplayController`] since it
+    /// depends on the height of the rows above, see [`RowPlacement`].
+    row_top: f64,
+    /// `true_item_idx` and scale of the tile that just lost the focus while it shrinks back.
+    shrinking: Option<(usize, f64)>,
+    /// Whether this row shows posters, brands..., it has its own [`Layout`] then.
+    style: RowStyle,
+    /// The window [`Layout`] adjusted for the `style`.
+    layout: Layout,
+    /// Which items of the `set_data` the tiles show, and in which order. See [`filter_bar`].
+    order: RowOrder,
 }
 
 impl<'a> SetRow<'a> {
-    /// Constructor.
-    fn new(set_data: SetData<'a>, true_set_idx: usize) -> Self {
+    /// Constructor. The [`RowStyle`] of the row is picked from the `set_data` and the `layout`,
+    /// a set without a title gets a placeholder in the language of the `locale`.
+    fn new(set_data: SetData<'a>, true_set_idx: usize, layout: Layout, locale: Locale) -> Self {
         debug!("Initialized Set row: {:?}", set_data);
-        let title = set_data.get_title();
+        let title = set_data
+            .find_title()
+            .unwrap_or_else(|| i18n::tr(locale, Text::TitleNotFound));
+        let style = RowStyle::for_set(&set_data, layout.poster_rows);
+        let set_data = match style.shape() {
+            TileShape::Landscape => set_data,
+            TileShape::Poster => set_data
+                .with_tile_aspect_ratio(AspectRatioPolicy::Closest(api::POSTER_ASPECT_RATIO)),
+        };
+        let layout = layout.for_style(style);
+        let order = RowOrder::new(&set_data, RowSort::Default, RowFilter::All);
         Self {
             set_data,
             title,
             true_set_idx,
-            cached_img_id: Vec::new(),
-            left_right_idx_adjustment: 0,
+            cached_img_id: TileRing::new(layout.row_stride),
+            evicted: Vec::new(),
+            retry_at: HashMap::new(),
+            scroll: RowScroll::default(),
+            last_item_idx: None,
+            top_shift: 0.0,
+            left_shift: 0.0,
+            row_top: 0.0,
+            shrinking: None,
+            style,
+            layout,
+            order,
         }
     }
 
-    /// Shift right on a given row. Returns a bool because it needs to check that row's specific
-    /// item count.
-    /// # Arguments
-    /// * `adjusted_item_idx`: this is the canvas index for the item (always between 0 and [`ROW_STRIDE`]-1).
-    /// * `true_item_idx`: this is the full index into this row's items.
-    fn shift_right(&mut self, adjusted_item_idx: usize, true_item_idx: usize) -> bool {
-        if (true_item_idx + 1) < self.set_data.get_item_count() {
-            if adjusted_item_idx + 4 > ROW_STRIDE {
-                self.left_right_idx_adjustment += 1;
-            }
-            true
-        } else {
-            false
+    /// Number of items shown, see [`RowOrder::item_count`].
+    fn item_count(&self) -> usize {
+        self.order.item_count()
+    }
+
+    /// Index in the [`SetData`] of the item of the tile `true_item_idx`, see [`RowOrder::item_idx`].
+    fn item_idx(&self, true_item_idx: usize) -> usize {
+        self.order.item_idx(true_item_idx)
+    }
+
+    /// Sorts and filters the items of the row as per `sort` and `filter`. The tiles then show other
+    /// items: every cached entry is evicted, the images are requested again on the next
+    /// [`show`](SetRow::show).
+    fn set_order(&mut self, sort: RowSort, filter: RowFilter) {
+        self.order = RowOrder::new(&self.set_data, sort, filter);
+        self.evict_all();
+        self.retry_at.clear();
+        self.shrinking = None;
+    }
+
+    /// Fetches the tile images of the items of the tiles `true_item_idxs` into the
+    /// [`ImageCache`](api::ImageCache) in the background, see [`SetData::prefetch_images`].
+    fn prefetch_images(&self, true_item_idxs: Range<usize>) -> PrefetchHandle {
+        self.set_data
+            .prefetch_images(true_item_idxs.map(|true_item_idx| self.item_idx(true_item_idx)))
+    }
+
+    /// What the [`NavigationModel`] needs to know about this row.
+    fn extent(&self) -> RowExtent {
+        RowExtent {
+            item_count: self.item_count(),
+            row_stride: self.layout.row_stride,
         }
     }
 
+    /// Where this row is drawn.
+    fn placement(&self) -> RowPlacement {
+        RowPlacement::new(&self.layout, self.row_top, self.top_shift, self.left_shift)
+    }
+
+    /// Returns the `adjusted_item_idx` of the tile under the window coordinates `x`, `y`, if any.
+    fn hit_test(&self, x: f64, y: f64) -> Option<usize> {
+        let placement = self.placement();
+        let scale = self.layout.image_scale_down_factor;
+        (0..self.layout.row_stride).find(|adjusted_item_idx| {
+            let true_item_idx = self.scroll.true_item_idx(*adjusted_item_idx);
+            let (w, h) = match self.cached(true_item_idx) {
+                Some(data) => (data.w, data.h),
+                None => (500.0 * scale, 220.0 * scale),
+            };
+            true_item_idx < self.item_count()
+                && placement.tile(*adjusted_item_idx, w, h).contains(x, y)
+        })
+    }
+
+    /// Submits a [`LoadRequest`] for the given item if it isn't cached or already being loaded.
+    /// Items waiting for a retry are only requested once their [`retry_at`](SetRow::retry_at) is due.
     ///
-    /// # Arguments
-    /// * `adjusted_item_idx`: this is the canvas index for the item (always between 0 and [`ROW_STRIDE`]-1).
-    fn shift_left(&mut self, adjusted_item_idx: usize) {
-        if self.left_right_idx_adjustment > 0 {
-            if adjusted_item_idx < 2 {
-                self.left_right_idx_adjustment -= 1;
+    /// If the item has no url the "image-not-found" tile is cached right away.
+    fn populate_cache_if_needed(
+        &mut self,
+        loader: &mut ImageLoader,
+        nf_id: &Id,
+        true_item_idx: usize,
+    ) {
+        if self
+            .cached(true_item_idx)
+            .map_or(false, |data| !data.preview)
+            || loader
+                .notifier()
+                .is_in_flight(LoadKind::Tile, self.true_set_idx, true_item_idx)
+        {
+            return;
+        }
+        // Still showing the preview after a failed attempt.
+        let has_preview = self.cached(true_item_idx).is_some();
+        if let Some(retry_at) = self.retry_at.get(&true_item_idx) {
+            if Instant::now() < *retry_at {
+                return;
+            }
+            self.retry_at.remove(&true_item_idx);
+        }
+
+        match self
+            .set_data
+            .get_home_tile_url(self.item_idx(true_item_idx))
+        {
+            Ok(url) => {
+                let req = LoadRequest {
+                    kind: LoadKind::Tile,
+                    true_set_idx: self.true_set_idx,
+                    true_item_idx,
+                    url,
+                    size: self.draw_size(true_item_idx),
+                    preview_url: None,
+                };
+                loader.submit(if has_preview { req } else { req.with_preview() })
+            }
+            Err(e) => {
+                debug!("No tile for item {}: {}", true_item_idx, e);
+                self.insert_loaded(true_item_idx, CachedImgData::not_found(nf_id.clone()));
             }
         }
     }
 
-    ///
-    /// # Arguments
-    /// * `adjusted_set_idx`: This is the canvas index for this set of data. This index is adjusted to
-    ///    stay between 0 and [`NUM_ROWS`]-1
-    fn get_top_offset(&self, adjusted_set_idx: usize) -> f64 {
-        (adjusted_set_idx as f64) * ROW_HEIGHT + ROW_TOP_MARGIN
+    /// Size, in whole pixels, the tile of `true_item_idx` is drawn at when it doesn't have the focus.
+    fn draw_size(&self, true_item_idx: usize) -> Option<(u32, u32)> {
+        let tile = self
+            .set_data
+            .get_home_tile(self.item_idx(true_item_idx))
+            .ok()?;
+        let (w, h) = self.layout.tile_size(tile.aspect_ratio as f64);
+        Some((w.round() as u32, h.round() as u32))
     }
 
-    /// # Arguments
-    /// * `adjusted_item_idx`: this is the canvas index for the item (always between 0 and [`ROW_STRIDE`]-1).
-    fn get_left_offset(&self, adjusted_item_idx: usize) -> f64 {
-        (adjusted_item_idx as f64) * IMAGE_WIDTH_PLUS_MARGIN * IMAGE_SCALE_DOWN_FACTOR
-            + ITEMS_MARGIN
+    /// The [`LoadKind::Focus`] request of `true_item_idx`, see [`Config::sharp_focus`]. None until its
+    /// tile is shown, previews and the "image-not-found" tile don't get one.
+    fn focus_request(&self, true_item_idx: usize) -> Option<LoadRequest> {
+        let shown = self.cached(true_item_idx)?;
+        if shown.preview || shown.region.is_none() {
+            return None;
+        }
+        let req = LoadRequest {
+            kind: LoadKind::Tile,
+            true_set_idx: self.true_set_idx,
+            true_item_idx,
+            url: self
+                .set_data
+                .get_home_tile_url(self.item_idx(true_item_idx))
+                .ok()?,
+            size: self.draw_size(true_item_idx),
+            preview_url: None,
+        };
+        Some(req.focused(self.layout.image_scale_up_factor))
     }
 
-    ///
-    /// # Arguments
-    /// * `adjusted_item_idx`: this is the canvas index for the item (always between 0 and [`ROW_STRIDE`]-1).
-    /// * `adjusted_set_idx`: This is the canvas index for this set of data. This index is adjusted to
-    ///    stay between 0 and [`NUM_ROWS`]-1
-    fn get_img_idx(&self, adjusted_item_idx: usize, adjusted_set_idx: usize) -> usize {
-        adjusted_set_idx * ROW_STRIDE + adjusted_item_idx
+    /// Returns the cached data for `true_item_idx` if its slot currently holds it.
+    /// Returns true while one of the items in view still shows the loading placeholder.
+    fn is_waiting_for_images(&self) -> bool {
+        let item_count = self.item_count();
+        (0..self.layout.row_stride)
+            .map(|adjusted_item_idx| self.scroll.true_item_idx(adjusted_item_idx))
+            .any(|true_item_idx| true_item_idx < item_count && self.cached(true_item_idx).is_none())
+    }
+
+    /// Returns true while one of the cached images is still fading in, see [`CachedImgData::opacity`].
+    fn is_fading(&self, now: Instant) -> bool {
+        self.cached_img_id
+            .iter()
+            .any(|(_, data)| data.opacity(now) < 1.0)
+    }
+
+    fn cached(&self, true_item_idx: usize) -> Option<&CachedImgData> {
+        self.cached_img_id.get(true_item_idx)
+    }
+
+    /// Returns true if `true_item_idx` is one of the [`row_stride`](Layout::row_stride) items currently in view.
+    fn is_in_window(&self, true_item_idx: usize) -> bool {
+        self.scroll
+            .is_in_window(self.layout.row_stride, true_item_idx)
+    }
+
+    /// Keeps the placeholder for `true_item_idx` and requests it again after [`TILE_RETRY_DELAY`].
+    fn retry_later(&mut self, true_item_idx: usize) {
+        self.retry_at
+            .insert(true_item_idx, Instant::now() + TILE_RETRY_DELAY);
+    }
+
+    /// Returns true if an item in view is due for another attempt.
+    fn is_retry_due(&self, now: Instant) -> bool {
+        self.retry_at
+            .iter()
+            .any(|(true_item_idx, retry_at)| *retry_at <= now && self.is_in_window(*true_item_idx))
+    }
+
+    /// Stores the texture data of an image loaded in the background.
+    /// Whatever was in its slot is moved to the [`evicted`](SetRow::evicted) list.
+    fn insert_loaded(&mut self, true_item_idx: usize, data: CachedImgData) {
+        if let Some(old) = self.cached_img_id.insert(true_item_idx, data) {
+            self.evicted.push(old);
+        }
+    }
+
+    /// Switches to a new window [`Layout`]. Every cached entry is evicted since their sizes depend on
+    /// the layout, the images are requested again on the next [`show`](SetRow::show).
+    fn set_layout(&mut self, layout: Layout) {
+        let layout = layout.for_style(self.style);
+        self.evict_all();
+        if layout.row_stride != self.layout.row_stride {
+            self.cached_img_id = TileRing::new(layout.row_stride);
+        }
+        self.layout = layout;
+    }
+
+    /// Moves every cached entry to the [`evicted`](SetRow::evicted) list.
+    fn evict_all(&mut self) {
+        self.evicted.extend(self.cached_img_id.drain());
+    }
+
+    /// Moves the cached entry of `true_item_idx` to the [`evicted`](SetRow::evicted) list, it's
+    /// requested again on the next [`show`](SetRow::show) in view.
+    fn evict(&mut self, true_item_idx: usize) {
+        self.evicted
+            .extend(self.cached_img_id.remove(true_item_idx));
     }
 
-    /// Sets the widget to display the appropriate image for this row given the `adjusted_*` indices.
-    /// Returns true if this image should be highlighted (scaled up).
+    /// Pushes the [`DrawCommand`] of the appropriate image for this row given the `adjusted_*` indices.
+    /// Returns the [`HighlightedItemData`] if this image should be highlighted (scaled up).
+    ///
+    /// While the image is loading the current frame of the [`Shimmer`] is used as a placeholder.
     ///
-    /// NOTE: The reason we don't set the scaled up widget here is because when the image scales up,
-    /// it takes some space from the previous and next image. If we set the scaled up image here then the next image
+    /// NOTE: The reason we don't draw the scaled up image here is because when the image scales up,
+    /// it takes some space from the previous and next image. If we draw the scaled up image here then the next image
     /// will overlap and it will appear on top of the currently highlighted image. The scaled up
     /// image is drawn last to make sure it will be on top.
     /// # Arguments
-    /// * `adjusted_item_idx`: this is the canvas index for the item (always between 0 and [`ROW_STRIDE`]-1).
-    /// * `adjusted_set_idx`: This is the canvas index for this set of data. This index is adjusted to
-    ///    stay between 0 and [`NUM_ROWS`]-1
+    /// * `adjusted_item_idx`: this is the canvas index for the item (always between 0 and [`row_stride`](Layout::row_stride)-1).
     fn show(
         &mut self,
-        display: &Display,
-        ui: &mut UiCell,
-        image_map: &mut Map<glium::texture::Texture2d>,
-        ids: &Ids,
+        commands: &mut Vec<DrawCommand<Id>>,
+        loader: &mut ImageLoader,
         cursor: &Cursor,
-        nf_id: &Id,
+        placeholders: &Placeholders,
         adjusted_item_idx: usize,
-        adjusted_set_idx: usize,
+        now: Instant,
     ) -> Option<HighlightedItemData> {
-        let true_item_idx = adjusted_item_idx + self.left_right_idx_adjustment;
-
-        if self.cached_img_id.get(true_item_idx).is_none() {
-            let img = self.set_data.get_home_tile_image(true_item_idx);
+        let true_item_idx = self.scroll.true_item_idx(adjusted_item_idx);
 
-            if let Ok(img) = img {
-                let img = helpers::load_img(display, img);
-                let (w, h) = (img.get_width(), img.get_height().unwrap());
-                let img_id = image_map.insert(img);
-                let w = (w as f64) * IMAGE_SCALE_DOWN_FACTOR;
-                let h = (h as f64) * IMAGE_SCALE_DOWN_FACTOR;
-                info!("put img {:?} ar {}", img_id, w / h);
-                self.cached_img_id.push(CachedImgData::new(img_id, w, h));
-            } else {
-                self.cached_img_id.push(CachedImgData::new(
-                    nf_id.clone(),
-                    500.0 * 0.75,
-                    220.0 * 0.75,
-                ));
-            }
-        };
+        self.populate_cache_if_needed(loader, &placeholders.not_found, true_item_idx);
 
-        // We know that from the previous if block there will be an item at true_item_idx now
-        let data = self.cached_img_id.get(true_item_idx).unwrap();
+        let placeholder = CachedImgData::loading(placeholders.loading);
+        let data = self.cached(true_item_idx).unwrap_or(&placeholder);
+        let opacity = data.opacity(now);
 
-        let hd =
-            if cursor.true_set_idx == self.true_set_idx && cursor.true_item_idx == true_item_idx {
-                Some(HighlightedItemData {
-                    img_id: data.img_id,
-                    w: data.w,
-                    h: data.h,
-                    true_set_idx: self.true_set_idx,
-                    adjusted_item_idx,
-                    adjusted_set_idx,
-                })
-            } else {
-                None
-            };
+        let hd = if !cursor.on_banner
+            && cursor.true_set_idx == self.true_set_idx
+            && cursor.true_item_idx == true_item_idx
+        {
+            Some(HighlightedItemData {
+                img_id: data.img_id,
+                region: data.region,
+                w: data.w,
+                h: data.h,
+                opacity,
+                true_set_idx: self.true_set_idx,
+                adjusted_item_idx,
+            })
+        } else {
+            None
+        };
 
-        self.draw_image(
-            data.img_id,
-            data.w,
-            data.h,
-            adjusted_set_idx,
-            adjusted_item_idx,
-            ids,
-            ui,
-        );
+        let placement = self.placement();
+        let rect = match self.shrinking {
+            Some((shrinking_idx, scale)) if hd.is_none() && shrinking_idx == true_item_idx => {
+                placement.highlighted_tile(adjusted_item_idx, data.w, data.h, scale)
+            }
+            _ => placement.tile(adjusted_item_idx, data.w, data.h),
+        };
+        if opacity < 1.0 {
+            // Crossfade, the placeholder fades out under the image fading in.
+            commands.push(DrawCommand::Image {
+                image: placeholder.img_id,
+                source: placeholder.source(),
+                rect: placement.tile(adjusted_item_idx, placeholder.w, placeholder.h),
+                alpha: 1.0 - opacity,
+            });
+        }
+        commands.push(DrawCommand::Image {
+            image: data.img_id,
+            source: data.source(),
+            rect,
+            alpha: opacity,
+        });
 
-        // Return true if this item needs to be scaled up (highlighted)
+        // Return Some if this item needs to be scaled up (highlighted)
         hd
     }
 
-    fn draw_image(
+    /// Pushes the [`DrawCommand`] of the image `data` enlarged by `scale`, see [`RowPlacement::highlighted_tile`].
+    /// The image is drawn with the `alpha` opacity. Returns where it's drawn.
+    fn draw_image_highlighted(
         &self,
-        img_id: Id,
-        w: f64,
-        h: f64,
-        adjusted_set_idx: usize,
+        commands: &mut Vec<DrawCommand<Id>>,
+        data: &CachedImgData,
+        scale: f64,
         adjusted_item_idx: usize,
-        ids: &Ids,
-        ui: &mut UiCell,
-    ) {
-        widget::Image::new(img_id)
-            .w_h(w, h)
-            .top_left_with_margins_on(
-                ui.window,
-                self.get_top_offset(adjusted_set_idx),
-                self.get_left_offset(adjusted_item_idx),
-            )
-            .set(
-                ids.imgs[self.get_img_idx(adjusted_item_idx, adjusted_set_idx)],
-                ui,
-            );
+        alpha: f32,
+    ) -> Rect {
+        let rect = self
+            .placement()
+            .highlighted_tile(adjusted_item_idx, data.w, data.h, scale);
+        commands.push(DrawCommand::Image {
+            image: data.img_id,
+            source: data.source(),
+            rect,
+            alpha,
+        });
+        rect
     }
 
-    /// Enlarges the image by [`IMAGE_SCALE_UP_FACTOR`] and also moves it back and up by [`ITEMS_MARGIN`].
-    fn draw_image_highlighted(
+    /// Pushes the [`DrawCommand`] of the glow around the focused tile of a [`RowStyle::Brands`] row,
+    /// to be drawn under it. Other rows don't glow. Same arguments as [`SetRow::draw_image_highlighted`].
+    fn show_glow(
         &self,
-        img_id: Id,
-        w: f64,
-        h: f64,
-        adjusted_set_idx: usize,
+        commands: &mut Vec<DrawCommand<Id>>,
+        data: &CachedImgData,
+        scale: f64,
         adjusted_item_idx: usize,
-        ids: &Ids,
-        ui: &mut UiCell,
     ) {
-        widget::Image::new(img_id)
-            .w_h(w * IMAGE_SCALE_UP_FACTOR, h * IMAGE_SCALE_UP_FACTOR)
-            .top_left_with_margins_on(
-                ui.window,
-                self.get_top_offset(adjusted_set_idx) - ITEMS_MARGIN,
-                self.get_left_offset(adjusted_item_idx) - ITEMS_MARGIN,
+        if self.style != RowStyle::Brands {
+            return;
+        }
+        let tile = self
+            .placement()
+            .highlighted_tile(adjusted_item_idx, data.w, data.h, scale);
+        commands.push(DrawCommand::Glow {
+            rect: RowPlacement::glow(tile),
+            alpha: 0.6,
+        });
+    }
+
+    /// Pushes the [`DrawCommand`]s of the strip below the focused `tile` telling what the item
+    /// `true_item_idx` is: its title, then its content type, release year and rating.
+    fn show_item_info(
+        &self,
+        commands: &mut Vec<DrawCommand<Id>>,
+        true_item_idx: usize,
+        tile: Rect,
+        theme: &Theme,
+    ) {
+        let item_idx = self.item_idx(true_item_idx);
+        let title = match self.set_data.get_item_title(item_idx) {
+            Some(title) => title,
+            None => return,
+        };
+        let max_width = self.placement().text_max_width() - 2.0 * theme.info_padding;
+        let title = theme.truncate(TextStyle::ItemTitle, title, max_width);
+        let info = self.set_data.get_item_metadata(item_idx);
+        let strip = RowPlacement::item_strip(tile, &title, &info, theme);
+        let ((title_x, title_y), (info_x, info_y)) = RowPlacement::item_text_points(strip, theme);
+        commands.push(DrawCommand::Backdrop {
+            rect: strip,
+            alpha: 0.75,
+        });
+        commands.push(DrawCommand::Text {
+            text: title.into_owned(),
+            x: title_x,
+            y: title_y,
+            style: TextStyle::ItemTitle,
+        });
+        if !info.is_empty() {
+            commands.push(DrawCommand::Text {
+                text: info,
+                x: info_x,
+                y: info_y,
+                style: TextStyle::ItemInfo,
+            });
+        }
+    }
+
+    /// Pushes the [`DrawCommand`]s of the set title, above the first leftmost image, followed by the
+    /// number of items of the row. The row with the focus also gets its position, on the right.
+    ///
+    /// # Arguments
+    /// * `loading`: while true the title is dimmed and followed by an ellipsis.
+    /// * `position`: number of the row and number of rows, for the row with the focus.
+    /// * `theme`: gives the size of the title, hence how far above the image it starts. Titles
+    ///   wider than the row are cut to fit, see [`Theme::truncate`].
+    /// * `locale`: the language of the number of items and of the position.
+    fn show_row_title(
+        &self,
+        commands: &mut Vec<DrawCommand<Id>>,
+        loading: bool,
+        position: Option<(usize, usize)>,
+        theme: &Theme,
+        locale: Locale,
+    ) {
+        let placement = self.placement();
+        let gap = self.layout.items_margin;
+        let item_count = match self.item_count() {
+            // The empty rows show a hint instead.
+            0 => None,
+            count => Some(i18n::item_count(locale, count)),
+        };
+        let position = position.map(|(row, rows)| {
+            i18n::trf_all(
+                locale,
+                Text::RowPosition,
+                &[&row.to_string(), &rows.to_string()],
             )
-            .set(
-                ids.imgs[self.get_img_idx(adjusted_item_idx, adjusted_set_idx)],
-                ui,
-            );
+        });
+        let hint_width = |hint: &Option<String>| {
+            hint.as_ref()
+                .map_or(0.0, |hint| theme.text_width(TextStyle::Hint, hint) + gap)
+        };
+        let max_width =
+            placement.text_max_width() - hint_width(&item_count) - hint_width(&position);
+        let (text, style) = if loading {
+            let style = TextStyle::LoadingTitle;
+            let max_width = max_width - theme.text_width(style, " …");
+            let title = theme.truncate(style, self.title, max_width);
+            (format!("{} …", title), style)
+        } else {
+            let style = TextStyle::Title;
+            (
+                theme.truncate(style, self.title, max_width).into_owned(),
+                style,
+            )
+        };
+        let (x, y) = placement.title_point(style, theme);
+        let title_width = theme.text_width(style, &text);
+        commands.push(DrawCommand::Text { text, x, y, style });
+
+        // Smaller than the title, they sit on the same line.
+        let (_, hint_y) = placement.title_point(TextStyle::Hint, theme);
+        if let Some(item_count) = item_count {
+            commands.push(DrawCommand::Text {
+                text: item_count,
+                x: x + title_width + gap,
+                y: hint_y,
+                style: TextStyle::Hint,
+            });
+        }
+        if let Some(position) = position {
+            let width = theme.text_width(TextStyle::Hint, &position);
+            commands.push(DrawCommand::Text {
+                text: position,
+                x: x + placement.text_max_width() - width,
+                y: hint_y,
+                style: TextStyle::Hint,
+            });
+        }
     }
 
-    /// Sets the text widget for the set title.
+    /// Pushes the [`DrawCommand`] of the text shown in place of the tiles when this row has no items.
     ///
-    /// This method places the index above the first leftmost image for a given set (`adjusted_set_idx`)
     /// # Arguments
-    /// * `adjusted_set_idx`: This is the canvas index for this set of data. This index is adjusted to
-    ///    stay between 0 and [`NUM_ROWS`]-1
-    fn show_row_title(&self, adjusted_set_idx: usize, ids: &Ids, ui: &mut UiCell) {
-        widget::Text::new(self.title)
-            .up_from(ids.imgs[ROW_STRIDE * adjusted_set_idx], 24.0)
-            .color(conrod::color::WHITE)
-            .font_size(28)
-            .set(ids.titles[self.true_set_idx % NUM_ROWS], ui);
+    /// * `text`: what to show, e.g. how to add items to this row.
+    fn show_empty(&self, commands: &mut Vec<DrawCommand<Id>>, text: &str) {
+        let (x, y) = self.placement().hint_point();
+        commands.push(DrawCommand::Text {
+            text: text.to_string(),
+            x,
+            y,
+            style: TextStyle::Hint,
+        });
     }
 }
 
@@ -373,91 +890,234 @@ impl<'a> SetRow<'a> {
 struct DisplayController<'a> {
     initialized: bool,
     rows: Vec<SetRow<'a>>,
+    /// Rows with a `true_set_idx` below this one are shown. See [`stream_rows`](DisplayController::stream_rows).
+    streamed_rows: usize,
     display: &'a Display,
-    image_map: Map<glium::texture::Texture2d>,
+    image_map: Map<glium::texture::SrgbTexture2d>,
+    /// Where the tiles of the rows are packed, its pages are in the `image_map`.
+    atlas: TextureAtlas,
+    /// Budget of the textures in the `image_map`, see [`DisplayController::enforce_gpu_budget`].
+    gpu: GpuBudget,
+    loader: ImageLoader,
+    /// Images loaded in the background waiting for their upload, see
+    /// [`poll_loaded_images`](DisplayController::poll_loaded_images).
+    uploads: VecDeque<LoadResult>,
     api_handle: &'a Api,
-    ids: Ids,
+    /// Ids of the widgets the rows are drawn with, see [`ConrodRenderer`].
+    renderer_ids: RendererIds,
     nf_id: Id,
-    prev_visible_range: Range<usize>,
-    cursor: Cursor,
+    /// Frames of the loading placeholder, see [`helpers::build_shimmer_frames`].
+    shimmer_ids: Vec<Id>,
+    /// Picks the frame of the loading placeholder to draw.
+    shimmer: Shimmer,
+    /// The cursor and the rows in view.
+    nav: NavigationModel,
+    /// Detail page drawn on top of the grid. See [`DisplayController::open_detail`].
+    detail: DetailView,
+    /// Stack of screens, the last one is the one drawn. [`Screen::Home`] is always at the bottom.
+    screens: Vec<Screen>,
+    /// State of the [`Screen::Search`].
+    search: SearchScreen,
+    /// State of the [`Screen::Grid`].
+    grid: GridScreen,
+    /// State of the [`Screen::ProfilePicker`].
+    picker: ProfilePicker,
+    /// PIN overlay drawn on top of everything else. See [`DisplayController::toggle_rating_ceiling`].
+    pin_entry: PinEntry,
+    /// Sort and filter overlay drawn on top of the home rows. See
+    /// [`DisplayController::open_filter_bar`].
+    filter_bar: FilterBar,
+    /// State of the [`Screen::Settings`].
+    settings: SettingsScreen,
+    /// State of the [`Screen::Screensaver`].
+    screensaver: Screensaver,
+    /// Banner drawn above the rows while the cursor is on the banner or the first row.
+    hero: HeroBanner,
+    /// Debug overlay drawn on top of every screen. See [`DisplayController::toggle_hud`].
+    hud: DebugHud,
+    /// Smooths out scrolling and focus changes. See [`DisplayController::tick`].
+    animator: Animator,
+    /// Backdrop of the home screen, tinted after the focused tile.
+    ambient: Ambient,
+    /// True if the animator was running during the last [`DisplayController::tick`].
+    was_animating: bool,
+    config: Config,
+    /// Both themes, the one in use is picked by [`Config::theme`].
+    themes: &'a Themes,
+    /// Sizes derived from the [`Config`] and the window size, see [`DisplayController::resize`].
+    layout: Layout,
+    /// Backs the rows ahead of the [`Api`] sets, see [`DisplayController::rebuild_local_row`].
+    local: LocalRows,
+    /// Tiles being fetched ahead of time, see [`DisplayController::prefetch_next_tiles`].
+    prefetches: Vec<PrefetchHandle>,
+    /// Cursor row, first item and row the [`prefetches`](DisplayController::prefetches) were issued for.
+    prefetch_target: Option<(usize, usize, usize)>,
+    /// Artwork the focused tile is drawn from, see [`DisplayController::request_focus_art`].
+    focus_art: Option<FocusArt>,
+    /// Video loop drawn over the focused tile, see [`DisplayController::update_preview`].
+    preview: VideoPreview,
+    /// External player the app is paused for, see [`DisplayController::launch_player`].
+    player: Option<ExternalPlayer>,
+    /// Plays the sounds of the moves, see [`DisplayController::play`].
+    audio: &'a Audio,
 }
 
 impl<'a> DisplayController<'a> {
-    fn new(display: &'a Display, api_handle: &'a Api, ui: &mut Ui) -> Self {
-        let mut ids = Ids::new(ui.widget_id_generator());
-        ids.imgs
-            .resize(NUM_OF_CACHED_IMAGES, &mut ui.widget_id_generator());
-        ids.titles.resize(NUM_ROWS, &mut ui.widget_id_generator());
+    /// Builds the controller for the home rows, the local ones coming from `local`.
+    fn new(
+        display: &'a Display,
+        api_handle: &'a Api,
+        ui: &mut Ui,
+        config: Config,
+        themes: &'a Themes,
+        local: LocalRows,
+        audio: &'a Audio,
+    ) -> Self {
+        // The window may not have the configured size, e.g. when starting fullscreen.
+        let layout = Layout::for_window(&config, ui.win_w, ui.win_h);
+        let mut renderer_ids = RendererIds::new(ui.widget_id_generator());
+        Self::reserve_renderer_ids(&mut renderer_ids, &layout, ui);
+        let detail = DetailView::new(DetailIds::new(ui.widget_id_generator()));
+        let hero = HeroBanner::new(HeroIds::new(ui.widget_id_generator()));
+        let hud = DebugHud::new(HudIds::new(ui.widget_id_generator()));
+        let ambient_ids = AmbientIds::new(ui.widget_id_generator());
+        let search_ids = SearchIds::new(ui.widget_id_generator());
+        let search = SearchScreen::new(search_ids, ui.widget_id_generator());
+        let grid = GridScreen::new(GridIds::new(ui.widget_id_generator()), (ui.win_w, ui.win_h));
+        let picker_ids = PickerIds::new(ui.widget_id_generator());
+        let picker = ProfilePicker::new(picker_ids, ui.widget_id_generator());
+        let pin_entry = PinEntry::new(PinIds::new(ui.widget_id_generator()));
+        let filter_bar = FilterBar::new(FilterBarIds::new(ui.widget_id_generator()));
+        let settings_ids = SettingsIds::new(ui.widget_id_generator());
+        let settings = SettingsScreen::new(settings_ids, ui.widget_id_generator());
+        let screensaver_ids = ScreensaverIds::new(ui.widget_id_generator());
+        let screensaver = Screensaver::new(screensaver_ids, config.animations);
+        let preview = VideoPreview::new(config.video_preview_delay());
 
-        let mut image_map = Map::<glium::texture::Texture2d>::new();
+        let mut image_map = Map::<glium::texture::SrgbTexture2d>::new();
         let nf = helpers::load_img_not_found();
         let img = helpers::load_img(display, nf);
         let nf_id = image_map.insert(img);
+        let shimmer_ids: Vec<Id> = helpers::build_shimmer_frames(128, 56, SHIMMER_FRAMES)
+            .into_iter()
+            .map(|frame| image_map.insert(helpers::load_img(display, frame)))
+            .collect();
 
         Self {
             initialized: false,
             rows: Vec::new(),
+            streamed_rows: 0,
             display,
             image_map,
+            atlas: TextureAtlas::default(),
+            gpu: GpuBudget::new(config.gpu_budget_mb * 1024 * 1024),
+            loader: ImageLoader::new(api_handle.image_cache(), config.gamma),
+            uploads: VecDeque::new(),
             api_handle,
-            ids,
+            renderer_ids,
             nf_id,
-            prev_visible_range: 0..NUM_ROWS,
-            cursor: Cursor::default(),
+            shimmer: Shimmer::new(shimmer_ids.len(), Instant::now()),
+            shimmer_ids,
+            nav: NavigationModel::new(layout.num_rows)
+                .with_wrap(config.wrap_navigation)
+                .with_skipped(if config.skip_empty_rows {
+                    Self::empty_rows(api_handle)
+                } else {
+                    Vec::new()
+                }),
+            detail,
+            screens: vec![Screen::Home],
+            search,
+            grid,
+            picker,
+            pin_entry,
+            filter_bar,
+            settings,
+            screensaver,
+            hero,
+            hud,
+            animator: Animator::new(
+                config.animations,
+                HERO_BANNER_HEIGHT,
+                layout.image_scale_up_factor,
+            ),
+            ambient: Ambient::new(
+                ambient_ids,
+                themes.get(config.theme).background,
+                config.animations,
+            ),
+            was_animating: false,
+            config,
+            themes,
+            layout,
+            local,
+            prefetches: Vec::new(),
+            prefetch_target: None,
+            focus_art: None,
+            preview,
+            player: None,
+            audio,
         }
     }
 
     /// Initialize the [`DisplayController`]. This is meant to be called once at start of the program.
-    fn initialize(&mut self, ui: &mut Ui, cursor: &Cursor) {
+    ///
+    /// Only the banner and the row under the cursor are set up here, the rest of the visible rows
+    /// are streamed in by [`stream_rows`](DisplayController::stream_rows).
+    fn initialize(&mut self, ui: &mut Ui) {
         if self.initialized {
             return;
         }
         self.initialized = true;
-        //NOTE: in this method, `true` amd `adjusted` indices are the same.
-        let ui = &mut ui.set_widgets();
-        for set_idx in self.prev_visible_range.clone() {
-            let row_data = self.api_handle.get_set(set_idx).unwrap();
-            let mut set_row = SetRow::new(row_data, set_idx);
-            for item_idx in 0..ROW_STRIDE {
-                set_row.show(
-                    self.display,
-                    ui,
-                    &mut self.image_map,
-                    &self.ids,
-                    &cursor,
-                    &self.nf_id,
-                    item_idx,
-                    set_idx,
-                );
-            }
-            set_row.show_row_title(set_idx, &self.ids, ui);
-            self.rows.push(set_row);
-        }
+        self.hero.request(self.api_handle, &mut self.loader);
+        self.update_image_widgets(ui);
     }
 
-    /// This function takes the `true_set_index` and produces the range of sets that are going to be visible
-    /// taking into account the expected number of visible rows.
-    ///
-    /// For example:
-    ///  - with [`NUM_ROWS`] set to 4
-    ///  - if set set_idx 0 through 2 the visible range is 0 to 4
-    ///  - if user goes down 3 times now set_idx is 3 and visible range is 1 to 5
-    ///  - if from 3 it goes to 4 then visible range now is 2 to 6
-    ///  - if user now goes BACK so set_idx is back to 3 the range is still 2 to 6
-    ///    This helps ease the transition since it won't jump all the rows back
-    fn visible_set_range(&mut self, true_set_index: usize) -> Range<usize> {
-        if (true_set_index - self.prev_visible_range.start) == 1 {
-            return self.prev_visible_range.clone();
-        }
-        let new_range = if true_set_index + 2 > NUM_ROWS {
-            let shift = (true_set_index + 2) - NUM_ROWS;
-            shift..(shift + NUM_ROWS)
-        } else {
-            0..NUM_ROWS
-        };
+    /// The `true_set_idx` of the [`Api`] sets without items, see [`Config::skip_empty_rows`].
+    fn empty_rows(api_handle: &Api) -> Vec<usize> {
+        (0..api_handle.get_num_of_sets().unwrap_or(0))
+            .filter(|set_idx| {
+                api_handle
+                    .get_set(*set_idx)
+                    .map_or(false, |set_data| set_data.get_item_count() == 0)
+            })
+            .map(|set_idx| set_idx + LOCAL_ROWS)
+            .collect()
+    }
+
+    /// Returns the number of rows that should end up built for the current visible range.
+    fn rows_to_stream(&self) -> usize {
+        let num_of_rows = self.api_handle.get_num_of_sets().unwrap_or(0) + LOCAL_ROWS;
+        self.nav.visible_rows().end.min(num_of_rows)
+    }
+
+    /// Returns true while some of the visible rows haven't been built yet.
+    fn is_streaming(&self) -> bool {
+        self.streamed_rows < self.rows_to_stream()
+    }
+
+    /// Called once per loop. Builds up to [`ROWS_PER_LOOP`] of the visible rows still missing
+    /// and refreshes the widgets if it did.
+    fn stream_rows(&mut self, ui: &mut Ui) {
+        if !self.is_streaming() {
+            return;
+        }
+        self.streamed_rows = (self.streamed_rows + ROWS_PER_LOOP).min(self.rows_to_stream());
+        debug!("streamed rows: {}", self.streamed_rows);
+        self.refresh(ui);
+    }
 
-        self.prev_visible_range = new_range.clone();
-        new_range
+    /// Makes room for every tile in view plus the highlighted one, twice over since they are drawn
+    /// along with the placeholder while crossfading, for a title, its number of items and a hint per
+    /// row plus the position of the focused row, for the strip below the focused tile and for its glow.
+    fn reserve_renderer_ids(renderer_ids: &mut RendererIds, layout: &Layout, ui: &mut Ui) {
+        renderer_ids.reserve(
+            2 * (layout.num_of_cached_images() + 1),
+            3 * layout.num_rows + 3,
+            1,
+            1,
+            ui,
+        );
     }
 
     /// This associated function is meant to be the access point of the [`rows`](DisplayController::rows) vector.
@@ -465,22 +1125,30 @@ impl<'a> DisplayController<'a> {
         rows: &'b mut Vec<SetRow<'a>>,
         true_set_idx: usize,
         api_handle: &'a Api,
+        local: &LocalRows,
+        layout: Layout,
     ) -> Option<&'b mut SetRow<'a>> {
-        if rows.get_mut(true_set_idx).is_some() {
-            return rows.get_mut(true_set_idx);
-        }
-        // we know res is none so need to fetch the data for this set.
-        let set_row_opt = if let Some(row_data) = api_handle.get_set(true_set_idx) {
-            let set_row = SetRow::new(row_data, true_set_idx);
-            Some(set_row)
-        } else {
-            None
-        };
-        if let Some(set_row) = set_row_opt {
+        // The rows are indexed by their `true_set_idx`, so the ones in between are built too when
+        // jumping ahead, see `DisplayController::jump_to_set`.
+        while rows.len() <= true_set_idx {
+            let row_data = Self::get_row_data(api_handle, local, rows.len())?;
+            let set_row = SetRow::new(row_data, rows.len(), layout, local.locale);
             rows.push(set_row);
-            rows.last_mut()
+        }
+        rows.get_mut(true_set_idx)
+    }
+
+    /// Returns the [`SetData`] backing the row `true_set_idx`, either one assembled locally or
+    /// the [`Api`] set shifted by [`LOCAL_ROWS`].
+    fn get_row_data(
+        api_handle: &'a Api,
+        local: &LocalRows,
+        true_set_idx: usize,
+    ) -> Option<SetData<'a>> {
+        if true_set_idx < LOCAL_ROWS {
+            local.get_set(api_handle, true_set_idx)
         } else {
-            None
+            api_handle.get_set(true_set_idx - LOCAL_ROWS)
         }
     }
 
@@ -488,205 +1156,2401 @@ impl<'a> DisplayController<'a> {
         info!(
             "Image map size {}. idx:{}",
             self.image_map.len(),
-            self.cursor.true_item_idx
+            self.nav.cursor.true_item_idx
         );
         let ui = &mut ui.set_widgets();
+        let now = Instant::now();
+        let placeholders = Placeholders {
+            not_found: self.nf_id,
+            loading: self.shimmer_ids[self.shimmer.frame(now)],
+        };
+        let theme = self.themes.get(self.config.theme);
         let mut highlighted_data = None;
-        for (adjusted_set_idx, true_set_idx) in
-            self.visible_set_range(self.cursor.true_set_idx).enumerate()
-        {
-            let fetched = Self::fetch_row(&mut self.rows, true_set_idx, self.api_handle);
+        let mut commands = Vec::new();
+        let focused_color = self
+            .rows
+            .get(self.nav.cursor.true_set_idx)
+            .filter(|_| !self.nav.cursor.on_banner)
+            .and_then(|row| row.cached(self.nav.cursor.true_item_idx))
+            .and_then(|data| data.dominant_color);
+        self.ambient
+            .set_target(theme.background, focused_color, now);
+        self.ambient.show(ui, now);
+        let banner_shift = if self.is_hero_visible() {
+            self.hero.show(ui, self.nav.cursor.on_banner, &self.nf_id);
+            HERO_BANNER_HEIGHT
+        } else {
+            0.0
+        };
+        let visible = self.nav.visible_set_range();
+        self.loader.focus(self.load_focus(visible.clone()));
+        let num_of_rows = self.api_handle.get_num_of_sets().unwrap_or(0) + LOCAL_ROWS;
+        let focus = if self.nav.cursor.on_banner {
+            None
+        } else {
+            Some((self.nav.cursor.true_set_idx, self.nav.cursor.true_item_idx))
+        };
+        self.animator
+            .scroll_vertically(self.nav.shown_row(visible.start), banner_shift, now);
+        self.animator
+            .set_focus(focus, self.layout.image_scale_up_factor, now);
+        let top_shift = self.animator.top_shift(now)
+            + self.animator.vertical_offset(now) * self.layout.row_height;
+        let shrinking = self.animator.shrinking(now);
+
+        // The row with the cursor is always shown right away, the ones below it stream in.
+        self.streamed_rows = self.streamed_rows.max(self.nav.cursor.true_set_idx + 1);
+        let mut row_top = 0.0;
+        for true_set_idx in visible.clone() {
+            if true_set_idx >= self.streamed_rows {
+                break;
+            }
+            if self.nav.is_skipped(true_set_idx) {
+                continue;
+            }
+            let fetched = Self::fetch_row(
+                &mut self.rows,
+                true_set_idx,
+                self.api_handle,
+                &self.local,
+                self.layout,
+            );
             if fetched.is_none() {
                 break;
             }
             let set_row = fetched.unwrap();
-            for adjusted_item_idx in 0..ROW_STRIDE {
+            set_row.top_shift = top_shift;
+            set_row.row_top = row_top;
+            row_top += set_row.layout.row_height;
+            set_row.left_shift = self.animator.row_offset(true_set_idx, now);
+            set_row.shrinking = shrinking
+                .filter(|((set_idx, _), _)| *set_idx == true_set_idx)
+                .map(|((_, item_idx), scale)| (item_idx, scale));
+            // The empty api sets only get here unless skipped, see `Config::skip_empty_rows`.
+            let num_of_tiles = if set_row.item_count() == 0 {
+                let hint = if set_row.order.is_filtered() {
+                    i18n::tr(self.config.locale, Text::NothingMatches)
+                } else {
+                    self.local.empty_hint(true_set_idx)
+                };
+                set_row.show_empty(&mut commands, hint);
+                0
+            } else {
+                set_row.layout.row_stride
+            };
+            for adjusted_item_idx in 0..num_of_tiles {
                 let found_highlighted = set_row.show(
-                    self.display,
-                    ui,
-                    &mut self.image_map,
-                    &self.ids,
-                    &self.cursor,
-                    &self.nf_id,
+                    &mut commands,
+                    &mut self.loader,
+                    &self.nav.cursor,
+                    &placeholders,
                     adjusted_item_idx,
-                    adjusted_set_idx,
+                    now,
                 );
                 if found_highlighted.is_some() {
                     highlighted_data = found_highlighted;
                 }
             }
-            set_row.show_row_title(adjusted_set_idx, &self.ids, ui);
+            let loading = self
+                .loader
+                .notifier()
+                .is_row_in_flight(LoadKind::Tile, true_set_idx);
+            let position = match focus {
+                Some((set_idx, _)) if set_idx == true_set_idx => Some((
+                    self.nav.shown_row(true_set_idx) + 1,
+                    self.nav.shown_row(num_of_rows),
+                )),
+                _ => None,
+            };
+            set_row.show_row_title(&mut commands, loading, position, theme, self.config.locale);
         }
 
+        let mut focus_req = None;
         if let Some(HighlightedItemData {
             img_id,
+            region,
             w,
             h,
+            opacity,
             true_set_idx,
             adjusted_item_idx,
-            adjusted_set_idx,
         }) = highlighted_data
         {
-            self.cursor.adjusted_item_idx = adjusted_item_idx;
-            if let Some(highlighted_row) =
-                Self::fetch_row(&mut self.rows, true_set_idx, self.api_handle)
-            {
-                highlighted_row.draw_image_highlighted(
-                    img_id,
-                    w,
-                    h,
-                    adjusted_set_idx,
+            if let Some(highlighted_row) = Self::fetch_row(
+                &mut self.rows,
+                true_set_idx,
+                self.api_handle,
+                &self.local,
+                self.layout,
+            ) {
+                let scale = self.animator.focus_scale(now);
+                if self.config.sharp_focus {
+                    focus_req = highlighted_row.focus_request(self.nav.cursor.true_item_idx);
+                }
+                // Same size as the tile, only sharper, or its video loop.
+                let sharp_id = self
+                    .focus_art
+                    .as_ref()
+                    .filter(|art| focus_req.as_ref().map_or(false, |req| req.url == art.url))
+                    .and_then(|art| art.img_id);
+                let art_id = self
+                    .preview
+                    .texture((true_set_idx, self.nav.cursor.true_item_idx))
+                    .or(sharp_id);
+                let (img_id, region) = match art_id {
+                    Some(art_id) => (art_id, None),
+                    None => (img_id, region),
+                };
+                let data = CachedImgData {
+                    region,
+                    ..CachedImgData::new(img_id, w, h)
+                };
+                highlighted_row.show_glow(&mut commands, &data, scale, adjusted_item_idx);
+                if opacity < 1.0 {
+                    let placeholder = CachedImgData::loading(placeholders.loading);
+                    highlighted_row.draw_image_highlighted(
+                        &mut commands,
+                        &placeholder,
+                        scale,
+                        adjusted_item_idx,
+                        1.0 - opacity,
+                    );
+                }
+                let rect = highlighted_row.draw_image_highlighted(
+                    &mut commands,
+                    &data,
+                    scale,
                     adjusted_item_idx,
-                    &self.ids,
-                    ui,
+                    opacity,
                 );
+                // Drawn last so it's on top of the row below.
+                highlighted_row.show_item_info(
+                    &mut commands,
+                    self.nav.cursor.true_item_idx,
+                    rect,
+                    theme,
+                );
+            }
+        }
+        ConrodRenderer::new(ui, &mut self.renderer_ids, theme).draw(&commands);
+        for command in &commands {
+            if let DrawCommand::Image { image, .. } = command {
+                self.gpu.drawn(*image, now);
             }
         }
+
+        if let Some(req) = focus_req {
+            self.request_focus_art(req);
+        }
+        self.enforce_gpu_budget(visible.clone());
+        self.evict_far_rows(visible);
+        self.prefetch_next_tiles();
+        self.detail.show(ui, &self.nf_id);
+        self.filter_bar.show(ui, self.theme(), self.config.locale);
+        self.pin_entry.show(ui, self.config.locale);
+        self.show_hud(ui);
     }
 
-    pub(crate) fn move_current_set_left(&mut self, ui: &mut Ui) {
-        if let Some(cur_row_data) =
-            Self::fetch_row(&mut self.rows, self.cursor.true_set_idx, self.api_handle)
-        {
-            cur_row_data.shift_left(self.cursor.adjusted_item_idx);
-            if self.cursor.true_item_idx > 0 {
-                self.cursor.true_item_idx -= 1;
+    /// Warms the [`ImageCache`](api::ImageCache) with the tiles likely to be shown next to hide the
+    /// network latency: the [`row_stride`](Layout::row_stride) items right of the ones in view on the
+    /// cursor row, and the tiles of the first row below the visible ones.
+    ///
+    /// Nothing is prefetched while the visible rows are still streaming in. The batches are only
+    /// issued again when the cursor row scrolls or the visible rows change, dropping the previous
+    /// ones cancels whatever they didn't fetch yet.
+    fn prefetch_next_tiles(&mut self) {
+        if self.is_streaming() {
+            return;
+        }
+        let cursor_set_idx = self.nav.cursor.true_set_idx;
+        let next_set_idx = self.nav.visible_rows().end;
+        // Rows not built yet start scrolled all the way left with the window layout.
+        let window_of = |rows: &Vec<SetRow>, true_set_idx: usize, layout: &Layout| {
+            rows.get(true_set_idx)
+                .map_or((0, layout.row_stride), |row| {
+                    (row.scroll.left_right_idx_adjustment, row.layout.row_stride)
+                })
+        };
+        let (scroll, row_stride) = window_of(&self.rows, cursor_set_idx, &self.layout);
+        let first_item_idx = scroll + row_stride;
+        let target = (cursor_set_idx, first_item_idx, next_set_idx);
+        if self.prefetch_target == Some(target) {
+            return;
+        }
+        self.prefetch_target = Some(target);
+
+        let mut prefetches = Vec::new();
+        if let Some(row) = self.rows.get(cursor_set_idx) {
+            prefetches.push(row.prefetch_images(first_item_idx..first_item_idx + row_stride));
+        }
+        let (first_item_idx, row_stride) = window_of(&self.rows, next_set_idx, &self.layout);
+        let next_items = first_item_idx..first_item_idx + row_stride;
+        match self.rows.get(next_set_idx) {
+            Some(row) => prefetches.push(row.prefetch_images(next_items)),
+            None => {
+                if let Some(set_data) =
+                    Self::get_row_data(self.api_handle, &self.local, next_set_idx)
+                {
+                    prefetches.push(set_data.prefetch_images(next_items));
+                }
             }
-            self.update_image_widgets(ui);
         }
+        debug!("prefetching around {:?}", target);
+        self.prefetches = prefetches;
     }
 
-    pub(crate) fn move_current_set_right(&mut self, ui: &mut Ui) {
-        if let Some(cur_row_data) =
-            Self::fetch_row(&mut self.rows, self.cursor.true_set_idx, self.api_handle)
-        {
-            if cur_row_data.shift_right(self.cursor.adjusted_item_idx, self.cursor.true_item_idx) {
-                self.cursor.true_item_idx += 1;
-            }
-            self.update_image_widgets(ui);
+    /// Where the cursor is among the `visible` rows, for the [`ImageLoader`] to load the tiles
+    /// closest to it first.
+    fn load_focus(&self, visible: Range<usize>) -> LoadFocus {
+        LoadFocus {
+            true_set_idx: self.nav.cursor.true_set_idx,
+            column: self.nav.cursor.adjusted_item_idx,
+            visible,
+            row_offsets: self
+                .rows
+                .iter()
+                .enumerate()
+                .map(|(true_set_idx, row)| (true_set_idx, row.scroll.left_right_idx_adjustment))
+                .collect(),
         }
     }
 
-    pub(crate) fn move_to_prev_set(&mut self, ui: &mut Ui) {
-        if self.cursor.true_set_idx > 0 {
-            self.cursor.true_set_idx -= 1;
-            if let Some(cur_row_data) =
-                Self::fetch_row(&mut self.rows, self.cursor.true_set_idx, self.api_handle)
-            {
-                self.cursor.true_item_idx =
-                    self.cursor.adjusted_item_idx + cur_row_data.left_right_idx_adjustment;
+    /// Releases the textures of the rows further than [`BUFFERED_ROWS`] from the `visible` ones,
+    /// along with whatever the rows pushed out of their [`cached_img_id`](SetRow::cached_img_id) ring.
+    ///
+    /// The tiles still loading for those rows, or for items scrolled out of view, are cancelled so
+    /// they don't hold up the ones in view.
+    fn evict_far_rows(&mut self, visible: Range<usize>) {
+        let keep = ui_core::buffered_range(visible, BUFFERED_ROWS);
+        let rows = &self.rows;
+        let cancelled = self.loader.cancel(|kind, true_set_idx, true_item_idx| {
+            kind == LoadKind::Tile
+                && !(keep.contains(&true_set_idx)
+                    && rows
+                        .get(true_set_idx)
+                        .map_or(false, |row| row.is_in_window(true_item_idx)))
+        });
+        if cancelled > 0 {
+            debug!("cancelled {} stale tiles", cancelled);
+        }
+        for (true_set_idx, row) in self.rows.iter_mut().enumerate() {
+            if !keep.contains(&true_set_idx) {
+                row.evict_all();
+            }
+            for evicted in row.evicted.drain(..) {
+                debug!("release img {:?} of set {}", evicted.img_id, true_set_idx);
+                Self::release(&mut self.image_map, &mut self.atlas, &self.nf_id, evicted);
             }
         }
-        self.update_image_widgets(ui);
     }
 
-    pub(crate) fn move_to_next_set(&mut self, ui: &mut Ui) {
-        if self.cursor.true_set_idx < self.api_handle.get_num_of_sets().unwrap() - 1 {
-            self.cursor.true_set_idx += 1;
-            if let Some(cur_row_data) =
-                Self::fetch_row(&mut self.rows, self.cursor.true_set_idx, self.api_handle)
-            {
-                self.cursor.true_item_idx =
-                    self.cursor.adjusted_item_idx + cur_row_data.left_right_idx_adjustment;
-            }
+    /// Releases the tiles drawn the longest ago while the textures take more than the
+    /// [`gpu_budget_mb`](Config::gpu_budget_mb), the ones in view of the `visible` rows excepted.
+    /// Only their entries are evicted here, [`evict_far_rows`](DisplayController::evict_far_rows)
+    /// releases them.
+    fn enforce_gpu_budget(&mut self, visible: Range<usize>) {
+        let usage = self.gpu_usage();
+        if !usage.is_over() {
+            return;
+        }
+        self.gpu.forget_released(&self.image_map);
+        // The tiles packed in the atlas don't give any memory back, see `gpu_budget`.
+        let image_map = &self.image_map;
+        let nf_id = self.nf_id;
+        let candidates = self
+            .rows
+            .iter()
+            .enumerate()
+            .flat_map(|(true_set_idx, row)| {
+                let in_view = visible.contains(&true_set_idx);
+                row.cached_img_id
+                    .iter()
+                    .filter(move |(true_item_idx, data)| {
+                        data.region.is_none()
+                            && data.img_id != nf_id
+                            && !(in_view && row.is_in_window(*true_item_idx))
+                    })
+                    .filter_map(move |(true_item_idx, data)| {
+                        let bytes = gpu_budget::texture_bytes(image_map.get(&data.img_id)?);
+                        Some(((true_set_idx, true_item_idx), data.img_id, bytes))
+                    })
+            });
+        let evictions = self.gpu.pick_evictions(candidates, usage.excess());
+        debug!(
+            "{} bytes of textures over the budget, evicting {} tiles",
+            usage.excess(),
+            evictions.len()
+        );
+        for (true_set_idx, true_item_idx) in evictions {
+            self.rows[true_set_idx].evict(true_item_idx);
         }
-        self.update_image_widgets(ui);
     }
-}
 
-/// Represents where the cursor is at on the screen. By cursor, it really means what are the indices
-/// of the highlighted item.
-#[derive(Default)]
-struct Cursor {
-    true_set_idx: usize,
-    true_item_idx: usize,
-    adjusted_item_idx: usize,
+    /// What the textures take on the GPU, see [`gpu_budget`].
+    pub(crate) fn gpu_usage(&self) -> GpuUsage {
+        self.gpu.usage(&self.image_map)
+    }
+
+    /// Releases the texture of `data`, or its room in the `atlas`. The not found image `nf_id` is
+    /// shared, it's never released.
+    fn release(
+        image_map: &mut Map<glium::texture::SrgbTexture2d>,
+        atlas: &mut TextureAtlas,
+        nf_id: &Id,
+        data: CachedImgData,
+    ) {
+        match data.region {
+            Some(region) => atlas.remove(&region),
+            None if data.img_id != *nf_id => {
+                image_map.remove(data.img_id);
+            }
+            None => (),
+        }
+    }
+
+    /// Recomputes the [`Layout`] for a window of `width` x `height` and lays the widgets out again.
+    ///
+    /// More [`RendererIds`] are reserved if needed and the cursor row is scrolled if needed so the
+    /// focused tile stays in view.
+    pub(crate) fn resize(&mut self, width: f64, height: f64, ui: &mut Ui) {
+        self.grid.resize(width, height);
+        if self.screens.contains(&Screen::Grid) {
+            self.request_grid_images();
+            self.refresh(ui);
+        }
+        let layout = Layout::for_window(&self.config, width, height);
+        if layout == self.layout {
+            return;
+        }
+        info!("Resized to {}x{}: {:?}", width, height, layout);
+        self.layout = layout;
+        Self::reserve_renderer_ids(&mut self.renderer_ids, &layout, ui);
+
+        for row in self.rows.iter_mut() {
+            row.set_layout(layout);
+        }
+        // Keep the focused tile away from the right edge, same as `RowScroll::shift_right` does.
+        if let Some(row) = self.rows.get_mut(self.nav.cursor.true_set_idx) {
+            self.nav.clamp_to_row(&mut row.scroll, row.extent());
+        }
+        // Start over from the first range so `visible_set_range` lands the cursor row in view.
+        self.nav.reset(layout.num_rows);
+        self.refresh(ui);
+    }
+
+    /// The [`HeroBanner`] is only shown while the cursor is on it or on the first row so the
+    /// rows further down always fit in the window.
+    fn is_hero_visible(&self) -> bool {
+        self.nav.cursor.on_banner || self.nav.cursor.true_set_idx == 0
+    }
+
+    /// Returns true while a transition is in progress, tiles fading in and shimmering placeholders
+    /// included.
+    fn is_animating(&self) -> bool {
+        let now = Instant::now();
+        let visible = self.nav.visible_rows();
+        self.animator.is_animating(now)
+            || self.ambient.is_animating(now)
+            || self.screensaver.is_fading(now)
+            || self
+                .rows
+                .iter()
+                .skip(visible.start)
+                .take(visible.len())
+                .any(|row| row.is_fading(now) || row.is_waiting_for_images())
+    }
+
+    /// Called once per loop. Refreshes the widgets while a transition is in progress, plus one
+    /// extra time once it's done so everything lands on its resting position. Also refreshes when
+    /// a tile that failed to load is due for another attempt, see [`SetRow::retry_later`], and
+    /// when a frame of the video loop of the focused tile comes.
+    ///
+    /// Nothing happens while the [`ExternalPlayer`] runs, the widgets are refreshed once it exits.
+    fn tick(&mut self, ui: &mut Ui) {
+        if let Some(player) = self.player.as_mut() {
+            if !player.has_exited() {
+                return;
+            }
+            self.player = None;
+            self.refresh(ui);
+        }
+        let animating = self.is_animating();
+        let now = Instant::now();
+        let preview_changed = self.update_preview(now);
+        if self.screen() == Screen::Screensaver {
+            self.screensaver
+                .request_next(self.api_handle, &mut self.loader, now);
+        }
+        let visible = self.nav.visible_rows();
+        let retry_due = self
+            .rows
+            .iter()
+            .skip(visible.start)
+            .take(visible.len())
+            .any(|row| row.is_retry_due(now));
+        if animating || self.was_animating || retry_due || preview_changed || self.hud.is_due(now) {
+            self.refresh(ui);
+        }
+        self.was_animating = animating;
+    }
+
+    /// Moves the [`VideoPreview`] along with the focus, stopping it off the home screen, and writes
+    /// the newest frame of the loop playing to its texture. Returns true if the focused tile is to
+    /// be drawn again.
+    fn update_preview(&mut self, now: Instant) -> bool {
+        let key = Some((self.nav.cursor.true_set_idx, self.nav.cursor.true_item_idx))
+            .filter(|_| !self.nav.cursor.on_banner && self.screen() == Screen::Home);
+        let mut changed = false;
+        if !self.preview.is_for(key) {
+            let scale = self.layout.image_scale_up_factor;
+            let video = key.and_then(|(true_set_idx, true_item_idx)| {
+                let row = self.rows.get(true_set_idx)?;
+                let video = row
+                    .set_data
+                    .get_item_video_art(row.item_idx(true_item_idx))
+                    .ok()?;
+                let (w, h) = row.draw_size(true_item_idx)?;
+                let size = (
+                    (w as f64 * scale).round() as u32,
+                    (h as f64 * scale).round() as u32,
+                );
+                Some((video, size))
+            });
+            if let Some(img_id) = self.preview.rest_on(key, video, now) {
+                self.image_map.remove(img_id);
+                changed = true;
+            }
+        }
+
+        let (frame, released) = self.preview.poll(now);
+        if let Some(img_id) = released {
+            self.image_map.remove(img_id);
+            changed = true;
+        }
+        if let Some(frame) = frame {
+            let texture = key
+                .and_then(|key| self.preview.texture(key))
+                .and_then(|img_id| self.image_map.get(&img_id));
+            match texture {
+                Some(texture) => helpers::write_rgba(texture, frame),
+                None => {
+                    let img_id = self
+                        .image_map
+                        .insert(helpers::upload_rgba(self.display, frame));
+                    self.preview.set_texture(img_id);
+                }
+            }
+            changed = true;
+        }
+        changed
+    }
+
+    /// When the video loop of the focused tile is due to start, see [`VideoPreview::due_at`].
+    fn preview_due(&self) -> Option<Instant> {
+        self.preview.due_at()
+    }
+
+    /// Wakes the event loop up with the `waker` whenever a frame of the video loop of the focused
+    /// tile is decoded, see [`VideoPreview::wake_with`].
+    fn wake_on_preview(&mut self, waker: Waker) {
+        self.preview.wake_with(waker);
+    }
+
+    /// Shows the [`DebugHud`] if it's hidden, hides it otherwise.
+    fn toggle_hud(&mut self, ui: &mut Ui) {
+        self.hud.toggle();
+        self.refresh(ui);
+    }
+
+    /// Returns true while the [`DebugHud`] is shown, it's then updated a few times per second.
+    fn is_hud_shown(&self) -> bool {
+        self.hud.is_shown()
+    }
+
+    /// Remembers a frame drawn in `duration`, ending at `end`, for the [`DebugHud`].
+    fn record_frame(&mut self, end: Instant, duration: Duration) {
+        self.hud.record_frame(end, duration);
+    }
+
+    /// Sets the [`DebugHud`] widgets, if it's shown. Called last so it's on top of the screen.
+    fn show_hud(&mut self, ui: &mut conrod::UiCell) {
+        let stats = HudStats {
+            gpu: self.gpu_usage(),
+            in_flight: self.loader.notifier().in_flight_count(),
+            queued_uploads: self.uploads.len(),
+            image_tier: self.api_handle.image_tier(),
+            throughput: self.api_handle.image_cache().bandwidth().throughput(),
+            cursor: &self.nav.cursor,
+        };
+        self.hud.show(ui, &stats);
+    }
+
+    /// Returns true while there are images being loaded in the background.
+    fn is_loading(&self) -> bool {
+        self.loader.notifier().in_flight_count() > 0
+    }
+
+    /// Returns true while there are images loaded waiting for their upload, see [`UploadBudget`].
+    fn has_pending_uploads(&self) -> bool {
+        !self.uploads.is_empty()
+    }
+
+    /// Wakes the event loop up with the `waker` whenever an image is loaded, see
+    /// [`ImageLoader::wake_with`].
+    fn wake_on_loaded(&self, waker: Waker) {
+        self.loader.wake_with(waker);
+    }
+
+    /// Picks up the images loaded in the background, uploads them as textures and refreshes the widgets.
+    ///
+    /// The uploads are spread over the frames as per the [`UploadBudget`], the images left over
+    /// wait in the [`uploads`](DisplayController::uploads) queue. The ones no longer wanted by then
+    /// are dropped without being uploaded.
+    fn poll_loaded_images(&mut self, ui: &mut Ui) {
+        self.uploads.extend(self.loader.poll());
+        if self.uploads.is_empty() {
+            return;
+        }
+
+        let mut budget = UploadBudget::per_frame();
+        while !budget.is_spent() {
+            let res = match self.uploads.pop_front() {
+                Some(res) => res,
+                None => break,
+            };
+            self.loader.handled(&res);
+            let LoadResult {
+                kind,
+                true_set_idx,
+                true_item_idx,
+                url,
+                img,
+                dominant_color,
+                preview,
+            } = res;
+            if kind == LoadKind::Hero || kind == LoadKind::Logo {
+                self.insert_loaded_detail_art(kind, true_set_idx, true_item_idx, img, &mut budget);
+                continue;
+            }
+            if kind == LoadKind::Focus {
+                self.insert_loaded_focus(&url, img, &mut budget);
+                continue;
+            }
+            if kind == LoadKind::Screensaver {
+                self.insert_loaded_slide(true_set_idx, true_item_idx, img, &mut budget);
+                continue;
+            }
+            if kind == LoadKind::Banner {
+                match img {
+                    Ok(img) => {
+                        budget.spend(&img);
+                        let img = helpers::upload_rgba(self.display, img);
+                        let (w, h) = (img.get_width(), img.get_height().unwrap());
+                        let img_id = self.image_map.insert(img);
+                        self.hero.set_image(img_id, w as f64, h as f64);
+                    }
+                    Err(e) => info!("Failed to load the banner: {}", e),
+                }
+                continue;
+            }
+            // The row may have scrolled away (or the search changed, or the grid closed) while the image was loading,
+            // don't upload it for nothing. The local rows can also have been rebuilt with a
+            // different item in that spot, hence the url check.
+            // The tiles are sized after the aspect ratio of the artwork picked for them.
+            // A preview is only of use until the image itself is shown, which then replaces it.
+            let (wanted, tile_size, shows_preview) = if kind == LoadKind::Search {
+                (self.search.wants(true_set_idx, true_item_idx), None, false)
+            } else if kind == LoadKind::Grid {
+                let open = self.screens.contains(&Screen::Grid);
+                (
+                    open && self.grid.wants(true_set_idx, true_item_idx),
+                    None,
+                    false,
+                )
+            } else {
+                let tile = self
+                    .rows
+                    .get(true_set_idx)
+                    .filter(|row| row.is_in_window(true_item_idx))
+                    .and_then(|row| {
+                        let tile = row
+                            .set_data
+                            .get_home_tile(row.item_idx(true_item_idx))
+                            .ok()?;
+                        // The size in the url changes with the artwork tier, the rest doesn't.
+                        if UrlBuilder::new(&tile.url).base() != UrlBuilder::new(&url).base() {
+                            return None;
+                        }
+                        let shown = row.cached(true_item_idx).map(|data| data.preview);
+                        Some((row.layout.tile_size(tile.aspect_ratio as f64), shown))
+                    });
+                match tile {
+                    Some((_, Some(false))) if preview => (false, None, false),
+                    Some((tile_size, shown)) => (true, Some(tile_size), shown == Some(true)),
+                    None => (false, None, false),
+                }
+            };
+            if !wanted {
+                debug!(
+                    "drop item {} of set {}, no longer in view",
+                    true_item_idx, true_set_idx
+                );
+                continue;
+            }
+            let data = match img {
+                Ok(img) => {
+                    budget.spend(&img);
+                    // The search results come and go with each key typed, only the rows are packed.
+                    let packed = match kind {
+                        LoadKind::Tile if !preview => {
+                            let may_grow = self.gpu_usage().fits(atlas::PAGE_BYTES);
+                            self.atlas
+                                .insert(self.display, &mut self.image_map, img, may_grow)
+                        }
+                        _ => Err(img),
+                    };
+                    let (img_id, region, (w, h)) = match packed {
+                        Ok(region) => (region.page, Some(region), (region.w, region.h)),
+                        Err(img) => {
+                            let img = helpers::upload_rgba(self.display, img);
+                            let size = (img.get_width(), img.get_height().unwrap());
+                            (self.image_map.insert(img), None, size)
+                        }
+                    };
+                    let (w, h) = match tile_size {
+                        Some(tile_size) => tile_size,
+                        // Already requested at the width it's drawn at, see `Config::image_options`.
+                        None => (w as f64, h as f64),
+                    };
+                    info!("put img {:?} ar {}", img_id, w / h);
+                    let data = CachedImgData {
+                        region,
+                        dominant_color,
+                        preview,
+                        ..CachedImgData::new(img_id, w, h)
+                    };
+                    if self.config.animations && !shows_preview {
+                        data.fading_in(Instant::now())
+                    } else {
+                        data
+                    }
+                }
+                Err(e) => {
+                    info!(
+                        "Failed to load item {} of set {}: {}",
+                        true_item_idx, true_set_idx, e
+                    );
+                    if kind == LoadKind::Tile && e.is_retryable() {
+                        if let Some(row) = self.rows.get_mut(true_set_idx) {
+                            row.retry_later(true_item_idx);
+                        }
+                        continue;
+                    }
+                    CachedImgData::not_found(self.nf_id.clone())
+                }
+            };
+            if kind == LoadKind::Search {
+                self.search.insert_loaded(true_set_idx, true_item_idx, data);
+            } else if kind == LoadKind::Grid {
+                self.grid.insert_loaded(true_item_idx, data);
+            } else if let Some(row) = self.rows.get_mut(true_set_idx) {
+                row.insert_loaded(true_item_idx, data);
+            }
+        }
+        self.release_search_evicted();
+        self.release_grid_evicted();
+        self.refresh(ui);
+    }
+
+    /// Uploads the hero image or the title logo for the detail page, as per `kind`, unless the page
+    /// was closed or moved to another item.
+    fn insert_loaded_detail_art(
+        &mut self,
+        kind: LoadKind,
+        true_set_idx: usize,
+        true_item_idx: usize,
+        img: Result<image::RgbaImage, api::ApiError>,
+        budget: &mut UploadBudget,
+    ) {
+        if !self.detail.is_showing(true_set_idx, true_item_idx) {
+            return;
+        }
+        match img {
+            Ok(img) => {
+                budget.spend(&img);
+                let img = helpers::upload_rgba(self.display, img);
+                let (w, h) = (img.get_width(), img.get_height().unwrap());
+                let img_id = self.image_map.insert(img);
+                if kind == LoadKind::Logo {
+                    self.detail.set_logo(img_id, w as f64, h as f64);
+                } else {
+                    self.detail.set_hero(img_id, w as f64, h as f64);
+                }
+            }
+            Err(e) => info!(
+                "Failed to load {:?} of item {} of set {}: {}",
+                kind, true_item_idx, true_set_idx, e
+            ),
+        }
+    }
+
+    /// Loads the artwork of the focused tile from `req`, see [`SetRow::focus_request`], unless it's
+    /// already shown or loading. The artwork of the tile focused before is released, only the last
+    /// one is kept.
+    fn request_focus_art(&mut self, req: LoadRequest) {
+        if self
+            .focus_art
+            .as_ref()
+            .map_or(false, |art| art.url == req.url)
+        {
+            return;
+        }
+        self.loader.cancel(|kind, _, _| kind == LoadKind::Focus);
+        if let Some(img_id) = self.focus_art.take().and_then(|art| art.img_id) {
+            self.image_map.remove(img_id);
+        }
+        self.focus_art = Some(FocusArt {
+            url: req.url.clone(),
+            img_id: None,
+        });
+        self.loader.submit(req);
+    }
+
+    /// Uploads the artwork of the focused tile, unless the focus moved on in the meantime. The tile
+    /// is drawn as is if it fails to load.
+    fn insert_loaded_focus(
+        &mut self,
+        url: &str,
+        img: Result<image::RgbaImage, api::ApiError>,
+        budget: &mut UploadBudget,
+    ) {
+        let art = match self.focus_art.as_mut() {
+            Some(art) if art.url == url && art.img_id.is_none() => art,
+            _ => return,
+        };
+        match img {
+            Ok(img) => {
+                budget.spend(&img);
+                let img = helpers::upload_rgba(self.display, img);
+                art.img_id = Some(self.image_map.insert(img));
+            }
+            Err(e) => debug!("No focus artwork from {}: {}", url, e),
+        }
+    }
+
+    /// Uploads the artwork shown by the [`Screensaver`] and releases the one it replaces, unless the
+    /// screensaver was closed in the meantime.
+    fn insert_loaded_slide(
+        &mut self,
+        true_set_idx: usize,
+        true_item_idx: usize,
+        img: Result<image::RgbaImage, api::ApiError>,
+        budget: &mut UploadBudget,
+    ) {
+        if !self.screensaver.wants(true_set_idx, true_item_idx) {
+            return;
+        }
+        match img {
+            Ok(img) => {
+                budget.spend(&img);
+                let img = helpers::upload_rgba(self.display, img);
+                let (w, h) = (img.get_width(), img.get_height().unwrap());
+                let img_id = self.image_map.insert(img);
+                let replaced =
+                    self.screensaver
+                        .set_image(img_id, w as f64, h as f64, Instant::now());
+                if let Some(replaced) = replaced {
+                    self.image_map.remove(replaced);
+                }
+            }
+            Err(e) => {
+                info!(
+                    "Failed to load hero of item {} of set {}: {}",
+                    true_item_idx, true_set_idx, e
+                );
+                self.screensaver.skip();
+            }
+        }
+    }
+
+    /// Opens the detail page for the item under the cursor. The [`Cursor`] is left untouched so
+    /// closing the page returns to the same spot in the grid.
+    ///
+    /// When the [`HeroBanner`] has the focus the page is opened for the item it shows.
+    pub(crate) fn open_detail(&mut self, ui: &mut Ui) {
+        if let Some((true_set_idx, true_item_idx)) = self.focused_api_indices() {
+            self.open_detail_at(true_set_idx, true_item_idx, ui);
+        }
+    }
+
+    /// Indices in the [`Api`] sets of the item under the cursor, or of the one the [`HeroBanner`]
+    /// shows when it has the focus. See [`DisplayController::api_indices`].
+    fn focused_api_indices(&self) -> Option<(usize, usize)> {
+        let cursor = &self.nav.cursor;
+        if cursor.on_banner {
+            return Some((0, 0));
+        }
+        let row = self.rows.get(cursor.true_set_idx)?;
+        self.api_indices(cursor.true_set_idx, row.item_idx(cursor.true_item_idx))
+    }
+
+    /// Indices in the [`Api`] sets of the item `item_idx` of the set of the row `true_set_idx`, see
+    /// [`SetRow::item_idx`]. None if the item of a local row can't be found in any of them.
+    fn api_indices(&self, true_set_idx: usize, item_idx: usize) -> Option<(usize, usize)> {
+        if true_set_idx < LOCAL_ROWS {
+            // The items of the local rows are looked up in the set they come from.
+            let content_id = self
+                .rows
+                .get(true_set_idx)
+                .and_then(|row| row.set_data.get_content_id(item_idx))?;
+            self.api_handle.find_item(content_id)
+        } else {
+            Some((true_set_idx - LOCAL_ROWS, item_idx))
+        }
+    }
+
+    /// The `contentId` of the item under the cursor, None while the [`HeroBanner`] has the focus.
+    fn focused_content_id(&self) -> Option<&'a str> {
+        let cursor = &self.nav.cursor;
+        if cursor.on_banner {
+            return None;
+        }
+        let row = self.rows.get(cursor.true_set_idx)?;
+        row.set_data
+            .get_content_id(row.item_idx(cursor.true_item_idx))
+    }
+
+    /// Opens the detail page for the given item. Unlike [`open_detail`](DisplayController::open_detail)
+    /// the item doesn't need to be in any of the [`rows`](DisplayController::rows).
+    ///
+    /// # Arguments
+    /// * `true_set_idx`: index of the set as returned by the [`Api`], i.e. not shifted by [`LOCAL_ROWS`].
+    fn open_detail_at(&mut self, true_set_idx: usize, true_item_idx: usize, ui: &mut Ui) {
+        let provider: &dyn ContentProvider = self.api_handle;
+        let metadata = match provider.item_metadata(true_set_idx, true_item_idx) {
+            Some(metadata) => metadata,
+            None => return,
+        };
+        self.detail.open(DetailContent::from_metadata(
+            true_set_idx,
+            true_item_idx,
+            &metadata,
+            self.config.locale,
+        ));
+        self.screens.push(Screen::Detail);
+        self.play(Sound::Select);
+        if let Some(content_id) = &metadata.content_id {
+            self.local
+                .progress
+                .record_opened(content_id, true_set_idx, true_item_idx);
+        }
+        // The large artwork and the logo are particular to the home.json, other catalogs only have
+        // the tiles.
+        let urls = self.api_handle.get_set(true_set_idx).map(|set_data| {
+            [
+                (LoadKind::Hero, set_data.get_hero_image_url(true_item_idx)),
+                (
+                    LoadKind::Logo,
+                    set_data
+                        .get_title_treatment(true_item_idx)
+                        .map(|logo| logo.url),
+                ),
+            ]
+        });
+        for (kind, url) in urls.into_iter().flatten() {
+            match url {
+                Ok(url) => self.loader.submit(LoadRequest {
+                    kind,
+                    true_set_idx,
+                    true_item_idx,
+                    url,
+                    size: None,
+                    preview_url: None,
+                }),
+                Err(e) => debug!("{}", e),
+            }
+        }
+        self.refresh(ui);
+    }
+
+    /// Pops the detail page [`Screen`] and releases its hero and logo textures.
+    ///
+    /// The [`CONTINUE_WATCHING_ROW`] is only rebuilt here so it doesn't move around while the page is open.
+    fn close_detail(&mut self, ui: &mut Ui) {
+        if self.screen() != Screen::Detail {
+            return;
+        }
+        self.screens.pop();
+        for art in self.detail.close() {
+            self.image_map.remove(art.img_id);
+        }
+        if self.nav.cursor.true_set_idx == CONTINUE_WATCHING_ROW && !self.nav.cursor.on_banner {
+            // The item just closed is now the first one, keep the focus on it.
+            self.nav.cursor.true_item_idx = 0;
+        }
+        self.rebuild_local_row(CONTINUE_WATCHING_ROW);
+        self.refresh(ui);
+    }
+
+    /// The [`Theme`] picked in the [`Config`].
+    pub(crate) fn theme(&self) -> &'a Theme {
+        self.themes.get(self.config.theme)
+    }
+
+    /// The [`Screen`] currently on top of the stack.
+    pub(crate) fn screen(&self) -> Screen {
+        *self.screens.last().unwrap_or(&Screen::Home)
+    }
+
+    /// Sets the widgets of the [`Screen`] on top of the stack. The overlays are drawn on top of the
+    /// screen below them, see [`Screen::is_overlay`].
+    fn refresh(&mut self, ui: &mut Ui) {
+        let base = self
+            .screens
+            .iter()
+            .rev()
+            .find(|screen| !screen.is_overlay())
+            .copied()
+            .unwrap_or(Screen::Home);
+        match base {
+            // Draws the overlays itself as it's also called outside of `refresh`.
+            Screen::Home => self.update_image_widgets(ui),
+            Screen::Search => {
+                let ui = &mut ui.set_widgets();
+                self.search
+                    .show(ui, &self.nf_id, self.theme(), self.config.locale);
+                self.detail.show(ui, &self.nf_id);
+                self.pin_entry.show(ui, self.config.locale);
+                self.show_hud(ui);
+            }
+            Screen::Grid => {
+                let ui = &mut ui.set_widgets();
+                if let Some(row) = self.rows.get(self.grid.true_set_idx()) {
+                    self.grid.show(
+                        ui,
+                        &row.set_data,
+                        row.title,
+                        &self.nf_id,
+                        self.theme(),
+                        self.config.locale,
+                    );
+                }
+                self.detail.show(ui, &self.nf_id);
+                self.pin_entry.show(ui, self.config.locale);
+                self.show_hud(ui);
+            }
+            Screen::ProfilePicker => {
+                let ui = &mut ui.set_widgets();
+                self.picker.show(ui, self.theme(), self.config.locale);
+                self.show_hud(ui);
+            }
+            Screen::Settings => {
+                let ui = &mut ui.set_widgets();
+                self.settings.show(ui, &self.config, self.theme());
+                self.show_hud(ui);
+            }
+            Screen::Screensaver => {
+                let ui = &mut ui.set_widgets();
+                self.screensaver.show(ui, Instant::now());
+                self.show_hud(ui);
+            }
+            Screen::Detail | Screen::PinEntry | Screen::FilterBar => {
+                unreachable!("overlays are skipped")
+            }
+        }
+    }
+
+    /// Routes the `action` to the [`Screen`] on top of the stack. Returns why the [`EventLoop`] has
+    /// to stop, None to keep going. The actions are dropped while the [`ExternalPlayer`] runs.
+    pub(crate) fn handle(&mut self, action: Action, ui: &mut Ui) -> Option<ExitReason> {
+        if self.player.is_some() {
+            return None;
+        }
+        match self.screen() {
+            Screen::Home => return self.handle_home(action, ui),
+            Screen::Detail => match action {
+                Action::Escape | Action::Back | Action::Backspace => self.close_detail(ui),
+                Action::Select | Action::Play => {
+                    if let Some((true_set_idx, true_item_idx)) = self.detail.item() {
+                        self.launch_player(true_set_idx, true_item_idx);
+                    }
+                }
+                _ => (),
+            },
+            Screen::Search => match action {
+                Action::Escape | Action::Back => self.close_search(ui),
+                Action::Char(c) => self.type_char(c, ui),
+                Action::Left => self.move_search_focus(SearchScreen::move_left, ui),
+                Action::Right => self.move_search_focus(SearchScreen::move_right, ui),
+                Action::Up => self.move_search_focus(SearchScreen::move_up, ui),
+                Action::Down => self.move_search_focus(SearchScreen::move_down, ui),
+                Action::Select => self.select_search_focus(ui),
+                _ => (),
+            },
+            Screen::Grid => match action {
+                Action::Escape | Action::Back | Action::Backspace => self.close_grid(ui),
+                Action::Left => self.move_grid_focus(GridScreen::move_left, ui),
+                Action::Right => self.move_grid_focus(GridScreen::move_right, ui),
+                Action::Up => self.move_grid_focus(GridScreen::move_up, ui),
+                Action::Down => self.move_grid_focus(GridScreen::move_down, ui),
+                Action::PageUp => self.move_grid_focus(GridScreen::page_up, ui),
+                Action::PageDown => self.move_grid_focus(GridScreen::page_down, ui),
+                Action::First => self.move_grid_focus(GridScreen::first, ui),
+                Action::Last => self.move_grid_focus(GridScreen::last, ui),
+                Action::Select => {
+                    let indices = self.api_indices(self.grid.true_set_idx(), self.grid.focused());
+                    if let Some((true_set_idx, true_item_idx)) = indices {
+                        self.open_detail_at(true_set_idx, true_item_idx, ui);
+                    }
+                }
+                _ => (),
+            },
+            Screen::ProfilePicker => match action {
+                Action::Escape => return Some(ExitReason::Quit),
+                Action::Left => {
+                    self.picker.move_left();
+                    self.refresh(ui);
+                }
+                Action::Right => {
+                    self.picker.move_right();
+                    self.refresh(ui);
+                }
+                Action::Select => {
+                    return Some(ExitReason::Reload(Reload::Profile(self.picker.selected())))
+                }
+                _ => (),
+            },
+            Screen::PinEntry => match action {
+                Action::Escape | Action::Back | Action::Backspace => self.close_pin_entry(ui),
+                Action::Char(c) => {
+                    if self.type_pin(c, ui) {
+                        return Some(ExitReason::Reload(Reload::RatingCeiling(None)));
+                    }
+                }
+                _ => (),
+            },
+            Screen::FilterBar => match action {
+                Action::Escape
+                | Action::Back
+                | Action::Backspace
+                | Action::Select
+                | Action::Filter => self.close_filter_bar(ui),
+                Action::Up | Action::Down => {
+                    self.filter_bar.move_focus();
+                    self.refresh(ui);
+                }
+                Action::Left => self.change_row_order(false, ui),
+                Action::Right => self.change_row_order(true, ui),
+                _ => (),
+            },
+            Screen::Settings => match action {
+                Action::Escape | Action::Back | Action::Backspace => {
+                    return self.close_settings(ui)
+                }
+                Action::Up => {
+                    self.settings.move_up();
+                    self.refresh(ui);
+                }
+                Action::Down => {
+                    self.settings.move_down();
+                    self.refresh(ui);
+                }
+                Action::Left => self.change_setting(false, ui),
+                Action::Right | Action::Select => self.change_setting(true, ui),
+                _ => (),
+            },
+            // Whatever the action, it only brings back the screen below.
+            Screen::Screensaver => self.close_screensaver(ui),
+        }
+        None
+    }
+
+    fn handle_home(&mut self, action: Action, ui: &mut Ui) -> Option<ExitReason> {
+        match action {
+            Action::Left => self.move_current_set_left(ui),
+            Action::Right => self.move_current_set_right(ui),
+            Action::Up => self.move_to_prev_set(ui),
+            Action::Down => self.move_to_next_set(ui),
+            Action::PageUp => self.page_up(ui),
+            Action::PageDown => self.page_down(ui),
+            Action::First => self.jump_home(ui),
+            Action::Last => self.jump_end(ui),
+            Action::Select => self.open_detail(ui),
+            Action::Search => self.open_search(ui),
+            Action::ToggleWatchlist => self.toggle_watchlist(ui),
+            Action::Parental => return self.toggle_rating_ceiling(ui),
+            Action::Settings => self.open_settings(ui),
+            Action::SeeAll => self.open_grid(ui),
+            Action::Filter => self.open_filter_bar(ui),
+            Action::Play => {
+                if let Some((true_set_idx, true_item_idx)) = self.focused_api_indices() {
+                    self.launch_player(true_set_idx, true_item_idx);
+                }
+            }
+            Action::Escape => return Some(ExitReason::Quit),
+            Action::Back | Action::Backspace | Action::Char(_) => (),
+        }
+        None
+    }
+
+    /// Hands the item `true_item_idx` of the [`Api`] set `true_set_idx` over to the
+    /// [`ExternalPlayer`], with the url of its video loop or else the
+    /// [`player_url`](Config::player_url). Does nothing without a
+    /// [`player_command`](Config::player_command), a move going nowhere is played if there is
+    /// nothing to play.
+    fn launch_player(&mut self, true_set_idx: usize, true_item_idx: usize) {
+        let template = match self.config.player_command.as_deref() {
+            Some(template) => template,
+            None => return,
+        };
+        let set_data = match self.api_handle.get_set(true_set_idx) {
+            Some(set_data) => set_data,
+            None => return,
+        };
+        let player_url = self.config.player_url.as_deref();
+        let url = set_data
+            .get_item_video_art(true_item_idx)
+            .map(|video| video.url)
+            .ok()
+            .or_else(|| {
+                let content_id = set_data.get_content_id(true_item_idx)?;
+                Some(player_url?.replace("{content_id}", content_id))
+            });
+        let url = match url {
+            Some(url) => url,
+            None => {
+                debug!(
+                    "Nothing to play for item {} of set {}",
+                    true_item_idx, true_set_idx
+                );
+                self.play(Sound::Error);
+                return;
+            }
+        };
+        let title = set_data.get_item_title(true_item_idx).unwrap_or_default();
+        match ExternalPlayer::launch(template, &url, title) {
+            Ok(player) => {
+                self.play(Sound::Select);
+                if let Some(img_id) = self.preview.rest_on(None, None, Instant::now()) {
+                    self.image_map.remove(img_id);
+                }
+                self.player = Some(player);
+            }
+            Err(e) => {
+                info!("Could not start the player: {}", e);
+                self.play(Sound::Error);
+            }
+        }
+    }
+
+    /// Returns true while the app is paused for the [`ExternalPlayer`].
+    fn is_player_running(&self) -> bool {
+        self.player.is_some()
+    }
+
+    /// Pushes the [`Screen::Screensaver`] on top of whatever is shown, unless it takes typed text
+    /// (see [`Screen::takes_text`]) or none of the items has hero artwork.
+    pub(crate) fn open_screensaver(&mut self, ui: &mut Ui) {
+        let screen = self.screen();
+        if screen == Screen::Screensaver || screen.takes_text() {
+            return;
+        }
+        if !self.screensaver.open(self.api_handle) {
+            debug!("No hero artwork to show in the screensaver");
+            return;
+        }
+        self.screens.push(Screen::Screensaver);
+        self.refresh(ui);
+    }
+
+    /// Pops the [`Screen::Screensaver`] and releases the texture of its artwork.
+    pub(crate) fn close_screensaver(&mut self, ui: &mut Ui) {
+        if self.screen() != Screen::Screensaver {
+            return;
+        }
+        self.screens.pop();
+        if let Some(img_id) = self.screensaver.close() {
+            self.image_map.remove(img_id);
+        }
+        self.refresh(ui);
+    }
+
+    /// When the [`Screensaver`] is due to show its next artwork, None if it's not shown or while
+    /// the artwork is loading.
+    pub(crate) fn next_slide(&self) -> Option<Instant> {
+        self.screensaver
+            .next_slide()
+            .filter(|_| self.screen() == Screen::Screensaver)
+    }
+
+    /// Pushes the [`Screen::ProfilePicker`] listing the profiles `names`, the one at `focus` first
+    /// focused.
+    pub(crate) fn open_profile_picker(&mut self, names: Vec<String>, focus: usize, ui: &mut Ui) {
+        self.picker.reset(names, focus);
+        self.screens.push(Screen::ProfilePicker);
+        self.refresh(ui);
+    }
+
+    /// `P` on the home screen: pushes the [`Screen::PinEntry`] to lift the rating ceiling, or asks
+    /// for the [`max_rating`](Config::max_rating) to be put back once it was lifted.
+    fn toggle_rating_ceiling(&mut self, ui: &mut Ui) -> Option<ExitReason> {
+        if self.api_handle.rating_ceiling().is_none() {
+            let max_rating = self.config.max_rating?;
+            return Some(ExitReason::Reload(Reload::RatingCeiling(Some(max_rating))));
+        }
+        if self.config.parental_pin.is_some() {
+            self.pin_entry.open();
+            self.screens.push(Screen::PinEntry);
+            self.refresh(ui);
+        }
+        None
+    }
+
+    /// Pops the [`Screen::PinEntry`].
+    fn close_pin_entry(&mut self, ui: &mut Ui) {
+        if self.screen() != Screen::PinEntry {
+            return;
+        }
+        self.screens.pop();
+        self.pin_entry.close();
+        self.refresh(ui);
+    }
+
+    /// Handles a character typed on the [`Screen::PinEntry`]. Returns true once the
+    /// [`parental_pin`](Config::parental_pin) was typed, the screen is then popped.
+    fn type_pin(&mut self, c: char, ui: &mut Ui) -> bool {
+        let unlocked = match self.config.parental_pin {
+            Some(pin) => self.pin_entry.type_char(c, pin),
+            None => false,
+        };
+        if unlocked {
+            self.screens.pop();
+        } else if self.pin_entry.is_wrong() {
+            self.play(Sound::Error);
+        }
+        self.refresh(ui);
+        unlocked
+    }
+
+    /// `F` on the home screen: pushes the [`Screen::FilterBar`] for the cursor row. Does nothing
+    /// while the banner has the focus.
+    fn open_filter_bar(&mut self, ui: &mut Ui) {
+        if self.nav.cursor.on_banner {
+            return;
+        }
+        let true_set_idx = self.nav.cursor.true_set_idx;
+        let row = match self.rows.get(true_set_idx) {
+            Some(row) => row,
+            None => return,
+        };
+        self.filter_bar.open(true_set_idx, &row.order);
+        self.screens.push(Screen::FilterBar);
+        self.refresh(ui);
+    }
+
+    /// Pops the [`Screen::FilterBar`], the row keeping the order picked.
+    fn close_filter_bar(&mut self, ui: &mut Ui) {
+        if self.screen() != Screen::FilterBar {
+            return;
+        }
+        self.screens.pop();
+        self.filter_bar.close();
+        self.refresh(ui);
+    }
+
+    /// Changes the focused line of the [`FilterBar`], see [`FilterBar::change`], and sorts and
+    /// filters its row right away. The cursor stays on the item it was on while it's shown, it goes
+    /// back to the first one otherwise.
+    fn change_row_order(&mut self, forward: bool, ui: &mut Ui) {
+        let true_set_idx = match self.filter_bar.true_set_idx() {
+            Some(true_set_idx) => true_set_idx,
+            None => return,
+        };
+        self.filter_bar.change(forward);
+        let (sort, filter) = self.filter_bar.picked();
+        let row = match self.rows.get_mut(true_set_idx) {
+            Some(row) => row,
+            None => return,
+        };
+        let item_idx = row.item_idx(self.nav.cursor.true_item_idx);
+        row.set_order(sort, filter);
+        // The tiles in flight are the ones of the items shown before.
+        self.loader
+            .cancel(|kind, set_idx, _| kind == LoadKind::Tile && set_idx == true_set_idx);
+        let extent = row.extent();
+        let true_item_idx = row.order.true_item_idx(item_idx).unwrap_or_else(|| {
+            row.scroll.left_right_idx_adjustment = 0;
+            0
+        });
+        self.nav
+            .return_to_item(&mut row.scroll, extent, true_item_idx);
+        row.last_item_idx = None;
+        // The focused tile may show another item, its loop is looked up again.
+        if let Some(img_id) = self.preview.rest_on(None, None, Instant::now()) {
+            self.image_map.remove(img_id);
+        }
+        self.play(Sound::Move);
+        self.refresh(ui);
+    }
+
+    /// Pushes the [`Screen::Settings`] on top of the home rows.
+    fn open_settings(&mut self, ui: &mut Ui) {
+        self.settings.open(self.config.clone());
+        self.screens.push(Screen::Settings);
+        self.refresh(ui);
+    }
+
+    /// Changes the focused setting, see [`SettingsScreen::change`], and applies it to the rows. The
+    /// image quality is left for [`close_settings`](DisplayController::close_settings).
+    fn change_setting(&mut self, forward: bool, ui: &mut Ui) {
+        self.settings.change(&mut self.config, forward);
+        match self.settings.focused() {
+            Setting::Animations => {
+                self.animator.set_enabled(self.config.animations);
+                self.ambient.set_enabled(self.config.animations);
+            }
+            // Same as when the window is resized, the rows are laid out again.
+            Setting::RowDensity => self.resize(ui.win_w, ui.win_h, ui),
+            // A sound at the new volume, to hear how loud it is.
+            Setting::Sounds => self.play(Sound::Move),
+            Setting::ImageQuality | Setting::Theme => (),
+        }
+        self.refresh(ui);
+    }
+
+    /// Pops the [`Screen::Settings`]. Asks for the settings to be saved if they changed, the
+    /// [`Api`] needs the new image quality.
+    fn close_settings(&mut self, ui: &mut Ui) -> Option<ExitReason> {
+        self.screens.pop();
+        if self.settings.is_changed(&self.config) {
+            return Some(ExitReason::Reload(Reload::Settings(self.config.clone())));
+        }
+        self.refresh(ui);
+        None
+    }
+
+    /// Pushes the search [`Screen`] on top of the home rows. Does nothing on other screens.
+    pub(crate) fn open_search(&mut self, ui: &mut Ui) {
+        if self.screen() != Screen::Home {
+            return;
+        }
+        self.screens.push(Screen::Search);
+        self.refresh(ui);
+    }
+
+    /// Pops the search [`Screen`] and releases the textures of its results.
+    fn close_search(&mut self, ui: &mut Ui) {
+        if self.screen() != Screen::Search {
+            return;
+        }
+        self.screens.pop();
+        self.search.reset();
+        self.release_search_evicted();
+        self.refresh(ui);
+    }
+
+    fn release_search_evicted(&mut self) {
+        for evicted in self.search.take_evicted() {
+            Self::release(&mut self.image_map, &mut self.atlas, &self.nf_id, evicted);
+        }
+    }
+
+    /// Pushes the [`Screen::Grid`] with every item of the cursor row, the focused one first
+    /// focused. Does nothing on other screens or while the banner has the focus.
+    fn open_grid(&mut self, ui: &mut Ui) {
+        if self.screen() != Screen::Home || self.nav.cursor.on_banner {
+            return;
+        }
+        let cursor = &self.nav.cursor;
+        let row = match self.rows.get(cursor.true_set_idx) {
+            Some(row) => row,
+            None => return,
+        };
+        // The grid shows the items in the order of the set.
+        self.grid.open(
+            cursor.true_set_idx,
+            &row.set_data,
+            row.item_idx(cursor.true_item_idx),
+        );
+        self.screens.push(Screen::Grid);
+        self.request_grid_images();
+        self.refresh(ui);
+    }
+
+    /// Pops the [`Screen::Grid`] and releases the textures of its tiles.
+    fn close_grid(&mut self, ui: &mut Ui) {
+        if self.screen() != Screen::Grid {
+            return;
+        }
+        self.screens.pop();
+        self.grid.close();
+        self.release_grid_evicted();
+        self.refresh(ui);
+    }
+
+    /// Moves the focus of the [`Screen::Grid`] with `move_focus`, e.g. [`GridScreen::move_down`],
+    /// and loads the tiles scrolled into view.
+    fn move_grid_focus(&mut self, move_focus: fn(&mut GridScreen), ui: &mut Ui) {
+        move_focus(&mut self.grid);
+        self.request_grid_images();
+        self.refresh(ui);
+    }
+
+    /// See [`GridScreen::request_images`].
+    fn request_grid_images(&mut self) {
+        if let Some(row) = self.rows.get(self.grid.true_set_idx()) {
+            self.grid
+                .request_images(&row.set_data, &mut self.loader, &self.nf_id);
+        }
+        self.release_grid_evicted();
+    }
+
+    fn release_grid_evicted(&mut self) {
+        for evicted in self.grid.take_evicted() {
+            Self::release(&mut self.image_map, &mut self.atlas, &self.nf_id, evicted);
+        }
+    }
+
+    /// Handles a character typed on the search [`Screen`].
+    fn type_char(&mut self, c: char, ui: &mut Ui) {
+        if self.search.type_char(c, self.api_handle) {
+            self.search
+                .request_images(self.api_handle, &mut self.loader, &self.nf_id);
+            self.release_search_evicted();
+            self.refresh(ui);
+        }
+    }
+
+    /// Moves the focus of the search [`Screen`] with `move_focus`, e.g. [`SearchScreen::move_left`].
+    fn move_search_focus(&mut self, move_focus: fn(&mut SearchScreen), ui: &mut Ui) {
+        move_focus(&mut self.search);
+        self.refresh(ui);
+    }
+
+    /// Activates the focused element of the search [`Screen`]: opens the detail page of a result or
+    /// presses a key of the on-screen keyboard.
+    fn select_search_focus(&mut self, ui: &mut Ui) {
+        match self.search.select(self.api_handle) {
+            Some((true_set_idx, true_item_idx)) => {
+                self.open_detail_at(true_set_idx, true_item_idx, ui)
+            }
+            None => {
+                self.search
+                    .request_images(self.api_handle, &mut self.loader, &self.nf_id);
+                self.release_search_evicted();
+                self.refresh(ui);
+            }
+        }
+    }
+
+    /// Adds the focused tile to the [`Watchlist`](watchlist::Watchlist), or removes it if it's
+    /// already there, and rebuilds the [`WATCHLIST_ROW`].
+    pub(crate) fn toggle_watchlist(&mut self, ui: &mut Ui) {
+        if self.screen() != Screen::Home || self.nav.cursor.on_banner {
+            return;
+        }
+        let content_id = match self.focused_content_id() {
+            Some(content_id) => content_id,
+            None => return,
+        };
+        let added = self.local.watchlist.toggle(content_id);
+        info!(
+            "{} {} the watchlist",
+            content_id,
+            if added { "added to" } else { "removed from" }
+        );
+        self.rebuild_local_row(WATCHLIST_ROW);
+        self.refresh(ui);
+    }
+
+    /// Replaces the local row `true_set_idx` with one built from the current [`LocalRows`] state,
+    /// keeping the cursor on a valid item if it's on that row. Does nothing if the items didn't change.
+    fn rebuild_local_row(&mut self, true_set_idx: usize) {
+        if self.rows.len() <= true_set_idx {
+            // Not built yet, it's picked up by `fetch_row` once in view.
+            return;
+        }
+        let set_data = match self.local.get_set(self.api_handle, true_set_idx) {
+            Some(set_data) => set_data,
+            None => return,
+        };
+        let old = &mut self.rows[true_set_idx];
+        let count = set_data.get_item_count();
+        if count == old.set_data.get_item_count()
+            && (0..count)
+                .all(|idx| set_data.get_content_id(idx) == old.set_data.get_content_id(idx))
+        {
+            return;
+        }
+        let mut row = SetRow::new(set_data, true_set_idx, self.layout, self.local.locale);
+        row.set_order(old.order.sort(), old.order.filter());
+        old.evict_all();
+        row.evicted.append(&mut old.evicted);
+
+        row.scroll.left_right_idx_adjustment = old
+            .scroll
+            .left_right_idx_adjustment
+            .min(row.item_count().saturating_sub(1));
+        // `enter_row` clamps it to the new items.
+        row.last_item_idx = old.last_item_idx;
+        if self.nav.cursor.true_set_idx == true_set_idx {
+            self.nav.clamp_to_row(&mut row.scroll, row.extent());
+        }
+        // The old textures are released by the next `evict_far_rows`.
+        self.rows[true_set_idx] = row;
+    }
+
+    /// Moves the [`Cursor`] to the tile under the window coordinates `x`, `y`.
+    /// Returns false if there is no tile there.
+    pub(crate) fn focus_at(&mut self, x: f64, y: f64, ui: &mut Ui) -> bool {
+        let visible = self.nav.visible_rows();
+        for true_set_idx in visible.filter(|idx| !self.nav.is_skipped(*idx)) {
+            let row = match self.rows.get(true_set_idx) {
+                Some(row) => row,
+                None => break,
+            };
+            if let Some(adjusted_item_idx) = row.hit_test(x, y) {
+                let scroll = row.scroll;
+                self.leave_row();
+                self.nav.focus(true_set_idx, &scroll, adjusted_item_idx);
+                self.update_image_widgets(ui);
+                return true;
+            }
+        }
+        false
+    }
+
+    pub(crate) fn move_current_set_left(&mut self, ui: &mut Ui) {
+        if self.nav.cursor.on_banner {
+            return;
+        }
+        let before = self.cursor_spot();
+        if let Some(cur_row_data) = Self::fetch_row(
+            &mut self.rows,
+            self.nav.cursor.true_set_idx,
+            self.api_handle,
+            &self.local,
+            self.layout,
+        ) {
+            let prev_adjustment = cur_row_data.scroll.left_right_idx_adjustment;
+            let extent = cur_row_data.extent();
+            self.nav.move_left(&mut cur_row_data.scroll, extent);
+            self.animator.scroll_row(
+                self.nav.cursor.true_set_idx,
+                prev_adjustment,
+                cur_row_data.scroll.left_right_idx_adjustment,
+                Instant::now(),
+            );
+            self.play_move(before, Sound::Move);
+            self.update_image_widgets(ui);
+        }
+    }
+
+    pub(crate) fn move_current_set_right(&mut self, ui: &mut Ui) {
+        if self.nav.cursor.on_banner {
+            return;
+        }
+        let before = self.cursor_spot();
+        if let Some(cur_row_data) = Self::fetch_row(
+            &mut self.rows,
+            self.nav.cursor.true_set_idx,
+            self.api_handle,
+            &self.local,
+            self.layout,
+        ) {
+            let prev_adjustment = cur_row_data.scroll.left_right_idx_adjustment;
+            let extent = cur_row_data.extent();
+            self.nav.move_right(&mut cur_row_data.scroll, extent);
+            self.animator.scroll_row(
+                self.nav.cursor.true_set_idx,
+                prev_adjustment,
+                cur_row_data.scroll.left_right_idx_adjustment,
+                Instant::now(),
+            );
+            self.play_move(before, Sound::Move);
+            self.update_image_widgets(ui);
+        }
+    }
+
+    /// Moves up one set. Going up from the first set gives the focus to the [`HeroBanner`], and
+    /// going up from there to the last set when [wrapping](Config::wrap_navigation).
+    pub(crate) fn move_to_prev_set(&mut self, ui: &mut Ui) {
+        let num_of_sets = self.api_handle.get_num_of_sets().unwrap() + LOCAL_ROWS;
+        let before = self.cursor_spot();
+        self.leave_row();
+        if let Some(true_set_idx) = self.nav.move_up(num_of_sets) {
+            self.enter_row(true_set_idx);
+        }
+        self.play_move(before, Sound::RowChange);
+        self.update_image_widgets(ui);
+    }
+
+    /// Moves down one set. Going down from the [`HeroBanner`] gives the focus back to the first set,
+    /// and going down from the last set too when [wrapping](Config::wrap_navigation).
+    pub(crate) fn move_to_next_set(&mut self, ui: &mut Ui) {
+        let num_of_sets = self.api_handle.get_num_of_sets().unwrap() + LOCAL_ROWS;
+        let before = self.cursor_spot();
+        self.leave_row();
+        if let Some(true_set_idx) = self.nav.move_down(num_of_sets) {
+            self.enter_row(true_set_idx);
+        }
+        self.play_move(before, Sound::RowChange);
+        self.update_image_widgets(ui);
+    }
+
+    /// Moves to the set `true_set_idx`, however far it is from the cursor row. Past the last set goes
+    /// to the last set.
+    pub(crate) fn jump_to_set(&mut self, true_set_idx: usize, ui: &mut Ui) {
+        let num_of_sets = self.api_handle.get_num_of_sets().unwrap() + LOCAL_ROWS;
+        let before = self.cursor_spot();
+        self.leave_row();
+        if let Some(true_set_idx) = self.nav.jump_to_set(true_set_idx, num_of_sets) {
+            self.enter_row(true_set_idx);
+        }
+        self.play_move(before, Sound::RowChange);
+        self.update_image_widgets(ui);
+    }
+
+    /// Moves up [`num_rows`](Layout::num_rows) sets, stopping at the first one.
+    pub(crate) fn page_up(&mut self, ui: &mut Ui) {
+        if self.screen() != Screen::Home || self.nav.cursor.on_banner {
+            return;
+        }
+        let true_set_idx = self
+            .nav
+            .cursor
+            .true_set_idx
+            .saturating_sub(self.layout.num_rows);
+        self.jump_to_set(true_set_idx, ui);
+    }
+
+    /// Moves down [`num_rows`](Layout::num_rows) sets, stopping at the last one.
+    pub(crate) fn page_down(&mut self, ui: &mut Ui) {
+        if self.screen() != Screen::Home {
+            return;
+        }
+        let true_set_idx = if self.nav.cursor.on_banner {
+            0
+        } else {
+            self.nav.cursor.true_set_idx + self.layout.num_rows
+        };
+        self.jump_to_set(true_set_idx, ui);
+    }
+
+    /// Moves to the first item of the first set.
+    pub(crate) fn jump_home(&mut self, ui: &mut Ui) {
+        if self.screen() != Screen::Home {
+            return;
+        }
+        let num_of_sets = self.api_handle.get_num_of_sets().unwrap() + LOCAL_ROWS;
+        let before = self.cursor_spot();
+        self.leave_row();
+        self.nav.jump_to_set(0, num_of_sets);
+        if let Some(first_row) =
+            Self::fetch_row(&mut self.rows, 0, self.api_handle, &self.local, self.layout)
+        {
+            let prev_adjustment = first_row.scroll.left_right_idx_adjustment;
+            self.nav.move_to_first_item(&mut first_row.scroll);
+            self.animator
+                .scroll_row(0, prev_adjustment, 0, Instant::now());
+        }
+        self.play_move(before, Sound::RowChange);
+        self.update_image_widgets(ui);
+    }
+
+    /// Moves to the last set.
+    pub(crate) fn jump_end(&mut self, ui: &mut Ui) {
+        if self.screen() != Screen::Home {
+            return;
+        }
+        self.jump_to_set(usize::MAX, ui);
+    }
+
+    /// Plays the `sound` at the [`sound_volume`](Config::sound_volume).
+    fn play(&self, sound: Sound) {
+        self.audio.play(sound, self.config.sound_volume);
+    }
+
+    /// Whether the banner has the focus, and the `true_set_idx` and `true_item_idx` of the cursor.
+    /// Taken before a move to tell whether it went anywhere, see
+    /// [`play_move`](DisplayController::play_move).
+    fn cursor_spot(&self) -> (bool, usize, usize) {
+        let cursor = &self.nav.cursor;
+        (cursor.on_banner, cursor.true_set_idx, cursor.true_item_idx)
+    }
+
+    /// Plays the `sound` of a move if the cursor left the spot it was on `before`, [`Sound::Error`]
+    /// if it's stuck there, e.g. at the end of a row.
+    fn play_move(&self, before: (bool, usize, usize), sound: Sound) {
+        if self.cursor_spot() == before {
+            self.play(Sound::Error);
+        } else {
+            self.play(sound);
+        }
+    }
+
+    /// Remembers the item the cursor is on in its row before it moves to another one, see
+    /// [`SetRow::last_item_idx`].
+    fn leave_row(&mut self) {
+        if self.nav.cursor.on_banner {
+            return;
+        }
+        if let Some(row) = self.rows.get_mut(self.nav.cursor.true_set_idx) {
+            row.last_item_idx = Some(self.nav.cursor.true_item_idx);
+        }
+    }
+
+    /// Where the cursor is, to put it back there once the rows are rebuilt from reloaded home data.
+    fn position(&mut self) -> Position {
+        self.leave_row();
+        let cursor = &self.nav.cursor;
+        let content_id = self.focused_content_id().map(String::from);
+        // The screensaver is left out, the screen below it is the one the user was on.
+        let screen = self
+            .screens
+            .iter()
+            .rev()
+            .find(|screen| **screen != Screen::Screensaver)
+            .copied();
+        Position {
+            cursor: cursor.clone(),
+            content_id,
+            last_item_idxs: self.rows.iter().map(|row| row.last_item_idx).collect(),
+            row_scrolls: self.rows.iter().map(|row| row.scroll).collect(),
+            screen: screen.filter(|screen| screen.is_resumable()),
+        }
+    }
+
+    /// Puts the cursor back where it was before the home data got reloaded, following the sets
+    /// around as per the `diff`. The cursor lands on the same item if it's still in its set, and
+    /// the rows whose items didn't change get their [`last_item_idx`](SetRow::last_item_idx) and
+    /// their [`scroll`](SetRow::scroll) back. The screen that was on top is opened again.
+    fn restore_position(&mut self, position: Position, diff: &HomeDataDiff, ui: &mut Ui) {
+        let num_of_sets = self.api_handle.get_num_of_sets().unwrap_or(0) + LOCAL_ROWS;
+        // The local rows don't come from the home data, they stay where they are.
+        let current_idx = |true_set_idx: usize| match true_set_idx.checked_sub(LOCAL_ROWS) {
+            Some(set_idx) => diff.current_idx(set_idx).map(|idx| idx + LOCAL_ROWS),
+            None => Some(true_set_idx),
+        };
+        let is_kept = |true_set_idx: usize| match true_set_idx.checked_sub(LOCAL_ROWS) {
+            Some(set_idx) => diff.is_kept(set_idx),
+            None => true,
+        };
+
+        let mut row_scrolls = position.row_scrolls.into_iter();
+        for (previous_idx, last_item_idx) in position.last_item_idxs.into_iter().enumerate() {
+            let scroll = row_scrolls.next();
+            let true_set_idx = match current_idx(previous_idx) {
+                Some(true_set_idx) if last_item_idx.is_some() && is_kept(true_set_idx) => {
+                    true_set_idx
+                }
+                _ => continue,
+            };
+            if let Some(row) = Self::fetch_row(
+                &mut self.rows,
+                true_set_idx,
+                self.api_handle,
+                &self.local,
+                self.layout,
+            ) {
+                row.last_item_idx = last_item_idx;
+                if let Some(scroll) = scroll {
+                    row.scroll = scroll;
+                }
+            }
+        }
+
+        // When its set is gone the cursor stays on whichever set took its place.
+        let cursor = position.cursor;
+        let true_set_idx = current_idx(cursor.true_set_idx).unwrap_or(cursor.true_set_idx);
+        let true_set_idx = match self.nav.jump_to_set(true_set_idx, num_of_sets) {
+            Some(true_set_idx) => true_set_idx,
+            None => return,
+        };
+        if let Some(row) = Self::fetch_row(
+            &mut self.rows,
+            true_set_idx,
+            self.api_handle,
+            &self.local,
+            self.layout,
+        ) {
+            let content_id = position.content_id.as_deref();
+            let true_item_idx = row
+                .set_data
+                .items()
+                .position(|item| content_id.is_some() && item.content_id.as_deref() == content_id)
+                .unwrap_or(cursor.true_item_idx);
+            row.last_item_idx = Some(true_item_idx);
+        }
+        self.enter_row(true_set_idx);
+        if cursor.on_banner {
+            self.nav.move_up(num_of_sets);
+        }
+        self.update_image_widgets(ui);
+        match position.screen {
+            Some(Screen::Detail) => self.open_detail(ui),
+            Some(Screen::Search) => self.open_search(ui),
+            Some(Screen::Settings) => self.open_settings(ui),
+            Some(Screen::Grid) => self.open_grid(ui),
+            _ => (),
+        }
+    }
+
+    /// Lands the cursor on the row `true_set_idx` it just moved to: back on the item it was on the
+    /// last time it left the row, if any, see [`NavigationModel::return_to_item`]. Otherwise on the
+    /// same column, see [`NavigationModel::enter_row`].
+    fn enter_row(&mut self, true_set_idx: usize) {
+        if let Some(row) = Self::fetch_row(
+            &mut self.rows,
+            true_set_idx,
+            self.api_handle,
+            &self.local,
+            self.layout,
+        ) {
+            let extent = row.extent();
+            match row.last_item_idx {
+                Some(true_item_idx) => {
+                    self.nav
+                        .return_to_item(&mut row.scroll, extent, true_item_idx)
+                }
+                None => self.nav.enter_row(&mut row.scroll, extent),
+            }
+        }
+    }
+}
+
+/// Why the [`EventLoop`] stopped, see [`DisplayController::handle`].
+enum ExitReason {
+    /// The app is closing.
+    Quit,
+    /// The rows have to be rebuilt.
+    Reload(Reload),
+}
+
+/// Why the rows are rebuilt, see the end of [`main`].
+enum Reload {
+    /// The home data was fetched again and differs from the one shown, see [`refresh`].
+    HomeData(HomeSnapshot),
+    /// The [`max_rating`](Config::max_rating) filter is turned on, or off with None.
+    RatingCeiling(Option<ContentRating>),
+    /// A profile was picked, see [`switch_profile`].
+    Profile(PickerChoice),
+    /// The [`settings`] screen was closed with this config, some of its settings changed.
+    Settings(Config),
+}
+
+/// Where the cursor was before the home data got reloaded, see [`DisplayController::position`].
+/// Also saved with the [`Profile`](profiles::Profile) when the app is closed, and put back when
+/// the profile is picked again with [`resume_session`](Config::resume_session).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Position {
+    #[serde(flatten)]
+    cursor: Cursor,
+    /// `contentId` of the item under the cursor, to find it again if it moved within its set.
+    content_id: Option<String>,
+    /// [`SetRow::last_item_idx`] of each row, by `true_set_idx`.
+    #[serde(default)]
+    last_item_idxs: Vec<Option<usize>>,
+    /// [`SetRow::scroll`] of each row, by `true_set_idx`. Like the `last_item_idxs`, only put back
+    /// on the rows whose items didn't change.
+    #[serde(default)]
+    row_scrolls: Vec<RowScroll>,
+    /// The [`Screen`] on top of the home rows, if it's [resumable](Screen::is_resumable).
+    #[serde(default)]
+    screen: Option<Screen>,
+}
+
+/// Images drawn by [`SetRow::show`] in place of the artwork.
+struct Placeholders {
+    /// The "image-not-found" tile, cached for the items without artwork.
+    not_found: Id,
+    /// Frame of the [`Shimmer`] drawn while the artwork is loading.
+    loading: Id,
 }
 
 /// Encapsulates the data of the item that should be highlighted so that it can be drawn last.
 struct HighlightedItemData {
     img_id: Id,
+    region: Option<AtlasRegion>,
     w: f64,
     h: f64,
+    /// Below 1 while the image is fading in over the placeholder.
+    opacity: f32,
     true_set_idx: usize,
     adjusted_item_idx: usize,
-    adjusted_set_idx: usize,
+}
+
+/// Artwork of the focused tile at its scaled up size, see [`Config::sharp_focus`].
+struct FocusArt {
+    /// Url it's loaded from, which also tells the item and the size it's for.
+    url: String,
+    /// None while it's loading.
+    img_id: Option<Id>,
+}
+
+/// Builds the [`Api`] with its caches and client set up as per the `config`. The home data is not
+/// loaded yet. Fails if the client can't be set up, e.g. with an unreadable
+/// [`ca_certificate`](Config::ca_certificate).
+fn build_api(config: &Config) -> Result<Api, api::ApiError> {
+    let disk_cache = api::DiskCache::in_default_dir(api::DEFAULT_DISK_CACHE_TTL);
+    let mut image_cache =
+        api::ImageCache::with_disk_cache(api::DEFAULT_IMAGE_CACHE_BYTES, disk_cache);
+    if config.offline {
+        image_cache = image_cache.offline(Some(&helpers::offline_samples_dir()));
+    }
+    let api = api::Api::builder()
+        .image_cache(image_cache)
+        .client_options(config.client_options())
+        .build()?;
+    Ok(api
+        .with_image_options(config.image_options())
+        .with_rating_ceiling(config.max_rating)
+        .with_language(config.language())
+        .with_home_cache(api::HomeCache::in_default_dir()))
+}
+
+/// Loads the rows of the `catalog`: the home data from the network, or the assets folder when
+/// [`offline`](Config::offline). A folder or a media server instead, see [`Catalog`].
+#[instrument(skip_all, fields(offline = config.offline, catalog = ?catalog))]
+fn load_home_data(api: &mut Api, config: &Config, catalog: &Catalog) -> Result<(), api::ApiError> {
+    match catalog {
+        Catalog::Home if config.offline => api.load_from_file(helpers::offline_home_path()),
+        Catalog::Home => api.load_home_data(),
+        Catalog::Folder(dir) => api.load_from_provider(&FolderProvider::scan(dir)?),
+        Catalog::Tmdb => {
+            let api_key = config
+                .tmdb_api_key
+                .ok_or_else(|| api::ApiError::MissingField {
+                    path: String::from("tmdb_api_key"),
+                })?;
+            let provider = api::Tmdb::new(api_key)
+                .with_language(config.language())
+                .fetch(api.client(), api.retry_policy())?;
+            api.load_from_provider(&provider)
+        }
+        #[cfg(feature = "media-server")]
+        Catalog::MediaServer { server, url, token } => {
+            let transport = server
+                .transport(token.as_str())
+                .with_transport(api.client().clone());
+            let provider =
+                api::MediaServerProvider::connect(*server, url, &transport, api.retry_policy())?;
+            // The posters need the token too.
+            api.set_transport(transport);
+            api.load_from_provider(&provider)
+        }
+    }
+}
+
+/// Starts using the profile at `idx`, the [`Api`] is given its rating ceiling. Returns where to put
+/// the cursor, None if the profile was never used or without [`resume_session`](Config::resume_session).
+///
+/// # Arguments
+/// * `defaults`: the config the parental settings of the profile apply to, see [`Profile::apply`](profiles::Profile::apply).
+fn switch_profile(
+    api: &mut Api,
+    profiles: &mut ProfileStore,
+    idx: usize,
+    defaults: &Config,
+) -> Option<(Position, HomeDataDiff)> {
+    let profile = profiles.select(idx)?;
+    info!("using profile '{}'", profile.name);
+    let diff = api.set_rating_ceiling(profile.apply(defaults.clone()).max_rating);
+    profile
+        .last_position
+        .clone()
+        .filter(|_| defaults.resume_session)
+        .map(|position| (position, diff))
+}
+
+/// Routes the `action` to the `controller`, see [`DisplayController::handle`], writing it to the
+/// `--record` file first.
+fn dispatch(
+    controller: &mut DisplayController,
+    recorder: &mut Option<Recorder>,
+    action: Action,
+    ui: &mut Ui,
+) -> Option<ExitReason> {
+    if let Some(recorder) = recorder.as_mut() {
+        recorder.record(action, Instant::now());
+    }
+    controller.handle(action, ui)
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    env_logger::init();
-    let (display, mut events_loop, mut ui) = helpers::build_display();
+    let mut command = Cli::parse().into_command();
+    let trace_path = telemetry::take_trace_path(command.flags_mut())?;
+    let _trace_guard = telemetry::init(trace_path);
+    match command {
+        Command::Run { flags } => run(flags),
+        Command::Dump { format, flags } => cli::dump(format, flags),
+        Command::Prefetch { rows, flags } => cli::prefetch(rows, flags),
+        Command::Validate { flags } => cli::validate(flags),
+    }
+}
 
-    let api_handle = {
-        let mut a = api::Api::new();
-        a.load_home_data()?;
-        a
-    };
+/// `helloplus run`: opens the window and shows the rows until it's closed. The `args` are the
+/// config, catalog, `--headless`, `--tui`, `--demo`, `--record`, `--replay` and `--chaos` flags.
+fn run(mut args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "chaos")]
+    let faults = headless::take_value(&mut args, "--chaos")?
+        .map(|value| {
+            value
+                .parse::<api::FaultRates>()
+                .map_err(|_| config::ConfigError::InvalidValue {
+                    key: String::from("chaos"),
+                    value,
+                })
+        })
+        .transpose()?;
+    let demo = DemoOptions::take_from_args(&mut args)?;
+    let record_path = headless::take_value(&mut args, "--record")?.map(PathBuf::from);
+    let mut replay = headless::take_value(&mut args, "--replay")?
+        .map(|path| Replay::load(Path::new(&path)))
+        .transpose()?;
+    let headless = HeadlessOptions::take_from_args(&mut args)?;
+    let terminal = tui::take_from_args(&mut args);
+    let catalog = Catalog::take_from_args(&mut args)?;
+    let mut defaults = Config::load(&args)?;
+    let config_path = Config::path(&args)?;
+    if let Some(options) = headless {
+        return headless::run(defaults, &options, &catalog, replay);
+    }
+    if terminal {
+        return tui::run(defaults, &catalog);
+    }
+    let config = defaults.clone();
+    let mut themes = Themes::load()?;
+    let keymap = KeyMap::load()?;
+    let audio = Audio::load();
+
+    let mut window_mode = config.window_mode;
+    let mut event_loop = EventLoop::new().with_refresh_rate(config.refresh_rate);
+    let mut demo = demo
+        .map(|options| Demo::new(&options, Instant::now()))
+        .transpose()?;
+    let (display, mut ui) = helpers::build_display(&config, window_mode, &event_loop, &mut themes);
 
     let mut renderer = conrod::backend::glium::Renderer::new(&display).unwrap();
-    let mut event_loop = EventLoop::new();
 
-    let mut controller = DisplayController::new(&display, &api_handle, &mut ui);
-    controller.initialize(&mut ui, &Cursor::default());
+    let api_handle = build_api(&config)?;
+    #[cfg(feature = "chaos")]
+    let api_handle = match faults {
+        Some(faults) => {
+            let client = api_handle.client().clone();
+            api_handle.with_transport(api::ChaosTransport::new(client, faults))
+        }
+        None => api_handle,
+    };
+    let (mut api_handle, stale) = match startup::wait_for_home_data(
+        &display,
+        &mut event_loop,
+        &mut ui,
+        &mut renderer,
+        api_handle,
+        config.clone(),
+        catalog.clone(),
+        themes.get(config.theme),
+    )? {
+        Startup::Ready(api_handle) => (api_handle, false),
+        Startup::Stale(api_handle) => (api_handle, true),
+        Startup::Closed => return Ok(()),
+    };
 
-    let mut navigation_debounce = Instant::now();
+    let mut key_repeat = config.key_repeat();
+    let mut mouse = MouseState::default();
+    // Only the home data is reloaded, see [`Catalog::refreshes`].
+    let interval = config
+        .home_refresh_interval()
+        .filter(|_| catalog.refreshes());
+    // The cached home data is fetched again right away, refresh or not.
+    let first = if stale {
+        Some(Duration::default())
+    } else {
+        interval
+    };
+    let refresher = first.map(|first| {
+        HomeRefresher::spawn(
+            api_handle.home_fetcher(),
+            first,
+            interval,
+            event_loop.waker(),
+        )
+    });
+    #[cfg(all(feature = "remote", target_os = "linux"))]
+    let remote = remote::Remote::spawn(&event_loop);
+    let mut recorder = record_path
+        .map(|path| Recorder::create(&path))
+        .transpose()?;
+    let mut profiles = ProfileStore::load_default();
+    let mut restore = None;
 
-    'main: loop {
-        // Render the `Ui` and then display it on the screen.
-        if let Some(primitives) = ui.draw_if_changed() {
-            renderer.fill(&display, primitives, &controller.image_map);
-            let mut target = display.draw();
-            target.clear_color(0.0, 0.0, 0.013, 1.0);
-            renderer
-                .draw(&display, &mut target, &controller.image_map)
-                .unwrap();
-            target.finish().unwrap();
+    // The rows borrow the home data, so they are rebuilt from scratch whenever it's reloaded.
+    loop {
+        let config = profiles.active().map_or_else(
+            || defaults.clone(),
+            |profile| profile.apply(defaults.clone()),
+        );
+        let local = profiles.active().map_or_else(
+            || LocalRows::empty(config.locale),
+            |profile| LocalRows::load(profile, config.locale),
+        );
+        let mut controller = DisplayController::new(
+            &display,
+            &api_handle,
+            &mut ui,
+            config.clone(),
+            &themes,
+            local,
+            &audio,
+        );
+        controller.initialize(&mut ui);
+        controller.wake_on_loaded(event_loop.waker());
+        controller.wake_on_preview(event_loop.waker());
+        if let Some((position, diff)) = restore.take() {
+            controller.restore_position(position, &diff, &mut ui);
+        }
+        if profiles.active().is_none() {
+            controller.open_profile_picker(profiles.names(), profiles.last_selected(), &mut ui);
         }
-        let mut events = Vec::new();
-        events_loop.poll_events(|event| events.push(event));
+        if profiles.active().is_some() {
+            // Recording and replaying start once a profile is picked, see `recording`.
+            let now = Instant::now();
+            recorder.iter_mut().for_each(|recorder| recorder.start(now));
+            replay.iter_mut().for_each(|replay| replay.start(now));
+        }
+        // Reloaded home data that differs from the one shown, until the home screen is shown.
+        let mut pending = None;
+        let mut exit = None;
 
-        for event in event_loop.next(&mut events_loop) {
+        event_loop.run(|event, event_loop, control_flow| {
             match event {
-                glium::glutin::Event::WindowEvent { event, .. } => match event {
-                    glium::glutin::WindowEvent::Closed
-                    | glium::glutin::WindowEvent::KeyboardInput {
-                        input:
-                            glium::glutin::KeyboardInput {
-                                virtual_keycode: Some(glium::glutin::VirtualKeyCode::Escape),
-                                ..
-                            },
-                        ..
-                    } => break 'main,
-                    glium::glutin::WindowEvent::KeyboardInput {
-                        input:
-                            glium::glutin::KeyboardInput {
-                                virtual_keycode: Some(key_code),
-                                ..
-                            },
-                        ..
-                    } => {
-                        if navigation_debounce.elapsed().as_millis()
-                            < NAVIGATION_KEYS_DEBOUNCE_THRESHOLD
-                        {
-                            break;
+                Event::Input(Input::CloseRequested) => exit = Some(ExitReason::Quit),
+                Event::Input(Input::FocusLost) => key_repeat.release_all(),
+                Event::Input(Input::Resized(w, h)) => {
+                    // Let the `Ui` know first so `ui.win_w`/`ui.win_h` are up to date.
+                    ui.handle_event(conrod::event::Input::Resize(w, h));
+                    controller.resize(w as f64, h as f64, &mut ui);
+                }
+                Event::Input(input)
+                    if input.is_user_input() && controller.screen() == Screen::Screensaver =>
+                {
+                    // Only wakes the screen up, the key or click isn't acted upon.
+                    controller.close_screensaver(&mut ui);
+                }
+                Event::Input(Input::CursorMoved(x, y)) => mouse.moved((x, y)),
+                Event::Input(Input::LeftClick) if controller.screen() == Screen::Home => {
+                    match mouse.clicked() {
+                        MouseAction::Focus(x, y) => {
+                            controller.focus_at(x, y, &mut ui);
                         }
-                        navigation_debounce = Instant::now();
-
-                        if key_code == VirtualKeyCode::Left {
-                            controller.move_current_set_left(&mut ui);
-                        } else if key_code == VirtualKeyCode::Right {
-                            controller.move_current_set_right(&mut ui);
-                        } else if key_code == VirtualKeyCode::Up {
-                            controller.move_to_prev_set(&mut ui);
-                        } else if key_code == VirtualKeyCode::Down {
-                            controller.move_to_next_set(&mut ui);
+                        MouseAction::Open(x, y) => {
+                            if controller.focus_at(x, y, &mut ui) {
+                                controller.open_detail(&mut ui);
+                            }
                         }
+                        _ => (),
                     }
-                    _ => (),
-                },
-                _ => (),
+                }
+                Event::Input(Input::Scrolled(delta)) if controller.screen() == Screen::Home => {
+                    match mouse.scrolled(delta) {
+                        Some(MouseAction::ScrollUp) => controller.move_to_prev_set(&mut ui),
+                        Some(MouseAction::ScrollDown) => controller.move_to_next_set(&mut ui),
+                        _ => (),
+                    }
+                }
+                Event::Input(Input::Key { key, pressed, alt })
+                    if key == VirtualKeyCode::F11 || (key == VirtualKeyCode::Return && alt) =>
+                {
+                    // Released events are swallowed too so Alt+Enter doesn't open the detail page.
+                    if pressed {
+                        window_mode = window_mode.toggled(config.window_mode);
+                        helpers::set_window_mode(&display, event_loop, &config, window_mode);
+                    }
+                }
+                Event::Input(Input::Key {
+                    key: VirtualKeyCode::F3,
+                    pressed,
+                    ..
+                }) => {
+                    if pressed {
+                        controller.toggle_hud(&mut ui);
+                    }
+                }
+                Event::Input(Input::Char(c)) => {
+                    exit = dispatch(&mut controller, &mut recorder, Action::Char(c), &mut ui)
+                }
+                Event::Input(Input::Key {
+                    key,
+                    pressed: false,
+                    ..
+                }) => key_repeat.release(key),
+                Event::Input(Input::Key { key, .. }) => {
+                    // Ignores the OS repeating a held key.
+                    if key_repeat.press(key, keymap.repeats(key), Instant::now()) {
+                        if let Some(action) = keymap.action(key) {
+                            exit = dispatch(&mut controller, &mut recorder, action, &mut ui);
+                        }
+                    }
+                }
+                Event::Input(_) => (),
+                Event::Gamepad(action) => {
+                    let action = Action::from_gamepad(action);
+                    exit = dispatch(&mut controller, &mut recorder, action, &mut ui)
+                }
+                Event::MainEventsCleared => {
+                    let _frame = info_span!("frame").entered();
+                    let frame_start = Instant::now();
+                    if let Some(snapshot) = refresher.as_ref().and_then(HomeRefresher::try_recv) {
+                        if api_handle.diff_home_data(&snapshot).is_unchanged() {
+                            debug!("home data unchanged");
+                        } else {
+                            pending = Some(snapshot);
+                        }
+                    }
+                    // The other screens point at the sets by index, they are left alone until
+                    // closed. So is the PIN entry, not to lose what's typed.
+                    if pending.is_some() && controller.screen() == Screen::Home {
+                        exit = pending
+                            .take()
+                            .map(|snapshot| ExitReason::Reload(Reload::HomeData(snapshot)));
+                    }
+
+                    let repeated = key_repeat.poll(Instant::now());
+                    if let Some(action) = repeated.and_then(|key| keymap.action(key)) {
+                        exit = exit
+                            .or_else(|| dispatch(&mut controller, &mut recorder, action, &mut ui));
+                    }
+                    #[cfg(all(feature = "remote", target_os = "linux"))]
+                    for action in remote.iter().flat_map(remote::Remote::try_recv) {
+                        event_loop.record_input();
+                        exit = exit
+                            .or_else(|| dispatch(&mut controller, &mut recorder, action, &mut ui));
+                    }
+                    // Watching counts as input, the screensaver doesn't start over the player.
+                    if controller.is_player_running() {
+                        event_loop.record_input();
+                    }
+                    let now = Instant::now();
+                    let idle = event_loop.idle_for(now);
+                    if let Some(action) = demo.as_mut().and_then(|demo| demo.poll(now, idle)) {
+                        exit = exit
+                            .or_else(|| dispatch(&mut controller, &mut recorder, action, &mut ui));
+                    }
+                    for action in replay.iter_mut().flat_map(|replay| replay.poll(now)) {
+                        event_loop.record_input();
+                        exit = exit.or_else(|| controller.handle(action, &mut ui));
+                    }
+                    // The demo keeps the screen busy.
+                    let screensaver_timeout =
+                        config.screensaver_timeout().filter(|_| demo.is_none());
+                    if screensaver_timeout.map_or(false, |timeout| idle >= timeout) {
+                        controller.open_screensaver(&mut ui);
+                    }
+                    controller.stream_rows(&mut ui);
+                    controller.poll_loaded_images(&mut ui);
+                    controller.tick(&mut ui);
+
+                    // Render the `Ui` and then display it on the screen.
+                    if let Some(primitives) = ui.draw_if_changed() {
+                        let _render = info_span!("render").entered();
+                        renderer.fill(&display, primitives, &controller.image_map);
+                        let mut target = display.draw();
+                        let [r, g, b] = controller.theme().background;
+                        target.clear_color_srgb(r, g, b, 1.0);
+                        renderer
+                            .draw(&display, &mut target, &controller.image_map)
+                            .unwrap();
+                        target.finish().unwrap();
+                        let frame_end = Instant::now();
+                        controller.record_frame(frame_end, frame_end - frame_start);
+                    }
+
+                    // The images still loading wake the loop up once they come.
+                    // Only wakes up to see whether the player exited while it runs.
+                    let keep_polling = !controller.is_player_running()
+                        && (controller.has_pending_uploads()
+                            || controller.is_hud_shown()
+                            || controller.is_animating()
+                            || controller.is_streaming()
+                            || key_repeat.is_repeating());
+                    // Wakes up to start the screensaver, or for its next artwork.
+                    let deadline = if controller.screen() == Screen::Screensaver {
+                        controller.next_slide()
+                    } else {
+                        screensaver_timeout
+                            .and_then(|timeout| timeout.checked_sub(idle))
+                            .map(|left| now + left)
+                            .filter(|deadline| *deadline > now)
+                    };
+                    // And for the video loop of the focused tile, the player exiting, the next
+                    // action of the demo or of the replay.
+                    let deadline = deadline
+                        .into_iter()
+                        .chain(controller.preview_due())
+                        .chain(
+                            controller
+                                .is_player_running()
+                                .then(|| now + PLAYER_POLL_INTERVAL),
+                        )
+                        .chain(demo.as_ref().map(Demo::next_at))
+                        .chain(replay.as_ref().and_then(Replay::next_at))
+                        .min();
+                    *control_flow = match deadline {
+                        _ if keep_polling => ControlFlow::Poll,
+                        Some(deadline) => ControlFlow::WaitUntil(deadline),
+                        None => ControlFlow::Wait,
+                    };
+                }
             }
-        }
+            if exit.is_some() {
+                *control_flow = ControlFlow::Exit;
+            }
+        });
+
+        let position = controller.position();
+        drop(controller);
+        let reload = match exit {
+            Some(ExitReason::Reload(reload)) => reload,
+            Some(ExitReason::Quit) | None => {
+                profiles.save_position(position);
+                return Ok(());
+            }
+        };
+        let diff = match reload {
+            Reload::HomeData(snapshot) => {
+                let diff = api_handle.set_home_data(snapshot);
+                info!(
+                    "home data reloaded: {} changed, {} removed",
+                    diff.changed.len(),
+                    diff.removed
+                );
+                diff
+            }
+            Reload::RatingCeiling(rating_ceiling) => {
+                info!("rating ceiling set to {:?}", rating_ceiling);
+                api_handle.set_rating_ceiling(rating_ceiling)
+            }
+            Reload::Profile(choice) => {
+                let idx = match choice {
+                    PickerChoice::Profile(idx) => Some(idx),
+                    PickerChoice::Add => profiles.add(),
+                };
+                restore = idx
+                    .and_then(|idx| switch_profile(&mut api_handle, &mut profiles, idx, &defaults));
+                continue;
+            }
+            Reload::Settings(settings) => {
+                defaults = defaults.with_settings(&settings);
+                if let Some(path) = config_path.as_ref() {
+                    if let Err(e) = defaults.save_settings(path) {
+                        info!("Could not save the settings: {}", e);
+                    }
+                }
+                api_handle.set_image_options(defaults.image_options());
+                HomeDataDiff::unchanged(api_handle.get_num_of_sets().unwrap_or(0))
+            }
+        };
+        restore = Some((position, diff));
     }
-    Ok(())
 }
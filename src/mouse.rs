@@ -0,0 +1,81 @@
+//! Mouse input handling.
+//!
+//! Keeps track of the pointer position, detects double clicks and turns wheel/trackpad deltas
+//! into row changes. The actual hit-testing is done by the [`DisplayController`](crate::DisplayController).
+use crate::event_loop::ScrollDelta;
+use std::time::{Duration, Instant};
+
+/// Max time between two clicks on the same tile to be considered a double click.
+const DOUBLE_CLICK_THRESHOLD: Duration = Duration::from_millis(400);
+/// Amount of pixels a trackpad needs to scroll to move by one row.
+const PIXELS_PER_ROW: f64 = 120.0;
+
+/// What the main loop should do in response to the mouse.
+#[derive(Debug, PartialEq)]
+pub enum MouseAction {
+    /// Move the cursor to the tile under the pointer (`x`, `y`).
+    Focus(f64, f64),
+    /// Open the tile under the pointer (`x`, `y`).
+    Open(f64, f64),
+    /// Move up one row.
+    ScrollUp,
+    /// Move down one row.
+    ScrollDown,
+}
+
+/// Mouse state carried across events.
+#[derive(Default)]
+pub struct MouseState {
+    position: (f64, f64),
+    last_click: Option<(Instant, (f64, f64))>,
+    scroll_acc: f64,
+}
+
+impl MouseState {
+    /// Call on every [`Input::CursorMoved`](crate::event_loop::Input::CursorMoved) event.
+    pub fn moved(&mut self, position: (f64, f64)) {
+        self.position = position;
+    }
+
+    /// Call when the left button is pressed. Returns [`MouseAction::Open`] if this is the second
+    /// click of a double click on roughly the same spot.
+    pub fn clicked(&mut self) -> MouseAction {
+        let (x, y) = self.position;
+        let now = Instant::now();
+        let is_double = match self.last_click {
+            Some((at, (px, py))) => {
+                now.duration_since(at) < DOUBLE_CLICK_THRESHOLD
+                    && (px - x).abs() < 4.0
+                    && (py - y).abs() < 4.0
+            }
+            None => false,
+        };
+
+        if is_double {
+            self.last_click = None;
+            MouseAction::Open(x, y)
+        } else {
+            self.last_click = Some((now, (x, y)));
+            MouseAction::Focus(x, y)
+        }
+    }
+
+    /// Call on every [`Input::Scrolled`](crate::event_loop::Input::Scrolled) event. Returns an action
+    /// once enough scrolling has accumulated.
+    pub fn scrolled(&mut self, delta: ScrollDelta) -> Option<MouseAction> {
+        match delta {
+            ScrollDelta::Lines(y) => self.scroll_acc += y as f64 * PIXELS_PER_ROW,
+            ScrollDelta::Pixels(y) => self.scroll_acc += y,
+        }
+
+        if self.scroll_acc >= PIXELS_PER_ROW {
+            self.scroll_acc = 0.0;
+            Some(MouseAction::ScrollUp)
+        } else if self.scroll_acc <= -PIXELS_PER_ROW {
+            self.scroll_acc = 0.0;
+            Some(MouseAction::ScrollDown)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,858 @@
+//! Cursor movement over the home rows.
+//!
+//! [`NavigationModel`] keeps the [`Cursor`] and the [`Viewport`] in sync as the focus moves around.
+//! It only deals with indices, the rows are described by their [`RowExtent`] and [`RowScroll`], so
+//! it can be exercised without a window. The tests below check that whatever the moves:
+//!  - the `adjusted_item_idx` stays below the [`row_stride`](RowExtent::row_stride) and maps to the
+//!    `true_item_idx` through the row's [`RowScroll`],
+//!  - the `true_item_idx` is an item of the row,
+//!  - the visible rows contain the cursor row.
+//!
+//! They also run it frame after frame against fake rows whose images take a few frames to load,
+//! doing with the tiles what the [`DisplayController`](crate::DisplayController) does: requesting
+//! the ones in view, dropping those loaded once out of view, caching them in a
+//! [`TileRing`](crate::virtualization::TileRing) and evicting the rows further than
+//! [`BUFFERED_ROWS`](crate::BUFFERED_ROWS) from the visible ones, cancelling their loads. Nothing is
+//! requested past the end of a row, only the buffered rows keep tiles or load them, and a tile in
+//! view is a placeholder for no longer than its load takes, or once the cursor stops until the
+//! loads still going are done.
+//!
+//! With [`wrap`](NavigationModel::with_wrap) on, moving past either end of a row or of the list of
+//! rows continues from the other end.
+//!
+//! The [`skipped`](NavigationModel::with_skipped) rows, e.g. the sets without items, are left out:
+//! the cursor goes over them and the [`Viewport`] only counts the other rows, see
+//! [`NavigationModel::shown_row`].
+use crate::virtualization::{Cursor, RowScroll, Viewport};
+use std::ops::Range;
+
+/// Smallest distance, in tiles, kept between the cursor and the right edge of a row. Matches the
+/// point where [`RowScroll::shift_right`] starts scrolling.
+const RIGHT_EDGE_MARGIN: usize = 3;
+
+/// What the [`NavigationModel`] needs to know about a row.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RowExtent {
+    /// Number of items in the row.
+    pub item_count: usize,
+    /// Number of tiles drawn for the row, see [`Layout::row_stride`](crate::layout::Layout::row_stride). At least 4.
+    pub row_stride: usize,
+}
+
+/// The [`Cursor`] and the rows in view.
+///
+/// Vertical moves return the row the cursor lands on, the caller then hands that row's
+/// [`RowScroll`] to [`NavigationModel::enter_row`]. That way the rows can be built lazily.
+#[derive(Debug)]
+pub struct NavigationModel {
+    /// Where the focus is.
+    pub cursor: Cursor,
+    /// Rows in view, counting the rows that aren't skipped only, see [`NavigationModel::visible_rows`].
+    pub viewport: Viewport,
+    /// Number of rows in view.
+    num_rows: usize,
+    /// Whether moving past the last item/row goes back to the first one, and the other way around.
+    wrap: bool,
+    /// `true_set_idx` of the rows left out, ascending.
+    skipped: Vec<usize>,
+}
+
+impl NavigationModel {
+    /// Cursor on the first item of the first row, `num_rows` rows in view.
+    pub fn new(num_rows: usize) -> Self {
+        Self {
+            cursor: Cursor::default(),
+            viewport: Viewport::new(num_rows),
+            num_rows,
+            wrap: false,
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Turns the wrap-around on or off. Off by default.
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Leaves out the rows `skipped`, given by their `true_set_idx`. None by default.
+    pub fn with_skipped(mut self, mut skipped: Vec<usize>) -> Self {
+        skipped.sort_unstable();
+        skipped.dedup();
+        self.skipped = skipped;
+        self
+    }
+
+    /// Returns true if the row `true_set_idx` is left out, see [`NavigationModel::with_skipped`].
+    pub fn is_skipped(&self, true_set_idx: usize) -> bool {
+        self.skipped.binary_search(&true_set_idx).is_ok()
+    }
+
+    /// Position of the row `true_set_idx` among the rows not skipped, i.e. the number of those above
+    /// it. The number of rows shown out of `num_of_sets` for `true_set_idx == num_of_sets`.
+    pub fn shown_row(&self, true_set_idx: usize) -> usize {
+        true_set_idx
+            - self
+                .skipped
+                .iter()
+                .take_while(|idx| **idx < true_set_idx)
+                .count()
+    }
+
+    /// The `true_set_idx` of the row not skipped at position `shown_row`, see [`NavigationModel::shown_row`].
+    fn true_set_idx(&self, shown_row: usize) -> usize {
+        let mut true_set_idx = shown_row;
+        for idx in &self.skipped {
+            if *idx > true_set_idx {
+                break;
+            }
+            true_set_idx += 1;
+        }
+        true_set_idx
+    }
+
+    /// The `true_set_idx` of the rows of the `viewport` range `shown`. The rows skipped in between are
+    /// part of it and left to the caller to skip.
+    fn true_range(&self, shown: Range<usize>) -> Range<usize> {
+        self.true_set_idx(shown.start)..self.true_set_idx(shown.end)
+    }
+
+    /// The row not skipped the closest to `true_set_idx` out of `num_of_sets`, looking past it first
+    /// if `forward`. None if every row is skipped.
+    fn closest_shown(
+        &self,
+        true_set_idx: usize,
+        num_of_sets: usize,
+        forward: bool,
+    ) -> Option<usize> {
+        let after = (true_set_idx..num_of_sets).find(|idx| !self.is_skipped(*idx));
+        let before = (0..true_set_idx.min(num_of_sets))
+            .rev()
+            .find(|idx| !self.is_skipped(*idx));
+        if forward {
+            after.or(before)
+        } else {
+            before.or(after)
+        }
+    }
+
+    /// Starts over with `num_rows` rows in view, e.g. after a resize. The cursor doesn't move.
+    pub fn reset(&mut self, num_rows: usize) {
+        self.num_rows = num_rows;
+        self.viewport.reset(num_rows);
+    }
+
+    /// The rows in view once the cursor row is brought into view, see [`Viewport::visible_set_range`].
+    /// The skipped ones in between are part of it, without taking the place of another row.
+    pub fn visible_set_range(&mut self) -> Range<usize> {
+        let shown = self
+            .viewport
+            .visible_set_range(self.shown_row(self.cursor.true_set_idx), self.num_rows);
+        self.true_range(shown)
+    }
+
+    /// The range returned by the last call to [`visible_set_range`](NavigationModel::visible_set_range).
+    pub fn visible_rows(&self) -> Range<usize> {
+        self.true_range(self.viewport.current())
+    }
+
+    /// Moves left on the cursor `row`, its `scroll` follows once the cursor gets close to the left
+    /// edge. Stops at the first item, unless wrapping in which case it goes to the last one.
+    pub fn move_left(&mut self, scroll: &mut RowScroll, row: RowExtent) {
+        if self.cursor.on_banner {
+            return;
+        }
+        if self.wrap && self.cursor.true_item_idx == 0 && row.item_count > 1 {
+            self.cursor.true_item_idx = row.item_count - 1;
+            self.clamp_to_row(scroll, row);
+            return;
+        }
+        scroll.shift_left(self.cursor.adjusted_item_idx);
+        if self.cursor.true_item_idx > 0 {
+            self.cursor.true_item_idx -= 1;
+        }
+        self.sync_adjusted(scroll);
+    }
+
+    /// Moves right on the cursor `row`, its `scroll` follows once the cursor gets close to the right
+    /// edge. Stops at the last item, unless wrapping in which case it goes back to the first one.
+    pub fn move_right(&mut self, scroll: &mut RowScroll, row: RowExtent) {
+        if self.cursor.on_banner {
+            return;
+        }
+        if self.wrap && self.cursor.true_item_idx + 1 >= row.item_count {
+            self.cursor.true_item_idx = 0;
+            scroll.left_right_idx_adjustment = 0;
+            self.sync_adjusted(scroll);
+            return;
+        }
+        if scroll.shift_right(
+            row.row_stride,
+            row.item_count,
+            self.cursor.adjusted_item_idx,
+            self.cursor.true_item_idx,
+        ) {
+            self.cursor.true_item_idx += 1;
+        }
+        self.sync_adjusted(scroll);
+    }
+
+    /// Moves up one set out of `num_of_sets`, going up from the first one gives the focus to the
+    /// banner. When wrapping, going up from the banner goes to the last set. The skipped sets are
+    /// gone over.
+    /// Returns the `true_set_idx` of the row to [`enter`](NavigationModel::enter_row), if any.
+    pub fn move_up(&mut self, num_of_sets: usize) -> Option<usize> {
+        let prev = (0..self.cursor.true_set_idx.min(num_of_sets))
+            .rev()
+            .find(|idx| !self.is_skipped(*idx));
+        let last = self.closest_shown(num_of_sets, num_of_sets, false);
+        match (self.cursor.on_banner, prev) {
+            (true, _) if self.wrap && last.is_some() => {
+                self.cursor.on_banner = false;
+                self.cursor.true_set_idx = last?;
+                last
+            }
+            (false, Some(prev)) => {
+                self.cursor.true_set_idx = prev;
+                Some(prev)
+            }
+            _ => {
+                self.cursor.on_banner = true;
+                None
+            }
+        }
+    }
+
+    /// Moves down one set out of `num_of_sets`, going down from the banner gives the focus back to
+    /// the first one. When wrapping, going down from the last set goes back to the first one. The
+    /// skipped sets are gone over.
+    /// Returns the `true_set_idx` of the row to [`enter`](NavigationModel::enter_row), if any.
+    pub fn move_down(&mut self, num_of_sets: usize) -> Option<usize> {
+        let next = (self.cursor.true_set_idx + 1..num_of_sets).find(|idx| !self.is_skipped(*idx));
+        let first = self.closest_shown(0, num_of_sets, true);
+        if self.cursor.on_banner {
+            self.cursor.on_banner = false;
+            if !self.is_skipped(self.cursor.true_set_idx) {
+                return None;
+            }
+            self.cursor.true_set_idx = first?;
+            first
+        } else if next.is_some() {
+            self.cursor.true_set_idx = next?;
+            next
+        } else if self.wrap && first.map_or(false, |first| first < self.cursor.true_set_idx) {
+            self.cursor.true_set_idx = first?;
+            first
+        } else {
+            None
+        }
+    }
+
+    /// Moves to the set `true_set_idx` out of `num_of_sets`, however far it is from the cursor row.
+    /// Past the last set goes to the last set, a skipped set to the next one not skipped, or the
+    /// previous one if there is none. The cursor keeps its column.
+    /// Returns the `true_set_idx` of the row to [`enter`](NavigationModel::enter_row), None if there are
+    /// no sets left.
+    pub fn jump_to_set(&mut self, true_set_idx: usize, num_of_sets: usize) -> Option<usize> {
+        let true_set_idx = self.closest_shown(
+            true_set_idx.min(num_of_sets.checked_sub(1)?),
+            num_of_sets,
+            true,
+        )?;
+        self.cursor.on_banner = false;
+        self.cursor.true_set_idx = true_set_idx;
+        self.viewport
+            .jump_to(self.shown_row(true_set_idx), self.num_rows);
+        Some(true_set_idx)
+    }
+
+    /// Puts the cursor on the first item of the row it's on, scrolling the row all the way left.
+    pub fn move_to_first_item(&mut self, scroll: &mut RowScroll) {
+        self.cursor.true_item_idx = 0;
+        scroll.left_right_idx_adjustment = 0;
+        self.sync_adjusted(scroll);
+    }
+
+    /// Lands the cursor on the `row` it just moved to. The cursor keeps its column, see
+    /// [`NavigationModel::clamp_to_row`].
+    pub fn enter_row(&mut self, scroll: &mut RowScroll, row: RowExtent) {
+        let adjusted_item_idx = self
+            .cursor
+            .adjusted_item_idx
+            .min(row.row_stride - RIGHT_EDGE_MARGIN);
+        self.cursor.true_item_idx = scroll.true_item_idx(adjusted_item_idx);
+        self.clamp_to_row(scroll, row);
+    }
+
+    /// Lands the cursor on the item `true_item_idx` of the `row` it just moved to, e.g. the one it was
+    /// on when it last left the row. Its `scroll` is kept as long as the item is in view.
+    pub fn return_to_item(&mut self, scroll: &mut RowScroll, row: RowExtent, true_item_idx: usize) {
+        self.cursor.true_item_idx = true_item_idx;
+        self.clamp_to_row(scroll, row);
+    }
+
+    /// Keeps the cursor on one of the items of its `row` and away from its right edge, scrolling the
+    /// row if needed. Rows don't all hold the same number of tiles (see [`TileShape`](crate::layout::TileShape))
+    /// and the local rows change size.
+    pub fn clamp_to_row(&mut self, scroll: &mut RowScroll, row: RowExtent) {
+        let last_item_idx = row.item_count.saturating_sub(1);
+        let max_adjusted_item_idx = row.row_stride - RIGHT_EDGE_MARGIN;
+        self.cursor.true_item_idx = self.cursor.true_item_idx.min(last_item_idx);
+        scroll.left_right_idx_adjustment = scroll
+            .left_right_idx_adjustment
+            .min(self.cursor.true_item_idx);
+        if self.cursor.true_item_idx - scroll.left_right_idx_adjustment > max_adjusted_item_idx {
+            scroll.left_right_idx_adjustment = self.cursor.true_item_idx - max_adjusted_item_idx;
+        }
+        self.sync_adjusted(scroll);
+    }
+
+    /// Puts the cursor on the tile at `adjusted_item_idx` of the row `true_set_idx`, e.g. when it's
+    /// clicked.
+    pub fn focus(&mut self, true_set_idx: usize, scroll: &RowScroll, adjusted_item_idx: usize) {
+        self.cursor.on_banner = false;
+        self.cursor.true_set_idx = true_set_idx;
+        self.cursor.true_item_idx = scroll.true_item_idx(adjusted_item_idx);
+        self.cursor.adjusted_item_idx = adjusted_item_idx;
+    }
+
+    fn sync_adjusted(&mut self, scroll: &RowScroll) {
+        self.cursor.adjusted_item_idx =
+            self.cursor.true_item_idx - scroll.left_right_idx_adjustment;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::virtualization::{self, TileRing};
+    use crate::{BUFFERED_ROWS, ROWS_PER_LOOP};
+    use proptest::prelude::*;
+    use std::collections::{HashMap, VecDeque};
+
+    #[derive(Clone, Copy, Debug)]
+    enum Move {
+        Left,
+        Right,
+        Up,
+        Down,
+        Jump(usize),
+    }
+
+    fn a_move() -> impl Strategy<Value = Move> {
+        prop_oneof![
+            Just(Move::Left),
+            Just(Move::Right),
+            Just(Move::Up),
+            Just(Move::Down),
+            (0usize..40).prop_map(Move::Jump)
+        ]
+    }
+
+    fn moves() -> impl Strategy<Value = Vec<Move>> {
+        prop::collection::vec(a_move(), 0..200)
+    }
+
+    fn rows() -> impl Strategy<Value = Vec<RowExtent>> {
+        prop::collection::vec(
+            (0usize..40, 4usize..12).prop_map(|(item_count, row_stride)| RowExtent {
+                item_count,
+                row_stride,
+            }),
+            1..30,
+        )
+    }
+
+    /// Applies `mv` the way the [`DisplayController`](crate::DisplayController) does.
+    fn apply(nav: &mut NavigationModel, scrolls: &mut [RowScroll], rows: &[RowExtent], mv: Move) {
+        let cur = nav.cursor.true_set_idx;
+        let entered = match mv {
+            Move::Left => {
+                nav.move_left(&mut scrolls[cur], rows[cur]);
+                None
+            }
+            Move::Right => {
+                nav.move_right(&mut scrolls[cur], rows[cur]);
+                None
+            }
+            Move::Up => nav.move_up(rows.len()),
+            Move::Down => nav.move_down(rows.len()),
+            Move::Jump(true_set_idx) => nav.jump_to_set(true_set_idx, rows.len()),
+        };
+        if let Some(true_set_idx) = entered {
+            nav.enter_row(&mut scrolls[true_set_idx], rows[true_set_idx]);
+        }
+    }
+
+    fn check_invariants(nav: &mut NavigationModel, scrolls: &[RowScroll], rows: &[RowExtent]) {
+        let cursor = &nav.cursor;
+        let row = rows[cursor.true_set_idx];
+        let scroll = &scrolls[cursor.true_set_idx];
+        assert!(cursor.true_set_idx < rows.len());
+        assert!(
+            cursor.adjusted_item_idx < row.row_stride,
+            "{:?} {:?}",
+            cursor,
+            row
+        );
+        assert_eq!(
+            scroll.true_item_idx(cursor.adjusted_item_idx),
+            cursor.true_item_idx
+        );
+        if row.item_count > 0 {
+            assert!(
+                cursor.true_item_idx < row.item_count,
+                "{:?} {:?}",
+                cursor,
+                row
+            );
+        }
+        let true_set_idx = cursor.true_set_idx;
+        let visible = nav.visible_set_range();
+        assert!(
+            visible.contains(&true_set_idx),
+            "{:?} {:?}",
+            visible,
+            true_set_idx
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn cursor_stays_in_range(
+            rows in rows(),
+            num_rows in 2usize..7,
+            wrap in any::<bool>(),
+            moves in moves(),
+        ) {
+            let mut nav = NavigationModel::new(num_rows).with_wrap(wrap);
+            let mut scrolls = vec![RowScroll::default(); rows.len()];
+            for mv in moves {
+                apply(&mut nav, &mut scrolls, &rows, mv);
+                check_invariants(&mut nav, &scrolls, &rows);
+            }
+        }
+
+        #[test]
+        fn empty_rows_are_skipped(
+            rows in rows(),
+            num_rows in 2usize..7,
+            wrap in any::<bool>(),
+            moves in moves(),
+        ) {
+            // The first row stands for the local rows, they are never skipped.
+            let skipped = (1..rows.len()).filter(|idx| rows[*idx].item_count == 0).collect();
+            let mut nav = NavigationModel::new(num_rows).with_wrap(wrap).with_skipped(skipped);
+            let mut scrolls = vec![RowScroll::default(); rows.len()];
+            for mv in moves {
+                apply(&mut nav, &mut scrolls, &rows, mv);
+                check_invariants(&mut nav, &scrolls, &rows);
+                prop_assert!(!nav.is_skipped(nav.cursor.true_set_idx), "{:?}", nav.cursor);
+                let shown = nav.visible_rows().filter(|idx| !nav.is_skipped(*idx)).count();
+                prop_assert!(shown <= num_rows, "{:?} shows {} rows", nav.visible_rows(), shown);
+            }
+        }
+
+        #[test]
+        fn clamping_keeps_the_cursor_in_range(
+            rows in rows(),
+            moves in moves(),
+            item_count in 0usize..40,
+            row_stride in 4usize..12,
+        ) {
+            let mut nav = NavigationModel::new(4);
+            let mut scrolls = vec![RowScroll::default(); rows.len()];
+            for mv in moves {
+                apply(&mut nav, &mut scrolls, &rows, mv);
+            }
+            // The cursor row changes size, like a local row being rebuilt or a resize.
+            let mut rows = rows;
+            let cur = nav.cursor.true_set_idx;
+            rows[cur] = RowExtent { item_count, row_stride };
+            nav.clamp_to_row(&mut scrolls[cur], rows[cur]);
+            check_invariants(&mut nav, &scrolls, &rows);
+        }
+
+        #[test]
+        fn right_then_left_goes_back(item_count in 1usize..40, row_stride in 4usize..12, steps in 0usize..40) {
+            let row = RowExtent { item_count, row_stride };
+            let mut nav = NavigationModel::new(4);
+            let mut scroll = RowScroll::default();
+            for _ in 0..steps {
+                nav.move_right(&mut scroll, row);
+            }
+            prop_assert_eq!(nav.cursor.true_item_idx, steps.min(item_count - 1));
+            for _ in 0..steps {
+                nav.move_left(&mut scroll, row);
+            }
+            prop_assert_eq!(nav.cursor.true_item_idx, 0);
+            prop_assert_eq!(scroll.left_right_idx_adjustment, 0);
+        }
+    }
+
+    /// Longest a simulated load takes, in frames.
+    const MAX_LATENCY: usize = 8;
+
+    /// Replays what the [`DisplayController`](crate::DisplayController) does with the tiles frame
+    /// after frame, against rows of `item_count` fake items whose images take one of the `latencies`
+    /// to load.
+    struct Simulation {
+        nav: NavigationModel,
+        scrolls: Vec<RowScroll>,
+        rows: Vec<RowExtent>,
+        /// The tiles cached by each row, with the frame they came in.
+        rings: Vec<TileRing<usize>>,
+        /// Rows built so far, see [`ROWS_PER_LOOP`].
+        streamed_rows: usize,
+        /// `true_set_idx`, `true_item_idx` and frame it's due of the loads, in the order they were
+        /// submitted.
+        in_flight: VecDeque<(usize, usize, usize)>,
+        /// Frames each load takes, in turn.
+        latencies: Vec<usize>,
+        submitted: usize,
+        /// Loads handed over per frame at most, None for all of those due.
+        bandwidth: Option<usize>,
+        /// Number of frames in a row each tile drawn this frame has been a placeholder for.
+        placeholders: HashMap<(usize, usize), usize>,
+        frame: usize,
+    }
+
+    impl Simulation {
+        fn new(
+            rows: Vec<RowExtent>,
+            num_rows: usize,
+            latencies: Vec<usize>,
+            bandwidth: Option<usize>,
+        ) -> Self {
+            Self {
+                nav: NavigationModel::new(num_rows),
+                scrolls: vec![RowScroll::default(); rows.len()],
+                rings: rows
+                    .iter()
+                    .map(|row| TileRing::new(row.row_stride))
+                    .collect(),
+                rows,
+                streamed_rows: 0,
+                in_flight: VecDeque::new(),
+                latencies,
+                submitted: 0,
+                bandwidth,
+                placeholders: HashMap::new(),
+                frame: 0,
+            }
+        }
+
+        /// The loads done, the tiles drawn and the textures evicted, in that order.
+        fn run_frame(&mut self) {
+            self.frame += 1;
+            let frame = self.frame;
+
+            // `poll_loaded_images`: the tiles no longer in view are dropped.
+            let mut budget = self.bandwidth.unwrap_or(usize::MAX);
+            let mut pending = VecDeque::new();
+            for (true_set_idx, true_item_idx, due) in self.in_flight.drain(..) {
+                if due > frame || budget == 0 {
+                    pending.push_back((true_set_idx, true_item_idx, due));
+                    continue;
+                }
+                budget -= 1;
+                let row = self.rows[true_set_idx];
+                if self.scrolls[true_set_idx].is_in_window(row.row_stride, true_item_idx) {
+                    self.rings[true_set_idx].insert(true_item_idx, frame);
+                }
+            }
+            self.in_flight = pending;
+
+            // `refresh`: the placeholder is drawn until the tile is cached.
+            let visible = self.nav.visible_set_range();
+            self.streamed_rows = self.streamed_rows.max(self.nav.cursor.true_set_idx + 1);
+            let mut placeholders = HashMap::new();
+            for true_set_idx in visible.clone() {
+                if true_set_idx >= self.streamed_rows || true_set_idx >= self.rows.len() {
+                    break;
+                }
+                let row = self.rows[true_set_idx];
+                let num_of_tiles = if row.item_count == 0 {
+                    0
+                } else {
+                    row.row_stride
+                };
+                for adjusted_item_idx in 0..num_of_tiles {
+                    let true_item_idx = self.scrolls[true_set_idx].true_item_idx(adjusted_item_idx);
+                    let ring = &mut self.rings[true_set_idx];
+                    if true_item_idx >= row.item_count {
+                        // No tile in the provider, "image-not-found" is cached right away.
+                        ring.insert(true_item_idx, frame);
+                        continue;
+                    }
+                    if ring.get(true_item_idx).is_some() {
+                        continue;
+                    }
+                    let key = (true_set_idx, true_item_idx);
+                    let in_flight = self
+                        .in_flight
+                        .iter()
+                        .any(|(set_idx, item_idx, _)| (*set_idx, *item_idx) == key);
+                    if !in_flight {
+                        let latency = self.latencies[self.submitted % self.latencies.len()];
+                        self.submitted += 1;
+                        self.in_flight
+                            .push_back((true_set_idx, true_item_idx, frame + latency));
+                    }
+                    let shown_for = self.placeholders.get(&key).map_or(1, |frames| frames + 1);
+                    placeholders.insert(key, shown_for);
+                }
+            }
+            self.placeholders = placeholders;
+
+            // `evict_far_rows`, the loads of the tiles out of view are cancelled.
+            let keep = virtualization::buffered_range(visible.clone(), BUFFERED_ROWS);
+            for (true_set_idx, ring) in self.rings.iter_mut().enumerate() {
+                if !keep.contains(&true_set_idx) {
+                    ring.drain().for_each(drop);
+                }
+            }
+            let (rows, scrolls) = (&self.rows, &self.scrolls);
+            self.in_flight.retain(|&(true_set_idx, true_item_idx, _)| {
+                keep.contains(&true_set_idx)
+                    && scrolls[true_set_idx]
+                        .is_in_window(rows[true_set_idx].row_stride, true_item_idx)
+            });
+
+            // `stream_rows`
+            let rows_to_stream = visible.end.min(self.rows.len());
+            if self.streamed_rows < rows_to_stream {
+                self.streamed_rows = (self.streamed_rows + ROWS_PER_LOOP).min(rows_to_stream);
+            }
+        }
+
+        /// Checks the cursor, what is cached and what was requested.
+        fn check_invariants(&mut self) {
+            check_invariants(&mut self.nav, &self.scrolls, &self.rows);
+            let keep = virtualization::buffered_range(self.nav.viewport.current(), BUFFERED_ROWS);
+            for (true_set_idx, ring) in self.rings.iter().enumerate() {
+                let row = self.rows[true_set_idx];
+                for (true_item_idx, _) in ring.iter() {
+                    assert!(
+                        keep.contains(&true_set_idx),
+                        "row {} cached outside of {:?}",
+                        true_set_idx,
+                        keep
+                    );
+                    // Past the end of the row only while the row is scrolled to its end.
+                    assert!(
+                        true_item_idx < row.item_count + row.row_stride,
+                        "{} cached for {:?}",
+                        true_item_idx,
+                        row
+                    );
+                }
+            }
+            for &(true_set_idx, true_item_idx, _) in self.in_flight.iter() {
+                let row = self.rows[true_set_idx];
+                assert!(true_item_idx < row.item_count);
+                assert!(
+                    keep.contains(&true_set_idx),
+                    "row {} still loading",
+                    true_set_idx
+                );
+                assert!(self.scrolls[true_set_idx].is_in_window(row.row_stride, true_item_idx));
+            }
+        }
+
+        /// Returns true once every tile drawn is cached and no load is left.
+        fn is_settled(&self) -> bool {
+            self.placeholders.is_empty() && self.in_flight.is_empty()
+        }
+    }
+
+    fn latencies() -> impl Strategy<Value = Vec<usize>> {
+        prop::collection::vec(1usize..=MAX_LATENCY, 1..20)
+    }
+
+    proptest! {
+        #[test]
+        fn placeholders_go_away_with_enough_bandwidth(
+            rows in rows(),
+            num_rows in 2usize..7,
+            latencies in latencies(),
+            steps in prop::collection::vec((a_move(), 1usize..4), 0..200),
+        ) {
+            let mut sim = Simulation::new(rows, num_rows, latencies, None);
+            // Each move is followed by a few frames, the tiles in view are often still loading.
+            for (mv, frames) in steps {
+                apply(&mut sim.nav, &mut sim.scrolls, &sim.rows, mv);
+                for _ in 0..frames {
+                    sim.run_frame();
+                    sim.check_invariants();
+                    for (tile, waited) in sim.placeholders.iter() {
+                        prop_assert!(*waited <= MAX_LATENCY, "{:?} waited {} frames", tile, waited);
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn tiles_come_in_once_the_cursor_stops(
+            rows in rows(),
+            num_rows in 2usize..7,
+            latencies in latencies(),
+            bandwidth in 1usize..6,
+            moves in moves(),
+        ) {
+            let mut sim = Simulation::new(rows, num_rows, latencies, Some(bandwidth));
+            for mv in moves {
+                apply(&mut sim.nav, &mut sim.scrolls, &sim.rows, mv);
+                sim.run_frame();
+                sim.check_invariants();
+            }
+            // Whatever is still loading, then every tile in view, one per frame at worst.
+            let max_tiles = num_rows * 12;
+            let max_frames = sim.in_flight.len() + max_tiles + num_rows + MAX_LATENCY + 1;
+            let mut frames = 0;
+            loop {
+                sim.run_frame();
+                sim.check_invariants();
+                if sim.is_settled() {
+                    break;
+                }
+                frames += 1;
+                prop_assert!(frames <= max_frames, "{:?} still loading", sim.placeholders);
+            }
+        }
+    }
+
+    #[test]
+    fn up_from_the_first_row_goes_to_the_banner() {
+        let mut nav = NavigationModel::new(4);
+        assert_eq!(nav.move_up(10), None);
+        assert!(nav.cursor.on_banner);
+        assert_eq!(nav.move_up(10), None);
+        assert!(nav.cursor.on_banner);
+        assert_eq!(nav.move_down(10), None);
+        assert!(!nav.cursor.on_banner);
+        assert_eq!(nav.move_down(10), Some(1));
+    }
+
+    #[test]
+    fn entering_a_shorter_row_lands_on_its_last_item() {
+        let mut nav = NavigationModel::new(4);
+        let mut scrolls = vec![RowScroll::default(); 2];
+        let long = RowExtent {
+            item_count: 20,
+            row_stride: 6,
+        };
+        for _ in 0..10 {
+            nav.move_right(&mut scrolls[0], long);
+        }
+        let short = RowExtent {
+            item_count: 2,
+            row_stride: 6,
+        };
+        assert_eq!(nav.move_down(2), Some(1));
+        nav.enter_row(&mut scrolls[1], short);
+        assert_eq!(nav.cursor.true_item_idx, 1);
+        assert_eq!(nav.cursor.adjusted_item_idx, 1);
+    }
+
+    #[test]
+    fn wraps_around_the_row() {
+        let mut nav = NavigationModel::new(4).with_wrap(true);
+        let mut scroll = RowScroll::default();
+        let row = RowExtent {
+            item_count: 10,
+            row_stride: 6,
+        };
+        nav.move_left(&mut scroll, row);
+        assert_eq!(nav.cursor.true_item_idx, 9);
+        assert_eq!(nav.cursor.adjusted_item_idx, 3);
+        assert_eq!(scroll.left_right_idx_adjustment, 6);
+        nav.move_right(&mut scroll, row);
+        assert_eq!(nav.cursor.true_item_idx, 0);
+        assert_eq!(nav.cursor.adjusted_item_idx, 0);
+        assert_eq!(scroll.left_right_idx_adjustment, 0);
+    }
+
+    #[test]
+    fn wraps_around_the_rows() {
+        let mut nav = NavigationModel::new(4).with_wrap(true);
+        assert_eq!(nav.move_down(3), Some(1));
+        assert_eq!(nav.move_down(3), Some(2));
+        assert_eq!(nav.move_down(3), Some(0));
+        assert_eq!(nav.move_up(3), None);
+        assert!(nav.cursor.on_banner);
+        assert_eq!(nav.move_up(3), Some(2));
+        assert!(!nav.cursor.on_banner);
+        assert_eq!(nav.visible_set_range(), 0..4);
+    }
+
+    #[test]
+    fn jumps_bring_the_row_into_view() {
+        let mut nav = NavigationModel::new(4);
+        assert_eq!(nav.jump_to_set(30, 20), Some(19));
+        assert_eq!(nav.visible_set_range(), 17..21);
+        assert_eq!(nav.jump_to_set(3, 20), Some(3));
+        assert_eq!(nav.visible_set_range(), 1..5);
+        assert_eq!(nav.jump_to_set(0, 20), Some(0));
+        assert_eq!(nav.visible_set_range(), 0..4);
+    }
+
+    #[test]
+    fn goes_over_the_skipped_rows() {
+        let mut nav = NavigationModel::new(2).with_skipped(vec![4, 1, 2]);
+        assert_eq!(nav.shown_row(6), 3);
+        assert_eq!(nav.move_down(6), Some(3));
+        // The skipped rows don't take the place of the ones shown.
+        assert_eq!(nav.visible_set_range(), 0..5);
+        assert_eq!(nav.move_down(6), Some(5));
+        assert_eq!(nav.visible_set_range(), 5..7);
+        assert_eq!(nav.move_down(6), None);
+        assert_eq!(nav.move_up(6), Some(3));
+        assert_eq!(nav.move_up(6), Some(0));
+        assert_eq!(nav.move_up(6), None);
+        assert!(nav.cursor.on_banner);
+
+        assert_eq!(nav.jump_to_set(2, 6), Some(3));
+        assert_eq!(nav.visible_set_range(), 3..6);
+        // Nothing past the last row shown, the one before it then.
+        assert_eq!(nav.jump_to_set(4, 5), Some(3));
+    }
+
+    #[test]
+    fn wraps_around_the_skipped_rows() {
+        let mut nav = NavigationModel::new(4)
+            .with_wrap(true)
+            .with_skipped(vec![2, 3]);
+        assert_eq!(nav.move_down(4), Some(1));
+        assert_eq!(nav.move_down(4), Some(0));
+        assert_eq!(nav.move_up(4), None);
+        assert_eq!(nav.move_up(4), Some(1));
+    }
+
+    #[test]
+    fn returns_to_the_remembered_item() {
+        let mut nav = NavigationModel::new(4);
+        let mut scroll = RowScroll {
+            left_right_idx_adjustment: 5,
+        };
+        let row = RowExtent {
+            item_count: 10,
+            row_stride: 6,
+        };
+        nav.return_to_item(&mut scroll, row, 7);
+        assert_eq!(nav.cursor.true_item_idx, 7);
+        assert_eq!(nav.cursor.adjusted_item_idx, 2);
+        assert_eq!(scroll.left_right_idx_adjustment, 5);
+        // The row shrank meanwhile.
+        nav.return_to_item(
+            &mut scroll,
+            RowExtent {
+                item_count: 4,
+                ..row
+            },
+            7,
+        );
+        assert_eq!(nav.cursor.true_item_idx, 3);
+        assert_eq!(scroll.left_right_idx_adjustment, 3);
+    }
+}
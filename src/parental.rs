@@ -0,0 +1,166 @@
+//! Parental controls: the [`Pin`] lifting the [`max_rating`](crate::config::Config::max_rating) filter
+//! and the overlay it's typed in.
+//!
+//! Pressing `P` on the home screen asks for the PIN while the filter is on, and turns the filter back
+//! on once it was lifted. Without a [`parental_pin`](crate::config::Config::parental_pin) configured
+//! the filter can't be lifted at runtime.
+use crate::i18n::{self, Locale, Text};
+use conrod::{widget, Colorable, Positionable, Sizeable, UiCell, Widget};
+use serde::de::{self, Deserialize, Deserializer};
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Number of digits of a [`Pin`].
+pub const PIN_LENGTH: usize = 4;
+
+widget_ids!(
+    /// Hold the [`Id`](conrod::widget::Id)s for the PIN entry widgets.
+    pub struct PinIds {
+        backdrop,
+        title,
+        digits,
+        message,
+    }
+);
+
+/// Four digits code, written as a string in the config, e.g. `parental_pin = "1234"`, and in the
+/// [`profiles`](crate::profiles).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pin([u8; PIN_LENGTH]);
+
+impl FromStr for Pin {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut digits = [0; PIN_LENGTH];
+        let mut chars = s.chars();
+        for digit in digits.iter_mut() {
+            *digit = chars.next().and_then(|c| c.to_digit(10)).ok_or(())? as u8;
+        }
+        match chars.next() {
+            Some(_) => Err(()),
+            None => Ok(Pin(digits)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Pin {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse()
+            .map_err(|_| de::Error::invalid_value(de::Unexpected::Str(&s), &"4 digits"))
+    }
+}
+
+impl fmt::Display for Pin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.iter().try_for_each(|digit| write!(f, "{}", digit))
+    }
+}
+
+impl Serialize for Pin {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Overlay asking for the [`Pin`], drawn on top of whatever screen is shown.
+pub struct PinEntry {
+    ids: PinIds,
+    /// The digits typed so far, None while the overlay is closed.
+    typed: Option<Vec<u8>>,
+    /// Set once a wrong PIN was typed, until the next digit.
+    wrong: bool,
+}
+
+impl PinEntry {
+    /// Constructor.
+    pub fn new(ids: PinIds) -> Self {
+        Self {
+            ids,
+            typed: None,
+            wrong: false,
+        }
+    }
+
+    /// Shows the overlay, with no digit typed.
+    pub fn open(&mut self) {
+        self.typed = Some(Vec::with_capacity(PIN_LENGTH));
+        self.wrong = false;
+    }
+
+    /// Hides the overlay.
+    pub fn close(&mut self) {
+        self.typed = None;
+    }
+
+    /// Handles a character typed while the overlay is shown, anything but digits is ignored.
+    ///
+    /// Returns true once the typed digits make up the `pin`, the overlay is then closed. A wrong
+    /// PIN is cleared so it can be typed again.
+    pub fn type_char(&mut self, c: char, pin: Pin) -> bool {
+        let (typed, digit) = match (self.typed.as_mut(), c.to_digit(10)) {
+            (Some(typed), Some(digit)) => (typed, digit as u8),
+            _ => return false,
+        };
+        self.wrong = false;
+        typed.push(digit);
+        if typed.len() < PIN_LENGTH {
+            return false;
+        }
+        if typed[..] == pin.0[..] {
+            self.close();
+            return true;
+        }
+        typed.clear();
+        self.wrong = true;
+        false
+    }
+
+    /// Returns true if the last PIN typed was wrong, until the next digit is typed.
+    pub fn is_wrong(&self) -> bool {
+        self.wrong
+    }
+
+    /// Sets the widgets for the overlay, the texts in the language of the `locale`. Does nothing if
+    /// it's not open.
+    pub fn show(&self, ui: &mut UiCell, locale: Locale) {
+        let typed = match self.typed {
+            Some(ref typed) => typed,
+            None => return,
+        };
+
+        widget::Rectangle::fill_with([ui.win_w, ui.win_h], conrod::color::BLACK.alpha(0.9))
+            .middle_of(ui.window)
+            .set(self.ids.backdrop, ui);
+
+        widget::Text::new(i18n::tr(locale, Text::EnterPin))
+            .mid_top_with_margin_on(ui.window, ui.win_h / 3.0)
+            .color(conrod::color::WHITE)
+            .font_size(36)
+            .set(self.ids.title, ui);
+
+        let digits: Vec<&str> = (0..PIN_LENGTH)
+            .map(|idx| if idx < typed.len() { "*" } else { "_" })
+            .collect();
+        widget::Text::new(&digits.join(" "))
+            .down_from(self.ids.title, 40.0)
+            .align_middle_x_of(self.ids.title)
+            .color(conrod::color::WHITE)
+            .font_size(64)
+            .set(self.ids.digits, ui);
+
+        let message = if self.wrong {
+            Text::WrongPin
+        } else {
+            Text::EscapeToCancel
+        };
+        widget::Text::new(i18n::tr(locale, message))
+            .down_from(self.ids.digits, 40.0)
+            .align_middle_x_of(self.ids.title)
+            .color(conrod::color::LIGHT_GREY)
+            .font_size(24)
+            .set(self.ids.message, ui);
+    }
+}
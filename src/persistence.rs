@@ -0,0 +1,58 @@
+//! Small helpers to keep state across runs as json files under the data dir,
+//! see [`helpers::data_dir`](crate::helpers::data_dir).
+//!
+//! Errors are logged and otherwise ignored: losing the state is better than not starting.
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// A value stored in a json file. The file is only written by [`Persisted::save`].
+#[derive(Debug, Default)]
+pub struct Persisted<T> {
+    /// Where the value is saved. None if the data dir couldn't be resolved, the value is then only
+    /// kept in memory.
+    path: Option<PathBuf>,
+    /// The loaded value.
+    pub value: T,
+}
+
+impl<T: Serialize + DeserializeOwned + Default> Persisted<T> {
+    /// Loads `file_name` from the data dir. A missing or invalid file gives the default value.
+    pub fn load(file_name: &str) -> Self {
+        let path = crate::helpers::data_dir().map(|dir| dir.join(file_name));
+        let value = match path.as_ref().map(fs::read_to_string) {
+            Some(Ok(contents)) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                info!("Ignoring invalid {}: {}", file_name, e);
+                T::default()
+            }),
+            Some(Err(e)) if e.kind() != io::ErrorKind::NotFound => {
+                info!("Could not read {}: {}", file_name, e);
+                T::default()
+            }
+            _ => T::default(),
+        };
+        Self { path, value }
+    }
+
+    /// Writes the value back to its file.
+    pub fn save(&self) {
+        if let Some(path) = self.path.as_ref() {
+            if let Err(e) = write_json(path, &self.value) {
+                info!("Could not save {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(value)?;
+    // Write to a temporary file first so a crash never leaves a truncated file behind.
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).and_then(|_| fs::rename(&tmp, path))
+}
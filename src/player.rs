@@ -0,0 +1,129 @@
+//! Hands an item over to an external player, see
+//! [`Config::player_command`](crate::config::Config::player_command).
+//!
+//! The command is a template split on whitespace, e.g. `mpv --fs --title={title} {url}`, its
+//! `{url}` and `{title}` replaced in each argument once split so they can hold spaces. Without a
+//! `{url}` the url is passed as the last argument. The url is the one of the `videoArt` loop of the
+//! item, otherwise the [`player_url`](crate::config::Config::player_url) with its `{content_id}`
+//! replaced.
+//!
+//! While the player runs the app is paused: the actions are dropped and nothing is drawn, the loop
+//! only waking up every [`PLAYER_POLL_INTERVAL`] to see whether it exited. The player is killed if
+//! the app quits first.
+use std::io;
+use std::process::{Child, Command};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Time between two checks of whether the player exited.
+pub const PLAYER_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The `template` split into the program and its arguments, `{url}` and `{title}` replaced. None if
+/// the template is blank.
+pub fn player_args(template: &str, url: &str, title: &str) -> Option<(String, Vec<String>)> {
+    let mut words = template.split_whitespace();
+    let program = words.next()?.to_string();
+    let mut args: Vec<String> = words
+        .map(|word| word.replace("{url}", url).replace("{title}", title))
+        .collect();
+    if !template.contains("{url}") {
+        args.push(url.to_string());
+    }
+    Some((program, args))
+}
+
+/// An external player playing an item.
+pub struct ExternalPlayer {
+    child: Child,
+    started: Instant,
+}
+
+impl ExternalPlayer {
+    /// Starts the player of the `template` for the `url`, see [`player_args`].
+    pub fn launch(template: &str, url: &str, title: &str) -> io::Result<Self> {
+        let (program, args) = player_args(template, url, title)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty player command"))?;
+        info!("Playing {} with {}", url, program);
+        let child = Command::new(&program).args(&args).spawn()?;
+        Ok(Self {
+            child,
+            started: Instant::now(),
+        })
+    }
+
+    /// Returns true once the player exited, or can't be waited for.
+    pub fn has_exited(&mut self) -> bool {
+        match self.child.try_wait() {
+            Ok(None) => false,
+            Ok(Some(status)) => {
+                info!(
+                    "Player exited with {} after {:?}",
+                    status,
+                    self.started.elapsed()
+                );
+                true
+            }
+            Err(e) => {
+                info!("Could not wait for the player: {}", e);
+                true
+            }
+        }
+    }
+}
+
+impl Drop for ExternalPlayer {
+    fn drop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_the_url_and_the_title() {
+        assert_eq!(
+            player_args("mpv --fs --title={title} {url}", "http://a/b.mp4", "Loki"),
+            Some((
+                "mpv".to_string(),
+                vec![
+                    "--fs".to_string(),
+                    "--title=Loki".to_string(),
+                    "http://a/b.mp4".to_string(),
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn appends_the_url_without_a_placeholder() {
+        assert_eq!(
+            player_args("vlc --fullscreen", "http://a/b.mp4", "Loki"),
+            Some((
+                "vlc".to_string(),
+                vec!["--fullscreen".to_string(), "http://a/b.mp4".to_string()]
+            ))
+        );
+    }
+
+    #[test]
+    fn keeps_a_title_with_spaces_in_a_single_argument() {
+        let (_, args) = player_args(
+            "mpv --title={title} {url}",
+            "http://a/b.mp4",
+            "The Bad Batch",
+        )
+        .unwrap();
+        assert_eq!(args, vec!["--title=The Bad Batch", "http://a/b.mp4"]);
+    }
+
+    #[test]
+    fn a_blank_template_plays_nothing() {
+        assert_eq!(player_args("", "http://a/b.mp4", "Loki"), None);
+        assert_eq!(player_args("   ", "http://a/b.mp4", "Loki"), None);
+    }
+}
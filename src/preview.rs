@@ -0,0 +1,277 @@
+//! Video loop played in place of the artwork of the focused tile, see
+//! [`Config::video_preview_ms`](crate::config::Config::video_preview_ms).
+//!
+//! Once the focus rests on a tile for that long, its `videoArt` loop (see
+//! [`SetData::get_item_video_art`](api::SetData::get_item_video_art)) is decoded by an [`FFMPEG`]
+//! process, scaled to the size the focused tile is drawn at. Its frames are read from a thread of
+//! their own and handed over through a channel, the newest one being written to a single texture
+//! every frame the [`EventLoop`](crate::event_loop::EventLoop) is woken up for.
+//!
+//! Moving the focus stops the process and releases the texture right away. If none of the urls of
+//! the loop can be played, e.g. without `ffmpeg` installed, the tile keeps its artwork and the loop
+//! isn't tried again until the focus comes back to it.
+use crate::event_loop::Waker;
+use api::VideoLoop;
+use conrod::image::Id;
+use image::RgbaImage;
+use std::io::{self, Read};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, SyncSender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+/// Program decoding the video loops, looked up in the `PATH`.
+pub const FFMPEG: &str = "ffmpeg";
+/// Frames decoded ahead of the one drawn. The decoder waits once they are all waiting.
+const QUEUED_FRAMES: usize = 2;
+
+/// A tile, as its `true_set_idx` and `true_item_idx`.
+pub type TileKey = (usize, usize);
+
+/// What the decoding thread hands over.
+enum Message {
+    Frame(RgbaImage),
+    /// None of the urls could be played.
+    Failed,
+}
+
+/// What the [`VideoPreview`] is doing for its tile.
+enum State {
+    /// The focus is elsewhere, or on a tile without a video loop.
+    Idle,
+    /// Waiting for the focus to rest on the tile until `due`.
+    Waiting {
+        video: VideoLoop,
+        size: (u32, u32),
+        due: Instant,
+    },
+    /// Decoding the loop. `img_id` is the texture of the frames, None until the first one comes.
+    Playing { player: Player, img_id: Option<Id> },
+    /// The loop couldn't be played, the artwork is drawn instead.
+    Failed,
+}
+
+/// The video loop of the focused tile.
+pub struct VideoPreview {
+    /// Time the focus rests on a tile before its loop plays, None if it never does.
+    delay: Option<Duration>,
+    /// Tile the `state` is about, None while the focus isn't on a tile.
+    key: Option<TileKey>,
+    state: State,
+    /// Wakes the event loop up when a frame comes, see [`VideoPreview::wake_with`].
+    waker: Option<Arc<Mutex<Waker>>>,
+}
+
+impl VideoPreview {
+    /// Plays the loops once the focus rests on a tile for `delay`. None never plays them.
+    pub fn new(delay: Option<Duration>) -> Self {
+        Self {
+            delay,
+            key: None,
+            state: State::Idle,
+            waker: None,
+        }
+    }
+
+    /// Wakes the event loop up with the `waker` whenever a frame is decoded.
+    pub fn wake_with(&mut self, waker: Waker) {
+        self.waker = Some(Arc::new(Mutex::new(waker)));
+    }
+
+    /// Returns true if the preview is about the tile `key`, or about none if `key` is None.
+    pub fn is_for(&self, key: Option<TileKey>) -> bool {
+        self.key == key
+    }
+
+    /// Moves the preview to the tile `key`, whose loop is `video` to be drawn at `size` in pixels.
+    /// Whatever was playing is stopped, the texture of its frames is returned to be released.
+    pub fn rest_on(
+        &mut self,
+        key: Option<TileKey>,
+        video: Option<(VideoLoop, (u32, u32))>,
+        now: Instant,
+    ) -> Option<Id> {
+        let released = match std::mem::replace(&mut self.state, State::Idle) {
+            State::Playing { img_id, .. } => img_id,
+            _ => None,
+        };
+        self.key = key;
+        if let (Some(delay), Some((video, size))) = (self.delay, video) {
+            self.state = State::Waiting {
+                video,
+                size,
+                due: now + delay,
+            };
+        }
+        released
+    }
+
+    /// When the loop waited for is due to start, None if none is.
+    pub fn due_at(&self) -> Option<Instant> {
+        match &self.state {
+            State::Waiting { due, .. } => Some(*due),
+            _ => None,
+        }
+    }
+
+    /// Starts the loop waited for if it's due at `now`, then returns the newest frame decoded, if
+    /// any. A loop that fails is given up on, the artwork being drawn instead. Its texture is then
+    /// returned as the second value, to be released.
+    pub fn poll(&mut self, now: Instant) -> (Option<RgbaImage>, Option<Id>) {
+        if let State::Waiting { video, size, due } = &self.state {
+            if *due > now {
+                return (None, None);
+            }
+            let player = Player::spawn(video, *size, self.waker.clone());
+            self.state = State::Playing {
+                player,
+                img_id: None,
+            };
+        }
+        let (player, img_id) = match &mut self.state {
+            State::Playing { player, img_id } => (player, *img_id),
+            _ => return (None, None),
+        };
+        let mut frame = None;
+        loop {
+            match player.frames.try_recv() {
+                Ok(Message::Frame(img)) => frame = Some(img),
+                Err(TryRecvError::Empty) => return (frame, None),
+                Ok(Message::Failed) | Err(TryRecvError::Disconnected) => {
+                    self.state = State::Failed;
+                    return (None, img_id);
+                }
+            }
+        }
+    }
+
+    /// Remembers `img_id` holds the frames of the loop playing.
+    pub fn set_texture(&mut self, id: Id) {
+        if let State::Playing { img_id, .. } = &mut self.state {
+            *img_id = Some(id);
+        }
+    }
+
+    /// The texture holding the frames of the loop of `key`, None unless it's playing.
+    pub fn texture(&self, key: TileKey) -> Option<Id> {
+        match &self.state {
+            State::Playing { img_id, .. } if self.key == Some(key) => *img_id,
+            _ => None,
+        }
+    }
+}
+
+/// State of the process shared with its thread, so dropping the [`Player`] can kill it.
+#[derive(Default)]
+struct Process {
+    child: Option<Child>,
+    stopped: bool,
+}
+
+/// An [`FFMPEG`] process decoding a loop, killed once dropped.
+struct Player {
+    process: Arc<Mutex<Process>>,
+    frames: Receiver<Message>,
+}
+
+impl Player {
+    /// Decodes `video` at `size` from a thread of its own, trying its urls in order until one plays.
+    fn spawn(video: &VideoLoop, size: (u32, u32), waker: Option<Arc<Mutex<Waker>>>) -> Self {
+        let process = Arc::new(Mutex::new(Process::default()));
+        let (tx, frames) = mpsc::sync_channel(QUEUED_FRAMES);
+        let urls: Vec<String> = std::iter::once(video.url.clone())
+            .chain(video.fallback_urls.iter().cloned())
+            .collect();
+        let shared = process.clone();
+        thread::spawn(move || {
+            let mut played = false;
+            for url in &urls {
+                if shared.lock().unwrap().stopped {
+                    return;
+                }
+                match decode(url, size, &shared, &tx, waker.as_deref()) {
+                    Ok(true) => {
+                        played = true;
+                        break;
+                    }
+                    Ok(false) => debug!("No frames decoded from {}", url),
+                    // No point trying the other urls.
+                    Err(e) => {
+                        info!("Could not start {}: {}", FFMPEG, e);
+                        break;
+                    }
+                }
+            }
+            if !played {
+                let _ = tx.send(Message::Failed);
+                if let Some(waker) = waker {
+                    waker.lock().unwrap().wake();
+                }
+            }
+        });
+        Self { process, frames }
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        let mut process = self.process.lock().unwrap();
+        process.stopped = true;
+        if let Some(child) = process.child.as_mut() {
+            let _ = child.kill();
+        }
+    }
+}
+
+/// Sends the frames of the video at `url`, scaled to `size`, until it ends or the [`Player`] is
+/// dropped. Returns false if not a single frame could be decoded, an error if [`FFMPEG`] couldn't
+/// be started, e.g. when it isn't installed.
+fn decode(
+    url: &str,
+    (w, h): (u32, u32),
+    process: &Mutex<Process>,
+    tx: &SyncSender<Message>,
+    waker: Option<&Mutex<Waker>>,
+) -> io::Result<bool> {
+    let mut child = Command::new(FFMPEG)
+        .args(&["-loglevel", "error", "-stream_loop", "-1", "-re", "-i", url])
+        .args(&["-an", "-vf", &format!("scale={}:{}", w, h)])
+        .args(&["-f", "rawvideo", "-pix_fmt", "rgba", "pipe:1"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let mut stdout = child.stdout.take().unwrap();
+    {
+        let mut process = process.lock().unwrap();
+        if process.stopped {
+            let _ = child.kill();
+        }
+        process.child = Some(child);
+    }
+
+    let mut frames = 0;
+    loop {
+        let mut buf = vec![0; w as usize * h as usize * 4];
+        if stdout.read_exact(&mut buf).is_err() {
+            break;
+        }
+        let img = RgbaImage::from_raw(w, h, buf).unwrap();
+        if tx.send(Message::Frame(img)).is_err() {
+            break;
+        }
+        frames += 1;
+        if let Some(waker) = waker {
+            waker.lock().unwrap().wake();
+        }
+    }
+
+    let child = process.lock().unwrap().child.take();
+    if let Some(mut child) = child {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    Ok(frames > 0)
+}
@@ -0,0 +1,113 @@
+//! Screen picking the [`Profile`](crate::profiles::Profile) to use, shown at startup when there is
+//! more than one.
+//!
+//! The profiles are laid out as a single row of tiles followed by an "Add profile" tile, left/right
+//! move the focus and select picks the focused one.
+use crate::i18n::{self, Locale, Text};
+use crate::profiles::MAX_PROFILES;
+use crate::theme::Theme;
+use conrod::widget::id::Generator;
+use conrod::{widget, Colorable, Labelable, Positionable, Sizeable, UiCell, Widget};
+
+const TILE_SIZE: f64 = 220.0;
+const TILE_MARGIN: f64 = 40.0;
+
+widget_ids!(
+    /// Hold the [`Id`](conrod::widget::Id)s for the profile picker widgets.
+    /// `tiles` has [`MAX_PROFILES`] elements, the "Add profile" tile takes the one after the last
+    /// profile.
+    pub struct PickerIds {
+        title,
+        tiles[],
+    }
+);
+
+/// What the picker was told to pick, see [`ProfilePicker::selected`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PickerChoice {
+    /// The profile at this index.
+    Profile(usize),
+    /// A new profile.
+    Add,
+}
+
+/// State of the profile picker.
+pub struct ProfilePicker {
+    ids: PickerIds,
+    names: Vec<String>,
+    focus: usize,
+}
+
+impl ProfilePicker {
+    /// Constructor. Uses the `generator` to size the `tiles` buffer of the `ids`.
+    pub fn new(mut ids: PickerIds, mut generator: Generator) -> Self {
+        ids.tiles.resize(MAX_PROFILES, &mut generator);
+        Self {
+            ids,
+            names: Vec::new(),
+            focus: 0,
+        }
+    }
+
+    /// Lists the profiles `names`, with the focus on the one at `focus`.
+    pub fn reset(&mut self, names: Vec<String>, focus: usize) {
+        self.names = names;
+        self.focus = focus.min(self.num_of_tiles() - 1);
+    }
+
+    /// The profiles plus the "Add profile" tile, as long as there is room for another profile.
+    fn num_of_tiles(&self) -> usize {
+        (self.names.len() + 1).min(MAX_PROFILES)
+    }
+
+    /// Moves the focus to the previous tile.
+    pub fn move_left(&mut self) {
+        self.focus = self.focus.saturating_sub(1);
+    }
+
+    /// Moves the focus to the next tile.
+    pub fn move_right(&mut self) {
+        self.focus = (self.focus + 1).min(self.num_of_tiles() - 1);
+    }
+
+    /// What the focused tile stands for.
+    pub fn selected(&self) -> PickerChoice {
+        if self.focus < self.names.len() {
+            PickerChoice::Profile(self.focus)
+        } else {
+            PickerChoice::Add
+        }
+    }
+
+    /// Sets the profile picker widgets, the title following the `theme` and the texts in the
+    /// language of the `locale`.
+    pub fn show(&self, ui: &mut UiCell, theme: &Theme, locale: Locale) {
+        theme
+            .text_widget(i18n::tr(locale, Text::WhoIsWatching), theme.text)
+            .mid_top_with_margin_on(ui.window, ui.win_h / 4.0)
+            .font_size(48)
+            .set(self.ids.title, ui);
+
+        let num_of_tiles = self.num_of_tiles();
+        let row_width = num_of_tiles as f64 * (TILE_SIZE + TILE_MARGIN) - TILE_MARGIN;
+        for idx in 0..num_of_tiles {
+            let label = self
+                .names
+                .get(idx)
+                .map_or(i18n::tr(locale, Text::AddProfile), String::as_str);
+            let x = idx as f64 * (TILE_SIZE + TILE_MARGIN) - (row_width - TILE_SIZE) / 2.0;
+            widget::Button::new()
+                .w_h(TILE_SIZE, TILE_SIZE)
+                .x_y_relative_to(ui.window, x, 0.0)
+                .color(if idx == self.focus {
+                    conrod::color::LIGHT_BLUE
+                } else {
+                    conrod::color::DARK_CHARCOAL
+                })
+                .label(label)
+                .label_color(conrod::color::WHITE)
+                .label_font_size(28)
+                .set(self.ids.tiles[idx], ui);
+        }
+    }
+}
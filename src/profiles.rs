@@ -0,0 +1,155 @@
+//! User profiles, each with its own watchlist, "Continue Watching" row, parental settings and cursor.
+//!
+//! The profiles are stored in `profiles.json`, see [`persistence`](crate::persistence), and the state
+//! of each one under `profiles/<dir>/` in the data dir. On first run a single profile is created,
+//! which keeps its state at the root of the data dir where it was before profiles existed.
+//!
+//! The profile is picked at startup with the [`ProfilePicker`](crate::profile_picker::ProfilePicker),
+//! which is also where new profiles are added.
+use crate::config::Config;
+use crate::parental::Pin;
+use crate::persistence::Persisted;
+use crate::Position;
+use api::ContentRating;
+use serde::{Deserialize, Serialize};
+
+/// Max number of profiles, the picker stops offering to add one past it.
+pub const MAX_PROFILES: usize = 6;
+/// Name of the profile created on first run.
+const DEFAULT_PROFILE_NAME: &str = "Default";
+
+/// A profile as stored in `profiles.json`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Profile {
+    /// Shown in the picker.
+    pub name: String,
+    /// Sub directory of `profiles/` holding the state of the profile. Empty for the profile created
+    /// on first run, see [`Profile::file_name`].
+    dir: String,
+    /// Overrides [`Config::max_rating`] when set.
+    pub max_rating: Option<ContentRating>,
+    /// Overrides [`Config::parental_pin`] when set.
+    pub parental_pin: Option<Pin>,
+    /// Where the cursor was when the app was last closed with this profile, and which screen was
+    /// open.
+    pub last_position: Option<Position>,
+}
+
+impl Profile {
+    fn new(name: String, dir: String) -> Self {
+        Self {
+            name,
+            dir,
+            max_rating: None,
+            parental_pin: None,
+            last_position: None,
+        }
+    }
+
+    /// Path, relative to the data dir, of the file `name` holding some of the profile's state.
+    pub fn file_name(&self, name: &str) -> String {
+        if self.dir.is_empty() {
+            name.to_string()
+        } else {
+            format!("profiles/{}/{}", self.dir, name)
+        }
+    }
+
+    /// The `config` with the parental settings of the profile, the ones it doesn't set are kept.
+    pub fn apply(&self, mut config: Config) -> Config {
+        config.max_rating = self.max_rating.or(config.max_rating);
+        config.parental_pin = self.parental_pin.or(config.parental_pin);
+        config
+    }
+}
+
+/// What `profiles.json` holds.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct StoredProfiles {
+    profiles: Vec<Profile>,
+    /// Index of the profile picked last, preselected in the picker.
+    last_selected: Option<usize>,
+}
+
+/// The persisted profiles and the one in use.
+#[derive(Debug)]
+pub struct ProfileStore {
+    stored: Persisted<StoredProfiles>,
+    active: Option<usize>,
+}
+
+impl ProfileStore {
+    /// Loads the profiles from the data dir. A missing or unreadable file gives the single
+    /// [`DEFAULT_PROFILE_NAME`] profile. No profile is active yet, see [`ProfileStore::select`].
+    pub fn load_default() -> Self {
+        let mut stored = Persisted::<StoredProfiles>::load("profiles.json");
+        if stored.value.profiles.is_empty() {
+            let profile = Profile::new(DEFAULT_PROFILE_NAME.to_string(), String::new());
+            stored.value.profiles.push(profile);
+        }
+        Self {
+            stored,
+            active: None,
+        }
+    }
+
+    /// Names of the profiles, in the order they were added.
+    pub fn names(&self) -> Vec<String> {
+        self.profiles().iter().map(|p| p.name.clone()).collect()
+    }
+
+    /// Index of the profile picked last, 0 if none was.
+    pub fn last_selected(&self) -> usize {
+        self.stored
+            .value
+            .last_selected
+            .filter(|idx| *idx < self.profiles().len())
+            .unwrap_or(0)
+    }
+
+    /// The profile in use, None until one is selected.
+    pub fn active(&self) -> Option<&Profile> {
+        self.active.and_then(|idx| self.profiles().get(idx))
+    }
+
+    /// Starts using the profile at `idx` and remembers it was picked. Returns None if there is no
+    /// such profile.
+    pub fn select(&mut self, idx: usize) -> Option<&Profile> {
+        if idx >= self.profiles().len() {
+            return None;
+        }
+        self.active = Some(idx);
+        self.stored.value.last_selected = Some(idx);
+        self.stored.save();
+        self.active()
+    }
+
+    /// Adds a profile named after its position, e.g. "Profile 2", and saves the profiles. Returns
+    /// its index, or None once there are [`MAX_PROFILES`].
+    pub fn add(&mut self) -> Option<usize> {
+        let idx = self.profiles().len();
+        if idx >= MAX_PROFILES {
+            return None;
+        }
+        // Profiles are never removed, so the directory is not used by another one.
+        let profile = Profile::new(
+            format!("Profile {}", idx + 1),
+            format!("profile-{}", idx + 1),
+        );
+        self.stored.value.profiles.push(profile);
+        self.stored.save();
+        Some(idx)
+    }
+
+    /// Remembers where the cursor is for the profile in use, see [`Profile::last_position`].
+    pub fn save_position(&mut self, position: Position) {
+        if let Some(idx) = self.active {
+            self.stored.value.profiles[idx].last_position = Some(position);
+            self.stored.save();
+        }
+    }
+
+    fn profiles(&self) -> &[Profile] {
+        &self.stored.value.profiles
+    }
+}
@@ -0,0 +1,75 @@
+//! Per item viewing state, used to build the "Continue Watching" row.
+//!
+//! Every time the detail page of an item is opened its [`Progress`] is updated and saved to the
+//! `progress.json` of the [`Profile`] in use, see [`persistence`](crate::persistence). Items are
+//! identified by their `contentId`.
+use crate::persistence::Persisted;
+use crate::profiles::Profile;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Max number of items remembered, the least recently opened ones are dropped first.
+const MAX_ENTRIES: usize = 50;
+
+/// What is remembered about an item.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Progress {
+    /// Index of the set, as returned by the [`Api`](api::Api), the item was last opened from.
+    pub true_set_idx: usize,
+    /// Index of the item within that set.
+    pub true_item_idx: usize,
+    /// Seconds since the unix epoch of the last time the item was opened.
+    pub last_opened: u64,
+}
+
+/// The persisted [`Progress`] of every item opened, keyed by `contentId`.
+#[derive(Debug, Default)]
+pub struct ProgressStore {
+    entries: Persisted<HashMap<String, Progress>>,
+}
+
+impl ProgressStore {
+    /// Loads the progress of the `profile` from the data dir. A missing or unreadable file gives an
+    /// empty store.
+    pub fn load(profile: &Profile) -> Self {
+        Self {
+            entries: Persisted::load(&profile.file_name("progress.json")),
+        }
+    }
+
+    /// Records that `content_id`, found at `true_set_idx`, `true_item_idx`, was just opened and
+    /// saves the store.
+    pub fn record_opened(&mut self, content_id: &str, true_set_idx: usize, true_item_idx: usize) {
+        let last_opened = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let entries = &mut self.entries.value;
+        entries.insert(
+            content_id.to_string(),
+            Progress {
+                true_set_idx,
+                true_item_idx,
+                last_opened,
+            },
+        );
+        if entries.len() > MAX_ENTRIES {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, progress)| progress.last_opened)
+                .map(|(id, _)| id.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        self.entries.save();
+    }
+
+    /// The ids of the items opened so far, most recent first.
+    pub fn recent(&self) -> Vec<&str> {
+        let mut entries = self.entries.value.iter().collect::<Vec<_>>();
+        entries.sort_by(|a, b| b.1.last_opened.cmp(&a.1.last_opened).then(a.0.cmp(b.0)));
+        entries.into_iter().map(|(id, _)| id.as_str()).collect()
+    }
+}
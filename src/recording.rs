@@ -0,0 +1,158 @@
+//! Recording and replaying a session, with `--record <path>` and `--replay <path>`.
+//!
+//! The [`Action`]s handed to the [`DisplayController`](crate::DisplayController) by the keyboard,
+//! the gamepad, the remote and the [`demo`](crate::demo) are written to a JSON lines file, each with
+//! the milliseconds since the recording started:
+//! ```text
+//! {"ms":1520,"action":"Down"}
+//! {"ms":2210,"action":"Right"}
+//! {"ms":4005,"action":{"Char":"a"}}
+//! ```
+//! Replaying hands them over again at the same times. The clock of both starts once a profile is
+//! picked, the [`ProfilePicker`](crate::profile_picker::ProfilePicker) isn't recorded. The mouse
+//! isn't either.
+//!
+//! Along with `--headless` the actions are replayed before the screenshot is taken, each one on the
+//! frame its time falls in, so a bug can be turned into a screenshot to compare, see
+//! [`headless`](crate::headless).
+use crate::config::ConfigError;
+use crate::screens::Action;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// A line of the file.
+#[derive(Debug, Deserialize, Serialize)]
+struct RecordedAction {
+    /// Milliseconds since the recording started.
+    ms: u64,
+    action: Action,
+}
+
+/// Writes the actions to the `--record` file as they are handled.
+pub struct Recorder {
+    path: PathBuf,
+    /// None after an error.
+    file: Option<File>,
+    /// When the clock started, see [`Recorder::start`]. Nothing is recorded before.
+    started: Option<Instant>,
+}
+
+impl Recorder {
+    /// Creates the file at `path`, replacing the one there.
+    pub fn create(path: &Path) -> Result<Self, ConfigError> {
+        let file = File::create(path).map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Some(file),
+            started: None,
+        })
+    }
+
+    /// Starts the clock `now`. Only the first call does anything.
+    pub fn start(&mut self, now: Instant) {
+        self.started.get_or_insert(now);
+    }
+
+    /// Writes the `action`, handled `now`. The file isn't buffered so it's complete even if the
+    /// app crashes. After an error nothing is recorded anymore.
+    pub fn record(&mut self, action: Action, now: Instant) {
+        let (started, file) = match (self.started, self.file.as_mut()) {
+            (Some(started), Some(file)) => (started, file),
+            _ => return,
+        };
+        let recorded = RecordedAction {
+            ms: now.saturating_duration_since(started).as_millis() as u64,
+            action,
+        };
+        let mut line = serde_json::to_string(&recorded).expect("actions serialize to JSON");
+        line.push('\n');
+        if let Err(e) = file.write_all(line.as_bytes()) {
+            info!("Stopped recording to {}: {}", self.path.display(), e);
+            self.file = None;
+        }
+    }
+}
+
+/// Hands the actions of a `--replay` file over at the times they were recorded.
+pub struct Replay {
+    /// When each action is due after the start, in order.
+    actions: Vec<(Duration, Action)>,
+    /// Index in `actions` of the next one due.
+    next: usize,
+    /// When the clock started, see [`Replay::start`]. Nothing is due before.
+    started: Option<Instant>,
+}
+
+impl Replay {
+    /// Reads the actions recorded at `path`. Fails if a line isn't a recorded action.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+        let mut actions = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str::<RecordedAction>(line)
+                    .map(|recorded| (Duration::from_millis(recorded.ms), recorded.action))
+                    .map_err(|_| ConfigError::InvalidValue {
+                        key: String::from("replay"),
+                        value: line.to_string(),
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // Stable, the actions recorded in the same millisecond keep their order.
+        actions.sort_by_key(|(at, _)| *at);
+        info!(
+            "replaying {} actions from {}",
+            actions.len(),
+            path.display()
+        );
+        Ok(Self {
+            actions,
+            next: 0,
+            started: None,
+        })
+    }
+
+    /// Starts the clock `now`. Only the first call does anything.
+    pub fn start(&mut self, now: Instant) {
+        self.started.get_or_insert(now);
+    }
+
+    /// The actions due at `now` not handed over yet, in order.
+    pub fn poll(&mut self, now: Instant) -> Vec<Action> {
+        let elapsed = match self.started {
+            Some(started) => now.saturating_duration_since(started),
+            None => return Vec::new(),
+        };
+        let due = self.actions[self.next..]
+            .iter()
+            .take_while(|(at, _)| *at <= elapsed)
+            .map(|(_, action)| *action)
+            .collect::<Vec<_>>();
+        self.next += due.len();
+        due
+    }
+
+    /// When the next action is due, None once they all have been handed over or before the start.
+    pub fn next_at(&self) -> Option<Instant> {
+        let (at, _) = self.actions.get(self.next)?;
+        Some(self.started? + *at)
+    }
+
+    /// Time from the start to the last action.
+    pub fn duration(&self) -> Duration {
+        self.actions
+            .last()
+            .map_or_else(Duration::default, |(at, _)| *at)
+    }
+
+    /// Returns true once every action has been handed over.
+    pub fn is_done(&self) -> bool {
+        self.next >= self.actions.len()
+    }
+}
@@ -0,0 +1,63 @@
+//! Periodic reload of the home data.
+//!
+//! Left running (e.g. as a kiosk) the app would otherwise show the rows as they were at startup for
+//! ever. Every [`home_refresh_minutes`](crate::config::Config::home_refresh_minutes) a background thread
+//! fetches the home data again and wakes the [`EventLoop`](crate::event_loop::EventLoop) up to hand
+//! it over. What's done with it is up to the loop, see [`HomeRefresher::try_recv`].
+//!
+//! It also revalidates the home data shown at startup when it came from the
+//! [`HomeCache`](api::HomeCache), right away and once, if the refresh is off.
+use crate::event_loop::Waker;
+use api::{HomeFetcher, HomeSnapshot};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, info};
+
+/// Receiving end of the thread reloading the home data. Dropping it stops the thread, at the latest
+/// after its next fetch.
+pub struct HomeRefresher {
+    rx: Receiver<HomeSnapshot>,
+}
+
+impl HomeRefresher {
+    /// Starts fetching the home data after `first`, then every `interval`.
+    ///
+    /// # Arguments
+    /// * `fetcher` - Fetches the home data, see [`Api::home_fetcher`](api::Api::home_fetcher).
+    /// * `first` - Time before the first fetch.
+    /// * `interval` - Time between two fetches, None to stop after the first one.
+    /// * `waker` - Wakes the loop up once a fetch succeeded.
+    pub fn spawn(
+        fetcher: HomeFetcher,
+        first: Duration,
+        interval: Option<Duration>,
+        waker: Waker,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut wait = Some(first);
+            while let Some(delay) = wait {
+                thread::sleep(delay);
+                wait = interval;
+                match fetcher.fetch() {
+                    Ok(snapshot) => {
+                        debug!("home data refreshed");
+                        if tx.send(snapshot).is_err() {
+                            return;
+                        }
+                        waker.wake();
+                    }
+                    // Try again next time, the rows keep showing what was loaded before.
+                    Err(e) => info!("Could not refresh the home data: {}", e),
+                }
+            }
+        });
+        Self { rx }
+    }
+
+    /// The home data fetched since the last call, the latest one if several fetches went through.
+    pub fn try_recv(&self) -> Option<HomeSnapshot> {
+        self.rx.try_iter().last()
+    }
+}
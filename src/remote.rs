@@ -0,0 +1,180 @@
+//! Infrared and HDMI-CEC remotes, enabled on Linux by the `remote` feature. Handy on a HTPC or a
+//! Raspberry Pi plugged into a TV.
+//!
+//! The buttons are read from two sources, whichever are available at startup:
+//!  - the socket of the [LIRC](https://www.lirc.org) daemon, the buttons being named after the
+//!    Linux input keys in its config, e.g. `KEY_UP`,
+//!  - the output of `cec-client` from [libcec](https://github.com/Pulse-Eight/libcec), for the remote
+//!    of the TV passing its buttons over HDMI.
+//!
+//! Each source is read on its own thread, the buttons are turned into the same [`Action`]s as the
+//! keyboard and handed to the [`EventLoop`](crate::event_loop::EventLoop) by waking it up, see
+//! [`Remote::try_recv`]. Holding a direction repeats it at the pace of the remote.
+use crate::event_loop::{EventLoop, Waker};
+use crate::screens::Action;
+use std::io::{BufRead, BufReader, Read};
+use std::os::unix::net::UnixStream;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use tracing::{debug, info};
+
+/// Where the LIRC daemon listens, the first one found is used. The first is the default of
+/// lirc 0.9.4 and later, the second the one of older versions.
+const LIRC_SOCKETS: [&str; 2] = ["/var/run/lirc/lircd", "/dev/lircd"];
+/// The libcec command line client, looked up in the `PATH`.
+const CEC_CLIENT: &str = "cec-client";
+/// Arguments of the [`CEC_CLIENT`]: logs the key presses, shows up on the TV as a playback device.
+const CEC_CLIENT_ARGS: [&str; 4] = ["-d", "16", "-t", "p"];
+/// Start of the lines the [`CEC_CLIENT`] logs a key press with, e.g.
+/// `DEBUG:   [   4261]	key pressed: up (1)`.
+const CEC_KEY_PRESSED: &str = "key pressed: ";
+
+/// The buttons of the remotes, read on background threads. Dropping it stops the `cec-client`,
+/// the LIRC thread stops on its next button.
+pub struct Remote {
+    rx: Receiver<Action>,
+    cec_client: Option<Child>,
+}
+
+impl Remote {
+    /// Starts reading the LIRC socket and the `cec-client` output, each thread waking the
+    /// `event_loop` up when a button is pressed. Returns None if neither is available.
+    pub fn spawn(event_loop: &EventLoop) -> Option<Self> {
+        let (tx, rx) = mpsc::channel();
+        let lirc = spawn_lirc(tx.clone(), event_loop.waker());
+        let cec_client = spawn_cec_client(tx, event_loop.waker());
+        if !lirc && cec_client.is_none() {
+            info!("No LIRC daemon nor cec-client found, remote support disabled");
+            return None;
+        }
+        Some(Self { rx, cec_client })
+    }
+
+    /// The actions of the buttons pressed since the last call, in order.
+    pub fn try_recv(&self) -> Vec<Action> {
+        self.rx.try_iter().collect()
+    }
+}
+
+impl Drop for Remote {
+    fn drop(&mut self) {
+        if let Some(child) = self.cec_client.as_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Reads the buttons sent by the LIRC daemon on a thread. Returns false if its socket couldn't be
+/// opened.
+fn spawn_lirc(tx: Sender<Action>, waker: Waker) -> bool {
+    let stream = match LIRC_SOCKETS
+        .iter()
+        .find_map(|path| UnixStream::connect(path).ok())
+    {
+        Some(stream) => stream,
+        None => return false,
+    };
+    info!("Reading the buttons of the LIRC daemon");
+    spawn_reader(stream, tx, waker, lirc_action);
+    true
+}
+
+/// Reads the buttons the TV passes over HDMI from a `cec-client` child. Returns None if it couldn't
+/// be started.
+fn spawn_cec_client(tx: Sender<Action>, waker: Waker) -> Option<Child> {
+    let mut child = Command::new(CEC_CLIENT)
+        .args(&CEC_CLIENT_ARGS)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| debug!("Could not start {}: {}", CEC_CLIENT, e))
+        .ok()?;
+    let stdout = child.stdout.take()?;
+    info!("Reading the buttons of the HDMI-CEC remote");
+    spawn_reader(stdout, tx, waker, cec_action);
+    Some(child)
+}
+
+/// Turns each line read from `source` into an action with `parse`, until `source` ends or the
+/// [`Remote`] is dropped.
+fn spawn_reader<R: Read + Send + 'static>(
+    source: R,
+    tx: Sender<Action>,
+    waker: Waker,
+    parse: fn(&str) -> Option<Action>,
+) {
+    thread::spawn(move || {
+        for line in BufReader::new(source).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    info!("Stopped reading the remote: {}", e);
+                    return;
+                }
+            };
+            if let Some(action) = parse(&line) {
+                if tx.send(action).is_err() {
+                    return;
+                }
+                waker.wake();
+            }
+        }
+    });
+}
+
+/// The action of a line sent by the LIRC daemon: `<code> <repeat count> <button> <remote>`, e.g.
+/// `0000000000f40bf0 00 KEY_UP devinput`. The repeats only count for the directions.
+fn lirc_action(line: &str) -> Option<Action> {
+    let mut fields = line.split_whitespace().skip(1);
+    let repeat = u32::from_str_radix(fields.next()?, 16).ok()?;
+    let action = match fields.next()? {
+        "KEY_LEFT" => Action::Left,
+        "KEY_RIGHT" => Action::Right,
+        "KEY_UP" => Action::Up,
+        "KEY_DOWN" => Action::Down,
+        "KEY_PAGEUP" | "KEY_CHANNELUP" => Action::PageUp,
+        "KEY_PAGEDOWN" | "KEY_CHANNELDOWN" => Action::PageDown,
+        "KEY_HOME" => Action::First,
+        "KEY_END" => Action::Last,
+        "KEY_OK" | "KEY_ENTER" | "KEY_SELECT" => Action::Select,
+        "KEY_BACK" | "KEY_EXIT" => Action::Back,
+        "KEY_SEARCH" => Action::Search,
+        "KEY_FAVORITES" | "KEY_BOOKMARKS" => Action::ToggleWatchlist,
+        "KEY_SETUP" | "KEY_MENU" => Action::Settings,
+        "KEY_INFO" => Action::SeeAll,
+        "KEY_OPTION" => Action::Filter,
+        "KEY_PLAY" | "KEY_PLAYPAUSE" => Action::Play,
+        _ => return None,
+    };
+    match action {
+        Action::Left | Action::Right | Action::Up | Action::Down => Some(action),
+        _ if repeat == 0 => Some(action),
+        _ => None,
+    }
+}
+
+/// The action of a line logged by the `cec-client`, None unless it's a key press. The keys are
+/// named by libcec, e.g. `up` or `channel down`.
+fn cec_action(line: &str) -> Option<Action> {
+    let key = &line[line.find(CEC_KEY_PRESSED)? + CEC_KEY_PRESSED.len()..];
+    let key = key.split(" (").next()?;
+    let action = match key {
+        "left" => Action::Left,
+        "right" => Action::Right,
+        "up" => Action::Up,
+        "down" => Action::Down,
+        "channel up" => Action::PageUp,
+        "channel down" => Action::PageDown,
+        "select" => Action::Select,
+        "exit" => Action::Back,
+        "setup menu" => Action::Settings,
+        "contents menu" => Action::SeeAll,
+        "favorite menu" => Action::ToggleWatchlist,
+        "play" => Action::Play,
+        _ => return None,
+    };
+    Some(action)
+}
@@ -0,0 +1,119 @@
+//! The [`Screen`]s the [`DisplayController`](crate::DisplayController) stacks up, and the [`Action`]s
+//! the keyboard and the gamepad are turned into before being routed to the one on top, see
+//! [`DisplayController::handle`](crate::DisplayController::handle).
+use crate::gamepad::GamepadAction;
+use serde::{Deserialize, Serialize};
+
+/// Screens the [`DisplayController`](crate::DisplayController) can show. They are kept in a stack
+/// so closing one goes back to whatever was shown before.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Screen {
+    /// The rows of tiles.
+    Home,
+    /// The detail page of an item, see [`DetailView`](crate::detail::DetailView).
+    Detail,
+    /// The on-screen keyboard and results, see [`SearchScreen`](crate::search::SearchScreen).
+    Search,
+    /// The profiles to pick from at startup, see [`ProfilePicker`](crate::profile_picker::ProfilePicker).
+    /// The home rows below it are the ones of no profile until one is picked.
+    ProfilePicker,
+    /// The parental PIN, see [`PinEntry`](crate::parental::PinEntry).
+    PinEntry,
+    /// The sort and the filter of the focused row, see [`FilterBar`](crate::filter_bar::FilterBar).
+    FilterBar,
+    /// The settings, see [`SettingsScreen`](crate::settings::SettingsScreen).
+    Settings,
+    /// Every item of a row, see [`GridScreen`](crate::grid::GridScreen).
+    Grid,
+    /// The artwork of the rows shown full screen after a while without input, see
+    /// [`Screensaver`](crate::screensaver::Screensaver).
+    Screensaver,
+}
+
+impl Screen {
+    /// Returns true if the screen is drawn on top of the one below it instead of replacing it.
+    pub fn is_overlay(self) -> bool {
+        matches!(self, Screen::Detail | Screen::PinEntry | Screen::FilterBar)
+    }
+
+    /// Returns true if the screen is opened again at startup when it was on top as the app was
+    /// closed, see [`Config::resume_session`](crate::Config::resume_session). The PIN entry, the
+    /// filter bar and the profile picker are not.
+    pub fn is_resumable(self) -> bool {
+        matches!(
+            self,
+            Screen::Detail | Screen::Search | Screen::Settings | Screen::Grid
+        )
+    }
+
+    /// Returns true if the screen takes typed characters. The [`Screen::Screensaver`] doesn't start
+    /// on top of it, the key waking the screen up would be typed too.
+    pub fn takes_text(self) -> bool {
+        matches!(self, Screen::Search | Screen::PinEntry)
+    }
+}
+
+/// Input, whichever device it came from. What it does depends on the [`Screen`] on top.
+///
+/// The keys listed are the default ones, see [`keymap`](crate::keymap) to bind others.
+///
+/// Saved as is by `--record`, see [`recording`](crate::recording).
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Action {
+    /// Arrow keys, D-pad or left stick.
+    Left,
+    /// Arrow keys, D-pad or left stick.
+    Right,
+    /// Arrow keys, D-pad or left stick.
+    Up,
+    /// Arrow keys, D-pad or left stick.
+    Down,
+    /// `PageUp`.
+    PageUp,
+    /// `PageDown`.
+    PageDown,
+    /// `Home`.
+    First,
+    /// `End`.
+    Last,
+    /// `Enter` or `A`.
+    Select,
+    /// `Escape`. Closes the screen on top, quits from the home screen and the profile picker.
+    Escape,
+    /// `B`. Closes the screen on top.
+    Back,
+    /// `Backspace`. Only closes the overlays, on the search screen it erases the last character
+    /// through [`Action::Char`].
+    Backspace,
+    /// `/` or `Y`.
+    Search,
+    /// `W`.
+    ToggleWatchlist,
+    /// `P`, see [`parental`](crate::parental).
+    Parental,
+    /// `S`, see [`settings`](crate::settings).
+    Settings,
+    /// `G`, see [`grid`](crate::grid).
+    SeeAll,
+    /// `F`, see [`filter_bar`](crate::filter_bar).
+    Filter,
+    /// `PlayPause`, see [`player`](crate::player). `Enter` does the same on the detail page.
+    Play,
+    /// A typed character.
+    Char(char),
+}
+
+impl Action {
+    /// The action bound to the gamepad `action`.
+    pub fn from_gamepad(action: GamepadAction) -> Self {
+        match action {
+            GamepadAction::Left => Action::Left,
+            GamepadAction::Right => Action::Right,
+            GamepadAction::Up => Action::Up,
+            GamepadAction::Down => Action::Down,
+            GamepadAction::Select => Action::Select,
+            GamepadAction::Back => Action::Back,
+            GamepadAction::Search => Action::Search,
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Ambient mode started after a while without input, see
+//! [`Config::screensaver_minutes`](crate::config::Config::screensaver_minutes).
+//!
+//! The hero artwork of the items of the sets is shown full screen one after the other, each one
+//! fading in from black and staying up for [`SLIDE_DURATION`]. The sets take turns so two artworks
+//! in a row come from different sets. Any input closes it and does nothing else, the screens below
+//! it are left as they were, the cursor of the home rows included.
+use crate::animation::Easing;
+use crate::loader::{ImageLoader, LoadKind, LoadRequest};
+use crate::CachedImgData;
+use api::Api;
+use conrod::image::Id;
+use conrod::{widget, Colorable, Positionable, Sizeable, UiCell, Widget};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Time each artwork stays up before the next one is loaded.
+const SLIDE_DURATION: Duration = Duration::from_secs(12);
+/// Time an artwork takes to fade in from black.
+const FADE_DURATION: Duration = Duration::from_secs(2);
+
+widget_ids!(
+    /// Hold the [`Id`]s for the screensaver widgets.
+    pub struct ScreensaverIds {
+        backdrop,
+        image,
+        veil,
+    }
+);
+
+/// State of the [`Screen::Screensaver`](crate::screens::Screen::Screensaver).
+pub struct Screensaver {
+    ids: ScreensaverIds,
+    /// Indices in the [`Api`] sets of the items with hero artwork, in the order they are shown.
+    items: Vec<(usize, usize)>,
+    /// Index in `items` of the next one to show.
+    next: usize,
+    /// Item whose artwork is being loaded.
+    loading: Option<(usize, usize)>,
+    /// Artwork shown, along with when it came in.
+    shown: Option<(CachedImgData, Instant)>,
+    /// False shows the artworks without fading them in.
+    animations: bool,
+}
+
+impl Screensaver {
+    /// Constructor. The artworks fade in if `animations` is true.
+    pub fn new(ids: ScreensaverIds, animations: bool) -> Self {
+        Self {
+            ids,
+            items: Vec::new(),
+            next: 0,
+            loading: None,
+            shown: None,
+            animations,
+        }
+    }
+
+    /// Lists the items of the `api_handle` sets with hero artwork, the first item of every set
+    /// first, then the second ones and so on. Returns false if there are none, there is then nothing
+    /// to show.
+    pub fn open(&mut self, api_handle: &Api) -> bool {
+        let sets: Vec<_> = (0..api_handle.get_num_of_sets().unwrap_or(0))
+            .filter_map(|true_set_idx| Some((true_set_idx, api_handle.get_set(true_set_idx)?)))
+            .collect();
+        let max_count = sets
+            .iter()
+            .map(|(_, set)| set.get_item_count())
+            .max()
+            .unwrap_or(0);
+        self.items = (0..max_count)
+            .flat_map(|true_item_idx| {
+                sets.iter()
+                    .filter(move |(_, set)| set.get_hero_image_url(true_item_idx).is_ok())
+                    .map(move |(true_set_idx, _)| (*true_set_idx, true_item_idx))
+            })
+            .collect();
+        self.next = 0;
+        !self.items.is_empty()
+    }
+
+    /// Closes the screensaver. Returns the texture of the artwork shown so it can be released.
+    pub fn close(&mut self) -> Option<Id> {
+        self.items.clear();
+        self.loading = None;
+        self.shown.take().map(|(img, _)| img.img_id)
+    }
+
+    /// Submits the [`LoadRequest`] for the next artwork once the one shown has been up for
+    /// [`SLIDE_DURATION`]. Does nothing while one is loading.
+    pub fn request_next(&mut self, api_handle: &Api, loader: &mut ImageLoader, now: Instant) {
+        let due = match self.shown {
+            Some((_, shown_at)) => now.saturating_duration_since(shown_at) >= SLIDE_DURATION,
+            None => true,
+        };
+        if !due || self.loading.is_some() || self.items.is_empty() {
+            return;
+        }
+        let (true_set_idx, true_item_idx) = self.items[self.next];
+        self.next = (self.next + 1) % self.items.len();
+        let url = api_handle
+            .get_set(true_set_idx)
+            .map(|set_data| set_data.get_hero_image_url(true_item_idx));
+        match url {
+            Some(Ok(url)) => {
+                self.loading = Some((true_set_idx, true_item_idx));
+                loader.submit(LoadRequest {
+                    kind: LoadKind::Screensaver,
+                    true_set_idx,
+                    true_item_idx,
+                    url,
+                    size: None,
+                    preview_url: None,
+                })
+            }
+            Some(Err(e)) => debug!("{}", e),
+            None => (),
+        }
+    }
+
+    /// Returns true if the artwork of the item is the one being loaded.
+    pub fn wants(&self, true_set_idx: usize, true_item_idx: usize) -> bool {
+        self.loading == Some((true_set_idx, true_item_idx))
+    }
+
+    /// Shows the loaded artwork, fading in from `now`. `w` and `h` are the texture dimensions.
+    /// Returns the texture of the artwork shown until now so it can be released.
+    pub fn set_image(&mut self, img_id: Id, w: f64, h: f64, now: Instant) -> Option<Id> {
+        self.loading = None;
+        let previous = self.shown.take().map(|(img, _)| img.img_id);
+        self.shown = Some((CachedImgData::new(img_id, w, h), now));
+        previous
+    }
+
+    /// The artwork being loaded couldn't be, the next one is requested instead.
+    pub fn skip(&mut self) {
+        self.loading = None;
+    }
+
+    /// Returns true while the artwork shown is fading in.
+    pub fn is_fading(&self, now: Instant) -> bool {
+        match self.shown {
+            Some((_, shown_at)) if self.animations => {
+                now.saturating_duration_since(shown_at) < FADE_DURATION
+            }
+            _ => false,
+        }
+    }
+
+    /// When the next artwork is due, None while one is being loaded.
+    pub fn next_slide(&self) -> Option<Instant> {
+        match (self.loading, &self.shown) {
+            (None, Some((_, shown_at))) => Some(*shown_at + SLIDE_DURATION),
+            _ => None,
+        }
+    }
+
+    /// Sets the widgets covering the whole window, black until the first artwork comes in.
+    ///
+    /// The artwork is cropped to the aspect ratio of the window around its center.
+    pub fn show(&self, ui: &mut UiCell, now: Instant) {
+        widget::Rectangle::fill([ui.win_w, ui.win_h])
+            .color(conrod::color::BLACK)
+            .middle_of(ui.window)
+            .set(self.ids.backdrop, ui);
+        let (img, shown_at) = match self.shown {
+            Some((ref img, shown_at)) => (img, shown_at),
+            None => return,
+        };
+        let window_ratio = ui.win_w / ui.win_h;
+        let (src_w, src_h) = if img.w / img.h > window_ratio {
+            (img.h * window_ratio, img.h)
+        } else {
+            (img.w, img.w / window_ratio)
+        };
+        let (src_x, src_y) = ((img.w - src_w) / 2.0, (img.h - src_h) / 2.0);
+        widget::Image::new(img.img_id)
+            .source_rectangle(conrod::Rect::from_corners(
+                [src_x, src_y],
+                [src_x + src_w, src_y + src_h],
+            ))
+            .w_h(ui.win_w, ui.win_h)
+            .middle_of(ui.window)
+            .set(self.ids.image, ui);
+        if !self.is_fading(now) {
+            return;
+        }
+        let elapsed = now.saturating_duration_since(shown_at).as_secs_f64();
+        let t = Easing::EaseInOutQuad.apply(elapsed / FADE_DURATION.as_secs_f64());
+        widget::Rectangle::fill([ui.win_w, ui.win_h])
+            .color(conrod::color::BLACK.alpha(1.0 - t as f32))
+            .middle_of(ui.window)
+            .set(self.ids.veil, ui);
+    }
+}
@@ -0,0 +1,405 @@
+//! Search screen: an on-screen keyboard on the left and the results grid on the right.
+//!
+//! The query can be typed with the on-screen keyboard (arrows/gamepad to move, select to press a
+//! key) or straight from the physical keyboard. Results come from [`Api::search`] and selecting
+//! one opens its detail page.
+use crate::i18n::{self, Locale, Text};
+use crate::loader::{ImageLoader, LoadKind, LoadRequest};
+use crate::theme::{to_color, Theme};
+use crate::CachedImgData;
+use api::{Api, SearchResult};
+use conrod::image::Id;
+use conrod::widget::id::Generator;
+use conrod::{widget, Colorable, Labelable, Positionable, Sizeable, UiCell, Widget};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Number of columns of the results grid.
+const RESULT_COLUMNS: usize = 4;
+/// Max number of results shown.
+const MAX_RESULTS: usize = RESULT_COLUMNS * 3;
+const KEY_SIZE: f64 = 80.0;
+const KEY_MARGIN: f64 = 10.0;
+const KEYBOARD_LEFT: f64 = 60.0;
+const KEYBOARD_TOP: f64 = 200.0;
+const RESULTS_LEFT: f64 = 720.0;
+const RESULT_WIDTH: f64 = 270.0;
+const RESULT_MARGIN: f64 = 30.0;
+const RESULT_ROW_HEIGHT: f64 = 230.0;
+/// Characters of the on-screen keyboard, one string per row.
+const CHAR_ROWS: [&str; 6] = ["abcdef", "ghijkl", "mnopqr", "stuvwx", "yz1234", "567890"];
+/// Total number of keys, the character rows plus [`Key::Space`], [`Key::Backspace`] and [`Key::Clear`].
+const NUM_OF_KEYS: usize = 36 + 3;
+
+widget_ids!(
+    /// Hold the [`Id`]s for the search screen widgets.
+    /// `keys` has [`NUM_OF_KEYS`] elements, `results` and `result_titles` have [`MAX_RESULTS`].
+    pub struct SearchIds {
+        query,
+        no_results,
+        outline,
+        keys[],
+        results[],
+        result_titles[],
+    }
+);
+
+/// A key of the on-screen keyboard.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Key {
+    Char(char),
+    Space,
+    Backspace,
+    Clear,
+}
+
+impl Key {
+    fn label(self) -> String {
+        match self {
+            Key::Char(c) => c.to_string(),
+            Key::Space => String::from("space"),
+            Key::Backspace => String::from("del"),
+            Key::Clear => String::from("clear"),
+        }
+    }
+
+    /// The special keys are as wide as two character keys.
+    fn width(self) -> f64 {
+        match self {
+            Key::Char(_) => KEY_SIZE,
+            _ => KEY_SIZE * 2.0 + KEY_MARGIN,
+        }
+    }
+}
+
+/// Which part of the screen has the focus.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SearchFocus {
+    /// A key, by `row` and `col` in the keyboard layout.
+    Keyboard { row: usize, col: usize },
+    /// A result, by its index.
+    Results(usize),
+}
+
+/// State of the search screen.
+pub struct SearchScreen {
+    ids: SearchIds,
+    layout: Vec<Vec<Key>>,
+    query: String,
+    results: Vec<SearchResult>,
+    /// Textures of the results, keyed by `(true_set_idx, true_item_idx)`.
+    imgs: HashMap<(usize, usize), CachedImgData>,
+    /// Textures no longer used by any result, waiting to be released.
+    evicted: Vec<CachedImgData>,
+    focus: SearchFocus,
+}
+
+impl SearchScreen {
+    /// Constructor. Uses the `generator` to size the `keys` and `results` buffers of the `ids`.
+    pub fn new(mut ids: SearchIds, mut generator: Generator) -> Self {
+        let mut layout: Vec<Vec<Key>> = CHAR_ROWS
+            .iter()
+            .map(|row| row.chars().map(Key::Char).collect())
+            .collect();
+        layout.push(vec![Key::Space, Key::Backspace, Key::Clear]);
+        debug_assert_eq!(layout.iter().map(Vec::len).sum::<usize>(), NUM_OF_KEYS);
+
+        ids.keys.resize(NUM_OF_KEYS, &mut generator);
+        ids.results.resize(MAX_RESULTS, &mut generator);
+        ids.result_titles.resize(MAX_RESULTS, &mut generator);
+        Self {
+            ids,
+            layout,
+            query: String::new(),
+            results: Vec::new(),
+            imgs: HashMap::new(),
+            evicted: Vec::new(),
+            focus: SearchFocus::Keyboard { row: 0, col: 0 },
+        }
+    }
+
+    /// Clears the query and results so the next search starts from scratch.
+    /// The textures of the results are moved to the evicted list.
+    pub fn reset(&mut self) {
+        self.query.clear();
+        self.results.clear();
+        self.evicted.extend(self.imgs.drain().map(|(_, data)| data));
+        self.focus = SearchFocus::Keyboard { row: 0, col: 0 };
+    }
+
+    /// Moves the focus left. Going left from the first column of results goes back to the keyboard.
+    pub fn move_left(&mut self) {
+        self.focus = match self.focus {
+            SearchFocus::Keyboard { row, col } => SearchFocus::Keyboard {
+                row,
+                col: col.saturating_sub(1),
+            },
+            SearchFocus::Results(idx) if idx % RESULT_COLUMNS == 0 => {
+                let row = (idx / RESULT_COLUMNS).min(self.layout.len() - 1);
+                SearchFocus::Keyboard {
+                    row,
+                    col: self.layout[row].len() - 1,
+                }
+            }
+            SearchFocus::Results(idx) => SearchFocus::Results(idx - 1),
+        };
+    }
+
+    /// Moves the focus right. Going right from the last key of a row moves to the results.
+    pub fn move_right(&mut self) {
+        self.focus = match self.focus {
+            SearchFocus::Keyboard { row, col } if col + 1 < self.layout[row].len() => {
+                SearchFocus::Keyboard { row, col: col + 1 }
+            }
+            SearchFocus::Keyboard { row, col } => {
+                if self.results.is_empty() {
+                    SearchFocus::Keyboard { row, col }
+                } else {
+                    let idx = (row * RESULT_COLUMNS).min(self.results.len() - 1);
+                    SearchFocus::Results(idx - idx % RESULT_COLUMNS)
+                }
+            }
+            SearchFocus::Results(idx)
+                if (idx + 1) % RESULT_COLUMNS != 0 && idx + 1 < self.results.len() =>
+            {
+                SearchFocus::Results(idx + 1)
+            }
+            focus => focus,
+        };
+    }
+
+    /// Moves the focus up.
+    pub fn move_up(&mut self) {
+        self.focus = match self.focus {
+            SearchFocus::Keyboard { row, col } if row > 0 => SearchFocus::Keyboard {
+                row: row - 1,
+                col: col.min(self.layout[row - 1].len() - 1),
+            },
+            SearchFocus::Results(idx) if idx >= RESULT_COLUMNS => {
+                SearchFocus::Results(idx - RESULT_COLUMNS)
+            }
+            focus => focus,
+        };
+    }
+
+    /// Moves the focus down.
+    pub fn move_down(&mut self) {
+        self.focus = match self.focus {
+            SearchFocus::Keyboard { row, col } if row + 1 < self.layout.len() => {
+                SearchFocus::Keyboard {
+                    row: row + 1,
+                    col: col.min(self.layout[row + 1].len() - 1),
+                }
+            }
+            SearchFocus::Results(idx) if idx + RESULT_COLUMNS < self.results.len() => {
+                SearchFocus::Results(idx + RESULT_COLUMNS)
+            }
+            focus => focus,
+        };
+    }
+
+    /// Presses the focused key, or returns the `(true_set_idx, true_item_idx)` of the focused result.
+    pub fn select(&mut self, api_handle: &Api) -> Option<(usize, usize)> {
+        match self.focus {
+            SearchFocus::Keyboard { row, col } => {
+                match self.layout[row][col] {
+                    Key::Char(c) => self.query.push(c),
+                    Key::Space => self.query.push(' '),
+                    Key::Backspace => {
+                        self.query.pop();
+                    }
+                    Key::Clear => self.query.clear(),
+                }
+                self.run_query(api_handle);
+                None
+            }
+            SearchFocus::Results(idx) => self
+                .results
+                .get(idx)
+                .map(|r| (r.true_set_idx, r.true_item_idx)),
+        }
+    }
+
+    /// Handles a character typed on the physical keyboard. Returns true if the query changed.
+    pub fn type_char(&mut self, c: char, api_handle: &Api) -> bool {
+        match c {
+            // Backspace
+            '\u{8}' => {
+                if self.query.pop().is_none() {
+                    return false;
+                }
+            }
+            c if c.is_alphanumeric() || c == ' ' => self.query.extend(c.to_lowercase()),
+            _ => return false,
+        }
+        self.run_query(api_handle);
+        true
+    }
+
+    fn run_query(&mut self, api_handle: &Api) {
+        let mut results = api_handle.search(&self.query);
+        results.truncate(MAX_RESULTS);
+        self.results = results;
+
+        let results = &self.results;
+        let stale = self
+            .imgs
+            .keys()
+            .filter(|(s, i)| {
+                !results
+                    .iter()
+                    .any(|r| r.true_set_idx == *s && r.true_item_idx == *i)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in stale {
+            if let Some(data) = self.imgs.remove(&key) {
+                self.evicted.push(data);
+            }
+        }
+
+        if let SearchFocus::Results(idx) = self.focus {
+            if self.results.is_empty() {
+                self.focus = SearchFocus::Keyboard { row: 0, col: 0 };
+            } else if idx >= self.results.len() {
+                self.focus = SearchFocus::Results(self.results.len() - 1);
+            }
+        }
+    }
+
+    /// Submits the [`LoadRequest`]s for the results that don't have an image yet.
+    pub fn request_images(&mut self, api_handle: &Api, loader: &mut ImageLoader, nf_id: &Id) {
+        for r in &self.results {
+            let key = (r.true_set_idx, r.true_item_idx);
+            if self.imgs.contains_key(&key)
+                || loader
+                    .notifier()
+                    .is_in_flight(LoadKind::Search, key.0, key.1)
+            {
+                continue;
+            }
+            let url = api_handle
+                .get_set(key.0)
+                .ok_or_else(|| String::from("set not found"))
+                .and_then(|set| set.get_home_tile_url(key.1).map_err(|e| e.to_string()));
+            match url {
+                Ok(url) => loader.submit(LoadRequest {
+                    kind: LoadKind::Search,
+                    true_set_idx: key.0,
+                    true_item_idx: key.1,
+                    url,
+                    size: None,
+                    preview_url: None,
+                }),
+                Err(e) => {
+                    debug!("No artwork for result {:?}: {}", key, e);
+                    self.imgs
+                        .insert(key, CachedImgData::not_found(nf_id.clone()));
+                }
+            }
+        }
+    }
+
+    /// Returns true if `true_set_idx`, `true_item_idx` is one of the current results.
+    pub fn wants(&self, true_set_idx: usize, true_item_idx: usize) -> bool {
+        self.results
+            .iter()
+            .any(|r| r.true_set_idx == true_set_idx && r.true_item_idx == true_item_idx)
+    }
+
+    /// Stores the texture data of a result image loaded in the background.
+    pub fn insert_loaded(
+        &mut self,
+        true_set_idx: usize,
+        true_item_idx: usize,
+        data: CachedImgData,
+    ) {
+        if let Some(old) = self.imgs.insert((true_set_idx, true_item_idx), data) {
+            self.evicted.push(old);
+        }
+    }
+
+    /// Takes the textures that need to be released.
+    pub fn take_evicted(&mut self) -> Vec<CachedImgData> {
+        std::mem::take(&mut self.evicted)
+    }
+
+    /// Sets the search screen widgets, the texts following the `theme` and in the language of the
+    /// `locale`.
+    pub fn show(&self, ui: &mut UiCell, nf_id: &Id, theme: &Theme, locale: Locale) {
+        theme
+            .text_widget(
+                &i18n::trf(locale, Text::SearchPrompt, &self.query),
+                theme.text,
+            )
+            .top_left_with_margins_on(ui.window, 80.0, KEYBOARD_LEFT)
+            .font_size(40)
+            .set(self.ids.query, ui);
+
+        let mut key_idx = 0;
+        for (row, keys) in self.layout.iter().enumerate() {
+            let top = KEYBOARD_TOP + row as f64 * (KEY_SIZE + KEY_MARGIN);
+            let mut left = KEYBOARD_LEFT;
+            for (col, key) in keys.iter().enumerate() {
+                let focused = self.focus == SearchFocus::Keyboard { row, col };
+                let label = key.label();
+                widget::Button::new()
+                    .w_h(key.width(), KEY_SIZE)
+                    .top_left_with_margins_on(ui.window, top, left)
+                    .color(if focused {
+                        conrod::color::LIGHT_BLUE
+                    } else {
+                        conrod::color::DARK_CHARCOAL
+                    })
+                    .label(&label)
+                    .label_color(conrod::color::WHITE)
+                    .label_font_size(24)
+                    .set(self.ids.keys[key_idx], ui);
+                left += key.width() + KEY_MARGIN;
+                key_idx += 1;
+            }
+        }
+
+        if self.results.is_empty() && !self.query.trim().is_empty() {
+            theme
+                .text_widget(
+                    &i18n::trf(locale, Text::NoResults, self.query.trim()),
+                    theme.hint_text,
+                )
+                .top_left_with_margins_on(ui.window, KEYBOARD_TOP, RESULTS_LEFT)
+                .font_size(28)
+                .set(self.ids.no_results, ui);
+        }
+
+        // Results still loading show the not found image, same as the rows.
+        let placeholder = CachedImgData::not_found(nf_id.clone());
+        for (idx, r) in self.results.iter().enumerate() {
+            let key = (r.true_set_idx, r.true_item_idx);
+            let data = self.imgs.get(&key).unwrap_or(&placeholder);
+            let h = RESULT_WIDTH * data.h / data.w;
+            let top = KEYBOARD_TOP + (idx / RESULT_COLUMNS) as f64 * RESULT_ROW_HEIGHT;
+            let left =
+                RESULTS_LEFT + (idx % RESULT_COLUMNS) as f64 * (RESULT_WIDTH + RESULT_MARGIN);
+            widget::Image::new(data.img_id)
+                .w_h(RESULT_WIDTH, h)
+                .top_left_with_margins_on(ui.window, top, left)
+                .set(self.ids.results[idx], ui);
+            theme
+                .text_widget(&r.title, theme.text)
+                .down_from(self.ids.results[idx], 8.0)
+                .w(RESULT_WIDTH)
+                .font_size(18)
+                .set(self.ids.result_titles[idx], ui);
+
+            if self.focus == SearchFocus::Results(idx) {
+                widget::Rectangle::outline_styled(
+                    [RESULT_WIDTH + 8.0, h + 8.0],
+                    widget::line::Style::new().thickness(4.0),
+                )
+                .color(to_color(theme.text))
+                .middle_of(self.ids.results[idx])
+                .set(self.ids.outline, ui);
+            }
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! Settings screen, pushed with `S` from the home screen.
+//!
+//! Up/down move the focus between the settings, left/right (or select) change the focused one. The
+//! changes apply right away, except for the image quality which only applies to the artwork
+//! requested once the screen is closed. Closing the screen writes the settings to the config file,
+//! see [`Config::save_settings`].
+use crate::config::{Config, ThemeName};
+use crate::i18n::{self, Locale, Text};
+use crate::theme::Theme;
+use conrod::widget::id::Generator;
+use conrod::{widget, Colorable, Labelable, Positionable, Sizeable, UiCell, Widget};
+use std::fmt;
+
+const OPTION_WIDTH: f64 = 600.0;
+const OPTION_HEIGHT: f64 = 80.0;
+const OPTION_MARGIN: f64 = 24.0;
+
+/// Image quality tiers, with the jpeg quality requested to the CDN.
+const QUALITY_TIERS: [(Text, u8); 3] = [(Text::Low, 60), (Text::Medium, 75), (Text::High, 90)];
+/// Row densities, with the number of visible rows.
+const ROW_DENSITIES: [(Text, usize); 3] = [
+    (Text::Spacious, 3),
+    (Text::Comfortable, 4),
+    (Text::Compact, 5),
+];
+/// Sound volumes, off first.
+const SOUND_VOLUMES: [(Text, u8); 4] = [
+    (Text::Off, 0),
+    (Text::Low, 30),
+    (Text::Medium, 60),
+    (Text::High, 100),
+];
+
+widget_ids!(
+    /// Hold the [`Id`](conrod::widget::Id)s for the settings screen widgets.
+    /// `options` has one element per [`Setting`].
+    pub struct SettingsIds {
+        title,
+        options[],
+        hint,
+    }
+);
+
+/// What can be changed from the settings screen, in the order it's listed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Setting {
+    /// [`Config::animations`].
+    Animations,
+    /// [`Config::image_quality`], picked among a few tiers.
+    ImageQuality,
+    /// [`Config::num_rows`], picked among a few densities.
+    RowDensity,
+    /// [`Config::theme`].
+    Theme,
+    /// [`Config::sound_volume`], picked among a few volumes.
+    Sounds,
+}
+
+const SETTINGS: [Setting; 5] = [
+    Setting::Animations,
+    Setting::ImageQuality,
+    Setting::RowDensity,
+    Setting::Theme,
+    Setting::Sounds,
+];
+
+/// State of the settings screen.
+pub struct SettingsScreen {
+    ids: SettingsIds,
+    focus: usize,
+    /// The config when the screen was opened, see [`SettingsScreen::is_changed`].
+    opened_with: Option<Config>,
+}
+
+impl SettingsScreen {
+    /// Constructor. Uses the `generator` to size the `options` buffer of the `ids`.
+    pub fn new(mut ids: SettingsIds, mut generator: Generator) -> Self {
+        ids.options.resize(SETTINGS.len(), &mut generator);
+        Self {
+            ids,
+            focus: 0,
+            opened_with: None,
+        }
+    }
+
+    /// Starts over with the focus on the first setting, remembering the `config` it's opened with.
+    pub fn open(&mut self, config: Config) {
+        self.focus = 0;
+        self.opened_with = Some(config);
+    }
+
+    /// Returns true if `config` differs from the one the screen was opened with.
+    pub fn is_changed(&self, config: &Config) -> bool {
+        self.opened_with.as_ref() != Some(config)
+    }
+
+    /// Moves the focus to the previous setting.
+    pub fn move_up(&mut self) {
+        self.focus = self.focus.saturating_sub(1);
+    }
+
+    /// Moves the focus to the next setting.
+    pub fn move_down(&mut self) {
+        self.focus = (self.focus + 1).min(SETTINGS.len() - 1);
+    }
+
+    /// The focused setting.
+    pub fn focused(&self) -> Setting {
+        SETTINGS[self.focus]
+    }
+
+    /// Changes the focused setting of the `config` to its next value, or the previous one when
+    /// `forward` is false. The values wrap around.
+    pub fn change(&self, config: &mut Config, forward: bool) {
+        match self.focused() {
+            Setting::Animations => config.animations = !config.animations,
+            Setting::ImageQuality => {
+                config.image_quality = cycle(&QUALITY_TIERS, config.image_quality, forward)
+            }
+            Setting::RowDensity => {
+                config.num_rows = cycle(&ROW_DENSITIES, config.num_rows, forward)
+            }
+            Setting::Theme => config.theme = config.theme.toggled(),
+            Setting::Sounds => {
+                config.sound_volume = cycle(&SOUND_VOLUMES, config.sound_volume, forward)
+            }
+        }
+    }
+
+    /// Sets the settings screen widgets for the values of the `config`, the texts following the
+    /// `theme` and in the language of its [`locale`](Config::locale).
+    pub fn show(&self, ui: &mut UiCell, config: &Config, theme: &Theme) {
+        let locale = config.locale;
+        theme
+            .text_widget(i18n::tr(locale, Text::Settings), theme.text)
+            .mid_top_with_margin_on(ui.window, ui.win_h / 5.0)
+            .font_size(48)
+            .set(self.ids.title, ui);
+
+        for (idx, setting) in SETTINGS.iter().enumerate() {
+            let label = format!(
+                "{}:  {}",
+                i18n::tr(locale, name(*setting)),
+                value(*setting, config)
+            );
+            let top = ui.win_h / 5.0 + 120.0 + idx as f64 * (OPTION_HEIGHT + OPTION_MARGIN);
+            widget::Button::new()
+                .w_h(OPTION_WIDTH, OPTION_HEIGHT)
+                .mid_top_with_margin_on(ui.window, top)
+                .color(if idx == self.focus {
+                    conrod::color::LIGHT_BLUE
+                } else {
+                    conrod::color::DARK_CHARCOAL
+                })
+                .label(&label)
+                .label_color(conrod::color::WHITE)
+                .label_font_size(28)
+                .set(self.ids.options[idx], ui);
+        }
+
+        theme
+            .text_widget(i18n::tr(locale, Text::SettingsHint), theme.hint_text)
+            .down_from(self.ids.options[SETTINGS.len() - 1], 40.0)
+            .align_middle_x_of(self.ids.title)
+            .font_size(24)
+            .set(self.ids.hint, ui);
+    }
+}
+
+fn name(setting: Setting) -> Text {
+    match setting {
+        Setting::Animations => Text::Animations,
+        Setting::ImageQuality => Text::ImageQuality,
+        Setting::RowDensity => Text::RowDensity,
+        Setting::Theme => Text::Theme,
+        Setting::Sounds => Text::Sounds,
+    }
+}
+
+fn value(setting: Setting, config: &Config) -> String {
+    let locale = config.locale;
+    let text = match setting {
+        Setting::Animations if config.animations => Text::On,
+        Setting::Animations => Text::Off,
+        Setting::ImageQuality => return tier_name(&QUALITY_TIERS, config.image_quality, locale),
+        Setting::RowDensity => return tier_name(&ROW_DENSITIES, config.num_rows, locale),
+        Setting::Theme if config.theme == ThemeName::Dark => Text::Dark,
+        Setting::Theme => Text::Light,
+        Setting::Sounds => return tier_name(&SOUND_VOLUMES, config.sound_volume, locale),
+    };
+    i18n::tr(locale, text).to_string()
+}
+
+/// Name of the tier `current` is, or the value itself when set by hand in the config file.
+fn tier_name<T: PartialEq + fmt::Display>(
+    tiers: &[(Text, T)],
+    current: T,
+    locale: Locale,
+) -> String {
+    tiers
+        .iter()
+        .find(|(_, value)| *value == current)
+        .map_or_else(
+            || i18n::trf(locale, Text::Custom, &current.to_string()),
+            |(name, _)| i18n::tr(locale, *name).to_string(),
+        )
+}
+
+/// The value of the tier after the one of `current`, or before it when `forward` is false.
+/// A value that is not one of the `tiers` goes to the first one.
+fn cycle<T: PartialEq + Copy>(tiers: &[(Text, T)], current: T, forward: bool) -> T {
+    let len = tiers.len();
+    let next = match tiers.iter().position(|(_, value)| *value == current) {
+        Some(idx) if forward => (idx + 1) % len,
+        Some(idx) => (idx + len - 1) % len,
+        None => 0,
+    };
+    tiers[next].1
+}
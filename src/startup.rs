@@ -0,0 +1,110 @@
+//! Loading screen shown while the home data is being fetched.
+//!
+//! The home json (read from the assets folder when [`offline`](Config::offline)), along with the `SetRef` sets it points to, is fetched on a background thread so
+//! the window shows up right away and keeps processing events in the meantime.
+//!
+//! The home data saved by the last run, if any, is shown instead without waiting for the network,
+//! see [`Startup::Stale`].
+use crate::catalog::Catalog;
+use crate::config::Config;
+use crate::event_loop::{ControlFlow, Event, EventLoop, Input};
+use crate::i18n::{self, Text};
+use crate::theme::Theme;
+use api::{Api, ApiError};
+use conrod::backend::glium::glium::{self, Surface};
+use conrod::backend::glium::Renderer;
+use conrod::glium::Display;
+use conrod::image::Map;
+use conrod::{Positionable, Ui, Widget};
+use std::sync::mpsc::{self, TryRecvError};
+use std::thread;
+
+widget_ids!(
+    /// Hold the [`Id`](conrod::widget::Id)s for the loading screen widgets.
+    struct StartupIds {
+        loading,
+    }
+);
+
+/// Outcome of [`wait_for_home_data`].
+pub enum Startup {
+    /// The home data is loaded.
+    Ready(Api),
+    /// The home data of the last run is loaded from the [`HomeCache`](api::HomeCache), it's yet to
+    /// be fetched again.
+    Stale(Api),
+    /// The window was closed before the home data finished loading.
+    Closed,
+}
+
+/// Fetches the home data in the background while drawing a loading message.
+///
+/// # Arguments
+/// * `api`: the [`Api`] to load, it's handed back once loaded.
+/// * `config`: tells whether to load from the network, and the language of the loading message.
+/// * `catalog`: where the rows come from, see [`load_home_data`](crate::load_home_data).
+/// * `theme`: the loading message and the background are drawn with it.
+pub fn wait_for_home_data(
+    display: &Display,
+    event_loop: &mut EventLoop,
+    ui: &mut Ui,
+    renderer: &mut Renderer,
+    mut api: Api,
+    config: Config,
+    catalog: Catalog,
+    theme: &Theme,
+) -> Result<Startup, ApiError> {
+    let locale = config.locale;
+    let (tx, rx) = mpsc::channel();
+    let waker = event_loop.waker();
+    thread::spawn(move || {
+        let res =
+            if matches!(catalog, Catalog::Home) && !config.offline && api.load_cached_home_data() {
+                Ok(Startup::Stale(api))
+            } else {
+                crate::load_home_data(&mut api, &config, &catalog).map(|_| Startup::Ready(api))
+            };
+        // The receiver is gone if the window was closed, nothing left to do then.
+        if tx.send(res).is_ok() {
+            waker.wake();
+        }
+    });
+
+    let ids = StartupIds::new(ui.widget_id_generator());
+    let image_map = Map::<glium::texture::SrgbTexture2d>::new();
+    {
+        let ui = &mut ui.set_widgets();
+        theme
+            .text_widget(i18n::tr(locale, Text::Loading), theme.text)
+            .middle_of(ui.window)
+            .font_size(36)
+            .set(ids.loading, ui);
+    }
+
+    let mut outcome = Ok(Startup::Closed);
+    event_loop.run(|event, _, control_flow| match event {
+        Event::Input(Input::CloseRequested) => *control_flow = ControlFlow::Exit,
+        Event::MainEventsCleared => {
+            if let Some(primitives) = ui.draw_if_changed() {
+                renderer.fill(display, primitives, &image_map);
+                let mut target = display.draw();
+                let [r, g, b] = theme.background;
+                target.clear_color_srgb(r, g, b, 1.0);
+                renderer.draw(display, &mut target, &image_map).unwrap();
+                target.finish().unwrap();
+            }
+
+            match rx.try_recv() {
+                Ok(res) => {
+                    outcome = res;
+                    *control_flow = ControlFlow::Exit;
+                }
+                // Woken up once loaded.
+                Err(TryRecvError::Empty) => *control_flow = ControlFlow::Wait,
+                Err(TryRecvError::Disconnected) => panic!("home data loader thread died"),
+            }
+        }
+        _ => (),
+    });
+    outcome
+}
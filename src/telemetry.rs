@@ -0,0 +1,52 @@
+//! Logs and spans, through [`tracing`].
+//!
+//! The logs go to stderr, filtered with `RUST_LOG` as before (e.g. `RUST_LOG=helloplus=debug`); the
+//! ones of the [`api`] crate, which still uses the `log` macros, are forwarded to the same output.
+//!
+//! The spans time the parts of the main loop that can stall it:
+//!  - `load_home_data`, fetching (or reading) the home data, see [`load_home_data`](crate::load_home_data).
+//!  - `fetch_image`, each image fetched by the [`ImageLoader`](crate::loader::ImageLoader) workers,
+//!    then `decode` and `resize`, its decoding and resizing on the decoding threads.
+//!  - `texture_upload`, each image uploaded to the GPU, see [`upload_rgba`](crate::helpers::upload_rgba).
+//!  - `frame`, an iteration of the [`EventLoop`](crate::event_loop::EventLoop) and its `render` once
+//!    the [`Ui`](conrod::Ui) changed.
+//!
+//! `--trace-json <path>` writes them all to `path`, whatever `RUST_LOG` says, in the chrome tracing
+//! format: open it in `chrome://tracing` or <https://ui.perfetto.dev>.
+use crate::config::ConfigError;
+use crate::headless;
+use std::path::PathBuf;
+use tracing_chrome::{ChromeLayerBuilder, FlushGuard};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// Takes `--trace-json <path>` out of `args` so the rest can be handed to
+/// [`Config::load`](crate::config::Config::load).
+pub fn take_trace_path(args: &mut Vec<String>) -> Result<Option<PathBuf>, ConfigError> {
+    Ok(headless::take_value(args, "--trace-json")?.map(PathBuf::from))
+}
+
+/// Sets up the logs and, with a `trace_path`, the chrome tracing file.
+///
+/// The file is only complete once the returned guard is dropped, keep it until the app exits.
+pub fn init(trace_path: Option<PathBuf>) -> Option<FlushGuard> {
+    let logs = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_filter(EnvFilter::from_default_env());
+    let (chrome, guard) = match trace_path {
+        Some(path) => {
+            let (layer, guard) = ChromeLayerBuilder::new()
+                .file(path)
+                .include_args(true)
+                .build();
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+    tracing_subscriber::registry()
+        .with(logs)
+        .with(chrome)
+        .init();
+    guard
+}
@@ -0,0 +1,228 @@
+//! Colors, fonts and spacing the home rows and the screens are drawn with.
+//!
+//! There is a dark and a light [`Theme`], picked with [`Config::theme`](crate::config::Config::theme)
+//! and switched at runtime from the [`settings`](crate::settings) screen. Each one can be tweaked
+//! with a toml file named after it in the `themes` folder next to the config file, e.g.
+//! `~/.config/helloplus/themes/light.toml`. Every key is optional, the missing ones keep the value
+//! of the built-in theme:
+//! ```toml
+//! background = [0.9, 0.9, 0.92]
+//! text = [0.1, 0.1, 0.1]
+//! font = "NotoSans/NotoSans-Bold.ttf"
+//! fallback_fonts = ["NotoSansJP/NotoSansJP-Regular.ttf"]
+//! title_font_size = 32
+//! title_margin = 20.0
+//! ```
+//!
+//! The overlays (detail page, PIN entry) keep their dark backdrop whatever the theme.
+use crate::config::{self, ConfigError, ThemeName};
+use crate::fonts::{FontChain, FALLBACK_FONTS};
+use crate::ui_core::TextStyle;
+use conrod::{widget, Colorable};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::{fs, io};
+
+/// Rough width of a character relative to the font size, used to size the texts before the fonts
+/// are loaded.
+const CHAR_WIDTH_RATIO: f64 = 0.55;
+/// Ends the texts cut by [`Theme::truncate`].
+const ELLIPSIS: &str = "…";
+
+/// Look of the texts and the background.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Theme {
+    /// Color the window is cleared with, as rgb from 0 to 1.
+    pub background: [f32; 3],
+    /// Color of the texts drawn on the background, e.g. the row titles.
+    pub text: [f32; 3],
+    /// Color of the texts that are not final yet, e.g. the titles of the rows still loading.
+    pub dimmed_text: [f32; 3],
+    /// Color of the secondary texts, e.g. shown in place of the tiles of an empty row.
+    pub hint_text: [f32; 3],
+    /// Font file, relative to the `fonts` folder of the assets.
+    pub font: String,
+    /// Font files tried for the texts the [`font`](Theme::font) can't draw, in order. See
+    /// [`fonts`](crate::fonts).
+    pub fallback_fonts: Vec<String>,
+    /// Font size of the row titles, in pixels.
+    pub title_font_size: u32,
+    /// Font size of the secondary texts, in pixels.
+    pub hint_font_size: u32,
+    /// Font size of the title of the focused item, in pixels.
+    pub item_title_font_size: u32,
+    /// Font size of the content type, release year... of the focused item, in pixels.
+    pub item_info_font_size: u32,
+    /// Space between a row title and the top of the row.
+    pub title_margin: f64,
+    /// Space around and between the texts of the strip below the focused tile.
+    pub info_padding: f64,
+    /// The [`font`](Theme::font) then the [`fallback_fonts`](Theme::fallback_fonts) once loaded,
+    /// see [`helpers::load_fonts`](crate::helpers::load_fonts). Empty draws with the default font.
+    #[serde(skip)]
+    pub fonts: FontChain,
+}
+
+impl Theme {
+    /// Light texts on a near black background.
+    pub fn dark() -> Self {
+        Self {
+            background: [0.0, 0.0, 0.013],
+            text: [1.0, 1.0, 1.0],
+            dimmed_text: [0.33, 0.33, 0.33],
+            hint_text: [0.5, 0.5, 0.5],
+            font: String::from("NotoSans/NotoSans-Regular.ttf"),
+            fallback_fonts: FALLBACK_FONTS.iter().map(|font| font.to_string()).collect(),
+            title_font_size: 28,
+            hint_font_size: 22,
+            item_title_font_size: 22,
+            item_info_font_size: 16,
+            title_margin: 24.0,
+            info_padding: 8.0,
+            fonts: FontChain::default(),
+        }
+    }
+
+    /// Dark texts in a bolder font on a light grey background.
+    pub fn light() -> Self {
+        Self {
+            background: [0.9, 0.9, 0.92],
+            text: [0.08, 0.08, 0.1],
+            dimmed_text: [0.55, 0.55, 0.55],
+            hint_text: [0.4, 0.4, 0.4],
+            font: String::from("NotoSans/NotoSans-Bold.ttf"),
+            ..Self::dark()
+        }
+    }
+
+    /// The built-in theme `name`, with the values of its file in the `themes` folder if there is one.
+    pub fn load(name: ThemeName) -> Result<Self, ConfigError> {
+        let builtin = match name {
+            ThemeName::Dark => Self::dark(),
+            ThemeName::Light => Self::light(),
+        };
+        let path = match config::config_dir() {
+            Some(dir) => dir.join("themes").join(format!("{}.toml", name)),
+            None => return Ok(builtin),
+        };
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(builtin),
+            Err(e) => return Err(ConfigError::Io(path, e)),
+        };
+        builtin.merged(&contents, path)
+    }
+
+    /// The theme with the values of the toml `contents` read from `path`.
+    fn merged(&self, contents: &str, path: PathBuf) -> Result<Self, ConfigError> {
+        let overrides: toml::value::Table =
+            toml::from_str(contents).map_err(|e| ConfigError::Parse(path.clone(), e))?;
+        let mut table = match toml::Value::try_from(self) {
+            Ok(toml::Value::Table(table)) => table,
+            _ => unreachable!("a theme is a table of plain values"),
+        };
+        table.extend(overrides);
+        toml::Value::Table(table)
+            .try_into()
+            .map_err(|e| ConfigError::Parse(path, e))
+    }
+
+    /// Color of the texts in `style`, as rgb.
+    pub fn text_color(&self, style: TextStyle) -> [f32; 3] {
+        match style {
+            TextStyle::Title => self.text,
+            TextStyle::LoadingTitle => self.dimmed_text,
+            TextStyle::Hint => self.hint_text,
+            // Drawn on a backdrop, whatever the theme.
+            TextStyle::ItemTitle => [1.0, 1.0, 1.0],
+            TextStyle::ItemInfo => [0.83, 0.83, 0.83],
+        }
+    }
+
+    /// Font size of the texts in `style`, in pixels.
+    pub fn font_size(&self, style: TextStyle) -> u32 {
+        match style {
+            TextStyle::Title | TextStyle::LoadingTitle => self.title_font_size,
+            TextStyle::Hint => self.hint_font_size,
+            TextStyle::ItemTitle => self.item_title_font_size,
+            TextStyle::ItemInfo => self.item_info_font_size,
+        }
+    }
+
+    /// A text widget for `text` in the font of the theme, or the fallback covering it best, and
+    /// the `color`. The size is left to the caller.
+    pub fn text_widget<'t>(&self, text: &'t str, color: [f32; 3]) -> widget::Text<'t> {
+        let widget = widget::Text::new(text).color(to_color(color));
+        match self.fonts.pick(text) {
+            Some(font_id) => widget.font_id(font_id),
+            None => widget,
+        }
+    }
+
+    /// Width of `text` drawn in `style`, in pixels. Measured with the glyphs of the font it's drawn
+    /// with once the [`fonts`](Theme::fonts) are loaded, roughly estimated until then.
+    pub fn text_width(&self, style: TextStyle, text: &str) -> f64 {
+        let font_size = self.font_size(style);
+        self.fonts
+            .width(text, font_size)
+            .unwrap_or_else(|| text.chars().count() as f64 * font_size as f64 * CHAR_WIDTH_RATIO)
+    }
+
+    /// The `text` drawn in `style` cut so it's no wider than `max_width` pixels, an ellipsis taking
+    /// the place of the characters cut. The longest prefix that fits is looked for by bisection.
+    pub fn truncate<'t>(&self, style: TextStyle, text: &'t str, max_width: f64) -> Cow<'t, str> {
+        if text.is_empty() || self.text_width(style, text) <= max_width {
+            return Cow::Borrowed(text);
+        }
+        let ends: Vec<usize> = text.char_indices().map(|(idx, _)| idx).collect();
+        let shortened = |len: usize| format!("{}{}", text[..ends[len]].trim_end(), ELLIPSIS);
+        // The prefix of `fits` chars fits, the one of `too_long` doesn't.
+        let (mut fits, mut too_long) = (0, ends.len());
+        while too_long - fits > 1 {
+            let len = (fits + too_long) / 2;
+            if self.text_width(style, &shortened(len)) <= max_width {
+                fits = len;
+            } else {
+                too_long = len;
+            }
+        }
+        Cow::Owned(shortened(fits))
+    }
+}
+
+/// The conrod color for the `rgb` values of a [`Theme`].
+pub fn to_color(rgb: [f32; 3]) -> conrod::Color {
+    conrod::color::rgb(rgb[0], rgb[1], rgb[2])
+}
+
+/// Both themes, loaded once at startup so switching between them doesn't touch the disk.
+#[derive(Clone, Debug)]
+pub struct Themes {
+    dark: Theme,
+    light: Theme,
+}
+
+impl Themes {
+    /// Loads both themes, see [`Theme::load`].
+    pub fn load() -> Result<Self, ConfigError> {
+        Ok(Self {
+            dark: Theme::load(ThemeName::Dark)?,
+            light: Theme::load(ThemeName::Light)?,
+        })
+    }
+
+    /// The theme `name`.
+    pub fn get(&self, name: ThemeName) -> &Theme {
+        match name {
+            ThemeName::Dark => &self.dark,
+            ThemeName::Light => &self.light,
+        }
+    }
+
+    /// Both themes, to load their fonts.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Theme> {
+        vec![&mut self.dark, &mut self.light].into_iter()
+    }
+}
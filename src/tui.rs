@@ -0,0 +1,440 @@
+//! `--tui`: the rows drawn in the terminal with [`ratatui`], for when there is no OpenGL, e.g. over
+//! SSH, or to debug the navigation.
+//!
+//! Only the row titles and the item titles are drawn, no images. The cursor moves with the same
+//! keys as in the window, through the same [`NavigationModel`], and the rows are read through the
+//! [`ContentProvider`] of the [`Api`](api::Api) loaded with the [`Catalog`]. The local rows, the
+//! search and the settings are left out, so are the empty rows unless
+//! [`skip_empty_rows`](Config::skip_empty_rows) is off. `Enter` shows what is known about the
+//! focused item, `Escape` (or `q`) quits.
+use crate::catalog::Catalog;
+use crate::config::Config;
+use crate::i18n::{self, Locale, Text};
+use crate::navigation::{NavigationModel, RowExtent};
+use crate::screens::Action;
+use crate::ui_core::RowScroll;
+use api::ContentProvider;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+use std::error::Error;
+use std::io;
+use tracing::info;
+
+/// Columns given to an item title, the longer ones are cut.
+const ITEM_WIDTH: u16 = 24;
+/// Lines taken by a row: its title on the top border, its items, the bottom border.
+const ROW_HEIGHT: u16 = 3;
+/// Smallest [`row_stride`](RowExtent::row_stride) the [`NavigationModel`] deals with.
+const MIN_ROW_STRIDE: usize = 4;
+/// Smallest number of rows in view, same as [`Config::num_rows`].
+const MIN_NUM_ROWS: usize = 2;
+/// Keys listed in the header.
+const KEY_HINTS: &str = "arrows move  PgUp/PgDn/Home/End jump  Enter details  Esc quit";
+
+/// Takes `--tui` out of `args` so the rest can be handed to [`Config::load`]. Returns true if it
+/// was given.
+pub fn take_from_args(args: &mut Vec<String>) -> bool {
+    match args.iter().position(|a| a == "--tui") {
+        Some(pos) => {
+            args.remove(pos);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Loads the `catalog` and shows its rows in the terminal until `Escape` is pressed.
+pub fn run(config: Config, catalog: &Catalog) -> Result<(), Box<dyn Error>> {
+    let mut api = crate::build_api(&config)?;
+    crate::load_home_data(&mut api, &config, catalog)?;
+    let mut home = TuiHome::new(&api, &config);
+    info!("showing {} sets in the terminal", home.num_of_sets());
+
+    let _guard = RawTerminal::enter()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    loop {
+        terminal.draw(|frame| home.draw(frame))?;
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        if let Some(action) = action(key.code) {
+            if home.handle(action) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// The [`Action`] of a key, the same keys as in the window. None if the key does nothing here.
+fn action(code: KeyCode) -> Option<Action> {
+    Some(match code {
+        KeyCode::Left => Action::Left,
+        KeyCode::Right => Action::Right,
+        KeyCode::Up => Action::Up,
+        KeyCode::Down => Action::Down,
+        KeyCode::PageUp => Action::PageUp,
+        KeyCode::PageDown => Action::PageDown,
+        KeyCode::Home => Action::First,
+        KeyCode::End => Action::Last,
+        KeyCode::Enter => Action::Select,
+        KeyCode::Esc | KeyCode::Char('q') => Action::Escape,
+        KeyCode::Backspace => Action::Backspace,
+        _ => return None,
+    })
+}
+
+/// Raw mode and the alternate screen, both left when dropped so the terminal is restored even on
+/// an error.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> io::Result<Self> {
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        Ok(RawTerminal)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+        let _ = disable_raw_mode();
+    }
+}
+
+/// A row of the [`TuiHome`].
+#[derive(Debug, Default)]
+struct TuiRow {
+    scroll: RowScroll,
+    /// Same as [`SetRow::last_item_idx`](crate::SetRow::last_item_idx).
+    last_item_idx: Option<usize>,
+}
+
+/// The home rows as drawn in the terminal.
+struct TuiHome<'a> {
+    provider: &'a dyn ContentProvider,
+    locale: Locale,
+    nav: NavigationModel,
+    rows: Vec<TuiRow>,
+    /// Number of rows in view, follows the height of the terminal.
+    num_rows: usize,
+    /// Number of items drawn per row, follows the width of the terminal.
+    row_stride: usize,
+    /// Columns given to each item, at least [`ITEM_WIDTH`] unless the terminal is too narrow.
+    item_width: usize,
+    /// Whether the details of the focused item are shown on top of the rows.
+    detail_open: bool,
+}
+
+impl<'a> TuiHome<'a> {
+    fn new(provider: &'a dyn ContentProvider, config: &Config) -> Self {
+        let skipped = if config.skip_empty_rows {
+            provider
+                .list_sets()
+                .filter(|set_idx| provider.item_count(*set_idx) == 0)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let mut nav = NavigationModel::new(config.num_rows)
+            .with_wrap(config.wrap_navigation)
+            .with_skipped(skipped);
+        // The first row may be one of the skipped ones.
+        nav.jump_to_set(0, provider.list_sets().len());
+        Self {
+            provider,
+            locale: config.locale,
+            nav,
+            rows: provider.list_sets().map(|_| TuiRow::default()).collect(),
+            num_rows: config.num_rows,
+            row_stride: MIN_ROW_STRIDE,
+            item_width: ITEM_WIDTH as usize,
+            detail_open: false,
+        }
+    }
+
+    fn num_of_sets(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn extent(&self, true_set_idx: usize) -> RowExtent {
+        RowExtent {
+            item_count: self.provider.item_count(true_set_idx),
+            row_stride: self.row_stride,
+        }
+    }
+
+    /// Handles the `action`, returns true when the app has to quit.
+    fn handle(&mut self, action: Action) -> bool {
+        if self.detail_open {
+            if let Action::Select | Action::Escape | Action::Back | Action::Backspace = action {
+                self.detail_open = false;
+            }
+            return false;
+        }
+        let num_of_sets = self.num_of_sets();
+        let true_set_idx = self.nav.cursor.true_set_idx;
+        match action {
+            Action::Left | Action::Right => {
+                let extent = self.extent(true_set_idx);
+                if let Some(row) = self.rows.get_mut(true_set_idx) {
+                    if action == Action::Left {
+                        self.nav.move_left(&mut row.scroll, extent);
+                    } else {
+                        self.nav.move_right(&mut row.scroll, extent);
+                    }
+                }
+            }
+            Action::Up => {
+                self.leave_row();
+                if let Some(true_set_idx) = self.nav.move_up(num_of_sets) {
+                    self.enter_row(true_set_idx);
+                }
+            }
+            Action::Down => {
+                self.leave_row();
+                if let Some(true_set_idx) = self.nav.move_down(num_of_sets) {
+                    self.enter_row(true_set_idx);
+                }
+            }
+            Action::PageUp if !self.nav.cursor.on_banner => {
+                self.jump_to_set(true_set_idx.saturating_sub(self.num_rows))
+            }
+            Action::PageDown if self.nav.cursor.on_banner => self.jump_to_set(0),
+            Action::PageDown => self.jump_to_set(true_set_idx + self.num_rows),
+            Action::First => {
+                self.leave_row();
+                self.nav.jump_to_set(0, num_of_sets);
+                if let Some(row) = self.rows.first_mut() {
+                    self.nav.move_to_first_item(&mut row.scroll);
+                }
+            }
+            Action::Last => self.jump_to_set(usize::MAX),
+            Action::Select => {
+                self.detail_open = !self.nav.cursor.on_banner
+                    && self.nav.cursor.true_item_idx < self.extent(true_set_idx).item_count;
+            }
+            Action::Escape => return true,
+            _ => (),
+        }
+        false
+    }
+
+    fn jump_to_set(&mut self, true_set_idx: usize) {
+        self.leave_row();
+        if let Some(true_set_idx) = self.nav.jump_to_set(true_set_idx, self.num_of_sets()) {
+            self.enter_row(true_set_idx);
+        }
+    }
+
+    /// Same as [`DisplayController::leave_row`](crate::DisplayController::leave_row).
+    fn leave_row(&mut self) {
+        if self.nav.cursor.on_banner {
+            return;
+        }
+        if let Some(row) = self.rows.get_mut(self.nav.cursor.true_set_idx) {
+            row.last_item_idx = Some(self.nav.cursor.true_item_idx);
+        }
+    }
+
+    /// Same as [`DisplayController::enter_row`](crate::DisplayController::enter_row).
+    fn enter_row(&mut self, true_set_idx: usize) {
+        let extent = self.extent(true_set_idx);
+        if let Some(row) = self.rows.get_mut(true_set_idx) {
+            match row.last_item_idx {
+                Some(true_item_idx) => {
+                    self.nav
+                        .return_to_item(&mut row.scroll, extent, true_item_idx)
+                }
+                None => self.nav.enter_row(&mut row.scroll, extent),
+            }
+        }
+    }
+
+    /// Fits the rows in the `body` of the terminal, the cursor staying on its item.
+    fn resize(&mut self, body: Rect) {
+        let num_rows = ((body.height / ROW_HEIGHT) as usize).max(MIN_NUM_ROWS);
+        let inner_width = body.width.saturating_sub(2) as usize;
+        let row_stride = (inner_width / ITEM_WIDTH as usize).max(MIN_ROW_STRIDE);
+        self.item_width = inner_width / row_stride;
+        if (num_rows, row_stride) == (self.num_rows, self.row_stride) {
+            return;
+        }
+        self.num_rows = num_rows;
+        self.row_stride = row_stride;
+        self.nav.reset(num_rows);
+        let true_set_idx = self.nav.cursor.true_set_idx;
+        let extent = self.extent(true_set_idx);
+        if let Some(row) = self.rows.get_mut(true_set_idx) {
+            self.nav.clamp_to_row(&mut row.scroll, extent);
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        self.resize(body);
+
+        let title_style = if self.nav.cursor.on_banner {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default().add_modifier(Modifier::BOLD)
+        };
+        let header_line = Line::from(vec![
+            Span::styled(" Hello+ ", title_style),
+            Span::styled(
+                format!("  {}", KEY_HINTS),
+                Style::default().add_modifier(Modifier::DIM),
+            ),
+        ]);
+        frame.render_widget(Paragraph::new(header_line), header);
+
+        let visible = self.nav.visible_set_range();
+        let visible: Vec<_> = (visible.start..visible.end.min(self.num_of_sets()))
+            .filter(|true_set_idx| !self.nav.is_skipped(*true_set_idx))
+            .collect();
+        let mut constraints = vec![Constraint::Length(ROW_HEIGHT); visible.len()];
+        constraints.push(Constraint::Min(0));
+        let areas = Layout::vertical(constraints).split(body);
+        for (true_set_idx, area) in visible.into_iter().zip(areas.iter()) {
+            self.draw_row(frame, true_set_idx, *area);
+        }
+
+        let metadata = self.focused_metadata().unwrap_or_default();
+        let footer_line = match metadata.title {
+            Some(title) => format!(" {}  {}", title, metadata.summary()),
+            None => String::new(),
+        };
+        frame.render_widget(Paragraph::new(footer_line), footer);
+
+        if self.detail_open {
+            self.draw_detail(frame, body);
+        }
+    }
+
+    fn draw_row(&self, frame: &mut Frame, true_set_idx: usize, area: Rect) {
+        let cursor = &self.nav.cursor;
+        let is_cursor_row = !cursor.on_banner && cursor.true_set_idx == true_set_idx;
+        let item_count = self.provider.item_count(true_set_idx);
+        let title = self
+            .provider
+            .set_title(true_set_idx)
+            .unwrap_or_else(|| i18n::tr(self.locale, Text::TitleNotFound));
+        let title_style = if is_cursor_row {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        let block = Block::default().borders(Borders::ALL).title(Span::styled(
+            format!(
+                " {} ({}) ",
+                title,
+                i18n::item_count(self.locale, item_count)
+            ),
+            title_style,
+        ));
+
+        let first = self.rows[true_set_idx].scroll.left_right_idx_adjustment;
+        let items = (first..item_count.min(first + self.row_stride))
+            .map(|true_item_idx| {
+                let title = self
+                    .provider
+                    .item_metadata(true_set_idx, true_item_idx)
+                    .and_then(|metadata| metadata.title)
+                    .unwrap_or_else(|| i18n::tr(self.locale, Text::TitleNotFound).to_string());
+                let style = if is_cursor_row && cursor.true_item_idx == true_item_idx {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                Span::styled(cell(&title, self.item_width), style)
+            })
+            .collect::<Vec<_>>();
+        let line = if item_count == 0 {
+            Line::from(Span::styled(
+                i18n::tr(self.locale, Text::NothingHereYet),
+                Style::default().add_modifier(Modifier::DIM),
+            ))
+        } else {
+            Line::from(items)
+        };
+        frame.render_widget(Paragraph::new(line).block(block), area);
+    }
+
+    /// Everything known about the focused item, in a box in the middle of the `body`.
+    fn draw_detail(&self, frame: &mut Frame, body: Rect) {
+        let cursor = &self.nav.cursor;
+        let (set_idx, item_idx) = (cursor.true_set_idx, cursor.true_item_idx);
+        let metadata = self.focused_metadata().unwrap_or_default();
+        let title = metadata
+            .title
+            .clone()
+            .unwrap_or_else(|| i18n::tr(self.locale, Text::TitleNotFound).to_string());
+        let tile = self
+            .provider
+            .item_image_url(set_idx, item_idx)
+            .map(|tile| tile.url)
+            .unwrap_or_default();
+        let lines = vec![
+            Line::from(metadata.summary()),
+            Line::from(""),
+            Line::from(format!(
+                "content id: {}",
+                metadata.content_id.as_deref().unwrap_or("-")
+            )),
+            Line::from(format!("tile: {}", tile)),
+        ];
+        let area = centered(body, body.width * 2 / 3, 8);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).wrap(Wrap { trim: true }).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" {} ", title)),
+            ),
+            area,
+        );
+    }
+
+    fn focused_metadata(&self) -> Option<api::ItemMetadata> {
+        let cursor = &self.nav.cursor;
+        if cursor.on_banner {
+            return None;
+        }
+        self.provider
+            .item_metadata(cursor.true_set_idx, cursor.true_item_idx)
+    }
+}
+
+/// The `title` cut or padded to `width` columns, a space on each side.
+fn cell(title: &str, width: usize) -> String {
+    let width = width.saturating_sub(2);
+    let title: String = title.chars().take(width).collect();
+    format!(" {:<width$} ", title, width = width)
+}
+
+/// A `width` x `height` area in the middle of `area`, no larger than it.
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
@@ -0,0 +1,231 @@
+//! Backend agnostic part of the home screen.
+//!
+//! The tile geometry, the [`Cursor`] and the row virtualization (which rows and tiles are in view,
+//! see [`virtualization`](crate::virtualization)) don't depend on conrod/glium. Drawing a frame
+//! produces a list of [`DrawCommand`]s that a [`Renderer`] turns into whatever its backend needs,
+//! see [`ConrodRenderer`](crate::conrod_renderer::ConrodRenderer). This keeps the navigation logic
+//! usable without a window or a GPU.
+use crate::layout::Layout;
+use crate::theme::Theme;
+
+pub use crate::virtualization::{buffered_range, Cursor, RowScroll, TileRing, Viewport};
+
+/// How far the glow around a focused brand tile reaches.
+const GLOW_WIDTH: f64 = 12.0;
+
+/// Rectangle in window coordinates, in pixels from the top left corner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    /// Left edge.
+    pub x: f64,
+    /// Top edge.
+    pub y: f64,
+    /// Width.
+    pub w: f64,
+    /// Height.
+    pub h: f64,
+}
+
+impl Rect {
+    /// Returns true if the point `x`, `y` is inside the rectangle, edges included.
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x <= self.x + self.w && y >= self.y && y <= self.y + self.h
+    }
+}
+
+/// Part of an image, in pixels from its bottom left corner the way the textures are laid out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SourceRect {
+    /// Left edge.
+    pub left: f64,
+    /// Bottom edge.
+    pub bottom: f64,
+    /// Width.
+    pub w: f64,
+    /// Height.
+    pub h: f64,
+}
+
+/// How a text is emphasized. The [`Theme`] gives its actual font, size and color.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextStyle {
+    /// Title of a row.
+    Title,
+    /// Title of a row whose images are still being loaded.
+    LoadingTitle,
+    /// Secondary text, e.g. shown in place of the tiles of an empty row.
+    Hint,
+    /// Title of the focused item, below its tile.
+    ItemTitle,
+    /// Content type, release year... of the focused item, below its title.
+    ItemInfo,
+}
+
+/// A single thing to draw. `I` identifies an image loaded in the [`Renderer`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCommand<I> {
+    /// Draw `image` stretched over `rect`.
+    Image {
+        /// The image to draw.
+        image: I,
+        /// The part of `image` to draw, None for the whole of it. Set for the tiles packed into a
+        /// [`TextureAtlas`](crate::atlas::TextureAtlas).
+        source: Option<SourceRect>,
+        /// Where to draw it.
+        rect: Rect,
+        /// Opacity, from 0 (invisible) to 1 (opaque).
+        alpha: f32,
+    },
+    /// Fill `rect` with black, e.g. behind a text so it stays readable over the tiles.
+    Backdrop {
+        /// Where to draw it.
+        rect: Rect,
+        /// Opacity, from 0 (invisible) to 1 (opaque).
+        alpha: f32,
+    },
+    /// Soft light over `rect`, drawn behind a tile so it shows around its edges.
+    Glow {
+        /// Where to draw it.
+        rect: Rect,
+        /// Opacity, from 0 (invisible) to 1 (opaque).
+        alpha: f32,
+    },
+    /// Draw `text` on a single line starting at `x`, `y` (its top left corner).
+    Text {
+        /// The text to draw.
+        text: String,
+        /// Left edge.
+        x: f64,
+        /// Top edge.
+        y: f64,
+        /// Font and color to use.
+        style: TextStyle,
+    },
+}
+
+/// Draws the [`DrawCommand`]s of a frame. Later commands are drawn on top of earlier ones.
+pub trait Renderer {
+    /// Handle of a loaded image.
+    type Image;
+
+    /// Draws the `commands`, replacing whatever the previous call drew.
+    fn draw(&mut self, commands: &[DrawCommand<Self::Image>]);
+}
+
+/// Where the tiles of a row are drawn.
+#[derive(Clone, Copy, Debug)]
+pub struct RowPlacement<'l> {
+    layout: &'l Layout,
+    /// Offset of the row from the first row in view, the sum of the [`row_height`](Layout::row_height)s
+    /// of the rows above it. Rows don't all have the same height, see [`Layout::for_shape`].
+    row_top: f64,
+    /// Extra top offset, in pixels, e.g. while scrolling vertically.
+    top_shift: f64,
+    /// Extra left offset, in items, e.g. while scrolling horizontally.
+    left_shift: f64,
+}
+
+impl<'l> RowPlacement<'l> {
+    /// Constructor.
+    pub fn new(layout: &'l Layout, row_top: f64, top_shift: f64, left_shift: f64) -> Self {
+        Self {
+            layout,
+            row_top,
+            top_shift,
+            left_shift,
+        }
+    }
+
+    /// Top edge of the tiles.
+    pub fn top(&self) -> f64 {
+        self.row_top + self.layout.row_top_margin + self.top_shift
+    }
+
+    /// Left edge of the tile at `adjusted_item_idx`.
+    pub fn left(&self, adjusted_item_idx: usize) -> f64 {
+        (adjusted_item_idx as f64 + self.left_shift)
+            * self.layout.image_width_plus_margin
+            * self.layout.image_scale_down_factor
+            + self.layout.items_margin
+    }
+
+    /// The tile at `adjusted_item_idx` for an image of `w` x `h`.
+    pub fn tile(&self, adjusted_item_idx: usize, w: f64, h: f64) -> Rect {
+        Rect {
+            x: self.left(adjusted_item_idx),
+            y: self.top(),
+            w,
+            h,
+        }
+    }
+
+    /// Same as [`tile`](RowPlacement::tile) enlarged by `scale`. It's also moved back and up
+    /// proportionally so that at [`image_scale_up_factor`](Layout::image_scale_up_factor) it's moved
+    /// by [`items_margin`](Layout::items_margin).
+    pub fn highlighted_tile(&self, adjusted_item_idx: usize, w: f64, h: f64, scale: f64) -> Rect {
+        let margin =
+            self.layout.items_margin * (scale - 1.0) / (self.layout.image_scale_up_factor - 1.0);
+        Rect {
+            x: self.left(adjusted_item_idx) - margin,
+            y: self.top() - margin,
+            w: w * scale,
+            h: h * scale,
+        }
+    }
+
+    /// Top left corner of the row title drawn in `style`, above the leftmost tile.
+    pub fn title_point(&self, style: TextStyle, theme: &Theme) -> (f64, f64) {
+        (
+            self.left(0),
+            self.top() - theme.title_margin - theme.font_size(style) as f64,
+        )
+    }
+
+    /// Widest a text drawn along the row can be, e.g. the row title: the width of the tiles in
+    /// view, the one partly shown past the right edge of the window left out.
+    pub fn text_max_width(&self) -> f64 {
+        let visible_tiles = self.layout.row_stride.saturating_sub(1) as f64;
+        visible_tiles * self.layout.image_width_plus_margin * self.layout.image_scale_down_factor
+            - self.layout.items_margin
+    }
+
+    /// Strip below the `tile` holding the title and the info of the focused item, see
+    /// [`item_text_points`](RowPlacement::item_text_points). At least as wide as the tile, wider if
+    /// the texts need it.
+    pub fn item_strip(tile: Rect, title: &str, info: &str, theme: &Theme) -> Rect {
+        let text_width = theme
+            .text_width(TextStyle::ItemTitle, title)
+            .max(theme.text_width(TextStyle::ItemInfo, info));
+        let font_sizes =
+            theme.font_size(TextStyle::ItemTitle) + theme.font_size(TextStyle::ItemInfo);
+        Rect {
+            x: tile.x,
+            y: tile.y + tile.h,
+            w: tile.w.max(text_width + 2.0 * theme.info_padding),
+            h: 3.0 * theme.info_padding + font_sizes as f64,
+        }
+    }
+
+    /// Area lit around the focused `tile` of a [`RowStyle::Brands`](crate::layout::RowStyle::Brands) row.
+    pub fn glow(tile: Rect) -> Rect {
+        Rect {
+            x: tile.x - GLOW_WIDTH,
+            y: tile.y - GLOW_WIDTH,
+            w: tile.w + 2.0 * GLOW_WIDTH,
+            h: tile.h + 2.0 * GLOW_WIDTH,
+        }
+    }
+
+    /// Top left corners of the title and the info texts in the `strip`.
+    pub fn item_text_points(strip: Rect, theme: &Theme) -> ((f64, f64), (f64, f64)) {
+        let x = strip.x + theme.info_padding;
+        let title_y = strip.y + theme.info_padding;
+        let info_y = title_y + theme.font_size(TextStyle::ItemTitle) as f64 + theme.info_padding;
+        ((x, title_y), (x, info_y))
+    }
+
+    /// Top left corner of the text shown in place of the tiles.
+    pub fn hint_point(&self) -> (f64, f64) {
+        (self.left(0), self.top() + self.layout.items_margin)
+    }
+}
@@ -0,0 +1,196 @@
+//! Row virtualization: which rows and tiles of the home screen are in view, and what stays cached
+//! for them.
+//!
+//! Only depends on std and serde so it can be measured on its own, see `benches/navigation.rs`.
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Represents where the cursor is at on the screen. By cursor, it really means what are the indices
+/// of the highlighted item.
+///
+/// Saved with the [`Position`](crate::Position) of each profile, see [`Config::resume_session`](crate::Config::resume_session).
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Cursor {
+    /// Row of the highlighted item.
+    pub true_set_idx: usize,
+    /// Index of the highlighted item within its row.
+    pub true_item_idx: usize,
+    /// Canvas index of the highlighted item, see [`RowScroll`].
+    pub adjusted_item_idx: usize,
+    /// True when the [`HeroBanner`](crate::hero::HeroBanner) has the focus instead of a tile.
+    pub on_banner: bool,
+}
+
+/// Keeps track of the rows in view.
+#[derive(Debug)]
+pub struct Viewport {
+    prev_visible_range: Range<usize>,
+}
+
+impl Viewport {
+    /// Viewport showing the first `num_rows` rows.
+    pub fn new(num_rows: usize) -> Self {
+        Self {
+            prev_visible_range: 0..num_rows,
+        }
+    }
+
+    /// The range returned by the last call to [`visible_set_range`](Viewport::visible_set_range).
+    pub fn current(&self) -> Range<usize> {
+        self.prev_visible_range.clone()
+    }
+
+    /// Starts over from the first `num_rows` rows, e.g. after the number of rows changed.
+    pub fn reset(&mut self, num_rows: usize) {
+        self.prev_visible_range = 0..num_rows;
+    }
+
+    /// This function takes the `true_set_idx` and produces the range of sets that are going to be visible
+    /// taking into account the expected number of visible rows.
+    ///
+    /// For example:
+    ///  - with `num_rows` set to 4
+    ///  - if set set_idx 0 through 2 the visible range is 0 to 4
+    ///  - if user goes down 3 times now set_idx is 3 and visible range is 1 to 5
+    ///  - if from 3 it goes to 4 then visible range now is 2 to 6
+    ///  - if user now goes BACK so set_idx is back to 3 the range is still 2 to 6
+    ///    This helps ease the transition since it won't jump all the rows back
+    pub fn visible_set_range(&mut self, true_set_idx: usize, num_rows: usize) -> Range<usize> {
+        if true_set_idx.checked_sub(self.prev_visible_range.start) == Some(1) {
+            return self.prev_visible_range.clone();
+        }
+        self.jump_to(true_set_idx, num_rows)
+    }
+
+    /// Same as [`visible_set_range`](Viewport::visible_set_range) without easing the transition:
+    /// the range is computed from scratch, for when `true_set_idx` may be far from the rows in view.
+    pub fn jump_to(&mut self, true_set_idx: usize, num_rows: usize) -> Range<usize> {
+        let new_range = if true_set_idx + 2 > num_rows {
+            let shift = (true_set_idx + 2) - num_rows;
+            shift..(shift + num_rows)
+        } else {
+            0..num_rows
+        };
+
+        self.prev_visible_range = new_range.clone();
+        new_range
+    }
+}
+
+/// Horizontal scroll of a row.
+///
+/// The tiles in view are indexed from 0 to [`row_stride`](crate::layout::Layout::row_stride)-1, the `adjusted_item_idx`.
+/// Adding the `left_right_idx_adjustment` gives the `true_item_idx`, the full index into the row's items.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct RowScroll {
+    /// Combined with the `adjusted_item_idx` it produces the `true_item_idx` for this specific row.
+    pub left_right_idx_adjustment: usize,
+}
+
+impl RowScroll {
+    /// The `true_item_idx` of the tile at `adjusted_item_idx`.
+    pub fn true_item_idx(&self, adjusted_item_idx: usize) -> usize {
+        adjusted_item_idx + self.left_right_idx_adjustment
+    }
+
+    /// Returns true if `true_item_idx` is one of the `row_stride` items currently in view.
+    pub fn is_in_window(&self, row_stride: usize, true_item_idx: usize) -> bool {
+        true_item_idx >= self.left_right_idx_adjustment
+            && true_item_idx < self.left_right_idx_adjustment + row_stride
+    }
+
+    /// Shift right on a row of `item_count` items drawing `row_stride` tiles. Returns false if
+    /// `true_item_idx` is the last item. The row scrolls once the cursor gets close to the right edge.
+    pub fn shift_right(
+        &mut self,
+        row_stride: usize,
+        item_count: usize,
+        adjusted_item_idx: usize,
+        true_item_idx: usize,
+    ) -> bool {
+        if (true_item_idx + 1) < item_count {
+            if adjusted_item_idx + 4 > row_stride {
+                self.left_right_idx_adjustment += 1;
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Shift left. The row scrolls once the cursor gets close to the left edge.
+    pub fn shift_left(&mut self, adjusted_item_idx: usize) {
+        if self.left_right_idx_adjustment > 0 && adjusted_item_idx < 2 {
+            self.left_right_idx_adjustment -= 1;
+        }
+    }
+}
+
+/// Fixed size ring caching whatever is drawn for the tiles of a row, e.g. their textures.
+///
+/// The slot for an item is `true_item_idx % row_stride` and it also stores the `true_item_idx` it
+/// currently holds. Since the items in view are always [`row_stride`](crate::layout::Layout::row_stride)
+/// consecutive indices they never compete for a slot.
+#[derive(Debug)]
+pub struct TileRing<T> {
+    slots: Vec<Option<(usize, T)>>,
+}
+
+impl<T> TileRing<T> {
+    /// Empty ring for a row drawing `row_stride` tiles.
+    pub fn new(row_stride: usize) -> Self {
+        Self {
+            slots: (0..row_stride).map(|_| None).collect(),
+        }
+    }
+
+    /// What is cached for `true_item_idx`, None if its slot is empty or holds another item.
+    pub fn get(&self, true_item_idx: usize) -> Option<&T> {
+        match self.slots[true_item_idx % self.slots.len()] {
+            Some((idx, ref value)) if idx == true_item_idx => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Caches `value` for `true_item_idx`. Returns whatever was in its slot.
+    pub fn insert(&mut self, true_item_idx: usize, value: T) -> Option<T> {
+        let slot = true_item_idx % self.slots.len();
+        self.slots[slot]
+            .replace((true_item_idx, value))
+            .map(|(_, old)| old)
+    }
+
+    /// Takes what is cached for `true_item_idx`, None if its slot is empty or holds another item.
+    pub fn remove(&mut self, true_item_idx: usize) -> Option<T> {
+        let slot = true_item_idx % self.slots.len();
+        match self.slots[slot] {
+            Some((idx, _)) if idx == true_item_idx => {
+                self.slots[slot].take().map(|(_, value)| value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Empties the ring, returning what it held.
+    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        self.slots
+            .iter_mut()
+            .filter_map(|slot| slot.take())
+            .map(|(_, value)| value)
+    }
+
+    /// The `true_item_idx` and value of every filled slot.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.slots
+            .iter()
+            .flatten()
+            .map(|(true_item_idx, value)| (*true_item_idx, value))
+    }
+}
+
+/// The rows whose tiles stay cached around the `visible` ones: `buffered_rows` more on each side.
+/// The others release them, see [`TileRing`].
+pub fn buffered_range(visible: Range<usize>, buffered_rows: usize) -> Range<usize> {
+    visible.start.saturating_sub(buffered_rows)..visible.end + buffered_rows
+}
@@ -0,0 +1,46 @@
+//! Items the user flagged to watch later.
+//!
+//! Each [`Profile`] has its own watchlist, a json array of `contentId`s stored in `watchlist.json`,
+//! see [`persistence`](crate::persistence).
+//! Only the ids are persisted, the rest of the item (title, artwork) comes from the home data.
+use crate::persistence::Persisted;
+use crate::profiles::Profile;
+
+/// The persisted list of `contentId`s, most recently added first.
+#[derive(Debug, Default)]
+pub struct Watchlist {
+    content_ids: Persisted<Vec<String>>,
+}
+
+impl Watchlist {
+    /// Loads the watchlist of the `profile` from the data dir. A missing or unreadable file gives
+    /// an empty list.
+    pub fn load(profile: &Profile) -> Self {
+        Self {
+            content_ids: Persisted::load(&profile.file_name("watchlist.json")),
+        }
+    }
+
+    /// The ids in the list, most recently added first.
+    pub fn content_ids(&self) -> &[String] {
+        &self.content_ids.value
+    }
+
+    /// Adds `content_id` to the front of the list, or removes it if it was already there, and saves
+    /// the list. Returns true if it was added.
+    pub fn toggle(&mut self, content_id: &str) -> bool {
+        let content_ids = &mut self.content_ids.value;
+        let added = match content_ids.iter().position(|id| id == content_id) {
+            Some(pos) => {
+                content_ids.remove(pos);
+                false
+            }
+            None => {
+                content_ids.insert(0, content_id.to_string());
+                true
+            }
+        };
+        self.content_ids.save();
+        added
+    }
+}